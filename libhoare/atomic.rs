@@ -0,0 +1,141 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Atomic sections, see the `Struct invariants` section of ../readme.md.
+//
+// A method of a struct with an invariant (see refinement.rs, which generates
+// `__hoare_invariant`) may break the invariant inside a block marked
+// `#[atomic_section]`, as long as it restores it by the end of the block.
+// Plugins only see attributes on items, so `#[check_atomic_sections]` on the
+// method (or on an inherent impl, for all its methods) finds the marked blocks
+// and replaces each `#[atomic_section] { ... }` with
+// `{ let __hoare_section = { ... }; <check the invariant>; __hoare_section }`.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::attr;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::fold::{self, Folder};
+use syntax::ptr::P;
+use syntax::util::small_vector::SmallVector;
+
+const USAGE: &'static str =
+    "#[check_atomic_sections] can only be used on inherent impls and methods";
+
+pub fn check_atomic_sections(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    match item {
+        Annotatable::Item(item) => {
+            if let ast::ItemKind::Impl(_, _, _, _, None, _, _) = item.node {
+            } else {
+                cx.span_err(sp, USAGE);
+                return Annotatable::Item(item);
+            }
+            Annotatable::Item(item.map(|mut item| {
+                if let ast::ItemKind::Impl(_, _, _, _, _, _, ref mut items) = item.node {
+                    *items = items.drain(..).map(|impl_item| method(cx, impl_item)).collect();
+                }
+                item
+            }))
+        }
+        Annotatable::ImplItem(impl_item) => {
+            Annotatable::ImplItem(P(method(cx, (*impl_item).clone())))
+        }
+        item => {
+            cx.span_err(sp, USAGE);
+            item
+        }
+    }
+}
+
+// The method with the invariant checked after each atomic section in it.
+fn method(cx: &ExtCtxt, impl_item: ast::ImplItem) -> ast::ImplItem {
+    let (sig, body) = match impl_item.node {
+        ast::ImplItemKind::Method(ref sig, ref body) => (sig.clone(), body.clone()),
+        _ => return impl_item,
+    };
+    let mut folder = SectionFolder {
+        cx: cx,
+        label: format!("invariant after atomic section in {}", impl_item.ident),
+        sections: 0,
+    };
+    let body = folder.fold_block(body);
+    if folder.sections > 0 && !sig.decl.has_self() {
+        cx.span_err(
+            impl_item.span,
+            "#[atomic_section] can only be used in methods which take `self`",
+        );
+    }
+    ast::ImplItem {
+        node: ast::ImplItemKind::Method(sig, body),
+        ..impl_item
+    }
+}
+
+struct SectionFolder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    label: String,
+    sections: usize,
+}
+
+impl<'a, 'b> Folder for SectionFolder<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        let is_section = e.attrs.iter().any(|a| a.check_name("atomic_section"));
+        let e = e.map(|e| fold::noop_fold_expr(e, self));
+        if !is_section {
+            return e;
+        }
+        if let ast::ExprKind::Block(_) = e.node {
+        } else {
+            self.cx.span_err(e.span, "#[atomic_section] can only be used on blocks");
+            return e;
+        }
+
+        self.sections += 1;
+        let section = e.map(|mut e| {
+            e.attrs = e.attrs
+                .iter()
+                .filter(|a| {
+                    if a.check_name("atomic_section") {
+                        attr::mark_used(a);
+                        false
+                    } else {
+                        true
+                    }
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+                .into();
+            e
+        });
+        let label = &*self.label;
+        quote_expr!(self.cx, {
+            let __hoare_section = $section;
+            if !self.__hoare_invariant() {
+                panic!("{}", $label);
+            }
+            __hoare_section
+        })
+    }
+
+    // `self` in a nested item is not the method's.
+    fn fold_item(&mut self, i: P<ast::Item>) -> SmallVector<P<ast::Item>> {
+        SmallVector::one(i)
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        mac
+    }
+}
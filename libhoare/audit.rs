@@ -0,0 +1,168 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Audited unsafe blocks, see the `Auditing unsafe blocks` section of
+// ../readme.md.
+//
+// `#[audited_unsafe(reason="..", pred="..")]` (or `#[audited_unsafe=".."]`, for
+// just a reason) is an inert attribute on an unsafe block in a contracted
+// function. When compiling with `--cfg hoare_audit`, the first contract
+// expanded on the function rewrites each such block to
+//
+// {
+//     if !(pred) { panic!("audited unsafe block in f (pred): reason") }
+//     unsafe { .. }
+// }
+//
+// removing the attribute, so later contracts don't check it again, and writes
+// `fn_path \t file:line \t reason \t pred` to the audit manifest
+// (`<crate name>.audit` in the audit directory) for review.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::fold::{self, Folder};
+use syntax::ptr::P;
+use syntax::util::small_vector::SmallVector;
+
+use manifest;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_audit")
+}
+
+// body, with the audited unsafe blocks in it checked.
+pub fn audit(cx: &ExtCtxt, fn_name: &str, body: &ast::Block) -> Result<P<ast::Block>, ()> {
+    let mut folder = AuditFolder {
+        cx: cx,
+        fn_name: fn_name,
+        failed: false,
+    };
+    let body = folder.fold_block(P(body.clone()));
+    if folder.failed { Err(()) } else { Ok(body) }
+}
+
+struct AuditFolder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    fn_name: &'a str,
+    failed: bool,
+}
+
+impl<'a, 'b> AuditFolder<'a, 'b> {
+    // (reason, predicate) of the `audited_unsafe` attribute attr.
+    fn audit_attr(&mut self, attr: &ast::Attribute) -> Option<(String, Option<String>)> {
+        let meta = attr.meta();
+        if let Some(reason) = meta.as_ref().and_then(|meta| meta.value_str()) {
+            return Some((reason.to_string(), None));
+        }
+        let items = meta.as_ref().and_then(|meta| meta.meta_item_list()).map(|items| {
+            items.to_vec()
+        });
+        let value = |name| {
+            items.as_ref().and_then(|items| {
+                items.iter().find(|item| item.check_name(name)).and_then(|item| item.value_str())
+            })
+        };
+        let known = items.as_ref().map_or(false, |items| {
+            items.iter().all(|item| item.check_name("reason") || item.check_name("pred"))
+        });
+        match value("reason") {
+            Some(reason) if known => {
+                Some((reason.to_string(), value("pred").map(|pred| pred.to_string())))
+            }
+            _ => {
+                self.cx.span_err(
+                    attr.span,
+                    "expected #[audited_unsafe=\"reason\"] or \
+                     #[audited_unsafe(reason=\"..\", pred=\"..\")]",
+                );
+                self.failed = true;
+                None
+            }
+        }
+    }
+
+    // The audited unsafe block e, with its predicate checked before it.
+    fn audited(&mut self, e: P<ast::Expr>, attr: &ast::Attribute) -> P<ast::Expr> {
+        let cx = self.cx;
+        let (reason, pred) = match self.audit_attr(attr) {
+            Some(audit) => audit,
+            None => return e,
+        };
+        let unsafe_block = match e.node {
+            ast::ExprKind::Block(ref block) => is_unsafe(block),
+            _ => false,
+        };
+        if !unsafe_block {
+            cx.span_err(attr.span, "#[audited_unsafe] must be used on an unsafe block");
+            self.failed = true;
+            return e;
+        }
+
+        let loc = cx.codemap().lookup_char_pos(e.span.lo);
+        manifest::append(
+            cx,
+            &manifest::dir("HOARE_AUDIT_DIR", "target/hoare-audit"),
+            "audit",
+            &format!(
+                "{}\t{}:{}\t{}\t{}",
+                manifest::fn_path(cx, self.fn_name),
+                loc.file.name,
+                loc.line,
+                reason,
+                pred.clone().unwrap_or(String::new())
+            ),
+        );
+
+        let sp = e.span;
+        let block = e.map(|mut e| {
+            let mut attrs: Vec<ast::Attribute> = e.attrs.into();
+            attrs.retain(|attr| !attr.check_name("audited_unsafe"));
+            e.attrs = attrs.into();
+            e
+        });
+        let pred = match pred {
+            Some(pred) => pred,
+            None => return block,
+        };
+        let msg = &format!("audited unsafe block in {} ({}): {}", self.fn_name, pred, reason);
+        let pred = cx.parse_expr(pred);
+        let check = quote_stmt!(cx, if !($pred) { panic!("{}", $msg); }).unwrap();
+        cx.expr_block(cx.block(sp, vec![check, cx.stmt_expr(block)]))
+    }
+}
+
+fn is_unsafe(block: &ast::Block) -> bool {
+    match block.rules {
+        ast::BlockCheckMode::Unsafe(_) => true,
+        ast::BlockCheckMode::Default => false,
+    }
+}
+
+impl<'a, 'b> Folder for AuditFolder<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        let e = e.map(|e| fold::noop_fold_expr(e, self));
+        let attr = e.attrs.iter().find(|attr| attr.check_name("audited_unsafe")).cloned();
+        match attr {
+            Some(attr) => self.audited(e, &attr),
+            None => e,
+        }
+    }
+
+    // Nested items are audited by their own contracts.
+    fn fold_item(&mut self, item: P<ast::Item>) -> SmallVector<P<ast::Item>> {
+        SmallVector::one(item)
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
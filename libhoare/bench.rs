@@ -0,0 +1,174 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Benchmarks of the cost of contracts, see the `Benchmarking contracts`
+// section of ../readme.md.
+//
+// Under `--cfg hoare_bench`, `#[bench_contracts]` on a function `foo` (which
+// must also have `#[valid_input]`, see inputs.rs) generates copies of `foo`:
+// `__hoare_bench_foo_unchecked` without any contracts, and, for each contract,
+// `__hoare_bench_foo_<i>` with only that contract. It also generates
+// `bench_foo(&mut Criterion)`, which runs the copies and `foo` itself on the
+// same valid inputs as a criterion benchmark group named `hoare/<path of foo>`.
+// `hoare-bench` in the runtime crate reads criterion's results and reports the
+// overhead of each contract.
+//
+// Like `#[valid_input]`, this must come before the contract attributes, so that
+// they have not been expanded yet.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use inputs;
+use manifest;
+
+// Contracts which are benchmarked on their own.
+const CONTRACTS: &'static [&'static str] = &[
+    "precond",
+    "debug_precond",
+    "release_precond",
+    "postcond",
+    "debug_postcond",
+    "release_postcond",
+    "invariant",
+    "debug_invariant",
+    "release_invariant",
+    "transition",
+    "debug_transition",
+    "release_transition",
+];
+
+// The number of inputs each benchmark iteration runs the function on.
+const INPUTS: usize = 100;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_bench")
+}
+
+pub fn bench_contracts(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    let generated = match item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
+                if generics.is_parameterized() {
+                    cx.span_err(sp, "#[bench_contracts] can not be used on generic functions");
+                    None
+                } else if !item.attrs.iter().any(|attr| attr.check_name("valid_input")) {
+                    cx.span_err(
+                        sp,
+                        "#[bench_contracts] needs #[valid_input] (after it) to generate inputs",
+                    );
+                    None
+                } else if !enabled(cx) {
+                    Some(Vec::new())
+                } else {
+                    inputs::fn_args(cx, decl, "#[bench_contracts]")
+                        .map(|args| benchmarks(cx, item, &args))
+                }
+            }
+            _ => {
+                cx.span_err(sp, "#[bench_contracts] on non-function item");
+                None
+            }
+        },
+        _ => {
+            cx.span_err(sp, "#[bench_contracts] is only supported on free functions");
+            None
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.unwrap_or(Vec::new()).into_iter().map(Annotatable::Item));
+    result
+}
+
+// The copies of item and the benchmark function.
+fn benchmarks(
+    cx: &ExtCtxt,
+    item: &P<ast::Item>,
+    args: &[(ast::Ident, P<ast::Ty>)],
+) -> Vec<P<ast::Item>> {
+    let name = item.ident.to_string();
+    let mut result = vec![copy(item, &format!("__hoare_bench_{}_unchecked", name), None)];
+    let mut variants = vec![
+        ("unchecked".to_owned(), format!("__hoare_bench_{}_unchecked", name)),
+        ("checked".to_owned(), name.clone()),
+    ];
+    let contracts = item.attrs
+        .iter()
+        .filter(|attr| CONTRACTS.iter().any(|contract| attr.check_name(contract)));
+    for (i, attr) in contracts.enumerate() {
+        let copy_name = format!("__hoare_bench_{}_{}", name, i);
+        result.push(copy(item, &copy_name, Some(attr)));
+        variants.push((pprust::attr_to_string(attr), copy_name));
+    }
+
+    let arg_names = args.iter().map(|&(ident, _)| format!("{}, ", ident)).collect::<String>();
+    let benches = variants
+        .iter()
+        .map(|&(ref id, ref fn_name)| {
+            format!(
+                "group.bench_function({:?}, |b| b.iter(|| for input in &inputs {{\n\
+                 let ({args}) = ::std::clone::Clone::clone(input);\n\
+                 ::criterion::black_box({fn_name}({args}));\n\
+                 }}));\n",
+                id,
+                args = arg_names,
+                fn_name = fn_name
+            )
+        })
+        .collect::<String>();
+    let bench = cx.parse_item(format!(
+        "/// Benchmarks `{name}` with and without each of its contracts.\n\
+         #[allow(dead_code)]\n\
+         {vis} fn bench_{name}(c: &mut ::criterion::Criterion) {{\n\
+         let inputs: Vec<_> = ::libhoare::ValidInputs::new({name:?}, {decoder})\n\
+         .take({inputs})\n\
+         .collect();\n\
+         let mut group = c.benchmark_group({group:?});\n\
+         {benches}\
+         group.finish();\n\
+         }}",
+        name = name,
+        vis = pprust::vis_to_string(&item.vis).trim(),
+        decoder = inputs::decoder_name(item.ident),
+        inputs = INPUTS,
+        group = format!("hoare/{}", manifest::fn_path(cx, &name)),
+        benches = benches
+    ));
+    result.push(bench);
+    result
+}
+
+// A private copy of item called name, with only contract (if any) and the
+// attributes which affect code generation.
+fn copy(item: &P<ast::Item>, name: &str, contract: Option<&ast::Attribute>) -> P<ast::Item> {
+    let mut attrs: Vec<ast::Attribute> = item.attrs
+        .iter()
+        .filter(|attr| attr.check_name("inline") || attr.check_name("cold"))
+        .cloned()
+        .collect();
+    attrs.extend(contract.cloned());
+    P(ast::Item {
+        ident: ast::Ident::from_str(name),
+        attrs: attrs,
+        vis: ast::Visibility::Inherited,
+        ..(**item).clone()
+    })
+}
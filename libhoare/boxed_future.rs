@@ -0,0 +1,94 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contracts on functions which return boxed futures, see the `Boxed futures`
+// section of ../readme.md.
+//
+// Trait methods can't return `impl Future`, so asynchronous trait methods
+// return `Box<Future<Item = T, Error = E>>` (with any other bounds, e.g.,
+// `+ Send`), and do their work when the future is polled, not when they are
+// called. Checking a postcondition on the box itself is useless, so a
+// postcondition (or `exit_only` invariant) of such a function is checked on the
+// value the future resolves to, by wrapping the returned future in
+//
+// Box::new(::futures::Future::map(__result, move |__result| {
+//     <check>;
+//     __result
+// }))
+//
+// so `return` in the predicate is the future's item. The box is coerced back to
+// the declared return type when it is returned.
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+
+// Whether ret is a boxed future.
+pub fn is_boxed_future(ret: &ast::FunctionRetTy) -> bool {
+    let ty = match *ret {
+        ast::FunctionRetTy::Ty(ref ty) => ty,
+        ast::FunctionRetTy::Default(_) => return false,
+    };
+    let boxed = match ty.node {
+        ast::TyKind::Path(None, ref path) => match params(path, "Box") {
+            Some(data) if data.types.len() == 1 => data.types[0].clone(),
+            _ => return false,
+        },
+        _ => return false,
+    };
+    // `Box<Future<..>>` is parsed as a path, `Box<Future<..> + Send>` as a
+    // trait object.
+    let future = match boxed.node {
+        ast::TyKind::Path(None, ref path) => path.clone(),
+        ast::TyKind::TraitObject(ref bounds) => {
+            let mut traits = bounds.iter().filter_map(|bound| match *bound {
+                ast::TraitTyParamBound(ref poly, _) => Some(poly.trait_ref.path.clone()),
+                ast::RegionTyParamBound(_) => None,
+            });
+            match traits.next() {
+                Some(path) => path,
+                None => return false,
+            }
+        }
+        _ => return false,
+    };
+    match params(&future, "Future") {
+        Some(data) => data.bindings.iter().any(|binding| binding.ident.name == "Item"),
+        None => false,
+    }
+}
+
+// The angle bracketed parameters of path, if its last segment is name.
+fn params<'a>(path: &'a ast::Path, name: &str) -> Option<&'a ast::AngleBracketedParameterData> {
+    let segment = match path.segments.last() {
+        Some(segment) if segment.identifier.name == name => segment,
+        _ => return None,
+    };
+    match segment.parameters {
+        Some(ref parameters) => match **parameters {
+            ast::PathParameters::AngleBracketed(ref data) => Some(data),
+            ast::PathParameters::Parenthesized(_) => None,
+        },
+        None => None,
+    }
+}
+
+// Rebinds the result, a boxed future, to one which makes check on its item.
+pub fn check_item(cx: &ExtCtxt, sp: Span, check: ast::Stmt) -> ast::Stmt {
+    let result_name = super::result_name();
+    let item = cx.expr_ident(sp, result_name);
+    let checked = cx.expr_block(cx.block(sp, vec![check, cx.stmt_expr(item)]));
+    quote_stmt!(cx,
+        let $result_name = ::std::boxed::Box::new(
+            ::futures::Future::map($result_name, move |$result_name| $checked)
+        );
+    ).unwrap()
+}
@@ -0,0 +1,253 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Caching expansions, see the `Caching expansions` section of ../readme.md.
+//
+// If `HOARE_EXPANSION_CACHE` is set when compiling, the expansion of each
+// attribute wrapped in `Strippable` (see strip.rs) is pretty-printed to
+// `<dir>/<key>.rs`, where the key is a hash of everything the expansion reads:
+// the attribute, the item it is on (including its other attributes, such as
+// the contracts stacked on it), the module, the cfgs, crate policy, and profile
+// (see profile.rs) of the build, the shared predicates of other crates (see
+// shared_predicates.rs, the crate's own `#[predicate]`s are called by name, so
+// their definitions do not affect expansions), for `#[check_trait_invariant]`,
+// the trait invariants recorded so far (see trait_invariant.rs), and the
+// versions of the plugin and of rustc (recorded by ../build.rs). If the file
+// exists, it is parsed instead of expanding the attribute again.
+//
+// Expansions which report errors are not cached. Neither are expansions when
+// contract coverage, mutation testing, contract graphs, or unsafe audits (see
+// audit.rs) are enabled, since those write manifests while expanding, nor when
+// predicates are compiled to functions, since those are numbered while
+// expanding (see predicate_fns.rs).
+// Items generated by macros are not cached, since their hygiene marks can not
+// be printed (see hygiene.rs).
+
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::env;
+use std::fs::{self, File};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use syntax::ast::{self, MetaItem};
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::parse;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use audit;
+use coverage;
+use dump;
+use graph;
+use hygiene;
+use manifest;
+use mutation;
+use policy;
+use profile;
+use shared_predicates;
+use trait_invariant;
+
+pub const DIR_VAR: &'static str = "HOARE_EXPANSION_CACHE";
+
+// Each expanded item in a cache file follows one of these lines. The first
+// line of the file holds the run count (see lib.rs) after the expansion.
+const ITEM: &'static str = "// @hoare-cache item";
+const IMPL_ITEM: &'static str = "// @hoare-cache impl item";
+const TRAIT_ITEM: &'static str = "// @hoare-cache trait item";
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    env::var_os(DIR_VAR).is_some() && !coverage::enabled(cx) && !mutation::enabled(cx) &&
+        !graph::enabled(cx) && !audit::enabled(cx) &&
+        policy::crate_policy(cx).predicates == policy::Predicates::Inline
+}
+
+// Expands attr on item using f, or reuses the cached expansion.
+pub fn expand<F>(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable, f: F)
+    -> Vec<Annotatable>
+where
+    F: Fn(&mut ExtCtxt, Span, &MetaItem, Annotatable) -> Vec<Annotatable>,
+{
+    if !enabled(cx) || hygiene::from_macro(&item) {
+        return f(cx, sp, attr, item);
+    }
+
+    let path = manifest::dir(DIR_VAR, "").join(format!("{:016x}.rs", key(cx, attr, &item)));
+    if let Some(items) = load(cx, &path) {
+        for item in &items {
+            dump::expansion(cx, super::item_ident(item), item);
+        }
+        return items;
+    }
+
+    let errors = cx.parse_sess.span_diagnostic.err_count();
+    let items = f(cx, sp, attr, item);
+    if cx.parse_sess.span_diagnostic.err_count() == errors {
+        store(cx, &path, &items);
+    }
+    items
+}
+
+fn key(cx: &ExtCtxt, attr: &MetaItem, item: &Annotatable) -> u64 {
+    let mut cfgs: Vec<String> = cx.cfg()
+        .iter()
+        .map(|&(name, value)| match value {
+            Some(value) => format!("{}=\"{}\"", name, value),
+            None => name.to_string(),
+        })
+        .collect();
+    cfgs.sort();
+    let policy = policy::crate_policy(cx);
+
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    env!("HOARE_RUSTC_VERSION").hash(&mut hasher);
+    pprust::to_string(|s| s.print_meta_item(attr)).hash(&mut hasher);
+    to_string(item).hash(&mut hasher);
+    manifest::fn_path(cx, "").hash(&mut hasher);
+    cfgs.hash(&mut hasher);
+    let fn_path = manifest::fn_path(cx, &super::item_ident(item).name.as_str());
+    profile::overrides(cx, &fn_path).hash(&mut hasher);
+    shared_predicates::manifests(cx).hash(&mut hasher);
+    if attr.check_name("check_trait_invariant") {
+        trait_invariant::recorded().hash(&mut hasher);
+    }
+    format!(
+        "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+        policy.level,
+        policy.on_violation,
+        policy.backend,
+        policy.old,
+        policy.closure_return,
+        policy.predicate_panics,
+        policy.sample,
+        policy.tag.map(|tag| tag.to_string())
+    ).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn store(cx: &ExtCtxt, path: &Path, items: &[Annotatable]) {
+    let mut code = format!("// {}\n", super::run_count());
+    for item in items {
+        let separator = match *item {
+            Annotatable::Item(_) => ITEM,
+            Annotatable::ImplItem(_) => IMPL_ITEM,
+            Annotatable::TraitItem(_) => TRAIT_ITEM,
+        };
+        code = code + separator + "\n" + &to_string(item) + "\n";
+    }
+
+    // Write to a temporary file (with a random name, since `process::id` is
+    // unstable) and rename it, so a concurrent build never reads half a file.
+    let random = RandomState::new().build_hasher().finish();
+    let tmp = path.with_extension(format!("{:016x}.tmp", random));
+    let result = fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| File::create(&tmp))
+        .and_then(|mut file| file.write_all(code.as_bytes()))
+        .and_then(|_| fs::rename(&tmp, path));
+    if let Err(e) = result {
+        cx.span_warn(
+            cx.call_site(),
+            &format!("could not cache expansion in {}: {}", path.display(), e),
+        );
+    }
+}
+
+// The cached items, or None if they are not cached (or can't be parsed).
+fn load(cx: &ExtCtxt, path: &Path) -> Option<Vec<Annotatable>> {
+    let mut code = String::new();
+    if File::open(path).and_then(|mut file| file.read_to_string(&mut code)).is_err() {
+        return None;
+    }
+    let mut lines = code.lines();
+    let run_count = match lines.next().map(|line| line.trim_left_matches("// ").parse()) {
+        Some(Ok(run_count)) => run_count,
+        _ => return None,
+    };
+
+    let mut chunks: Vec<(&str, String)> = Vec::new();
+    for line in lines {
+        if line == ITEM || line == IMPL_ITEM || line == TRAIT_ITEM {
+            chunks.push((line, String::new()));
+        } else {
+            match chunks.last_mut() {
+                Some(&mut (_, ref mut chunk)) => {
+                    chunk.push_str(line);
+                    chunk.push('\n');
+                }
+                None => return None,
+            }
+        }
+    }
+
+    let name = path.display().to_string();
+    let mut items = Vec::new();
+    for (kind, chunk) in chunks {
+        let item = match kind {
+            IMPL_ITEM => parse_impl_item(cx, &name, chunk).map(Annotatable::ImplItem),
+            TRAIT_ITEM => parse_trait_item(cx, &name, chunk).map(Annotatable::TraitItem),
+            _ => parse(cx, &name, chunk).map(Annotatable::Item),
+        };
+        match item {
+            Some(item) => items.push(item),
+            None => return None,
+        }
+    }
+
+    // Later expansions must not reuse the names in the cached code.
+    super::raise_run_count(run_count);
+    Some(items)
+}
+
+fn parse(cx: &ExtCtxt, name: &str, code: String) -> Option<P<ast::Item>> {
+    match parse::parse_item_from_source_str(name.to_owned(), code, cx.parse_sess) {
+        Ok(item) => item,
+        Err(mut e) => {
+            e.cancel();
+            None
+        }
+    }
+}
+
+// Impl and trait items are parsed inside an impl or trait.
+fn parse_impl_item(cx: &ExtCtxt, name: &str, code: String) -> Option<P<ast::ImplItem>> {
+    let item = match parse(cx, name, format!("impl __HoareCached {{\n{}}}", code)) {
+        Some(item) => item,
+        None => return None,
+    };
+    match item.node {
+        ast::ItemKind::Impl(_, _, _, _, _, _, ref impl_items) if impl_items.len() == 1 => {
+            Some(P(impl_items[0].clone()))
+        }
+        _ => None,
+    }
+}
+
+fn parse_trait_item(cx: &ExtCtxt, name: &str, code: String) -> Option<P<ast::TraitItem>> {
+    let item = match parse(cx, name, format!("trait __HoareCached {{\n{}}}", code)) {
+        Some(item) => item,
+        None => return None,
+    };
+    match item.node {
+        ast::ItemKind::Trait(_, _, _, ref trait_items) if trait_items.len() == 1 => {
+            Some(P(trait_items[0].clone()))
+        }
+        _ => None,
+    }
+}
+
+fn to_string(item: &Annotatable) -> String {
+    match *item {
+        Annotatable::Item(ref item) => pprust::item_to_string(item),
+        Annotatable::ImplItem(ref item) => pprust::impl_item_to_string(item),
+        Annotatable::TraitItem(ref item) => pprust::trait_item_to_string(item),
+    }
+}
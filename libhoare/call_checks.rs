@@ -0,0 +1,331 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Checks of preconditions at call sites, see the `Checking preconditions at
+// call sites` section of ../readme.md.
+//
+// When compiling with `--cfg hoare_call_checks`, the preconditions of public
+// functions are written to a manifest (`<crate name>.contracts` in the
+// contracts directory), whether or not they are checked in the build, as lines
+// `fn_path \t arg, arg, .. \t predicate`. `#[check_calls]` on a module or
+// function of another crate then rewrites each call `f(a, b)` of a function in
+// a manifest (by its full path, or a name imported by `use`) to
+//
+// match (a, b) {
+//     (x, y) => {
+//         if !(pred) { panic!(..) }
+//         f(x, y)
+//     }
+// }
+//
+// in debug builds, so the arguments are evaluated once, in order, before the
+// parameters' names are bound.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use syntax::ast::{self, MetaItem};
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::fold::{self, Folder};
+use syntax::ptr::P;
+
+use manifest;
+
+pub const DIR_VAR: &'static str = "HOARE_CONTRACTS_DIR";
+
+// The preconditions of the functions of other crates, by path.
+type Contracts = HashMap<String, Vec<Precond>>;
+
+thread_local! {
+    static CONTRACTS: RefCell<Option<Rc<Contracts>>> = RefCell::new(None);
+}
+
+struct Precond {
+    args: Vec<String>,
+    pred: String,
+}
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_call_checks")
+}
+
+fn dir() -> PathBuf {
+    manifest::dir(DIR_VAR, "target/hoare-contracts")
+}
+
+// Writes attr to the manifest, if it is a precondition (or debug precondition)
+// and item is a public function whose arguments are all names. Predicates which
+// are not a string or `pred` (e.g., float clauses) are not written.
+pub fn export(cx: &ExtCtxt, attr: &MetaItem, item: &Annotatable) {
+    if !attr.check_name("precond") && !attr.check_name("debug_precond") {
+        return;
+    }
+    let item = match *item {
+        Annotatable::Item(ref item) if item.vis == ast::Visibility::Public => item,
+        _ => return,
+    };
+    let decl = match item.node {
+        ast::ItemKind::Fn(ref decl, ..) => decl,
+        _ => return,
+    };
+    let pred = match attr.value_str().or_else(|| super::pred_item(attr)) {
+        Some(pred) => pred,
+        None => return,
+    };
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        match arg.pat.node {
+            ast::PatKind::Ident(_, ref ident, None) => args.push(ident.node.to_string()),
+            _ => return,
+        }
+    }
+
+    let fn_path = manifest::fn_path(cx, &item.ident.name.as_str());
+    let pred = pred.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+    manifest::append(
+        cx,
+        &dir(),
+        "contracts",
+        &format!("{}\t{}\t{}", fn_path, args.join(", "), pred),
+    );
+}
+
+// `#[check_calls]`
+pub fn check_calls(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        _ => {
+            cx.span_err(sp, "#[check_calls] must be used on a module or function");
+            return vec![item];
+        }
+    };
+    match item.node {
+        ast::ItemKind::Mod(_) | ast::ItemKind::Fn(..) => {}
+        _ => {
+            cx.span_err(sp, "#[check_calls] must be used on a module or function");
+            return vec![Annotatable::Item(item)];
+        }
+    }
+    if !enabled(cx) || !super::has_cfg(cx, "debug_assertions") {
+        return vec![Annotatable::Item(item)];
+    }
+
+    let contracts = contracts(cx);
+    let mut folder = CallFolder {
+        cx: cx,
+        contracts: &contracts,
+        scopes: Vec::new(),
+    };
+    folder
+        .fold_item(item)
+        .into_iter()
+        .map(Annotatable::Item)
+        .collect()
+}
+
+fn contracts(cx: &ExtCtxt) -> Rc<Contracts> {
+    if let Some(contracts) = CONTRACTS.with(|contracts| contracts.borrow().clone()) {
+        return contracts;
+    }
+    let contracts = Rc::new(read_contracts(cx));
+    CONTRACTS.with(|c| *c.borrow_mut() = Some(contracts.clone()));
+    contracts
+}
+
+// The manifests of all crates other than this one.
+fn read_contracts(cx: &ExtCtxt) -> Contracts {
+    let mut contracts = Contracts::new();
+    let own = format!("{}.contracts", cx.ecfg.crate_name);
+    let entries = match fs::read_dir(dir()) {
+        Ok(entries) => entries,
+        Err(_) => return contracts,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+        if path.extension().map_or(true, |ext| ext != "contracts") || name == Some(own.clone()) {
+            continue;
+        }
+        let mut src = String::new();
+        if let Err(e) = File::open(&path).and_then(|mut file| file.read_to_string(&mut src)) {
+            cx.span_warn(
+                cx.call_site(),
+                &format!("could not read contracts {}: {}", path.display(), e),
+            );
+            continue;
+        }
+        for line in src.lines() {
+            let fields: Vec<&str> = line.splitn(3, '\t').collect();
+            if fields.len() != 3 {
+                continue;
+            }
+            let args = fields[1]
+                .split(", ")
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| arg.to_owned())
+                .collect();
+            contracts.entry(fields[0].to_owned()).or_insert(Vec::new()).push(Precond {
+                args: args,
+                pred: fields[2].to_owned(),
+            });
+        }
+    }
+    contracts
+}
+
+struct CallFolder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    contracts: &'a Contracts,
+    // The names imported by `use` in each enclosing module or block, to their
+    // paths.
+    scopes: Vec<HashMap<String, String>>,
+}
+
+impl<'a, 'b> CallFolder<'a, 'b> {
+    // The full path of the function called by path, resolving its first
+    // segment through `use` declarations.
+    fn resolve(&self, path: &ast::Path) -> String {
+        let mut segments: Vec<String> = path.segments
+            .iter()
+            .map(|segment| segment.identifier.to_string())
+            .filter(|segment| segment != "{{root}}")
+            .collect();
+        if let Some(first) = segments.first().cloned() {
+            if let Some(used) = self.scopes.iter().rev().filter_map(|s| s.get(&first)).next() {
+                segments[0] = used.clone();
+            }
+        }
+        segments.join("::")
+    }
+
+    // call with the preconditions of the called function checked.
+    fn checked_call(&self, call: P<ast::Expr>) -> P<ast::Expr> {
+        let called = match call.node {
+            ast::ExprKind::Call(ref f, ref args) => Some((f.clone(), args.clone())),
+            _ => None,
+        };
+        let (f, args) = match called {
+            Some(called) => called,
+            None => return call,
+        };
+        let fn_path = match f.node {
+            ast::ExprKind::Path(None, ref path) => self.resolve(path),
+            _ => return call,
+        };
+        let preconds: Vec<&Precond> = match self.contracts.get(&fn_path) {
+            Some(preconds) => {
+                preconds.iter().filter(|precond| precond.args.len() == args.len()).collect()
+            }
+            None => return call,
+        };
+        if preconds.is_empty() {
+            return call;
+        }
+
+        let cx = self.cx;
+        let sp = call.span;
+        // All the preconditions of a function have the same arguments.
+        let idents: Vec<ast::Ident> =
+            preconds[0].args.iter().map(|name| cx.ident_of(name)).collect();
+        let mut stmts = Vec::new();
+        for precond in preconds {
+            let pred = cx.parse_expr(precond.pred.clone());
+            let msg = &format!(
+                "precondition of {} ({}) checked by the caller",
+                fn_path,
+                precond.pred
+            );
+            stmts.push(quote_stmt!(cx, if !($pred) { panic!("{}", $msg); }).unwrap());
+        }
+        let arg_exprs = idents.iter().map(|&ident| cx.expr_ident(sp, ident)).collect();
+        stmts.push(cx.stmt_expr(cx.expr_call(sp, f, arg_exprs)));
+        let pats = idents.iter().map(|&ident| cx.pat_ident(sp, ident)).collect();
+        let arm = cx.arm(sp, vec![cx.pat_tuple(sp, pats)], cx.expr_block(cx.block(sp, stmts)));
+        cx.expr_match(sp, cx.expr_tuple(sp, args), vec![arm])
+    }
+
+    fn push_uses(&mut self, items: &[&ast::Item]) {
+        let mut uses = HashMap::new();
+        for item in items {
+            let view_path = match item.node {
+                ast::ItemKind::Use(ref view_path) => view_path,
+                _ => continue,
+            };
+            match view_path.node {
+                ast::ViewPathSimple(ident, ref path) => {
+                    uses.insert(ident.to_string(), self.resolve(path));
+                }
+                ast::ViewPathList(ref prefix, ref list) => {
+                    let prefix = self.resolve(prefix);
+                    for item in list {
+                        let name = item.node.name.to_string();
+                        // `use a::b::{self}` imports `b`.
+                        let (local, path) = if name == "self" {
+                            let last = prefix.rsplit("::").next().unwrap_or("").to_owned();
+                            (last, prefix.clone())
+                        } else {
+                            (name.clone(), format!("{}::{}", prefix, name))
+                        };
+                        let local = item.node.rename.map_or(local, |rename| rename.to_string());
+                        uses.insert(local, path);
+                    }
+                }
+                ast::ViewPathGlob(_) => {}
+            }
+        }
+        self.scopes.push(uses);
+    }
+}
+
+impl<'a, 'b> Folder for CallFolder<'a, 'b> {
+    fn fold_mod(&mut self, m: ast::Mod) -> ast::Mod {
+        self.push_uses(&m.items.iter().map(|item| &**item).collect::<Vec<_>>());
+        let m = fold::noop_fold_mod(m, self);
+        self.scopes.pop();
+        m
+    }
+
+    fn fold_block(&mut self, block: P<ast::Block>) -> P<ast::Block> {
+        {
+            let items: Vec<&ast::Item> = block.stmts
+                .iter()
+                .filter_map(|stmt| match stmt.node {
+                    ast::StmtKind::Item(ref item) => Some(&**item),
+                    _ => None,
+                })
+                .collect();
+            self.push_uses(&items);
+        }
+        let block = fold::noop_fold_block(block, self);
+        self.scopes.pop();
+        block
+    }
+
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        let e = e.map(|e| fold::noop_fold_expr(e, self));
+        self.checked_call(e)
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
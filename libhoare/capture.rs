@@ -0,0 +1,67 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Capture of inputs which violate postconditions, see the `Capturing violating
+// inputs` section of ../readme.md.
+//
+// `#[capture_violations]` is an inert attribute which stays on the item while
+// the contracts are expanded, so it works wherever it is placed. In debug
+// builds, functions with postconditions (or invariants) serialize their
+// arguments on entry and, if a postcondition fails, write them to the corpus
+// directory before panicking.
+
+use syntax::ast;
+use syntax::attr;
+use syntax::codemap::Span;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+
+use manifest;
+
+pub fn enabled(cx: &ExtCtxt, attrs: &[ast::Attribute]) -> bool {
+    attr::contains_name(attrs, "capture_violations") && super::has_cfg(cx, "debug_assertions")
+}
+
+// `let __hoare_capture_N = libhoare::corpus::serialize(&(&a, &b, ..));` for
+// the function's arguments (other than self).
+pub fn snapshot(cx: &ExtCtxt, sp: Span, decl: &ast::FnDecl) -> Result<ast::Stmt, ()> {
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        if arg.is_self() {
+            continue;
+        }
+        match arg.pat.node {
+            ast::PatKind::Ident(_, ref ident, None) => {
+                args.push(cx.expr_addr_of(sp, cx.expr_ident(sp, ident.node)))
+            }
+            _ => {
+                cx.span_err(
+                    arg.pat.span,
+                    "#[capture_violations] requires function arguments to be plain identifiers",
+                );
+                return Err(());
+            }
+        }
+    }
+
+    let args = cx.expr_addr_of(sp, cx.expr_tuple(sp, args));
+    let name = super::capture_name();
+    Ok(quote_stmt!(cx, let $name = ::libhoare::corpus::serialize($args);).unwrap())
+}
+
+// Writes the arguments kept by snapshot to the corpus.
+pub fn record(cx: &ExtCtxt, fn_name: &str) -> ast::Stmt {
+    let dir = manifest::dir("HOARE_CORPUS_DIR", "target/hoare-corpus");
+    let dir = dir.to_string_lossy().into_owned();
+    let dir = &dir.replace("\"", "\\\"");
+    let fn_path = &manifest::fn_path(cx, fn_name);
+    let name = super::capture_name();
+    quote_stmt!(cx, ::libhoare::corpus::capture($dir, $fn_path, &$name);).unwrap()
+}
@@ -0,0 +1,83 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Conditional compilation, see the `Conditional compilation` section of
+// ../readme.md.
+//
+// The compiler evaluates the `#[cfg]`s on an item before expanding its
+// attributes, but those inside it (on fields, statements, methods, etc.) only
+// afterwards. So every attribute of the plugin is wrapped in `Configured`,
+// which removes what is not compiled in this configuration from the item (and
+// evaluates its `#[cfg_attr]`s) first, the same way the compiler would.
+// Otherwise, e.g., setters would be generated for fields which don't exist, and
+// a `return` in a statement which is not compiled would still change how the
+// body is rewritten.
+
+use syntax::ast::{self, MetaItem};
+use syntax::codemap::Span;
+use syntax::config::StripUnconfigured;
+use syntax::ext::base::{Annotatable, ExtCtxt, MultiItemModifier};
+use syntax::fold::Folder;
+use syntax::ptr::P;
+
+use spec_coverage;
+
+pub struct Configured<M>(pub M);
+
+impl<M: MultiItemModifier> MultiItemModifier for Configured<M> {
+    fn expand(
+        &self,
+        cx: &mut ExtCtxt,
+        sp: Span,
+        attr: &MetaItem,
+        item: Annotatable,
+    ) -> Vec<Annotatable> {
+        if spec_coverage::enabled(cx) {
+            spec_coverage::record(cx);
+        }
+        match configure(cx, item) {
+            Some(item) => self.0.expand(cx, sp, attr, item),
+            None => Vec::new(),
+        }
+    }
+}
+
+// item as it is compiled in this configuration, or None if it is not.
+pub fn configure(cx: &ExtCtxt, item: Annotatable) -> Option<Annotatable> {
+    let mut strip = strip_unconfigured(cx);
+    match item {
+        Annotatable::Item(item) => {
+            strip.fold_item(item).into_iter().next().map(Annotatable::Item)
+        }
+        Annotatable::ImplItem(item) => strip
+            .fold_impl_item((*item).clone())
+            .into_iter()
+            .next()
+            .map(|item| Annotatable::ImplItem(P(item))),
+        Annotatable::TraitItem(item) => strip
+            .fold_trait_item((*item).clone())
+            .into_iter()
+            .next()
+            .map(|item| Annotatable::TraitItem(P(item))),
+    }
+}
+
+// krate as it is compiled in this configuration.
+pub fn configure_crate(cx: &ExtCtxt, krate: ast::Crate) -> ast::Crate {
+    strip_unconfigured(cx).fold_crate(krate)
+}
+
+fn strip_unconfigured<'a>(cx: &ExtCtxt<'a>) -> StripUnconfigured<'a> {
+    StripUnconfigured {
+        should_test: cx.ecfg.should_test,
+        sess: cx.parse_sess,
+        features: cx.ecfg.features,
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Checking constructors establish invariants, see the `Struct invariants`
+// section of ../readme.md.
+//
+// `#[check_constructors]` on an inherent impl of a struct with an invariant
+// (see refinement.rs, which generates `__hoare_invariant`) finds the
+// associated functions which do not take `self` and return `Self` (or
+// `Result<Self, _>` or `Option<Self>`), and checks the invariant of the value
+// they return.
+//
+// `#[establishes_invariant_of="Target"]` on a method (usually the `build`
+// method of a builder) checks the invariant of the `Target` (or
+// `Result<Target, _>` or `Option<Target>`) it returns in the same way.
+//
+// `invariant(e)` in the list of a pre- or postcondition is short for
+// `(e).__hoare_invariant()`, so `#[postcond(invariant(return))]` checks the
+// invariant of the value returned by any function, including associated
+// functions of traits such as `fn new() -> Self`.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use graph;
+
+// How a constructor returns the constructed value.
+pub enum Returns {
+    Value,
+    Result,
+    Option,
+}
+
+pub fn check_constructors(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[check_constructors] can only be used on inherent impls");
+            return item;
+        }
+    };
+    let (ty, impl_items) = match item.node {
+        ast::ItemKind::Impl(_, _, _, _, None, ref ty, ref impl_items) => {
+            (ty.clone(), impl_items.clone())
+        }
+        _ => {
+            cx.span_err(sp, "#[check_constructors] can only be used on inherent impls");
+            return Annotatable::Item(item);
+        }
+    };
+
+    let type_name = pprust::ty_to_string(&ty);
+    let mut found = false;
+    let impl_items = impl_items
+        .into_iter()
+        .map(|impl_item| {
+            let returns = match impl_item.node {
+                ast::ImplItemKind::Method(ref sig, _) if !sig.decl.has_self() => {
+                    match sig.decl.output {
+                        ast::FunctionRetTy::Ty(ref ret) => returns(&["Self", &type_name], ret),
+                        ast::FunctionRetTy::Default(_) => None,
+                    }
+                }
+                _ => None,
+            };
+            match returns {
+                Some(returns) => {
+                    found = true;
+                    super::new_run(cx, |cx| check_method(cx, impl_item, &type_name, returns))
+                }
+                None => impl_item,
+            }
+        })
+        .collect();
+    if !found {
+        cx.span_warn(sp, &format!("#[check_constructors] found no constructors of {}", type_name));
+    }
+
+    Annotatable::Item(item.map(|mut item| {
+        if let ast::ItemKind::Impl(_, _, _, _, _, _, ref mut items) = item.node {
+            *items = impl_items;
+        }
+        item
+    }))
+}
+
+pub fn establishes_invariant_of(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    let target = match attr.value_str() {
+        Some(target) => target.to_string(),
+        None => {
+            cx.span_err(sp, "expected #[establishes_invariant_of=\"Type\"]");
+            return item;
+        }
+    };
+    let impl_item = match item {
+        Annotatable::ImplItem(impl_item) => impl_item,
+        item => {
+            cx.span_err(sp, "#[establishes_invariant_of] can only be used on methods");
+            return item;
+        }
+    };
+
+    let returns = match impl_item.node {
+        ast::ImplItemKind::Method(ref sig, _) => match sig.decl.output {
+            ast::FunctionRetTy::Ty(ref ret) => returns(&[&target], ret),
+            ast::FunctionRetTy::Default(_) => None,
+        },
+        _ => None,
+    };
+    match returns {
+        Some(returns) => {
+            let impl_item = (*impl_item).clone();
+            let impl_item = super::new_run(cx, |cx| check_method(cx, impl_item, &target, returns));
+            Annotatable::ImplItem(P(impl_item))
+        }
+        None => {
+            cx.span_err(
+                sp,
+                &format!(
+                    "#[establishes_invariant_of=\"{0}\"] must be used on a method which returns \
+                     {0}, Result<{0}, _>, or Option<{0}>",
+                    target
+                ),
+            );
+            Annotatable::ImplItem(impl_item)
+        }
+    }
+}
+
+// Wraps the body of a constructor which returns a value of type_name (as
+// described by returns) to check the invariant of that value.
+pub fn check_method(
+    cx: &mut ExtCtxt,
+    impl_item: ast::ImplItem,
+    type_name: &str,
+    returns: Returns,
+) -> ast::ImplItem {
+    let (sig, body) = match impl_item.node {
+        ast::ImplItemKind::Method(ref sig, ref body) => (sig.clone(), body.clone()),
+        _ => return impl_item,
+    };
+    let sp = impl_item.span;
+    let fn_name = &*impl_item.ident.name.as_str();
+    if graph::enabled(cx) {
+        graph::establishes(cx, fn_name, type_name);
+    }
+    let label = format!("invariant of {} not established by {}", type_name, fn_name);
+    let label = &*label;
+    let result_name = super::result_name();
+
+    let check = quote_stmt!(cx,
+        if !__hoare_value.__hoare_invariant() {
+            panic!("{}", $label);
+        }
+    ).unwrap();
+    let check = match returns {
+        Returns::Value => quote_stmt!(cx, { let __hoare_value = &$result_name; $check }),
+        Returns::Result => quote_stmt!(cx, if let Ok(ref __hoare_value) = $result_name { $check }),
+        Returns::Option => quote_stmt!(cx, if let Some(ref __hoare_value) = $result_name { $check }),
+    }.unwrap();
+
+    let mut stmts = Vec::new();
+    stmts.extend(super::bind_result(cx, &body, sp, &sig.decl.output));
+    stmts.push(check);
+    let body = super::fn_body(cx, stmts, sp);
+
+    ast::ImplItem {
+        node: ast::ImplItemKind::Method(sig, body),
+        ..impl_item
+    }
+}
+
+// Whether ret is one of names (e.g., `Self` and the name of the type), or one
+// of those wrapped in a `Result` or `Option`.
+pub fn returns(names: &[&str], ret: &P<ast::Ty>) -> Option<Returns> {
+    let is_type = |ty: &ast::Ty| {
+        let ty = pprust::ty_to_string(ty);
+        names.iter().any(|name| *name == ty)
+    };
+    if is_type(ret) {
+        return Some(Returns::Value);
+    }
+
+    let path = match ret.node {
+        ast::TyKind::Path(None, ref path) => path,
+        _ => return None,
+    };
+    let segment = path.segments.last().unwrap();
+    let first_param = match segment.parameters {
+        Some(ref parameters) => match **parameters {
+            ast::PathParameters::AngleBracketed(ref data) if !data.types.is_empty() => {
+                data.types[0].clone()
+            }
+            _ => return None,
+        },
+        None => return None,
+    };
+    if !is_type(&first_param) {
+        return None;
+    }
+    match &*segment.identifier.name.as_str() {
+        "Result" => Some(Returns::Result),
+        "Option" => Some(Returns::Option),
+        _ => None,
+    }
+}
+
+// Whether item is an `invariant(e)` clause.
+pub fn is_invariant_clause(item: &ast::NestedMetaItem) -> bool {
+    item.check_name("invariant")
+}
+
+// The predicate for an `invariant(e)` clause.
+pub fn invariant_clause(cx: &ExtCtxt, item: &ast::NestedMetaItem) -> Result<String, ()> {
+    match item.meta_item_list() {
+        Some(args) if args.len() == 1 && args[0].is_word() => {
+            Ok(format!("({}).__hoare_invariant()", args[0].name().unwrap()))
+        }
+        _ => {
+            cx.span_err(item.span, "expected `invariant(return)` or `invariant(argument)`");
+            Err(())
+        }
+    }
+}
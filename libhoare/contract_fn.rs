@@ -0,0 +1,149 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contracts on closures, see the `Contracts on closures` section of
+// ../readme.md.
+//
+//     contract_fn!(|x: u32| x * 2, pre = "x < 100", post = "return % 2 == 0")
+//
+// expands to a closure with the same parameters which checks the
+// preconditions, calls the original closure, and checks the postconditions, so
+// that the contracts go wherever the closure does.
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::{DummyResult, ExtCtxt, MacEager, MacResult};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::parse::parser::Parser;
+use syntax::parse::token;
+use syntax::parse::PResult;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+use syntax::tokenstream::TokenTree;
+
+use returns;
+use strip;
+
+struct ContractFn {
+    closure: P<ast::Expr>,
+    name: String,
+    pres: Vec<String>,
+    posts: Vec<String>,
+}
+
+pub fn contract_fn(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    let contract_fn = {
+        let mut parser = cx.new_parser_from_tts(tts);
+        match parse_contract_fn(&mut parser) {
+            Ok(contract_fn) => contract_fn,
+            Err(mut e) => {
+                e.emit();
+                return DummyResult::expr(sp);
+            }
+        }
+    };
+
+    match super::new_run(cx, |cx| expand(cx, sp, contract_fn)) {
+        Some(expr) => MacEager::expr(expr),
+        None => DummyResult::expr(sp),
+    }
+}
+
+fn parse_contract_fn<'a>(parser: &mut Parser<'a>) -> PResult<'a, ContractFn> {
+    let closure = try!(parser.parse_expr());
+    let mut result = ContractFn {
+        closure: closure,
+        name: "closure".to_owned(),
+        pres: Vec::new(),
+        posts: Vec::new(),
+    };
+    while parser.eat(&token::Comma) {
+        if parser.token == token::Eof {
+            break;
+        }
+        let key = try!(parser.parse_ident());
+        try!(parser.expect(&token::Eq));
+        let (value, _) = try!(parser.parse_str());
+        let value = value.to_string();
+        match &*key.name.as_str() {
+            "pre" => result.pres.push(value),
+            "post" => result.posts.push(value),
+            "name" => result.name = value,
+            _ => {
+                return Err(parser.fatal(&format!(
+                    "expected `pre`, `post`, or `name`, found `{}`",
+                    key
+                )))
+            }
+        }
+    }
+    if parser.token != token::Eof {
+        return Err(parser.fatal("expected `,`"));
+    }
+    Ok(result)
+}
+
+fn expand(cx: &mut ExtCtxt, sp: Span, contract_fn: ContractFn) -> Option<P<ast::Expr>> {
+    let decl = match contract_fn.closure.node {
+        ast::ExprKind::Closure(_, ref decl, _, _) => decl.clone(),
+        _ => {
+            cx.span_err(sp, "the first argument of contract_fn! must be a closure");
+            return None;
+        }
+    };
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        match arg.pat.node {
+            ast::PatKind::Ident(_, ident, None) => args.push(cx.expr_ident(sp, ident.node)),
+            _ => {
+                cx.span_err(
+                    arg.pat.span,
+                    "contract_fn! only supports closures whose parameters are names",
+                );
+                return None;
+            }
+        }
+    }
+
+    if strip::stripped(cx) {
+        return Some(contract_fn.closure);
+    }
+
+    let fn_name = Symbol::intern(&contract_fn.name).as_str();
+    let result_name = super::result_name();
+    let closure_name = cx.ident_of("__hoare_closure");
+
+    let mut stmts = Vec::new();
+    for pred_str in contract_fn.pres {
+        let pred = cx.parse_expr(pred_str.clone());
+        stmts.push(super::assert(cx, "precondition of", &fn_name, pred, &pred_str, None));
+    }
+    let call = cx.expr_call(sp, cx.expr_ident(sp, closure_name), args);
+    stmts.push(cx.stmt_let(sp, false, result_name, call));
+    for pred_str in contract_fn.posts {
+        let pred_str = returns::rename(cx, &pred_str);
+        let pred = cx.parse_expr(pred_str.clone());
+        stmts.push(super::assert(cx, "postcondition of", &fn_name, pred, &pred_str, None));
+    }
+    let body = super::fn_body(cx, stmts, sp);
+
+    let wrapper = cx.expr(
+        sp,
+        ast::ExprKind::Closure(ast::CaptureBy::Value, decl, cx.expr_block(body), sp),
+    );
+    let closure = contract_fn.closure;
+    Some(quote_expr!(cx, {
+        // Mutable in case the closure is `FnMut`.
+        #[allow(unused_mut)]
+        let mut $closure_name = $closure;
+        $wrapper
+    }))
+}
@@ -0,0 +1,212 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Named sets of contracts, see the `Contract sets` section of ../readme.md.
+//
+//     contract_set! io_safety {
+//         pre: "self.is_open()",
+//         invariant: "self.buffered() <= MAX_BUFFER",
+//     }
+//
+// records the set (by its name) and expands to nothing.
+// `#[apply_contract_set(io_safety)]` on a function, method, trait, or impl
+// adds the set's contracts as attributes, `#[precond="self.is_open()"]`, etc.,
+// to the function, or to each method of the impl and each provided method of
+// the trait, which are then expanded as usual. A contract is not added to the
+// methods called in its predicate, since it would recurse, and, as for trait
+// invariants (see trait_invariant.rs), invariants are only added to methods
+// which take `&self` or `&mut self`.
+//
+// Plugins see one item at a time, so the set must be defined in the same crate,
+// and expanded before the items it is applied to, i.e., earlier in the crate.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use syntax::ast::{self, MetaItem};
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, DummyResult, ExtCtxt, MacEager, MacResult};
+use syntax::ext::build::AstBuilder;
+use syntax::parse::parser::Parser;
+use syntax::parse::token;
+use syntax::parse::PResult;
+use syntax::symbol::Symbol;
+use syntax::tokenstream::TokenTree;
+use syntax::util::small_vector::SmallVector;
+
+use trait_invariant;
+
+thread_local! {
+    // The contracts of each set, by name, as (attribute name, predicate).
+    static SETS: RefCell<HashMap<String, Vec<(&'static str, Symbol)>>> =
+        RefCell::new(HashMap::new());
+}
+
+// `contract_set! name { .. }`
+pub fn contract_set<'cx>(
+    cx: &'cx mut ExtCtxt,
+    sp: Span,
+    name: ast::Ident,
+    tts: Vec<TokenTree>,
+) -> Box<MacResult + 'cx> {
+    let contracts = {
+        let mut parser = cx.new_parser_from_tts(&tts);
+        match parse_contracts(&mut parser) {
+            Ok(contracts) => contracts,
+            Err(mut e) => {
+                e.emit();
+                return DummyResult::any(sp);
+            }
+        }
+    };
+    if contracts.is_empty() {
+        cx.span_err(sp, &format!("contract set `{}` has no contracts", name));
+        return DummyResult::any(sp);
+    }
+    SETS.with(|sets| sets.borrow_mut().insert(name.to_string(), contracts));
+    MacEager::items(SmallVector::new())
+}
+
+fn parse_contracts<'a>(parser: &mut Parser<'a>) -> PResult<'a, Vec<(&'static str, Symbol)>> {
+    let mut contracts = Vec::new();
+    while parser.token != token::Eof {
+        let key = try!(parser.parse_ident());
+        let attr_name = match &*key.name.as_str() {
+            "pre" => "precond",
+            "post" => "postcond",
+            "invariant" => "invariant",
+            _ => {
+                return Err(parser.fatal(&format!(
+                    "expected `pre`, `post`, or `invariant`, found `{}`",
+                    key
+                )))
+            }
+        };
+        try!(parser.expect(&token::Colon));
+        let (pred, _) = try!(parser.parse_str());
+        contracts.push((attr_name, pred));
+        if !parser.eat(&token::Comma) && parser.token != token::Eof {
+            return Err(parser.fatal("expected `,`"));
+        }
+    }
+    Ok(contracts)
+}
+
+// `#[apply_contract_set(name, ..)]`
+pub fn apply_contract_set(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    let mut contracts = Vec::new();
+    for set in attr.meta_item_list().unwrap_or(&[]) {
+        let name = match set.word() {
+            Some(word) => word.name.to_string(),
+            None => {
+                cx.span_err(sp, "expected #[apply_contract_set(name, ..)]");
+                return item;
+            }
+        };
+        match SETS.with(|sets| sets.borrow().get(&name).cloned()) {
+            Some(set) => contracts.extend(set),
+            None => {
+                cx.span_err(
+                    sp,
+                    &format!(
+                        "no contract set `{}`, or it is defined after this item or in another \
+                         crate",
+                        name
+                    ),
+                );
+                return item;
+            }
+        }
+    }
+    if contracts.is_empty() {
+        cx.span_err(sp, "expected #[apply_contract_set(name, ..)]");
+        return item;
+    }
+    // (contract, the methods called in its predicate)
+    let attrs: Vec<(MetaItem, Vec<ast::Name>)> = contracts
+        .into_iter()
+        .map(|(name, pred)| {
+            let attr = cx.meta_name_value(
+                sp,
+                Symbol::intern(name),
+                ast::LitKind::Str(pred, ast::StrStyle::Cooked),
+            );
+            let observers = trait_invariant::observers(cx, sp, &attr).unwrap_or(Vec::new());
+            (attr, observers)
+        })
+        .collect();
+
+    // The attributes to add to a method with decl.
+    let method_attrs = |decl: &ast::FnDecl, ident: ast::Ident| -> Vec<ast::Attribute> {
+        attrs
+            .iter()
+            .filter(|&&(ref attr, ref observers)| if attr.check_name("invariant") {
+                trait_invariant::checked(decl, ident, observers)
+            } else {
+                !observers.contains(&ident.name)
+            })
+            .map(|&(ref attr, _)| cx.attribute(sp, attr.clone()))
+            .collect()
+    };
+
+    match item {
+        Annotatable::Item(item) => {
+            let item = item.map(|mut item| {
+                match item.node {
+                    ast::ItemKind::Fn(..) => {
+                        let all = attrs.iter().map(|&(ref attr, _)| attr.clone());
+                        item.attrs.extend(all.map(|attr| cx.attribute(sp, attr)));
+                    }
+                    ast::ItemKind::Trait(_, _, _, ref mut items) => {
+                        for trait_item in items.iter_mut() {
+                            if let ast::TraitItemKind::Method(ref sig, Some(_)) = trait_item.node {
+                                let added = method_attrs(&sig.decl, trait_item.ident);
+                                trait_item.attrs.extend(added);
+                            }
+                        }
+                    }
+                    ast::ItemKind::Impl(_, _, _, _, _, _, ref mut items) => {
+                        for impl_item in items.iter_mut() {
+                            if let ast::ImplItemKind::Method(ref sig, _) = impl_item.node {
+                                let added = method_attrs(&sig.decl, impl_item.ident);
+                                impl_item.attrs.extend(added);
+                            }
+                        }
+                    }
+                    _ => cx.span_err(
+                        sp,
+                        "#[apply_contract_set] must be used on a function, method, trait, or impl",
+                    ),
+                }
+                item
+            });
+            Annotatable::Item(item)
+        }
+        Annotatable::ImplItem(item) => Annotatable::ImplItem(item.map(|mut item| {
+            if let ast::ImplItemKind::Method(ref sig, _) = item.node {
+                let added = method_attrs(&sig.decl, item.ident);
+                item.attrs.extend(added);
+            }
+            item
+        })),
+        Annotatable::TraitItem(item) => Annotatable::TraitItem(item.map(|mut item| {
+            if let ast::TraitItemKind::Method(ref sig, Some(_)) = item.node {
+                let added = method_attrs(&sig.decl, item.ident);
+                item.attrs.extend(added);
+            }
+            item
+        })),
+    }
+}
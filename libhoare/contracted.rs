@@ -0,0 +1,178 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contracts on trait objects, see the `Contracts on trait objects` section of
+// ../readme.md.
+//
+// Contracts on a trait's required methods can't be woven into the trait, and
+// callers of `dyn Trait` can't see the impl. For a `#[contracted]` trait we
+// remove the contracts from the required methods and instead generate
+//
+//     impl<__HoareT: ?Sized + Trait> Trait for ::libhoare::Contracted<Box<__HoareT>>
+//
+// where every method forwards to the wrapped object and carries the contracts
+// declared on the trait, so they are expanded as usual.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ptr::P;
+
+pub fn contracted(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[contracted] on non-trait item");
+            return vec![item];
+        }
+    };
+
+    let (trait_item, shims) = match item.node {
+        ast::ItemKind::Trait(ast::Unsafety::Normal, ref generics, ref bounds, ref items) => {
+            if generics.is_parameterized() {
+                cx.span_err(sp, "#[contracted] can not be used on generic traits");
+                return vec![Annotatable::Item(item.clone())];
+            }
+
+            let mut trait_items = Vec::new();
+            let mut shims = Vec::new();
+            for trait_item in items {
+                match trait_item.node {
+                    ast::TraitItemKind::Method(ref sig, ref body) => {
+                        let contracts = trait_item
+                            .attrs
+                            .iter()
+                            .filter(|attr| super::is_contract(attr))
+                            .cloned()
+                            .collect();
+                        match shim_method(cx, item.ident, trait_item, sig, contracts) {
+                            Some(shim) => shims.push(shim),
+                            None => return vec![Annotatable::Item(item.clone())],
+                        }
+
+                        // Provided methods keep their contracts, they are
+                        // checked in the default body as well as in the shim.
+                        let mut trait_item = trait_item.clone();
+                        if body.is_none() {
+                            trait_item.attrs.retain(|attr| !super::is_contract(attr));
+                        }
+                        trait_items.push(trait_item);
+                    }
+                    _ => {
+                        cx.span_err(
+                            trait_item.span,
+                            "#[contracted] traits can only contain methods",
+                        );
+                        return vec![Annotatable::Item(item.clone())];
+                    }
+                }
+            }
+
+            let trait_item = ast::Item {
+                node: ast::ItemKind::Trait(
+                    ast::Unsafety::Normal,
+                    generics.clone(),
+                    bounds.clone(),
+                    trait_items,
+                ),
+                ..(*item).clone()
+            };
+            (trait_item, shims)
+        }
+        _ => {
+            cx.span_err(sp, "#[contracted] can only be used on safe traits");
+            return vec![Annotatable::Item(item.clone())];
+        }
+    };
+
+    let trait_name = item.ident;
+    let shim = quote_item!(cx,
+        impl<__HoareT: ?Sized + $trait_name> $trait_name for ::libhoare::Contracted<Box<__HoareT>> {}
+    ).unwrap()
+        .map(|mut shim| {
+            if let ast::ItemKind::Impl(.., ref mut impl_items) = shim.node {
+                *impl_items = shims;
+            }
+            shim
+        });
+
+    vec![Annotatable::Item(P(trait_item)), Annotatable::Item(shim)]
+}
+
+// A method of the shim impl which forwards to the wrapped object, e.g.,
+// `fn foo(&self, x: i32) -> i32 { Trait::foo(&*self.0, x) }`.
+fn shim_method(
+    cx: &ExtCtxt,
+    trait_name: ast::Ident,
+    trait_item: &ast::TraitItem,
+    sig: &ast::MethodSig,
+    contracts: Vec<ast::Attribute>,
+) -> Option<ast::ImplItem> {
+    let sp = trait_item.span;
+    let receiver = match sig.decl.inputs.first().and_then(|arg| arg.to_self()) {
+        Some(ref self_kind) => {
+            match self_kind.node {
+                ast::SelfKind::Region(_, ast::Mutability::Immutable) => {
+                    quote_expr!(cx, &*self.0)
+                }
+                ast::SelfKind::Region(_, ast::Mutability::Mutable) => {
+                    quote_expr!(cx, &mut *self.0)
+                }
+                _ => {
+                    cx.span_err(
+                        sp,
+                        "#[contracted] only supports methods which take `&self` or `&mut self`",
+                    );
+                    return None;
+                }
+            }
+        }
+        None => {
+            cx.span_err(
+                sp,
+                "#[contracted] only supports methods which take `&self` or `&mut self`",
+            );
+            return None;
+        }
+    };
+
+    let mut args = vec![receiver];
+    for arg in &sig.decl.inputs[1..] {
+        match arg.pat.node {
+            ast::PatKind::Ident(_, ref ident, None) => args.push(cx.expr_ident(sp, ident.node)),
+            _ => {
+                cx.span_err(
+                    arg.pat.span,
+                    "#[contracted] requires method arguments to be plain identifiers",
+                );
+                return None;
+            }
+        }
+    }
+
+    let method = cx.expr_path(cx.path(sp, vec![trait_name, trait_item.ident]));
+    let body = cx.block_expr(cx.expr_call(sp, method, args));
+    Some(ast::ImplItem {
+        id: ast::DUMMY_NODE_ID,
+        ident: trait_item.ident,
+        vis: ast::Visibility::Inherited,
+        defaultness: ast::Defaultness::Final,
+        attrs: contracts,
+        node: ast::ImplItemKind::Method(sig.clone(), body),
+        span: sp,
+    })
+}
@@ -0,0 +1,77 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contract coverage, see the `Contract coverage` section of ../readme.md.
+//
+// When compiling with `--cfg hoare_coverage`, every contract check we generate
+// is written to a manifest (`<crate name>.contracts` in the coverage directory,
+// or `<crate name>.test.contracts` for the build of the crate's tests, see
+// manifest.rs) and the check itself records which branch of the predicate was
+// taken using `libhoare::coverage::record`. The runtime crate combines the two
+// into a report.
+//
+// Manifest lines have the form `cond_type \t fn_path \t branches \t predicate`,
+// where `branches` is the number of top-level disjuncts in the predicate.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ptr::P;
+
+use manifest;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_coverage")
+}
+
+// Replaces `assert!(pred, label)` with a check which also records the first
+// disjunct of pred to hold (or that none did). If the check fails, before_panic
+// is executed before panicking.
+pub fn assert(
+    cx: &ExtCtxt,
+    cond_type: &str,
+    fn_name: &str,
+    pred: P<ast::Expr>,
+    pred_str: &str,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> ast::Stmt {
+    let dir = manifest::dir("HOARE_COVERAGE_DIR", "target/hoare-coverage");
+    let fn_path = manifest::fn_path(cx, fn_name);
+    let pred_str = pred_str.split_whitespace().collect::<Vec<_>>().join(" ");
+    let branches = super::split_binary(&pred, ast::BinOpKind::Or);
+    manifest::append_per_build(
+        cx,
+        &dir,
+        "contracts",
+        &format!("{}\t{}\t{}\t{}", cond_type, fn_path, branches.len(), pred_str),
+    );
+
+    let mut branch = quote_expr!(cx, None);
+    for (i, disjunct) in branches.into_iter().enumerate().rev() {
+        branch = quote_expr!(cx, if $disjunct { Some($i) } else { $branch });
+    }
+
+    let dir = dir.to_string_lossy().into_owned();
+    let dir = &dir.replace("\"", "\\\"");
+    let fn_path = &fn_path;
+    let pred_str = &pred_str.replace("\"", "\\\"");
+    let check = match before_panic {
+        Some(before_panic) => {
+            quote_stmt!(cx, if __hoare_branch.is_none() { $before_panic panic!($label); })
+                .unwrap()
+        }
+        None => quote_stmt!(cx, assert!(__hoare_branch.is_some(), $label);).unwrap(),
+    };
+    quote_stmt!(cx, {
+        let __hoare_branch = $branch;
+        ::libhoare::coverage::record($dir, $cond_type, $fn_path, $pred_str, __hoare_branch);
+        $check
+    }).unwrap()
+}
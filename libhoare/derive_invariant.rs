@@ -0,0 +1,185 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// `#[derive(Invariant)]`, see the `Deriving invariants` section of
+// ../readme.md.
+//
+// The constraints in the `#[check(...)]` attributes on the fields of a struct
+// are turned into predicates using the predicate shorthands (see
+// predicates.rs), e.g., `#[check(range="0..100")]` on `x` becomes
+// `in_range!(self.x, 0..100)`. Together with the predicates in any
+// `#[check(pred="...")]` on the struct itself, these are the struct's
+// invariant, for which we generate the same items as refinement.rs, and a
+// `validate` method, which returns the first violated constraint as an `Err`.
+//
+// `#[invariant]` can not be used with the derive, since it might be expanded
+// first.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use refinement;
+
+// A constraint on one field.
+struct Check {
+    // The expression for the field, e.g., `self.x`.
+    field: String,
+    // (name of the function in libhoare::predicates, extra argument)
+    kind: (&'static str, Option<String>),
+}
+
+impl Check {
+    fn pred(&self) -> String {
+        match self.kind.1 {
+            Some(ref arg) => format!("{}!({}, {})", self.kind.0, self.field, arg),
+            None => format!("{}!({})", self.kind.0, self.field),
+        }
+    }
+}
+
+pub fn derive_invariant(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[derive(Invariant)] can only be used on structs");
+            return vec![item];
+        }
+    };
+    let fields = match item.node {
+        ast::ItemKind::Struct(ref data, _) => data.fields().to_vec(),
+        _ => {
+            cx.span_err(sp, "#[derive(Invariant)] can only be used on structs");
+            return vec![Annotatable::Item(item)];
+        }
+    };
+
+    let mut checks = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let name = match field.ident {
+            Some(ident) => format!("self.{}", ident),
+            None => format!("self.{}", i),
+        };
+        for attr in field.attrs.iter().filter(|attr| attr.check_name("check")) {
+            let items = match attr.meta_item_list() {
+                Some(items) => items,
+                None => {
+                    cx.span_err(attr.span, "expected #[check(...)]");
+                    continue;
+                }
+            };
+            for item in items {
+                let kind = if item.check_name("non_empty") && item.is_word() {
+                    ("non_empty", None)
+                } else if item.check_name("sorted") && item.is_word() {
+                    ("is_sorted", None)
+                } else if item.check_name("range") && item.value_str().is_some() {
+                    ("in_range", Some(item.value_str().unwrap().to_string()))
+                } else {
+                    cx.span_err(
+                        item.span,
+                        "expected `range=\"...\"`, `non_empty`, or `sorted` in #[check(...)]",
+                    );
+                    continue;
+                };
+                checks.push(Check { field: name.clone(), kind: kind });
+            }
+        }
+    }
+
+    let mut preds = Vec::new();
+    for attr in item.attrs.iter().filter(|attr| attr.check_name("check")) {
+        let pred = match attr.meta_item_list() {
+            Some(items) if items.len() == 1 && items[0].check_name("pred") => items[0].value_str(),
+            _ => None,
+        };
+        match pred {
+            Some(pred) => preds.push(pred.to_string()),
+            None => cx.span_err(attr.span, "expected #[check(pred=\"...\")] on a struct"),
+        }
+    }
+    if checks.is_empty() && preds.is_empty() {
+        cx.span_warn(sp, "#[derive(Invariant)] on a struct without constraints");
+    }
+
+    let validate = validate(cx, &item, &checks, &preds);
+    let item = item.map(|mut item| {
+        item.attrs.retain(|attr| !attr.check_name("check"));
+        match item.node {
+            ast::ItemKind::Struct(ast::VariantData::Struct(ref mut fields, _), _) |
+            ast::ItemKind::Struct(ast::VariantData::Tuple(ref mut fields, _), _) => {
+                for field in fields {
+                    field.attrs.retain(|attr| !attr.check_name("check"));
+                }
+            }
+            _ => {}
+        }
+        item
+    });
+
+    let mut all = checks.iter().map(Check::pred).collect::<Vec<_>>();
+    all.extend(preds);
+    if all.is_empty() {
+        all.push("true".to_owned());
+    }
+    let mut result = refinement::checked_struct(cx, sp, item, &all);
+    result.push(Annotatable::Item(validate));
+    result
+}
+
+// `validate`, which returns the message for the first violated constraint.
+fn validate(cx: &ExtCtxt, item: &ast::Item, checks: &[Check], preds: &[String]) -> P<ast::Item> {
+    let mut body = String::new();
+    for check in checks {
+        let args = match check.kind.1 {
+            Some(ref arg) => format!("&{}, &({})", check.field, arg),
+            None => format!("&{}", check.field),
+        };
+        body.push_str(&format!(
+            "if let Err(reason) = ::libhoare::predicates::{}({}) {{\n\
+             return Err(format!(\"{{}}: {{}}\", {:?}, reason));\n\
+             }}\n",
+            check.kind.0,
+            args,
+            check.pred()
+        ));
+    }
+    for pred in preds {
+        body.push_str(&format!(
+            "if !({}) {{ return Err({:?}.to_owned()); }}\n",
+            pred,
+            format!("{} does not hold", pred)
+        ));
+    }
+    cx.parse_item(format!(
+        "{header} {{\n\
+         /// Checks the invariant of `{name}`, returning the first constraint which is\n\
+         /// violated as an `Err`.\n\
+         #[allow(dead_code)]\n\
+         {vis} fn validate(&self) -> ::std::result::Result<(), String> {{\n\
+         {body}\
+         Ok(())\n\
+         }}\n\
+         }}",
+        header = refinement::impl_header(item, None),
+        name = item.ident,
+        vis = pprust::vis_to_string(&item.vis).trim(),
+        body = body
+    ))
+}
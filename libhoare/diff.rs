@@ -0,0 +1,72 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Rich messages for failed comparisons in test builds, see the `Failure
+// messages in tests` section of ../readme.md.
+//
+// Only with `--cfg hoare_diff`, since the checks use the runtime crate, which
+// test builds of a crate shouldn't need just because it has contracts. If a
+// predicate is a comparison, we evaluate each operand once and, if the
+// comparison fails, pass their `Debug` representations (if they have one) to
+// `libhoare::diff::comparison_failed`, which prints them and their diff.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+use syntax::ptr::P;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "test") && super::has_cfg(cx, "hoare_diff")
+}
+
+pub fn is_comparison(pred: &P<ast::Expr>) -> bool {
+    match pred.node {
+        ast::ExprKind::Paren(ref inner) => is_comparison(inner),
+        ast::ExprKind::Binary(op, _, _) => op.node.is_comparison(),
+        _ => false,
+    }
+}
+
+// The check for pred, which must be a comparison. If the check fails,
+// before_panic is executed before panicking.
+pub fn assert(
+    cx: &ExtCtxt,
+    pred: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> ast::Stmt {
+    let (op, lhs, rhs) = match pred.node {
+        ast::ExprKind::Paren(ref inner) => return assert(cx, inner, label, before_panic),
+        ast::ExprKind::Binary(op, ref lhs, ref rhs) => (op, lhs.clone(), rhs.clone()),
+        _ => panic!("not a comparison"),
+    };
+
+    let sp = pred.span;
+    let left = cx.expr_ident(sp, cx.ident_of("__hoare_left"));
+    let right = cx.expr_ident(sp, cx.ident_of("__hoare_right"));
+    let comparison = cx.expr_binary(sp, op.node, cx.expr_deref(sp, left), cx.expr_deref(sp, right));
+    let op_str = ast::BinOpKind::to_string(&op.node);
+    let before_panic = before_panic.into_iter().collect::<Vec<_>>();
+    quote_stmt!(cx, {
+        #[allow(unused_imports)]
+        use ::libhoare::diff::{DebugOperand, NoDebugOperand};
+        let __hoare_left = &$lhs;
+        let __hoare_right = &$rhs;
+        if !($comparison) {
+            $before_panic
+            ::libhoare::diff::comparison_failed(
+                $label,
+                $op_str,
+                (&::libhoare::diff::Operand(__hoare_left)).debug_operand(),
+                (&::libhoare::diff::Operand(__hoare_right)).debug_operand(),
+            );
+        }
+    }).unwrap()
+}
@@ -0,0 +1,56 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Dumping the code we generate, see the `Inspecting expanded code` section of
+// ../readme.md.
+//
+// If `HOARE_DUMP_EXPANSION` is set when compiling, each contracted function is
+// pretty-printed to `<dir>/<fn path>.rs` (with `::` replaced by `.`) after each
+// of its contracts is expanded, so the file ends up holding the function with
+// all its contracts expanded. Macros in the generated code (`assert!`, etc.)
+// are not expanded.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use syntax::ast;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::print::pprust;
+
+use manifest;
+
+pub const DIR_VAR: &'static str = "HOARE_DUMP_EXPANSION";
+
+pub fn expansion(cx: &ExtCtxt, ident: ast::Ident, item: &Annotatable) {
+    if env::var_os(DIR_VAR).is_none() {
+        return;
+    }
+
+    let dir = manifest::dir(DIR_VAR, "");
+    let fn_path = manifest::fn_path(cx, &ident.name.as_str());
+    let path: PathBuf = dir.join(format!("{}.rs", fn_path.replace("::", ".")));
+    let code = match item {
+        &Annotatable::Item(ref item) => pprust::item_to_string(item),
+        &Annotatable::ImplItem(ref item) => pprust::impl_item_to_string(item),
+        &Annotatable::TraitItem(ref item) => pprust::trait_item_to_string(item),
+    };
+
+    let result = fs::create_dir_all(&dir)
+        .and_then(|_| File::create(&path))
+        .and_then(|mut file| file.write_all((code + "\n").as_bytes()));
+    if let Err(e) = result {
+        cx.span_warn(
+            cx.call_site(),
+            &format!("could not dump expansion to {}: {}", path.display(), e),
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contracts about what a function does while it runs, rather than about its
+// inputs and outputs, see the `Effects` section of ../readme.md. Each wraps
+// the body of the function in code which uses the runtime crate, and is only
+// checked in debug builds.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+
+// `#[no_alloc]`, checks the function does not allocate (see
+// `libhoare::alloc`).
+pub fn no_alloc(cx: &mut ExtCtxt, sp: Span, _attr: &MetaItem, item: Annotatable) -> Annotatable {
+    around(cx, sp, item, "No alloc", |cx, fn_name, _decl, saved| {
+        Ok((
+            quote_stmt!(cx, let $saved = ::libhoare::alloc::allocations();).unwrap(),
+            quote_stmt!(cx, ::libhoare::alloc::check_no_alloc($saved, $fn_name);).unwrap(),
+        ))
+    })
+}
+
+// `#[max_duration="250us"]`, checks the function returns within the given
+// time (see `libhoare::duration`).
+pub fn max_duration(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let (limit, nanos) = match attr.value_str().map(|limit| limit.to_string()) {
+        Some(limit) => match parse_duration(&limit) {
+            Some(nanos) => (limit, nanos),
+            None => {
+                cx.span_err(sp, "expected a duration such as `250us`, `10ms`, or `2s`");
+                return item;
+            }
+        },
+        None => {
+            cx.span_err(sp, "expected #[max_duration=\"duration\"]");
+            return item;
+        }
+    };
+
+    around(cx, sp, item, "Max duration", |cx, fn_name, _decl, saved| {
+        let limit = &*limit;
+        Ok((
+            quote_stmt!(cx, let $saved = ::std::time::Instant::now();).unwrap(),
+            quote_stmt!(cx,
+                ::libhoare::duration::check_max_duration($saved, $nanos, $fn_name, $limit);
+            ).unwrap(),
+        ))
+    })
+}
+
+// `#[non_reentrant]` or `#[non_reentrant(per_object)]`, checks the function is
+// not entered again while it is executing on the same thread (on the same
+// object), see `libhoare::reentrancy`.
+pub fn non_reentrant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let per_object = match attr.meta_item_list() {
+        None => false,
+        Some(items) if items.len() == 1 && items[0].is_word() && items[0].check_name("per_object") => true,
+        Some(_) => {
+            cx.span_err(sp, "expected #[non_reentrant] or #[non_reentrant(per_object)]");
+            return item;
+        }
+    };
+
+    around(cx, sp, item, "Non reentrant", |cx, fn_name, decl, saved| {
+        let address = if !per_object {
+            quote_expr!(cx, 0)
+        } else {
+            match decl.inputs.first().and_then(|arg| arg.to_self()).map(|s| s.node) {
+                Some(ast::SelfKind::Region(..)) => {
+                    quote_expr!(cx, &*self as *const _ as *const () as usize)
+                }
+                _ => {
+                    cx.span_err(
+                        sp,
+                        "#[non_reentrant(per_object)] requires a method which takes `&self` or `&mut self`",
+                    );
+                    return Err(());
+                }
+            }
+        };
+        Ok((
+            quote_stmt!(cx,
+                let $saved = ::libhoare::reentrancy::enter(
+                    concat!(module_path!(), "::", $fn_name), $address, $fn_name);
+            ).unwrap(),
+            quote_stmt!(cx, ::std::mem::drop($saved);).unwrap(),
+        ))
+    })
+}
+
+// `#[no_panic]`, runs the body under `catch_unwind` and reports any panic as a
+// violation (see `libhoare::panics`).
+pub fn no_panic(cx: &mut ExtCtxt, sp: Span, _attr: &MetaItem, item: Annotatable) -> Annotatable {
+    super::if_debug(cx, |cx| {
+        super::map_fn_body(cx, sp, item.clone(), "No panic", |cx, ident, _attrs, decl, body| {
+            let fn_name = &*ident.name.as_str();
+            let ty = match decl.output {
+                ast::FunctionRetTy::Ty(ref ty) => ty.clone(),
+                ast::FunctionRetTy::Default(_) => quote_ty!(cx, ()),
+            };
+            // `return` in the body returns from the closure.
+            let body = body.clone();
+            let stmt = quote_stmt!(cx,
+                match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| -> $ty $body)) {
+                    Ok(result) => result,
+                    Err(payload) => ::libhoare::panics::no_panic_violated($fn_name, payload),
+                }
+            ).unwrap();
+            Ok(cx.block(sp, vec![stmt]))
+        })
+    }, item.clone())
+}
+
+// A duration with a unit, e.g., `250us`, in nanoseconds.
+fn parse_duration(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_digit(10)).unwrap_or(s.len());
+    let (n, unit) = s.split_at(split);
+    let n: u64 = match n.parse() {
+        Ok(n) => n,
+        Err(_) => return None,
+    };
+    let scale = match unit.trim() {
+        "ns" => 1,
+        "us" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        _ => return None,
+    };
+    n.checked_mul(scale)
+}
+
+// In debug builds, wraps the body of item with the statements returned by f,
+// the first of which is executed on entry to the function and the second on
+// leaving it. f is passed the function's name and declaration, and an
+// identifier for the first statement to save a value in.
+fn around<F>(cx: &mut ExtCtxt, sp: Span, item: Annotatable, kind: &str, f: F) -> Annotatable
+where
+    F: Fn(&mut ExtCtxt, &str, &ast::FnDecl, ast::Ident) -> Result<(ast::Stmt, ast::Stmt), ()>,
+{
+    super::if_debug(cx, |cx| super::new_run(cx, |cx| {
+        super::map_fn_body(cx, sp, item.clone(), kind, |cx, ident, _attrs, decl, body| {
+            let (enter, exit) = try!(f(cx, &ident.name.as_str(), decl, super::old_name(0)));
+            let mut stmts = Vec::new();
+            stmts.push(enter);
+            stmts.extend(super::bind_result(cx, body, sp, &decl.output));
+            stmts.push(exit);
+            Ok(super::fn_body(cx, stmts, sp))
+        })
+    }), item.clone())
+}
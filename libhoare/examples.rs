@@ -0,0 +1,384 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Generation of rustdoc examples from simple preconditions
+// (`#[contract_examples]`), see the `Documentation examples` section of
+// ../readme.md.
+//
+// A precondition is simple if it is a conjunction of comparisons between an
+// argument and a literal, where every argument is an integer, a float, a `&str`
+// or a `String`. We find argument values which satisfy all the comparisons
+// (and values which violate one of them) by trying a few candidates around the
+// literals.
+
+use std::cmp::Ordering;
+
+use syntax::ast;
+use syntax::ast::{BinOpKind, MetaItem};
+use syntax::codemap::Span;
+use syntax::ext::base::{ExtCtxt, Annotatable};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+use inputs;
+use manifest;
+
+// Unlike fuzzing, we only look at contracts which are always checked, since
+// the violating example relies on the check.
+const CHECKED_ENTRY_CONTRACTS: &'static [&'static str] = &["precond", "invariant"];
+
+pub fn contract_examples(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    match item {
+        Annotatable::Item(item) => {
+            let doc = match &item.node {
+                &ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
+                    if generics.is_parameterized() {
+                        cx.span_err(sp, "#[contract_examples] can not be used on generic functions");
+                        None
+                    } else if item.vis != ast::Visibility::Public {
+                        cx.span_warn(
+                            sp,
+                            "#[contract_examples] on a private function, documentation \
+                             examples can only call public functions",
+                        );
+                        None
+                    } else {
+                        inputs::fn_args(cx, decl, "#[contract_examples]")
+                            .and_then(|args| examples_doc(cx, sp, &item, &args))
+                    }
+                }
+                _ => {
+                    cx.span_err(sp, "#[contract_examples] on non-function item");
+                    None
+                }
+            };
+
+            match doc {
+                Some(doc) => {
+                    let doc = cx.meta_name_value(
+                        sp,
+                        Symbol::intern("doc"),
+                        ast::LitKind::Str(Symbol::intern(&doc), ast::StrStyle::Cooked),
+                    );
+                    let doc = cx.attribute(sp, doc);
+                    Annotatable::Item(item.map(|mut item| {
+                        item.attrs.push(doc);
+                        item
+                    }))
+                }
+                None => Annotatable::Item(item),
+            }
+        }
+        _ => {
+            cx.span_err(sp, "#[contract_examples] is only supported on free functions");
+            item
+        }
+    }
+}
+
+fn examples_doc(
+    cx: &ExtCtxt,
+    sp: Span,
+    item: &ast::Item,
+    args: &[(ast::Ident, P<ast::Ty>)],
+) -> Option<String> {
+    let kinds = match args.iter().map(|&(_, ref ty)| arg_kind(ty)).collect::<Option<Vec<_>>>() {
+        Some(kinds) => kinds,
+        None => {
+            cx.span_warn(
+                sp,
+                "#[contract_examples]: arguments must be integers, floats, `&str`, or `String`",
+            );
+            return None;
+        }
+    };
+
+    // (argument index, comparison operator, literal)
+    let mut comparisons = Vec::new();
+    for pred in inputs::entry_predicates(cx, &item.attrs, CHECKED_ENTRY_CONTRACTS) {
+        let pred = cx.parse_expr(pred);
+        for conjunct in super::split_binary(&pred, BinOpKind::And) {
+            match comparison(&conjunct, args) {
+                Some(c) => comparisons.push(c),
+                None => {
+                    cx.span_warn(
+                        sp,
+                        "#[contract_examples]: can only use comparisons between an argument \
+                         and a literal (an integer literal must be at most `i64::MAX`)",
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+    if comparisons.is_empty() {
+        cx.span_warn(sp, "#[contract_examples]: no preconditions found");
+        return None;
+    }
+
+    let constraints_on = |i: usize| -> Vec<(BinOpKind, Value)> {
+        comparisons
+            .iter()
+            .filter(|c| c.0 == i)
+            .map(|c| (c.1, c.2.clone()))
+            .collect()
+    };
+
+    let mut valid = Vec::new();
+    for (i, kind) in kinds.iter().enumerate() {
+        match choose(kind, &constraints_on(i), |v| constraints_on(i).iter().all(|c| holds(v, c))) {
+            Some(v) => valid.push(v),
+            None => {
+                cx.span_warn(
+                    sp,
+                    &format!(
+                        "#[contract_examples]: could not find a value for `{}` which \
+                         satisfies the precondition",
+                        args[i].0
+                    ),
+                );
+                return None;
+            }
+        }
+    }
+
+    // Violate the first comparison which can be, keep all other arguments
+    // valid.
+    let violating = comparisons
+        .iter()
+        .filter_map(|&(i, op, ref lit)| {
+            choose(&kinds[i], &constraints_on(i), |v| !holds(v, &(op, lit.clone()))).map(|v| {
+                let mut violating = valid.clone();
+                violating[i] = v;
+                violating
+            })
+        })
+        .next();
+
+    let fn_path = manifest::fn_path(cx, &item.ident.name.as_str());
+    let mut doc = format!(
+        "# Contract examples\n\
+         \n\
+         A call which satisfies the precondition:\n\
+         \n\
+         ```\n\
+         {}\n\
+         ```",
+        call(&fn_path, &kinds, &valid)
+    );
+    match violating {
+        Some(violating) => {
+            doc += &format!(
+                "\n\
+                 \n\
+                 A call which violates the precondition panics:\n\
+                 \n\
+                 ```should_panic\n\
+                 {}\n\
+                 ```",
+                call(&fn_path, &kinds, &violating)
+            );
+        }
+        None => cx.span_warn(
+            sp,
+            "#[contract_examples]: could not find arguments which violate the precondition, \
+             so there is no example of a call which panics",
+        ),
+    }
+    Some(doc)
+}
+
+enum ArgKind {
+    Int { signed: bool },
+    Float,
+    Str,
+    String,
+}
+
+#[derive(Clone, PartialEq, PartialOrd)]
+enum Value {
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+fn arg_kind(ty: &ast::Ty) -> Option<ArgKind> {
+    match ty.node {
+        ast::TyKind::Path(None, ref path) if path.segments.len() == 1 => {
+            match &*path.segments[0].identifier.name.as_str() {
+                "i8" | "i16" | "i32" | "i64" | "isize" => Some(ArgKind::Int { signed: true }),
+                "u8" | "u16" | "u32" | "u64" | "usize" => Some(ArgKind::Int { signed: false }),
+                "f32" | "f64" => Some(ArgKind::Float),
+                "String" => Some(ArgKind::String),
+                _ => None,
+            }
+        }
+        ast::TyKind::Rptr(_, ref mt)
+            if mt.mutbl == ast::Mutability::Immutable && inputs::is_str(&mt.ty) => {
+                Some(ArgKind::Str)
+            }
+        _ => None,
+    }
+}
+
+// Matches `arg op literal` or `literal op arg`, normalising to the former.
+fn comparison(
+    e: &ast::Expr,
+    args: &[(ast::Ident, P<ast::Ty>)],
+) -> Option<(usize, BinOpKind, Value)> {
+    let (op, lhs, rhs) = match e.node {
+        ast::ExprKind::Binary(op, ref lhs, ref rhs) if op.node.is_comparison() => (op.node, lhs, rhs),
+        ast::ExprKind::Paren(ref inner) => return comparison(inner, args),
+        _ => return None,
+    };
+
+    if let (Some(arg), Some(lit)) = (arg_index(lhs, args), literal(rhs)) {
+        return Some((arg, op, lit));
+    }
+    if let (Some(lit), Some(arg)) = (literal(lhs), arg_index(rhs, args)) {
+        let op = match op {
+            BinOpKind::Lt => BinOpKind::Gt,
+            BinOpKind::Le => BinOpKind::Ge,
+            BinOpKind::Gt => BinOpKind::Lt,
+            BinOpKind::Ge => BinOpKind::Le,
+            op => op,
+        };
+        return Some((arg, op, lit));
+    }
+    None
+}
+
+fn arg_index(e: &ast::Expr, args: &[(ast::Ident, P<ast::Ty>)]) -> Option<usize> {
+    match e.node {
+        ast::ExprKind::Path(None, ref path) if path.segments.len() == 1 => {
+            let name = path.segments[0].identifier.name;
+            args.iter().position(|&(ident, _)| ident.name == name)
+        }
+        _ => None,
+    }
+}
+
+fn literal(e: &ast::Expr) -> Option<Value> {
+    match e.node {
+        ast::ExprKind::Lit(ref lit) => {
+            match lit.node {
+                // Literals larger than `i64::MAX` are not supported.
+                ast::LitKind::Int(i, _) if i >> 63 == 0 => Some(Value::Int(i as i64)),
+                ast::LitKind::Float(f, _) |
+                ast::LitKind::FloatUnsuffixed(f) => f.as_str().parse().ok().map(Value::Float),
+                ast::LitKind::Str(s, _) => Some(Value::Str(s.to_string())),
+                _ => None,
+            }
+        }
+        ast::ExprKind::Unary(ast::UnOp::Neg, ref inner) => {
+            match literal(inner) {
+                Some(Value::Int(i)) => Some(Value::Int(-i)),
+                Some(Value::Float(f)) => Some(Value::Float(-f)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn holds(v: &Value, constraint: &(BinOpKind, Value)) -> bool {
+    let ord = match v.partial_cmp(&constraint.1) {
+        Some(ord) => ord,
+        None => return false,
+    };
+    match constraint.0 {
+        BinOpKind::Lt => ord == Ordering::Less,
+        BinOpKind::Le => ord != Ordering::Greater,
+        BinOpKind::Gt => ord == Ordering::Greater,
+        BinOpKind::Ge => ord != Ordering::Less,
+        BinOpKind::Eq => ord == Ordering::Equal,
+        BinOpKind::Ne => ord != Ordering::Equal,
+        _ => false,
+    }
+}
+
+// The first of a few candidates around the literals in constraints (which
+// match kind) which is accepted by ok.
+fn choose<F>(kind: &ArgKind, constraints: &[(BinOpKind, Value)], ok: F) -> Option<Value>
+where
+    F: Fn(&Value) -> bool,
+{
+    let mut candidates = Vec::new();
+    match *kind {
+        ArgKind::Int { signed } => {
+            candidates.push(Value::Int(0));
+            for &(_, ref lit) in constraints {
+                if let Value::Int(i) = *lit {
+                    let around = [i, i.saturating_add(1), i.saturating_sub(1)];
+                    candidates.extend(around.iter().map(|&i| Value::Int(i)));
+                }
+            }
+            if !signed {
+                candidates.retain(|v| *v >= Value::Int(0));
+            }
+        }
+        ArgKind::Float => {
+            candidates.push(Value::Float(0.0));
+            for &(_, ref lit) in constraints {
+                if let Value::Float(f) = *lit {
+                    candidates.extend([f, f + 1.0, f - 1.0].iter().map(|&f| Value::Float(f)));
+                }
+            }
+        }
+        ArgKind::Str | ArgKind::String => {
+            candidates.push(Value::Str(String::new()));
+            candidates.push(Value::Str("a".to_owned()));
+            for &(_, ref lit) in constraints {
+                if let Value::Str(ref s) = *lit {
+                    candidates.push(Value::Str(s.clone()));
+                    candidates.push(Value::Str(format!("{}a", s)));
+                }
+            }
+        }
+    }
+
+    // Comparing an integer argument with a float literal (or similar) does not
+    // type check, so don't try to make sense of it.
+    if constraints.iter().any(|c| !matches_kind(kind, &c.1)) {
+        return None;
+    }
+    candidates.into_iter().find(|v| ok(v))
+}
+
+fn matches_kind(kind: &ArgKind, v: &Value) -> bool {
+    match (kind, v) {
+        (&ArgKind::Int { .. }, &Value::Int(_)) |
+        (&ArgKind::Float, &Value::Float(_)) |
+        (&ArgKind::Str, &Value::Str(_)) |
+        (&ArgKind::String, &Value::Str(_)) => true,
+        _ => false,
+    }
+}
+
+fn call(fn_path: &str, kinds: &[ArgKind], values: &[Value]) -> String {
+    let args: Vec<String> = kinds
+        .iter()
+        .zip(values.iter())
+        .map(|(kind, v)| match (kind, v) {
+            (&ArgKind::String, &Value::Str(ref s)) => format!("String::from({:?})", s),
+            (_, &Value::Int(i)) => i.to_string(),
+            (_, &Value::Float(f)) => format!("{:?}", f),
+            (_, &Value::Str(ref s)) => format!("{:?}", s),
+        })
+        .collect();
+    format!("{}({});", fn_path, args.join(", "))
+}
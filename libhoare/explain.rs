@@ -0,0 +1,79 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Explaining failed predicates, see the `Explaining failures` section of
+// ../readme.md.
+//
+// With `--cfg hoare_explain`, if a predicate fails we re-evaluate its leaves
+// (the operands of its operators which are not themselves operators or
+// literals, e.g., `self.len()` and `cap` in `self.len() <= cap`) and pass
+// their `Debug` representations (if they have one) to
+// `libhoare::explain::failed`, which prints them after the label.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_explain")
+}
+
+// The leaves of pred, each once, in the order they appear.
+fn leaves(pred: &P<ast::Expr>, result: &mut Vec<(String, P<ast::Expr>)>) {
+    match pred.node {
+        ast::ExprKind::Paren(ref inner) |
+        ast::ExprKind::Unary(ast::UnOp::Not, ref inner) |
+        ast::ExprKind::Unary(ast::UnOp::Neg, ref inner) => leaves(inner, result),
+        ast::ExprKind::Binary(_, ref lhs, ref rhs) => {
+            leaves(lhs, result);
+            leaves(rhs, result);
+        }
+        ast::ExprKind::Lit(_) => {}
+        _ => {
+            let text = pprust::expr_to_string(pred);
+            if !result.iter().any(|&(ref seen, _)| *seen == text) {
+                result.push((text, pred.clone()));
+            }
+        }
+    }
+}
+
+// The check for pred. If the check fails, before_panic is executed before
+// panicking.
+pub fn assert(
+    cx: &ExtCtxt,
+    pred: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> ast::Stmt {
+    let sp = pred.span;
+    let mut found = Vec::new();
+    leaves(pred, &mut found);
+    let values = found
+        .into_iter()
+        .map(|(text, leaf)| {
+            let value = quote_expr!(cx, (&::libhoare::diff::Operand(&$leaf)).debug_operand());
+            cx.expr_tuple(sp, vec![cx.expr_str(sp, Symbol::intern(&text)), value])
+        })
+        .collect();
+    let values = cx.expr_vec_slice(sp, values);
+    let before_panic = before_panic.into_iter().collect::<Vec<_>>();
+    quote_stmt!(cx, {
+        if !($pred) {
+            $before_panic
+            #[allow(unused_imports)]
+            use ::libhoare::diff::{DebugOperand, NoDebugOperand};
+            ::libhoare::explain::failed($label, $values);
+        }
+    }).unwrap()
+}
@@ -0,0 +1,59 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Floating point clauses, see the `Floating point contracts` section of
+// ../readme.md.
+//
+// `#[precond(finite="x", no_nan="y")]` (and the same for postconditions) is
+// short for `#[precond="(x).is_finite() && !(y).is_nan()"]`.
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::ExtCtxt;
+
+// (name of the clause, predicate with `{}` for the expression)
+const CLAUSES: &'static [(&'static str, &'static str)] = &[
+    ("finite", "({}).is_finite()"),
+    ("no_nan", "!({}).is_nan()"),
+];
+
+// The predicate for a list of clauses.
+pub fn predicate(cx: &ExtCtxt, sp: Span, items: &[ast::NestedMetaItem]) -> Result<String, ()> {
+    if items.is_empty() {
+        cx.span_err(sp, "expected a predicate or clauses, e.g., `finite=\"x\"`");
+        return Err(());
+    }
+
+    let mut preds = Vec::new();
+    for item in items {
+        let clause = CLAUSES.iter().find(|&&(name, _)| item.check_name(name));
+        match (clause, item.value_str()) {
+            (Some(&(_, pred)), Some(expr)) => preds.push(pred.replace("{}", &expr.as_str())),
+            (Some(&(name, _)), None) => {
+                cx.span_err(item.span, &format!("expected `{}=\"...\"`", name));
+                return Err(());
+            }
+            (None, _) => {
+                cx.span_err(
+                    item.span,
+                    "unknown clause, expected `finite`, `no_nan`, `no_overflow`, or `invariant`",
+                );
+                return Err(());
+            }
+        }
+    }
+    Ok(preds.join(" && "))
+}
+
+// Whether items are all clauses handled by this module (rather than, e.g.,
+// those of a history invariant).
+pub fn is_float_clauses(items: &[ast::NestedMetaItem]) -> bool {
+    items.iter().all(|item| CLAUSES.iter().any(|&(name, _)| item.check_name(name)))
+}
@@ -0,0 +1,236 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contracts on generators, see the `Generators` section of ../readme.md.
+//
+// `#[yields="pred"]` and `#[completes="pred"]` are used on a function which
+// returns a generator, i.e., whose body ends with a closure containing
+// `yield`. In `yields`, `yield` is the value yielded and every
+// `yield e` in the generator (but not in closures or items nested in it)
+// becomes `{ let __hoare_yielded = e; <check>; yield __hoare_yielded }`. In
+// `completes`, `return` is the value the generator completes with, and the
+// body of the generator is checked like the body of a function with a
+// postcondition. A precondition of the function is checked when the generator
+// is created, as usual.
+//
+// `#[suspension_invariant="pred"]` checks pred before every `yield` (after
+// evaluating the value yielded), where the generator suspends and other code
+// can observe any state it shares, and when it completes. This is what an
+// invariant checked at every `.await` of an `async fn` would be, but the
+// compiler we target has no `async fn`.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::fold::{self, Folder};
+use syntax::ptr::P;
+use syntax::symbol::InternedString;
+use syntax::util::small_vector::SmallVector;
+use syntax::visit::{self, Visitor};
+
+pub fn yields(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    generator(cx, sp, attr, item, "yields")
+}
+
+pub fn completes(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    generator(cx, sp, attr, item, "completes")
+}
+
+pub fn suspension_invariant(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    generator(cx, sp, attr, item, "suspension_invariant")
+}
+
+fn generator(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    item: Annotatable,
+    kind: &str,
+) -> Annotatable {
+    let pred = match attr.value_str() {
+        Some(pred) => pred.to_string(),
+        None => {
+            cx.span_err(sp, &format!("expected #[{}=\"predicate\"]", kind));
+            return item;
+        }
+    };
+
+    let label = match kind {
+        "yields" => "Yields",
+        "completes" => "Completes",
+        _ => "Suspension invariant",
+    };
+    super::new_run(cx, |cx| {
+        super::map_fn_body(cx, sp, item, label, |cx, ident, _attrs, _decl, body| {
+            let fn_name = ident.name.as_str();
+            let mut body = body.clone();
+            let closure = match body.stmts.last_mut().map(|stmt| &mut stmt.node) {
+                Some(&mut ast::StmtKind::Expr(ref mut e)) if is_generator(e) => e,
+                _ => {
+                    cx.span_err(
+                        sp,
+                        &format!(
+                            "#[{}] must be used on a function which returns a generator (a closure \
+                             containing `yield`)",
+                            kind
+                        ),
+                    );
+                    return Err(());
+                }
+            };
+
+            let new_closure = match closure.node {
+                ast::ExprKind::Closure(capture, ref decl, ref gen_body, gen_sp) => {
+                    let gen_body = match kind {
+                        "yields" => {
+                            let yielded = "__hoare_yielded";
+                            let check =
+                                pred_check(cx, "yield of", &fn_name, &pred, "yield", yielded);
+                            YieldFolder { cx: cx, check: check }.fold_expr(gen_body.clone())
+                        }
+                        "completes" => {
+                            let result = super::result_name().to_string();
+                            let check =
+                                pred_check(cx, "completion of", &fn_name, &pred, "return", &result);
+                            try!(check_completion(cx, sp, kind, gen_body.clone(), check))
+                        }
+                        _ => {
+                            let check =
+                                pred_check(cx, "invariant suspending", &fn_name, &pred, "", "");
+                            let gen_body = gen_body.clone();
+                            let gen_body = YieldFolder { cx: cx, check: check }.fold_expr(gen_body);
+                            let check =
+                                pred_check(cx, "invariant leaving", &fn_name, &pred, "", "");
+                            try!(check_completion(cx, sp, kind, gen_body, check))
+                        }
+                    };
+                    ast::ExprKind::Closure(capture, decl.clone(), gen_body, gen_sp)
+                }
+                _ => unreachable!(),
+            };
+            *closure = cx.expr(closure.span, new_closure);
+            Ok(P(body))
+        })
+    })
+}
+
+// Whether e is a closure containing `yield`.
+fn is_generator(e: &ast::Expr) -> bool {
+    match e.node {
+        ast::ExprKind::Closure(_, _, ref body, _) => {
+            let mut finder = Finder { found: false, yields: true };
+            finder.visit_expr(body);
+            finder.found
+        }
+        _ => false,
+    }
+}
+
+// The check of pred, where name (e.g., `yield`) is renamed to replacement.
+fn pred_check(
+    cx: &ExtCtxt,
+    cond_type: &str,
+    fn_name: &InternedString,
+    pred: &str,
+    name: &str,
+    replacement: &str,
+) -> ast::Stmt {
+    let pred_str = if name.is_empty() { pred.to_owned() } else { pred.replace(name, replacement) };
+    super::assert(cx, cond_type, fn_name, cx.parse_expr(pred_str.clone()), &pred_str, None)
+}
+
+// The body of a generator, making check (which may use the result name) when
+// it completes.
+fn check_completion(
+    cx: &ExtCtxt,
+    sp: Span,
+    kind: &str,
+    body: P<ast::Expr>,
+    check: ast::Stmt,
+) -> Result<P<ast::Expr>, ()> {
+    let mut finder = Finder { found: false, yields: false };
+    finder.visit_expr(&body);
+    if finder.found {
+        cx.span_err(sp, &format!("#[{}] can not be used on a generator which returns early", kind));
+        return Err(());
+    }
+
+    let result_name = super::result_name();
+    Ok(quote_expr!(cx, {
+        let $result_name = $body;
+        $check
+        $result_name
+    }))
+}
+
+// Finds `yield` (or `return`) in a generator, not in nested closures or items.
+struct Finder {
+    found: bool,
+    yields: bool,
+}
+
+impl<'a> Visitor<'a> for Finder {
+    fn visit_expr(&mut self, e: &'a ast::Expr) {
+        match e.node {
+            ast::ExprKind::Yield(_) if self.yields => self.found = true,
+            ast::ExprKind::Ret(_) if !self.yields => self.found = true,
+            ast::ExprKind::Closure(..) => {}
+            _ => visit::walk_expr(self, e),
+        }
+    }
+
+    fn visit_item(&mut self, _: &'a ast::Item) {}
+
+    fn visit_mac(&mut self, _: &'a ast::Mac) {}
+}
+
+// Checks the value of each `yield` in a generator.
+struct YieldFolder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    check: ast::Stmt,
+}
+
+impl<'a, 'b> Folder for YieldFolder<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        match e.node {
+            ast::ExprKind::Yield(ref value) => {
+                let value = match *value {
+                    Some(ref value) => self.fold_expr(value.clone()),
+                    None => quote_expr!(self.cx, ()),
+                };
+                let check = &self.check;
+                return quote_expr!(self.cx, {
+                    let __hoare_yielded = $value;
+                    $check
+                    yield __hoare_yielded
+                });
+            }
+            ast::ExprKind::Closure(..) => return e,
+            _ => {}
+        }
+        e.map(|e| fold::noop_fold_expr(e, self))
+    }
+
+    fn fold_item(&mut self, i: P<ast::Item>) -> SmallVector<P<ast::Item>> {
+        SmallVector::one(i)
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        mac
+    }
+}
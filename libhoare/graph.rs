@@ -0,0 +1,118 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Graphs of contracts, see the `Contract graphs` section of ../readme.md.
+//
+// When compiling with `--cfg hoare_graph`, every contract check we generate,
+// every struct invariant, and every constructor checked to establish an
+// invariant is written to a manifest (`<crate name>.graph` in the graph
+// directory), which `hoare-graph` in the runtime crate renders.
+//
+// Manifest lines have one of the forms
+//  * `contract \t fn_path \t cond_type \t predicate`,
+//  * `invariant \t type_path \t predicate`,
+//  * `uses \t fn_path or type_path \t named predicate`, for each function
+//    (`f`), method (`.f`), or macro (`f!`) called in a predicate,
+//  * `establishes \t fn_path \t type_path`,
+//  * `spec \t fn_path or type_path \t kind \t text`, for each
+//    specification-only clause (see spec.rs).
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::visit::{self, Visitor};
+
+use manifest;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_graph")
+}
+
+pub fn contract(cx: &ExtCtxt, cond_type: &str, fn_name: &str, pred: &ast::Expr, pred_str: &str) {
+    let fn_path = manifest::fn_path(cx, fn_name);
+    let pred_str = pred_str.split_whitespace().collect::<Vec<_>>().join(" ");
+    write(cx, &format!("contract\t{}\t{}\t{}", fn_path, cond_type, pred_str));
+    uses(cx, &fn_path, pred);
+}
+
+pub fn invariant(cx: &ExtCtxt, type_name: &str, preds: &[String]) {
+    let type_path = manifest::fn_path(cx, type_name);
+    for pred in preds {
+        let pred_str = pred.split_whitespace().collect::<Vec<_>>().join(" ");
+        write(cx, &format!("invariant\t{}\t{}", type_path, pred_str));
+        uses(cx, &type_path, &cx.parse_expr(pred.clone()));
+    }
+}
+
+pub fn establishes(cx: &ExtCtxt, fn_name: &str, type_name: &str) {
+    // Drop any generic arguments, e.g., `Bounds<T>` is the type `Bounds`.
+    let type_name = type_name.split('<').next().unwrap().trim();
+    write(
+        cx,
+        &format!(
+            "establishes\t{}\t{}",
+            manifest::fn_path(cx, fn_name),
+            manifest::fn_path(cx, type_name)
+        ),
+    );
+}
+
+pub fn spec(cx: &ExtCtxt, kind: &str, name: &str, text: &str) {
+    write(cx, &format!("spec\t{}\t{}\t{}", manifest::fn_path(cx, name), kind, text));
+}
+
+fn uses(cx: &ExtCtxt, user: &str, pred: &ast::Expr) {
+    let mut finder = NamedPredicates { names: Vec::new() };
+    finder.visit_expr(pred);
+    for name in finder.names {
+        write(cx, &format!("uses\t{}\t{}", user, name));
+    }
+}
+
+fn write(cx: &ExtCtxt, line: &str) {
+    let dir = manifest::dir("HOARE_GRAPH_DIR", "target/hoare-graph");
+    manifest::append(cx, &dir, "graph", line);
+}
+
+// Finds the functions, methods, and macros called in a predicate. `old` (see
+// history.rs) is not a predicate.
+struct NamedPredicates {
+    names: Vec<String>,
+}
+
+impl NamedPredicates {
+    fn add(&mut self, name: String) {
+        if name != "old" && !self.names.contains(&name) {
+            self.names.push(name);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for NamedPredicates {
+    fn visit_expr(&mut self, e: &'a ast::Expr) {
+        match e.node {
+            ast::ExprKind::Call(ref f, _) => {
+                if let ast::ExprKind::Path(None, ref path) = f.node {
+                    self.add(pprust::path_to_string(path));
+                }
+            }
+            ast::ExprKind::MethodCall(ref method, _) => {
+                self.add(format!(".{}", method.identifier));
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, e)
+    }
+
+    fn visit_mac(&mut self, mac: &'a ast::Mac) {
+        self.add(format!("{}!", pprust::path_to_string(&mac.node.path)));
+    }
+}
@@ -0,0 +1,418 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// History invariants and transitions, see the `History invariants` and
+// `Transitions` sections of ../readme.md.
+//
+// Both relate the values of expressions on entering and leaving a function.
+// `#[invariant(monotonic="e", set_once="f")]` checks that `e` does not decrease
+// and that, once `f` (an `Option`) is `Some`, it does not change.
+// `#[transition="pred"]` checks pred on leaving the function, where `old(e)` in
+// pred is the value of `e` on entry. Each such expression is cloned on entry,
+// or for `old`, saved using its `Snapshot` implementation if it has one (unless
+// the contract's policy is `old="clone"`, see policy.rs). With `old="pooled"`,
+// it is copied into a pooled buffer, `libhoare::history::Pooled`, which `old(e)`
+// is replaced by a dereference of.
+//
+// A method which takes `self` by value consumes it, so `self` can not be used
+// after the body. In its transitions and postconditions, `self` and its fields
+// (`self.a.b`, etc.) are saved on entry as if they were in `old(...)`.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::fold::{self, Folder};
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use policy;
+use predicates;
+use returns;
+
+enum Clause {
+    Monotonic(String),
+    SetOnce(String),
+}
+
+impl Clause {
+    fn cond_type(&self) -> &'static str {
+        match self {
+            &Clause::Monotonic(_) => "monotonic invariant of",
+            &Clause::SetOnce(_) => "set-once invariant of",
+        }
+    }
+
+    fn expr(&self) -> &str {
+        match self {
+            &Clause::Monotonic(ref expr) | &Clause::SetOnce(ref expr) => expr,
+        }
+    }
+}
+
+// Expands an invariant with a list of clauses, e.g.,
+// `#[invariant(monotonic="self.version")]`.
+pub fn invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let clauses = match parse_clauses(cx, sp, attr) {
+        Ok(clauses) => clauses,
+        Err(_) => return item,
+    };
+
+    super::map_fn_body(cx, sp, item, "Invariant", |cx, ident, _attrs, decl, body| {
+        let mut olds = Vec::new();
+        let mut checks = Vec::new();
+        for (i, clause) in clauses.iter().enumerate() {
+            let old_name = super::old_name(i);
+            let expr = cx.parse_expr(clause.expr().to_owned());
+            olds.push(expr.clone());
+            let pred = match clause {
+                &Clause::Monotonic(_) => quote_expr!(cx, ($expr) >= $old_name),
+                &Clause::SetOnce(_) => quote_expr!(cx, $old_name.is_none() || ($expr) == $old_name),
+            };
+            checks.push((clause.cond_type(), pred, clause.expr().to_owned()));
+        }
+        Ok(history_body(ident, olds, policy::Old::Clone, checks, decl, body, cx, sp))
+    })
+}
+
+pub fn transition(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    policy::expand(
+        cx,
+        attr,
+        super::item_ident(&item),
+        |cx| super::new_run(cx, |cx| transition_body(cx, sp, attr, item.clone())),
+        item.clone(),
+    )
+}
+
+fn transition_body(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let pred_str = match attr.value_str().or_else(|| super::pred_item(attr)) {
+        Some(pred) => pred.to_string(),
+        None => {
+            cx.span_err(sp, "expected #[transition=\"predicate\"]");
+            return item;
+        }
+    };
+    let old = policy::current(cx).old;
+
+    super::map_fn_body(cx, sp, item, "Transition", |cx, ident, _attrs, decl, body| {
+        // As for postconditions, `return` is the returned value.
+        let pred_str = returns::rename(cx, &pred_str);
+        let (pred, olds) = {
+            let mut folder = OldFolder {
+                cx: cx,
+                olds: Vec::new(),
+                entry_self: super::consumes_self(decl),
+                pooled: old == policy::Old::Pooled,
+            };
+            let pred = folder.fold_expr(cx.parse_expr(pred_str.clone()));
+            (pred, folder.olds)
+        };
+        if olds.is_empty() {
+            cx.span_warn(sp, "transition without `old(...)`, use a postcondition instead");
+        }
+        Ok(history_body(
+            ident,
+            olds,
+            old,
+            vec![("transition of", pred, pred_str)],
+            decl,
+            body,
+            cx,
+            sp,
+        ))
+    })
+}
+
+// `#[snapshot]` on a struct with named fields generates `<Name>Snapshot`, a
+// struct containing a clone of each field without `#[snapshot_skip]`, and
+// implements `libhoare::Snapshot` for the struct using it, so that `old` can be
+// used with structs which can not (or should not) be cloned entirely. Any words
+// in the attribute (e.g., `#[snapshot(Debug, PartialEq)]`) are derived for the
+// snapshot. The snapshot of a generic struct has the same generic parameters,
+// and a `PhantomData` field in case the skipped fields were the only ones to
+// use some of them.
+pub fn snapshot(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    let generated = match item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Struct(ast::VariantData::Struct(ref fields, _), ref generics) => {
+                snapshot_items(cx, attr, item, fields, generics)
+            }
+            _ => {
+                cx.span_err(sp, "#[snapshot] can only be used on structs with named fields");
+                Vec::new()
+            }
+        },
+        _ => {
+            cx.span_err(sp, "#[snapshot] can only be used on structs");
+            Vec::new()
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.into_iter().map(Annotatable::Item));
+    result
+}
+
+fn snapshot_items(
+    cx: &ExtCtxt,
+    attr: &MetaItem,
+    item: &ast::Item,
+    fields: &[ast::StructField],
+    generics: &ast::Generics,
+) -> Vec<P<ast::Item>> {
+    let mut derives = Vec::new();
+    for nested in attr.meta_item_list().unwrap_or(&[]) {
+        match nested.name() {
+            Some(name) if nested.is_word() => derives.push(name.to_string()),
+            _ => cx.span_err(nested.span, "expected the name of a trait to derive"),
+        }
+    }
+
+    let name = item.ident;
+    let snapshot_name = format!("{}Snapshot", name);
+    let vis = format!("{} ", pprust::vis_to_string(&item.vis).trim());
+    let (params, args, where_clause) = super::generics_strings(generics);
+    let mut snapshot_fields = String::new();
+    let mut inits = String::new();
+    let mut clone_bounds = Vec::new();
+    if generics.is_parameterized() {
+        let used = generics
+            .lifetimes
+            .iter()
+            .map(|def| format!("&{} ()", pprust::lifetime_to_string(&def.lifetime)))
+            .chain(generics.ty_params.iter().map(|param| param.ident.to_string()))
+            .collect::<Vec<_>>();
+        snapshot_fields.push_str(&format!(
+            "#[doc(hidden)] pub __hoare_marker: ::std::marker::PhantomData<fn() -> ({},)>,\n",
+            used.join(", ")
+        ));
+        inits.push_str("__hoare_marker: ::std::marker::PhantomData,\n");
+    }
+    for field in fields {
+        if field.attrs.iter().any(|attr| attr.check_name("snapshot_skip")) {
+            continue;
+        }
+        let field_name = field.ident.unwrap();
+        snapshot_fields.push_str(&format!(
+            "{} {}: {},\n",
+            pprust::vis_to_string(&field.vis),
+            field_name,
+            pprust::ty_to_string(&field.ty)
+        ));
+        inits.push_str(&format!(
+            "{field}: ::std::clone::Clone::clone(&self.{field}),\n",
+            field = field_name
+        ));
+        clone_bounds.push(format!("{}: ::std::clone::Clone", pprust::ty_to_string(&field.ty)));
+    }
+    // Fields of generic types are only known to be `Clone` with these bounds.
+    let impl_where_clause = if !generics.is_parameterized() || clone_bounds.is_empty() {
+        where_clause.clone()
+    } else if where_clause.is_empty() {
+        format!(" where {}", clone_bounds.join(", "))
+    } else {
+        format!("{}, {}", where_clause, clone_bounds.join(", "))
+    };
+
+    let derive = if derives.is_empty() {
+        String::new()
+    } else {
+        format!("#[derive({})]", derives.join(", "))
+    };
+    let snapshot_struct = cx.parse_item(format!(
+        "/// The state of a `{name}` saved by `old`.\n\
+         {derive} #[allow(dead_code)]\n\
+         {vis}struct {snapshot_name}{params}{where_clause} {{\n{fields}}}",
+        name = name,
+        derive = derive,
+        vis = vis,
+        snapshot_name = snapshot_name,
+        params = params,
+        where_clause = where_clause,
+        fields = snapshot_fields
+    ));
+    let snapshot_impl = cx.parse_item(format!(
+        "impl{params} ::libhoare::Snapshot for {name}{args}{where_clause} {{\n\
+         type Snapshot = {snapshot_name}{args};\n\
+         fn snapshot(&self) -> Self::Snapshot {{ {snapshot_name} {{\n{inits}}} }}\n\
+         }}",
+        params = params,
+        name = name,
+        args = args,
+        where_clause = impl_where_clause,
+        snapshot_name = snapshot_name,
+        inits = inits
+    ));
+    vec![snapshot_struct, snapshot_impl]
+}
+
+fn parse_clauses(cx: &ExtCtxt, sp: Span, attr: &MetaItem) -> Result<Vec<Clause>, ()> {
+    let items = match attr.meta_item_list() {
+        Some(items) if !items.is_empty() => items,
+        _ => {
+            cx.span_err(sp, "expected #[invariant(monotonic=\"...\", set_once=\"...\")]");
+            return Err(());
+        }
+    };
+
+    let mut clauses = Vec::new();
+    for item in items.iter().filter(|item| !policy::is_option(item)) {
+        let expr = match item.value_str() {
+            Some(expr) => expr.to_string(),
+            None => {
+                cx.span_err(item.span, "expected a clause of the form `monotonic=\"...\"`");
+                return Err(());
+            }
+        };
+        if item.check_name("monotonic") {
+            clauses.push(Clause::Monotonic(expr));
+        } else if item.check_name("set_once") {
+            clauses.push(Clause::SetOnce(expr));
+        } else {
+            cx.span_err(
+                item.span,
+                "unknown invariant clause, expected `monotonic` or `set_once`",
+            );
+            return Err(());
+        }
+    }
+    Ok(clauses)
+}
+
+// Whether items are all clauses of a history invariant.
+pub fn is_history_clauses(items: &[ast::NestedMetaItem]) -> bool {
+    items.iter().all(|item| item.check_name("monotonic") || item.check_name("set_once"))
+}
+
+// Saves olds on entry to the function (as `__hoare_old_N_i`, which the checks
+// may refer to), and checks each (cond_type, pred, pred_str) on exit. olds are
+// saved as old says (see `libhoare::history`).
+fn history_body(
+    ident: ast::Ident,
+    olds: Vec<P<ast::Expr>>,
+    old: policy::Old,
+    checks: Vec<(&'static str, P<ast::Expr>, String)>,
+    decl: &ast::FnDecl,
+    body: &ast::Block,
+    cx: &mut ExtCtxt,
+    sp: Span,
+) -> P<ast::Block> {
+    let fn_name = ident.name.as_str();
+
+    let mut stmts = save_olds(cx, olds, old);
+    stmts.extend(super::bind_result(cx, body, sp, &decl.output));
+
+    for (cond_type, pred, pred_str) in checks {
+        stmts.push(super::assert(cx, cond_type, &fn_name, pred, &pred_str, None));
+    }
+
+    super::fn_body(cx, stmts, sp)
+}
+
+// The statements which save olds on entry (see `history_body`).
+pub fn save_olds(cx: &ExtCtxt, olds: Vec<P<ast::Expr>>, how: policy::Old) -> Vec<ast::Stmt> {
+    let mut stmts = Vec::new();
+    for (i, old) in olds.into_iter().enumerate() {
+        let old_name = super::old_name(i);
+        let value = match how {
+            policy::Old::Snapshot => quote_expr!(cx, {
+                #[allow(unused_imports)]
+                use ::libhoare::history::{CloneOld, SnapshotOld};
+                (&::libhoare::history::Old(&($old))).old()
+            }),
+            policy::Old::Clone => quote_expr!(cx, ::std::clone::Clone::clone(&($old))),
+            policy::Old::Pooled => quote_expr!(cx, ::libhoare::history::pooled(&($old))),
+        };
+        stmts.push(quote_stmt!(cx, let $old_name = $value;).unwrap());
+    }
+    stmts
+}
+
+// The postcondition pred of a method which takes `self` by value, with `self`,
+// its fields, and `old(e)` replaced by their values saved on entry, and the
+// expressions to save (with `save_olds`).
+pub fn entry_self(cx: &ExtCtxt, pred: P<ast::Expr>) -> (P<ast::Expr>, Vec<P<ast::Expr>>) {
+    let mut folder = OldFolder {
+        cx: cx,
+        olds: Vec::new(),
+        entry_self: true,
+        pooled: false,
+    };
+    let pred = folder.fold_expr(pred);
+    (pred, folder.olds)
+}
+
+// Replaces each `old(e)` (and, if entry_self, each projection of `self`) with
+// the name of the value of `e` saved on entry, and collects the `e`s.
+struct OldFolder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    olds: Vec<P<ast::Expr>>,
+    entry_self: bool,
+    // Whether the saved values are `Pooled`, which must be dereferenced.
+    pooled: bool,
+}
+
+impl<'a, 'b> OldFolder<'a, 'b> {
+    fn save(&mut self, e: P<ast::Expr>, sp: Span) -> P<ast::Expr> {
+        let old_name = super::old_name(self.olds.len());
+        self.olds.push(e);
+        if self.pooled {
+            self.cx.parse_expr(format!("(*{})", old_name))
+        } else {
+            self.cx.expr_ident(sp, old_name)
+        }
+    }
+}
+
+// Whether e is `self` or a (possibly nested) field of it.
+fn is_self_projection(e: &ast::Expr) -> bool {
+    match e.node {
+        ast::ExprKind::Path(None, ref path) => {
+            path.segments.len() == 1 && path.segments[0].identifier.name == "self"
+        }
+        ast::ExprKind::Field(ref base, _) | ast::ExprKind::TupField(ref base, _) => {
+            is_self_projection(base)
+        }
+        ast::ExprKind::Paren(ref inner) => is_self_projection(inner),
+        _ => false,
+    }
+}
+
+impl<'a, 'b> Folder for OldFolder<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        if let ast::ExprKind::Call(ref f, ref args) = e.node {
+            if let ast::ExprKind::Path(None, ref path) = f.node {
+                if args.len() == 1 && path.segments.len() == 1 &&
+                    path.segments[0].identifier.name == "old"
+                {
+                    return self.save(args[0].clone(), e.span);
+                }
+            }
+        }
+        if self.entry_self && is_self_projection(&e) {
+            let sp = e.span;
+            return self.save(e, sp);
+        }
+        // The arguments of a macro are only tokens, but those of a shorthand
+        // such as `grew_by!(old(v.len()), v.len(), 1)` are expressions.
+        let cx = self.cx;
+        if let Some(e) = predicates::map_args(cx, &e, |arg| self.fold_expr(arg)) {
+            return e;
+        }
+        e.map(|e| fold::noop_fold_expr(e, self))
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Recording which contracts were evaluated, for
+// `libhoare::assert_contract_hit`. See the `Checking contracts are evaluated`
+// section of ../readme.md.
+//
+// When compiling with `--cfg hoare_hits`, every contract check first calls
+// `libhoare::hits::record` with the path of the function and the kind of the
+// contract.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+
+use manifest;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_hits")
+}
+
+pub fn record(cx: &ExtCtxt, cond_type: &str, fn_name: &str) -> ast::Stmt {
+    let fn_path = &manifest::fn_path(cx, fn_name);
+    let kind = if cond_type.starts_with("precondition") {
+        quote_expr!(cx, ::libhoare::ContractKind::Precond)
+    } else if cond_type.starts_with("postcondition") {
+        quote_expr!(cx, ::libhoare::ContractKind::Postcond)
+    } else {
+        quote_expr!(cx, ::libhoare::ContractKind::Invariant)
+    };
+    quote_stmt!(cx, ::libhoare::hits::record($fn_path, $kind);).unwrap()
+}
@@ -0,0 +1,88 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contracts on functions generated by macros, see the `Contracts in macros`
+// section of ../readme.md.
+//
+// Predicates are parsed from strings, so their identifiers have no hygiene
+// marks. If a function is generated by a `macro_rules!` macro, the names of
+// its arguments (and `self`) which are written in the macro do have marks, so
+// an unmarked `x` in a predicate would not resolve to the argument `x`. So
+// after expanding a contract, we give every unmarked identifier in the body
+// which has the name of a marked argument that argument's marks. (Code in the
+// body which is unmarked, i.e., which was passed to the macro, could not refer
+// to the argument anyway, and it is renamed consistently, so its meaning does
+// not change.)
+//
+// Expansions of items from macros are not cached (see cache.rs), since the
+// marks can not be printed.
+
+use std::collections::HashMap;
+
+use syntax::ast;
+use syntax::ext::base::Annotatable;
+use syntax::ext::hygiene::SyntaxContext;
+use syntax::fold::{self, Folder};
+use syntax::ptr::P;
+use syntax::util::small_vector::SmallVector;
+
+// Whether item was generated by a macro.
+pub fn from_macro(item: &Annotatable) -> bool {
+    let span = match *item {
+        Annotatable::Item(ref item) => item.span,
+        Annotatable::ImplItem(ref item) => item.span,
+        Annotatable::TraitItem(ref item) => item.span,
+    };
+    span.ctxt != SyntaxContext::empty()
+}
+
+// body, with the marks of the arguments in decl.
+pub fn mark_args(decl: &ast::FnDecl, body: P<ast::Block>) -> P<ast::Block> {
+    let mut marks = HashMap::new();
+    for arg in &decl.inputs {
+        if let ast::PatKind::Ident(_, ref ident, _) = arg.pat.node {
+            if ident.node.ctxt != SyntaxContext::empty() {
+                marks.insert(ident.node.name, ident.node.ctxt);
+            }
+        }
+    }
+    if marks.is_empty() {
+        return body;
+    }
+    MarkFolder { marks: marks }.fold_block(body)
+}
+
+struct MarkFolder {
+    marks: HashMap<ast::Name, SyntaxContext>,
+}
+
+impl Folder for MarkFolder {
+    fn fold_ident(&mut self, ident: ast::Ident) -> ast::Ident {
+        match self.marks.get(&ident.name) {
+            Some(&ctxt) if ident.ctxt == SyntaxContext::empty() => ast::Ident {
+                name: ident.name,
+                ctxt: ctxt,
+            },
+            _ => ident,
+        }
+    }
+
+    // Nested items can't refer to the arguments (their own contracts are marked
+    // when they are expanded).
+    fn fold_item(&mut self, item: P<ast::Item>) -> SmallVector<P<ast::Item>> {
+        SmallVector::one(item)
+    }
+
+    // Shorthands such as `implies!` are expanded later, their arguments are
+    // tokens.
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
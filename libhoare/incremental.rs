@@ -0,0 +1,103 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Incremental invariants, see the `Incremental invariants` section of
+// ../readme.md.
+//
+// `#[invariant(incremental="pred", full="pred", every="n")]` checks the
+// incremental predicate on leaving the function, and the full predicate on
+// entering and leaving every nth call (counted in a static, see
+// `libhoare::incremental`).
+
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+
+use policy;
+use returns;
+
+// The number of calls between full checks if `every` is not given.
+const DEFAULT_EVERY: usize = 100;
+
+// Whether attr is an incremental invariant (rather than a history invariant).
+pub fn is_incremental(attr: &MetaItem) -> bool {
+    attr.meta_item_list().map_or(false, |items| {
+        items.iter().any(|item| item.check_name("incremental") || item.check_name("full"))
+    })
+}
+
+pub fn invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let mut incremental = None;
+    let mut full = None;
+    let mut every = DEFAULT_EVERY;
+    for item in attr.meta_item_list().unwrap().iter().filter(|item| !policy::is_option(item)) {
+        let value = item.value_str().map(|value| value.to_string());
+        if item.check_name("incremental") && value.is_some() {
+            incremental = value;
+        } else if item.check_name("full") && value.is_some() {
+            full = value;
+        } else if item.check_name("every") && value.is_some() {
+            match value.unwrap().trim().parse() {
+                Ok(n) => every = n,
+                Err(_) => cx.span_err(item.span, "expected `every=\"number of calls\"`"),
+            }
+        } else {
+            cx.span_err(
+                item.span,
+                "expected `incremental=\"...\"`, `full=\"...\"`, or `every=\"...\"`",
+            );
+        }
+    }
+    let (incremental, full) = match (incremental, full) {
+        (Some(incremental), Some(full)) => (incremental, full),
+        _ => {
+            cx.span_err(sp, "an incremental invariant requires both `incremental` and `full`");
+            return item;
+        }
+    };
+
+    super::map_fn_body(cx, sp, item, "Invariant", |cx, ident, _attrs, decl, body| {
+        let fn_name = ident.name.as_str();
+        let calls = super::calls_name();
+        let full_due = super::full_check_name();
+
+        // As for postconditions, `return` is the returned value.
+        let incremental = returns::rename(cx, &incremental);
+        let incremental_pred = cx.parse_expr(incremental.clone());
+        let full_pred = cx.parse_expr(full.clone());
+
+        let mut stmts = Vec::new();
+        stmts.push(quote_stmt!(cx,
+            static $calls: ::std::sync::atomic::AtomicUsize =
+                ::std::sync::atomic::ATOMIC_USIZE_INIT;
+        ).unwrap());
+        stmts.push(quote_stmt!(cx,
+            let $full_due = ::libhoare::incremental::full_check_due(&$calls, $every);
+        ).unwrap());
+        let check = super::assert(cx, "invariant entering", &fn_name, full_pred.clone(), &full, None);
+        stmts.push(quote_stmt!(cx, if $full_due { $check }).unwrap());
+
+        stmts.extend(super::bind_result(cx, body, sp, &decl.output));
+
+        stmts.push(super::assert(
+            cx,
+            "incremental invariant of",
+            &fn_name,
+            incremental_pred,
+            &incremental,
+            None,
+        ));
+        let check = super::assert(cx, "invariant leaving", &fn_name, full_pred, &full, None);
+        stmts.push(quote_stmt!(cx, if $full_due { $check }).unwrap());
+
+        Ok(super::fn_body(cx, stmts, sp))
+    })
+}
@@ -0,0 +1,106 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Keeping the checks of release builds cheap, see the `Benchmarking contracts`
+// section of ../readme.md.
+//
+// A contract turns a small function into a larger one (the checks, the result
+// binding, and, for early returns, the loop), which the optimizer is then less
+// willing to inline, so in release builds:
+//
+// * a function with at most SMALL_BODY statements gets `#[inline]` from its
+//   first contract, unless it already has an `inline` attribute, and
+// * a failed check calls a `#[cold]`, never inlined, function which panics, so
+//   the formatting and unwinding code is moved out of the function and the
+//   branch to it is predicted not taken:
+//
+//   if !(pred) {
+//       #[cold] #[inline(never)] fn __hoare_violation() -> ! { panic!(label) }
+//       __hoare_violation();
+//   }
+//
+// Debug builds are left as they are, so that their expansions stay readable.
+
+use syntax::ast;
+use syntax::attr;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+// The most statements a function's body can have for it to be hinted inline.
+const SMALL_BODY: usize = 3;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    !super::has_cfg(cx, "debug_assertions")
+}
+
+// Whether the function item is small and has no `inline` attribute.
+pub fn wants_hint(item: &Annotatable) -> bool {
+    let (attrs, body) = match *item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Fn(.., ref body) => (&item.attrs, body),
+            _ => return false,
+        },
+        Annotatable::ImplItem(ref item) => match item.node {
+            ast::ImplItemKind::Method(_, ref body) => (&item.attrs, body),
+            _ => return false,
+        },
+        Annotatable::TraitItem(ref item) => match item.node {
+            ast::TraitItemKind::Method(_, Some(ref body)) => (&item.attrs, body),
+            _ => return false,
+        },
+    };
+    !attr::contains_name(attrs, "inline") && body.stmts.len() <= SMALL_BODY
+}
+
+// item with `#[inline]`.
+pub fn hint(cx: &ExtCtxt, sp: Span, item: Annotatable) -> Annotatable {
+    let inline = cx.attribute(sp, cx.meta_word(sp, Symbol::intern("inline")));
+    match item {
+        Annotatable::Item(item) => Annotatable::Item(item.map(|mut item| {
+            item.attrs.push(inline);
+            item
+        })),
+        Annotatable::ImplItem(item) => Annotatable::ImplItem(item.map(|mut item| {
+            item.attrs.push(inline);
+            item
+        })),
+        Annotatable::TraitItem(item) => Annotatable::TraitItem(item.map(|mut item| {
+            item.attrs.push(inline);
+            item
+        })),
+    }
+}
+
+// The check of pred, which calls a cold function to panic with label if it
+// fails, after before_panic.
+pub fn check(
+    cx: &ExtCtxt,
+    pred: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> ast::Stmt {
+    let violation = quote_stmt!(cx, {
+        #[cold]
+        #[inline(never)]
+        fn __hoare_violation() -> ! {
+            panic!($label)
+        }
+        __hoare_violation();
+    }).unwrap();
+    match before_panic {
+        Some(before_panic) => {
+            quote_stmt!(cx, if !($pred) { $before_panic $violation }).unwrap()
+        }
+        None => quote_stmt!(cx, if !($pred) { $violation }).unwrap(),
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Generation of inputs for contracted functions from unstructured data: fuzz
+// targets (`#[fuzz]`) and generators of valid inputs (`#[valid_input]` and
+// `generate_valid_input!`). See the `Fuzzing` and `Generating inputs` sections
+// of ../readme.md.
+//
+// These attributes must come before the contract attributes on a function so
+// that they have not yet been expanded when we look for preconditions.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{ExtCtxt, Annotatable, DummyResult, MacEager, MacResult};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::ext::build::AstBuilder;
+use syntax::parse::parser::PathStyle;
+use syntax::parse::token;
+use syntax::ptr::P;
+use syntax::tokenstream::TokenTree;
+
+use floats;
+use history;
+use policy;
+
+// Contracts whose predicate must hold when entering the function. Inputs which
+// do not satisfy these are rejected rather than passed to the function.
+const ENTRY_CONTRACTS: &'static [&'static str] = &[
+    "precond",
+    "debug_precond",
+    "release_precond",
+    "invariant",
+    "debug_invariant",
+    "release_invariant",
+];
+
+pub fn fuzz(cx: &mut ExtCtxt, sp: Span, _attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    with_generated_item(cx, sp, item, "#[fuzz]", fuzz_target)
+}
+
+pub fn valid_input(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    with_generated_item(cx, sp, item, "#[valid_input]", input_decoder)
+}
+
+// Expands `generate_valid_input!(path::to::foo)` to a `libhoare::ValidInputs`
+// iterator using the decoder generated by `#[valid_input]` on `foo`.
+pub fn generate_valid_input(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    let mut parser = cx.new_parser_from_tts(tts);
+    let mut path = match parser.parse_path(PathStyle::Expr) {
+        Ok(path) => path,
+        Err(mut e) => {
+            e.emit();
+            return DummyResult::expr(sp);
+        }
+    };
+    if parser.token != token::Eof {
+        cx.span_err(sp, "generate_valid_input! takes a single path to a function");
+        return DummyResult::expr(sp);
+    }
+
+    let fn_name = path.to_string();
+    {
+        let last = path.segments.last_mut().unwrap();
+        last.identifier = decoder_name(last.identifier);
+    }
+    let decoder = cx.expr_path(path);
+    MacEager::expr(quote_expr!(cx, ::libhoare::ValidInputs::new($fn_name, $decoder)))
+}
+
+// Calls `generate` on a free function and returns the function followed by
+// whatever item was generated.
+fn with_generated_item<F>(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    item: Annotatable,
+    attr_name: &str,
+    generate: F,
+) -> Vec<Annotatable>
+where
+    F: Fn(&mut ExtCtxt, Span, &ast::Item, &[(ast::Ident, P<ast::Ty>)]) -> Option<P<ast::Item>>,
+{
+    let generated = match item {
+        Annotatable::Item(ref item) => {
+            match &item.node {
+                &ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
+                    if generics.is_parameterized() {
+                        cx.span_err(
+                            sp,
+                            &format!("{} can not be used on generic functions", attr_name),
+                        );
+                        None
+                    } else {
+                        fn_args(cx, decl, attr_name).and_then(
+                            |args| generate(cx, sp, item, &args),
+                        )
+                    }
+                }
+                _ => {
+                    cx.span_err(sp, &format!("{} on non-function item", attr_name));
+                    None
+                }
+            }
+        }
+        _ => {
+            cx.span_err(
+                sp,
+                &format!("{} is only supported on free functions", attr_name),
+            );
+            None
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.map(Annotatable::Item).into_iter());
+    result
+}
+
+// Builds `fn fuzz_foo(data: &[u8])` for a function `foo`. The fuzz target
+// decodes each argument from `data`, returns early if any entry predicate is
+// false, and then calls `foo`, so that any panic (i.e., a postcondition or
+// invariant violation) is reported by libFuzzer as a crash.
+fn fuzz_target(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    item: &ast::Item,
+    args: &[(ast::Ident, P<ast::Ty>)],
+) -> Option<P<ast::Item>> {
+    let mut stmts = vec![
+        quote_stmt!(cx, let __hoare_input = &mut ::arbitrary::Unstructured::new(data);)
+            .unwrap(),
+    ];
+    stmts.extend(decode_args(cx, sp, item, args, &["arbitrary"], quote_expr!(cx, return)));
+
+    let args = args.iter().map(|&(ident, _)| cx.expr_ident(sp, ident)).collect();
+    stmts.push(cx.stmt_semi(cx.expr_call_ident(sp, item.ident, args)));
+
+    let name = ast::Ident::from_str(&format!("fuzz_{}", item.ident));
+    let body = cx.block(sp, stmts);
+    let target = quote_item!(cx,
+        #[cfg(fuzzing)]
+        #[allow(dead_code, unused_mut)]
+        fn $name(data: &[u8]) $body
+    ).unwrap();
+
+    Some(target.map(|target| ast::Item { vis: item.vis.clone(), ..target }))
+}
+
+// Builds `fn __hoare_valid_input_foo(&mut Unstructured) -> Option<(A, B, ..)>`
+// for a function `foo(a: A, b: B, ..)`, which decodes the arguments and
+// returns them only if they satisfy the entry predicates.
+fn input_decoder(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    item: &ast::Item,
+    args: &[(ast::Ident, P<ast::Ty>)],
+) -> Option<P<ast::Item>> {
+    for &(_, ref ty) in args {
+        if let ast::TyKind::Rptr(..) = ty.node {
+            cx.span_err(
+                ty.span,
+                "#[valid_input] can only generate arguments which are not references",
+            );
+            return None;
+        }
+    }
+
+    let mut stmts = decode_args(
+        cx,
+        sp,
+        item,
+        args,
+        &["libhoare", "arbitrary"],
+        quote_expr!(cx, return None),
+    );
+    let tuple = cx.expr_tuple(
+        sp,
+        args.iter().map(|&(ident, _)| cx.expr_ident(sp, ident)).collect(),
+    );
+    stmts.push(cx.stmt_expr(cx.expr_some(sp, tuple)));
+
+    let name = decoder_name(item.ident);
+    let tuple_ty = cx.ty(
+        sp,
+        ast::TyKind::Tup(args.iter().map(|&(_, ref ty)| ty.clone()).collect()),
+    );
+    let body = cx.block(sp, stmts);
+    let decoder = quote_item!(cx,
+        #[doc(hidden)]
+        #[allow(dead_code, unused_mut)]
+        fn $name(__hoare_input: &mut ::libhoare::arbitrary::Unstructured) -> Option<$tuple_ty>
+            $body
+    ).unwrap();
+
+    Some(decoder.map(|decoder| ast::Item { vis: item.vis.clone(), ..decoder }))
+}
+
+pub fn decoder_name(fn_name: ast::Ident) -> ast::Ident {
+    ast::Ident::from_str(&format!("__hoare_valid_input_{}", fn_name))
+}
+
+// The names and types of the function's arguments, which must all be plain
+// identifiers.
+pub fn fn_args(
+    cx: &ExtCtxt,
+    decl: &ast::FnDecl,
+    attr_name: &str,
+) -> Option<Vec<(ast::Ident, P<ast::Ty>)>> {
+    let mut result = Vec::new();
+    for arg in &decl.inputs {
+        match arg.pat.node {
+            ast::PatKind::Ident(_, ref ident, None) => result.push((ident.node, arg.ty.clone())),
+            _ => {
+                cx.span_err(
+                    arg.pat.span,
+                    &format!(
+                        "{} requires function arguments to be plain identifiers",
+                        attr_name
+                    ),
+                );
+                return None;
+            }
+        }
+    }
+    Some(result)
+}
+
+// Decodes each argument from `__hoare_input` (a `&mut Unstructured`) and then
+// checks the entry predicates. `fail` is evaluated if either step fails.
+fn decode_args(
+    cx: &ExtCtxt,
+    sp: Span,
+    item: &ast::Item,
+    args: &[(ast::Ident, P<ast::Ty>)],
+    arbitrary_crate: &[&str],
+    fail: P<ast::Expr>,
+) -> Vec<ast::Stmt> {
+    let mut arbitrary_fn: Vec<ast::Ident> =
+        arbitrary_crate.iter().map(|s| ast::Ident::from_str(s)).collect();
+    arbitrary_fn.push(ast::Ident::from_str("Arbitrary"));
+    arbitrary_fn.push(ast::Ident::from_str("arbitrary"));
+    let arbitrary_fn = cx.expr_path(cx.path_global(sp, arbitrary_fn));
+
+    let mut stmts = Vec::new();
+    for &(ident, ref ty) in args {
+        let decode = quote_expr!(cx, match $arbitrary_fn(__hoare_input) {
+            Ok(v) => v,
+            Err(_) => $fail,
+        });
+        stmts.extend(decode_arg(cx, ident, ty, decode).into_iter());
+    }
+
+    for pred in entry_predicates(cx, &item.attrs, ENTRY_CONTRACTS) {
+        let pred = cx.parse_expr(pred);
+        stmts.push(quote_stmt!(cx, if !($pred) { $fail; }).unwrap());
+    }
+    stmts
+}
+
+// The predicates of any not yet expanded contracts on the item which are one
+// of `names`, except `exit_only` invariants, which need not hold on entry (and
+// may refer to `return`).
+pub fn entry_predicates(cx: &ExtCtxt, attrs: &[ast::Attribute], names: &[&str]) -> Vec<String> {
+    let mut result = Vec::new();
+    for attr in attrs {
+        if !names.iter().any(|name| attr.check_name(name)) {
+            continue;
+        }
+        match attr.value_str() {
+            Some(pred) => result.push(pred.to_string()),
+            None if attr.is_meta_item_list() => {
+                let items = attr.meta_item_list().unwrap();
+                if items.iter().any(|item| item.is_word() && item.check_name("exit_only")) {
+                    continue;
+                }
+                let items: Vec<_> = items
+                    .iter()
+                    .filter(|item| !policy::is_option(item))
+                    .cloned()
+                    .collect();
+                let full = items
+                    .iter()
+                    .find(|item| item.check_name("full"))
+                    .and_then(|item| item.value_str());
+                if let Some(pred) = super::list_pred(&items) {
+                    result.push(pred.to_string());
+                } else if floats::is_float_clauses(&items) {
+                    if let Ok(pred) = floats::predicate(cx, attr.span, &items) {
+                        result.push(pred);
+                    }
+                } else if history::is_history_clauses(&items) {
+                    // History invariants say nothing about a single state.
+                } else if let Some(full) = full {
+                    // An incremental invariant holds in full on entry.
+                    result.push(full.to_string());
+                } else {
+                    cx.span_err(attr.span, "unexpected format of condition");
+                }
+            }
+            None => cx.span_err(attr.span, "unexpected format of condition"),
+        }
+    }
+    result
+}
+
+// Decodes one argument. Reference arguments (other than `&str`, which
+// `arbitrary` can produce directly) are decoded into an owned temporary which
+// is then borrowed.
+fn decode_arg(
+    cx: &ExtCtxt,
+    ident: ast::Ident,
+    ty: &P<ast::Ty>,
+    decode: P<ast::Expr>,
+) -> Vec<ast::Stmt> {
+    match ty.node {
+        ast::TyKind::Rptr(_, ref mt) if !is_str(&mt.ty) => {
+            let tmp = ast::Ident::from_str(&format!("__hoare_{}", ident));
+            let inner = &mt.ty;
+            let borrow = match mt.mutbl {
+                ast::Mutability::Mutable => quote_expr!(cx, &mut $tmp),
+                ast::Mutability::Immutable => quote_expr!(cx, &$tmp),
+            };
+            vec![
+                quote_stmt!(cx, let mut $tmp: $inner = $decode;).unwrap(),
+                quote_stmt!(cx, let $ident: $ty = $borrow;).unwrap(),
+            ]
+        }
+        _ => vec![quote_stmt!(cx, let $ident: $ty = $decode;).unwrap()],
+    }
+}
+
+// Whether ty is `str`.
+pub fn is_str(ty: &ast::Ty) -> bool {
+    match ty.node {
+        ast::TyKind::Path(None, ref path) => {
+            path.segments.len() == 1 && path.segments[0].identifier.name == "str"
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Rendering predicates for the messages of failed checks.
+//
+// The predicate in a message is printed from its tokens on one line, with
+// the spacing of rustfmt (roughly: around binary operators, after commas, and
+// inside braces), so the message does not depend on how the attribute was
+// formatted: `x>0`, `x > 0`, and `x\n    > 0` are all `x > 0`. Literals are
+// printed as written. We don't use the pretty printer, since it prints the
+// arguments of macros (such as `implies!`) as a token per word, and the same
+// token can be unary or binary (`-`, `*`, `&`), an operator or a delimiter
+// (`<`, `|`), or part of a macro call (`!`), so each token is classified by
+// the one before it.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::parse;
+use syntax::print::pprust;
+use syntax::tokenstream::TokenTree;
+
+// Keywords which can be followed by an expression, so that `-x`, `!x`, `(x)`,
+// etc. after them are not a binary operator, a macro call, or a call.
+const KEYWORDS: &'static [&'static str] = &[
+    "as", "box", "break", "else", "for", "if", "in", "let", "match", "move", "mut", "ref",
+    "return", "unsafe", "while",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    // Identifiers, keywords, literals, and lifetimes.
+    Word,
+    Open,
+    Close,
+    Prefix,
+    Infix,
+    Postfix,
+    // `.`, `::`, and ranges.
+    Tight,
+    // `,`, `;`, and `:`.
+    Separator,
+    // The `!` of a macro call.
+    Bang,
+    // The `|`s around the parameters of a closure.
+    ParamsOpen,
+    ParamsClose,
+    // The `<` and `>` around generic arguments after `::`.
+    ArgsOpen,
+    ArgsClose,
+}
+
+// pred on one line, normalized. If pred does not parse, only its whitespace is
+// normalized.
+pub fn render(cx: &ExtCtxt, pred: &str) -> String {
+    match parse::parse_expr_from_source_str(
+        "<predicate>".to_owned(),
+        pred.to_owned(),
+        cx.parse_sess,
+    ) {
+        Ok(_) => {}
+        Err(mut e) => {
+            e.cancel();
+            return pred.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+    }
+
+    let mut tokens = Vec::new();
+    flatten(cx.parse_tts(pred.to_owned()), &mut tokens);
+
+    let mut result = String::new();
+    let mut prev: Option<(String, Kind)> = None;
+    let mut params = false;
+    let mut args = 0;
+    for text in tokens {
+        // `..=` is lexed as `..` and `=` by older compilers.
+        if text == "=" && prev.as_ref().map_or(false, |prev| prev.0 == "..") {
+            result.push_str("=");
+            prev = Some(("..=".to_owned(), Kind::Tight));
+            continue;
+        }
+        let kind = classify(&text, prev.as_ref(), &mut params, &mut args);
+        if let Some((ref prev_text, prev_kind)) = prev {
+            if space(prev_text, prev_kind, &text, kind) {
+                result.push(' ');
+            }
+        }
+        result.push_str(&text);
+        prev = Some((text, kind));
+    }
+    result
+}
+
+// The source of e, a part of a predicate, on one line.
+pub fn source(cx: &ExtCtxt, e: &ast::Expr) -> String {
+    match cx.codemap().span_to_snippet(e.span) {
+        Ok(snippet) => render(cx, &snippet),
+        Err(_) => render(cx, &pprust::expr_to_string(e)),
+    }
+}
+
+fn flatten(tts: Vec<TokenTree>, tokens: &mut Vec<String>) {
+    for tt in tts {
+        match tt {
+            TokenTree::Token(_, token) => tokens.push(pprust::token_to_string(&token)),
+            TokenTree::Delimited(_, delimited) => {
+                tokens.push(pprust::token_to_string(&delimited.open_token()));
+                flatten(delimited.stream().trees().collect(), tokens);
+                tokens.push(pprust::token_to_string(&delimited.close_token()));
+            }
+        }
+    }
+}
+
+// The kind of the token text after prev. params is whether we are in the
+// parameters of a closure, and args the depth of generic arguments.
+fn classify(
+    text: &str,
+    prev: Option<&(String, Kind)>,
+    params: &mut bool,
+    args: &mut usize,
+) -> Kind {
+    let (prev_text, prev_kind) = match prev {
+        Some(&(ref text, kind)) => (&**text, Some(kind)),
+        None => ("", None),
+    };
+    let keyword = prev_kind == Some(Kind::Word) && KEYWORDS.contains(&prev_text);
+    // Whether an operator here is unary (or a closure's `|`).
+    let operand = keyword ||
+        match prev_kind {
+            None |
+            Some(Kind::Open) |
+            Some(Kind::Prefix) |
+            Some(Kind::Infix) |
+            Some(Kind::Separator) |
+            Some(Kind::ParamsOpen) |
+            Some(Kind::ParamsClose) => true,
+            _ => false,
+        };
+    match text {
+        "(" | "[" | "{" => Kind::Open,
+        ")" | "]" | "}" => Kind::Close,
+        "," | ";" | ":" => Kind::Separator,
+        "." | "::" | ".." | "..." | "..=" => Kind::Tight,
+        "?" => Kind::Postfix,
+        "!" if prev_kind == Some(Kind::Word) && !keyword => Kind::Bang,
+        "!" => Kind::Prefix,
+        "-" | "*" | "&" | "&&" if operand => Kind::Prefix,
+        "|" if operand && !*params => {
+            *params = true;
+            Kind::ParamsOpen
+        }
+        "|" if *params => {
+            *params = false;
+            Kind::ParamsClose
+        }
+        "<" if prev_text == "::" => {
+            *args += 1;
+            Kind::ArgsOpen
+        }
+        ">" if *args > 0 => {
+            *args -= 1;
+            Kind::ArgsClose
+        }
+        ">>" if *args > 1 => {
+            *args -= 2;
+            Kind::ArgsClose
+        }
+        _ => {
+            let first = text.chars().next().unwrap_or(' ');
+            if first.is_alphanumeric() || first == '_' || first == '"' || first == '\'' {
+                Kind::Word
+            } else {
+                Kind::Infix
+            }
+        }
+    }
+}
+
+// Whether there is a space between the tokens prev and next.
+fn space(prev: &str, prev_kind: Kind, next: &str, next_kind: Kind) -> bool {
+    match next_kind {
+        Kind::Separator | Kind::Postfix | Kind::Bang | Kind::ParamsClose | Kind::ArgsClose => {
+            return false
+        }
+        Kind::Close if next != "}" => return false,
+        Kind::Close => return prev != "{",
+        _ => {}
+    }
+    match prev_kind {
+        Kind::Separator => return true,
+        Kind::Prefix | Kind::Bang | Kind::ParamsOpen | Kind::Tight | Kind::ArgsOpen => {
+            return false
+        }
+        Kind::Open => return prev == "{",
+        _ => {}
+    }
+    match next_kind {
+        Kind::Tight | Kind::ArgsOpen => false,
+        // Calls and indexing.
+        Kind::Open if next != "{" => match prev_kind {
+            Kind::Word => KEYWORDS.contains(&prev),
+            Kind::Close => prev == "}",
+            Kind::ArgsClose | Kind::Postfix => false,
+            _ => true,
+        },
+        _ => true,
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Checking the laws of operator and comparison traits, see the `Trait laws`
+// section of ../readme.md.
+//
+// `#[laws(samples="expr", partial_ord, hash)]` on a struct or enum generates a
+// test, for debug test builds, which calls the function of each law in
+// `libhoare::laws` with the type's name and the sample values.
+//
+// `#[laws(name="pred", ...)]` on a trait declares laws, predicates about `self`,
+// which every implementation should obey. These, and the trait's invariant (as
+// the law `invariant`), are generated as functions in a module (`laws`, or
+// `module`) with the trait's visibility, which implementors can call from their
+// tests. The predicates
+// are the provided methods of a private extension trait of the trait, so they
+// can use `self` and the trait's methods as in the trait itself.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+use trait_invariant;
+
+// The laws which can be checked, each is a function in `libhoare::laws`.
+const LAWS: &'static [&'static str] = &["partial_ord", "ord", "hash", "add_assoc", "mul_assoc"];
+
+const USAGE: &'static str = "#[laws] can only be used on non-generic structs, enums, and traits";
+
+pub fn laws(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    let generated = match item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Struct(_, ref generics) | ast::ItemKind::Enum(_, ref generics)
+                if !generics.is_parameterized() => {
+                test(cx, sp, attr, item.ident)
+            }
+            ast::ItemKind::Trait(_, ref generics, _, _) if !generics.is_parameterized() => {
+                trait_laws(cx, sp, attr, item)
+            }
+            _ => {
+                cx.span_err(sp, USAGE);
+                None
+            }
+        },
+        _ => {
+            cx.span_err(sp, USAGE);
+            None
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.into_iter().map(Annotatable::Item));
+    result
+}
+
+fn test(cx: &ExtCtxt, sp: Span, attr: &MetaItem, ty: ast::Ident) -> Option<P<ast::Item>> {
+    let items = match attr.meta_item_list() {
+        Some(items) => items,
+        None => {
+            cx.span_err(sp, "expected #[laws(samples=\"...\", law, ...)]");
+            return None;
+        }
+    };
+
+    let mut samples = None;
+    let mut laws = Vec::new();
+    for item in items {
+        if item.check_name("samples") {
+            match item.value_str() {
+                Some(expr) => samples = Some(cx.parse_expr(expr.to_string())),
+                None => cx.span_err(item.span, "expected `samples=\"expression\"`"),
+            }
+        } else if item.is_word() && LAWS.iter().any(|law| item.check_name(law)) {
+            laws.push(item.name().unwrap());
+        } else {
+            cx.span_err(
+                item.span,
+                &format!("unknown law, expected one of: {}", LAWS.join(", ")),
+            );
+        }
+    }
+    let samples = match samples {
+        Some(samples) => samples,
+        None => {
+            cx.span_err(sp, "#[laws] requires sample values, e.g., `samples=\"vec![...]\"`");
+            return None;
+        }
+    };
+    if laws.is_empty() {
+        cx.span_err(sp, "#[laws] requires at least one law");
+        return None;
+    }
+
+    let type_name = &*ty.name.as_str();
+    let checks: Vec<ast::Stmt> = laws
+        .into_iter()
+        .map(|law| {
+            let law = ast::Ident::with_empty_ctxt(law);
+            quote_stmt!(cx, ::libhoare::laws::$law($type_name, &__hoare_samples);).unwrap()
+        })
+        .collect();
+    let test_name = ast::Ident::with_empty_ctxt(Symbol::intern(&format!(
+        "laws_{}",
+        type_name.to_lowercase()
+    )));
+    quote_item!(cx,
+        #[cfg(all(test, debug_assertions))]
+        #[test]
+        fn $test_name() {
+            let __hoare_samples: Vec<$ty> = $samples;
+            $checks
+        }
+    )
+}
+
+// The module of the laws of the trait item.
+fn trait_laws(cx: &ExtCtxt, sp: Span, attr: &MetaItem, item: &ast::Item) -> Option<P<ast::Item>> {
+    let items = match attr.meta_item_list() {
+        Some(items) => items,
+        None => {
+            cx.span_err(sp, "expected #[laws(name=\"predicate\", ...)]");
+            return None;
+        }
+    };
+
+    let mut module = "laws".to_owned();
+    let mut laws = Vec::new();
+    if let Some(pred) = trait_invariant::invariant_pred(item) {
+        laws.push(("invariant".to_owned(), pred.to_string()));
+    }
+    for item in items {
+        match (item.name(), item.value_str()) {
+            (Some(_), Some(value)) if item.check_name("module") => module = value.to_string(),
+            (Some(name), Some(pred)) => {
+                let name = name.to_string();
+                if name == "check_laws" || laws.iter().any(|&(ref law, _)| *law == name) {
+                    cx.span_err(item.span, &format!("the law `{}` is already defined", name));
+                } else {
+                    laws.push((name, pred.to_string()));
+                }
+            }
+            _ => cx.span_err(item.span, "expected `name=\"predicate\"` or `module=\"name\"`"),
+        }
+    }
+    if laws.is_empty() {
+        cx.span_err(sp, "#[laws] on a trait requires at least one law, or an invariant");
+        return None;
+    }
+
+    let trait_name = item.ident;
+    let fn_vis = fn_visibility(&item.vis);
+    let mut preds = String::new();
+    let mut fns = String::new();
+    for &(ref law, ref pred) in &laws {
+        preds.push_str(&format!("fn __hoare_law_{}(&self) -> bool {{ {} }}\n", law, pred));
+        fns.push_str(&format!(
+            "/// Checks `value` obeys the law `{law}` of `{name}`: `{pred}`.\n\
+             ///\n\
+             /// # Panics\n\
+             ///\n\
+             /// If it does not.\n\
+             {vis} fn {law}<T: super::{name} + ?Sized>(value: &T) {{\n\
+             if !value.__hoare_law_{law}() {{\n\
+             panic!({label:?});\n\
+             }}\n\
+             }}\n",
+            vis = fn_vis,
+            law = law,
+            name = trait_name,
+            pred = pred.replace('`', "'"),
+            label = format!("law {} of {} violated ({})", law, trait_name, pred)
+        ));
+    }
+    let calls = laws
+        .iter()
+        .map(|&(ref law, _)| format!("{}(value);\n", law))
+        .collect::<String>();
+    Some(cx.parse_item(format!(
+        "/// The laws of `{name}`, for testing its implementations.\n\
+         #[allow(dead_code, unused_imports)]\n\
+         {mod_vis}mod {module} {{\n\
+         use super::*;\n\
+         trait __HoareLaws: super::{name} {{\n\
+         {preds}\
+         }}\n\
+         impl<T: super::{name} + ?Sized> __HoareLaws for T {{}}\n\
+         {fns}\
+         /// Checks `value` obeys all the laws of `{name}`.\n\
+         ///\n\
+         /// # Panics\n\
+         ///\n\
+         /// If it does not.\n\
+         {vis} fn check_laws<T: super::{name} + ?Sized>(value: &T) {{\n\
+         {calls}\
+         }}\n\
+         }}",
+        name = trait_name,
+        mod_vis = format!("{} ", pprust::vis_to_string(&item.vis).trim()),
+        vis = fn_vis,
+        module = module,
+        preds = preds,
+        fns = fns,
+        calls = calls
+    )))
+}
+
+// The visibility, within the laws module, of its functions: that of the trait,
+// since a function can not be more visible than the trait it is bounded by.
+fn fn_visibility(vis: &ast::Visibility) -> String {
+    match *vis {
+        ast::Visibility::Public => "pub".to_owned(),
+        ast::Visibility::Crate(..) => "pub(crate)".to_owned(),
+        ast::Visibility::Restricted { ref path, .. } => {
+            let path = pprust::path_to_string(path);
+            if path == "self" {
+                "pub(super)".to_owned()
+            } else if path.starts_with("super") {
+                // Relative to the laws module, which is one level deeper.
+                format!("pub(in super::{})", path)
+            } else {
+                format!("pub(in {})", path)
+            }
+        }
+        ast::Visibility::Inherited => "pub(super)".to_owned(),
+    }
+}
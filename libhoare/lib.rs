@@ -15,93 +15,499 @@
 extern crate rustc_plugin;
 extern crate syntax;
 
+use syntax::abi;
 use syntax::ast;
 use syntax::ast::{Item, MetaItem};
 use syntax::codemap::{self, Span, Spanned, dummy_spanned};
-use syntax::ext::base::{ExtCtxt, MultiModifier, Annotatable};
+use syntax::ext::base::{ExtCtxt, IdentTT, MultiModifier, Annotatable};
+use syntax::feature_gate::AttributeType;
 use syntax::ext::quote::rt::ExtParseUtils;
 use syntax::ext::build::AstBuilder;
 use syntax::fold::{Folder, noop_fold_expr, noop_fold_mac};
+use syntax::print::pprust;
 use syntax::ptr::P;
 use syntax::symbol::{InternedString, Symbol};
 use syntax::util::small_vector::SmallVector;
+use syntax::visit::{self, Visitor};
 use rustc_plugin::Registry;
 
+use configure::Configured;
+use strip::Strippable;
+
+mod atomic;
+mod audit;
+mod bench;
+mod boxed_future;
+mod cache;
+mod call_checks;
+mod capture;
+mod constructors;
+mod contract_fn;
+mod contract_set;
+mod configure;
+mod contracted;
+mod derive_invariant;
+mod dump;
+mod effects;
+mod coverage;
+mod diff;
+mod examples;
+mod explain;
+mod floats;
+mod generator;
+mod graph;
+mod hits;
+mod history;
+mod hygiene;
+mod incremental;
+mod inlining;
+mod inputs;
+mod label;
+mod laws;
+mod lock;
+mod loom;
+mod manifest;
+mod metrics;
+mod mutation;
+mod oracle;
+mod overflow;
+mod panics_doc;
+mod plugin_args;
+mod policy;
+mod predicate_fns;
+mod predicates;
+mod profile;
+mod protocol;
+mod race;
+mod refinement;
+mod replay;
+mod returns;
+mod round_trip;
+mod sample;
+mod shared_predicates;
+mod spec;
+mod spec_coverage;
+mod state_machine;
+mod strip;
+mod trait_invariant;
+mod unsupported;
+
+// The attributes which declare a contract.
+const CONTRACTS: &'static [&'static str] = &[
+    "precond",
+    "postcond",
+    "invariant",
+    "debug_precond",
+    "debug_postcond",
+    "debug_invariant",
+    "transition",
+    "debug_transition",
+    "release_precond",
+    "release_postcond",
+    "release_invariant",
+    "release_transition",
+];
+
 // Assuming this is going to be Ok because syntax extensions can't be used
 // concurrently. What could go wrong?
 static mut RUN_COUNT: u32 = 0;
+// The number of the expansion in progress, which the names it generates (e.g.,
+// `__result_3`) include. Each of the contracts stacked on a function is a
+// separate expansion, so their names never clash, whichever order the
+// attributes are in.
+static mut CURRENT_RUN: u32 = 0;
 
+// Starts a new expansion.
 fn inc_run_count() {
     unsafe {
         RUN_COUNT += 1;
+        CURRENT_RUN = RUN_COUNT;
+    }
+}
+
+// Runs f as a new expansion. The number of the enclosing expansion, if any, is
+// restored afterwards, so that the names it generates after f are the same as
+// those it generated before.
+fn new_run<F, T>(cx: &mut ExtCtxt, f: F) -> T
+where
+    F: FnOnce(&mut ExtCtxt) -> T,
+{
+    let outer = unsafe { CURRENT_RUN };
+    inc_run_count();
+    let result = f(cx);
+    unsafe {
+        CURRENT_RUN = outer;
+    }
+    result
+}
+
+fn current_run() -> u32 {
+    unsafe { CURRENT_RUN }
+}
+
+fn run_count() -> u32 {
+    unsafe { RUN_COUNT }
+}
+
+// Makes sure later expansions use numbers above n, e.g., when reusing cached
+// code which uses the names numbered up to n.
+fn raise_run_count(n: u32) {
+    unsafe {
+        if RUN_COUNT < n {
+            RUN_COUNT = n;
+        }
     }
 }
 
 #[plugin_registrar]
 pub fn plugin_registrar(reg: &mut Registry) {
-    reg.register_syntax_extension(Symbol::intern("precond"), MultiModifier(Box::new(precond)));
+    plugin_args::register(reg);
+    reg.register_syntax_extension(
+        Symbol::intern("precond"),
+        MultiModifier(Box::new(Configured(Strippable(precond)))),
+    );
     reg.register_syntax_extension(
         Symbol::intern("postcond"),
-        MultiModifier(Box::new(postcond)),
+        MultiModifier(Box::new(Configured(Strippable(postcond)))),
     );
     reg.register_syntax_extension(
         Symbol::intern("invariant"),
-        MultiModifier(Box::new(invariant)),
+        MultiModifier(Box::new(Configured(invariant))),
     );
     reg.register_syntax_extension(
         Symbol::intern("debug_precond"),
-        MultiModifier(Box::new(debug_precond)),
+        MultiModifier(Box::new(Configured(Strippable(debug_precond)))),
     );
     reg.register_syntax_extension(
         Symbol::intern("debug_postcond"),
-        MultiModifier(Box::new(debug_postcond)),
+        MultiModifier(Box::new(Configured(Strippable(debug_postcond)))),
     );
     reg.register_syntax_extension(
         Symbol::intern("debug_invariant"),
-        MultiModifier(Box::new(debug_invariant)),
+        MultiModifier(Box::new(Configured(debug_invariant))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("transition"),
+        MultiModifier(Box::new(Configured(Strippable(history::transition)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("debug_transition"),
+        MultiModifier(Box::new(Configured(Strippable(debug_transition)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("release_precond"),
+        MultiModifier(Box::new(Configured(Strippable(release_precond)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("release_postcond"),
+        MultiModifier(Box::new(Configured(Strippable(release_postcond)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("release_invariant"),
+        MultiModifier(Box::new(Configured(release_invariant))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("release_transition"),
+        MultiModifier(Box::new(Configured(Strippable(release_transition)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("fuzz"),
+        MultiModifier(Box::new(Configured(inputs::fuzz))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("valid_input"),
+        MultiModifier(Box::new(Configured(inputs::valid_input))),
+    );
+    reg.register_macro("generate_valid_input", inputs::generate_valid_input);
+    reg.register_syntax_extension(
+        Symbol::intern("bench_contracts"),
+        MultiModifier(Box::new(Configured(bench::bench_contracts))),
+    );
+    reg.register_macro("state_machine", state_machine::state_machine);
+    reg.register_macro("contract_fn", contract_fn::contract_fn);
+    reg.register_macro("in_range", predicates::in_range);
+    reg.register_macro("non_empty", predicates::non_empty);
+    reg.register_macro("is_sorted", predicates::is_sorted);
+    reg.register_macro("checked_at", predicates::checked_at);
+    reg.register_macro("valid_index", predicates::valid_index);
+    reg.register_macro("is_ascii", predicates::is_ascii);
+    reg.register_macro("is_char_boundary", predicates::is_char_boundary);
+    reg.register_macro("valid_utf8", predicates::valid_utf8);
+    reg.register_macro("increased", predicates::increased);
+    reg.register_macro("decreased", predicates::decreased);
+    reg.register_macro("increased_by", predicates::increased_by);
+    reg.register_macro("decreased_by", predicates::decreased_by);
+    reg.register_macro("grew_by", predicates::grew_by);
+    reg.register_macro("shrank_by", predicates::shrank_by);
+    reg.register_macro("implies", predicates::implies);
+    reg.register_macro("iff", predicates::iff);
+    reg.register_macro("each", predicates::each);
+    reg.register_syntax_extension(
+        Symbol::intern("contract_examples"),
+        MultiModifier(Box::new(Configured(examples::contract_examples))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("derive_Invariant"),
+        MultiModifier(Box::new(Configured(derive_invariant::derive_invariant))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("contract_panics"),
+        MultiModifier(Box::new(Configured(panics_doc::contract_panics))),
+    );
+    reg.register_attribute("capture_violations".to_owned(), AttributeType::Whitelisted);
+    reg.register_syntax_extension(
+        Symbol::intern("predicate"),
+        MultiModifier(Box::new(Configured(shared_predicates::predicate))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("replay_violations"),
+        MultiModifier(Box::new(Configured(replay::replay_violations))),
+    );
+    reg.register_attribute("audited_unsafe".to_owned(), AttributeType::Whitelisted);
+    reg.register_attribute("hoare_policy".to_owned(), AttributeType::CrateLevel);
+    reg.register_syntax_extension(
+        Symbol::intern("oracle"),
+        MultiModifier(Box::new(Configured(oracle::oracle))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("contracted"),
+        MultiModifier(Box::new(Configured(contracted::contracted))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("loom_model"),
+        MultiModifier(Box::new(Configured(loom::loom_model))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("protocol"),
+        MultiModifier(Box::new(Configured(protocol::protocol))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("lock_invariants"),
+        MultiModifier(Box::new(Configured(lock::lock_invariants))),
+    );
+    reg.register_attribute("lock_invariant".to_owned(), AttributeType::Whitelisted);
+    reg.register_syntax_extension(
+        Symbol::intern("laws"),
+        MultiModifier(Box::new(Configured(laws::laws))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("race_checks"),
+        MultiModifier(Box::new(Configured(race::race_checks))),
+    );
+    reg.register_attribute("race_check".to_owned(), AttributeType::Whitelisted);
+    reg.register_attribute("invariant_setters".to_owned(), AttributeType::Whitelisted);
+    reg.register_attribute("invariant_deserialize".to_owned(), AttributeType::Whitelisted);
+    reg.register_attribute("check_invariants".to_owned(), AttributeType::Whitelisted);
+    reg.register_attribute("invariant_suspension".to_owned(), AttributeType::Whitelisted);
+    reg.register_syntax_extension(
+        Symbol::intern("check_constructors"),
+        MultiModifier(Box::new(Configured(Strippable(constructors::check_constructors)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("check_trait_invariant"),
+        MultiModifier(Box::new(Configured(Strippable(trait_invariant::check_trait_invariant)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("check_atomic_sections"),
+        MultiModifier(Box::new(Configured(Strippable(atomic::check_atomic_sections)))),
+    );
+    reg.register_attribute("atomic_section".to_owned(), AttributeType::Whitelisted);
+    reg.register_syntax_extension(
+        Symbol::intern("establishes_invariant_of"),
+        MultiModifier(Box::new(Configured(Strippable(constructors::establishes_invariant_of)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("round_trip"),
+        MultiModifier(Box::new(Configured(Strippable(round_trip::round_trip)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("snapshot"),
+        MultiModifier(Box::new(Configured(history::snapshot))),
+    );
+    reg.register_attribute("snapshot_skip".to_owned(), AttributeType::Whitelisted);
+    reg.register_syntax_extension(
+        Symbol::intern("race_free"),
+        MultiModifier(Box::new(Configured(Strippable(race::race_free)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("no_alloc"),
+        MultiModifier(Box::new(Configured(Strippable(effects::no_alloc)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("max_duration"),
+        MultiModifier(Box::new(Configured(Strippable(effects::max_duration)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("no_panic"),
+        MultiModifier(Box::new(Configured(Strippable(effects::no_panic)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("non_reentrant"),
+        MultiModifier(Box::new(Configured(Strippable(effects::non_reentrant)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("yields"),
+        MultiModifier(Box::new(Configured(Strippable(generator::yields)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("completes"),
+        MultiModifier(Box::new(Configured(Strippable(generator::completes)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("suspension_invariant"),
+        MultiModifier(Box::new(Configured(Strippable(generator::suspension_invariant)))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("contract_set"),
+        IdentTT(Box::new(contract_set::contract_set), None, false),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("apply_contract_set"),
+        MultiModifier(Box::new(Configured(contract_set::apply_contract_set))),
+    );
+    reg.register_syntax_extension(
+        Symbol::intern("check_calls"),
+        MultiModifier(Box::new(Configured(call_checks::check_calls))),
     );
 }
 
-fn precond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
-    inc_run_count();
-    map_annotatble(cx, sp, attr, item, Contract::Precond)
+fn precond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    policy::expand(cx, attr, item_ident(&item), |cx| {
+        new_run(cx, |cx| contract(cx, sp, attr, item.clone(), Contract::Precond))
+    }, vec![item.clone()])
 }
 
-fn postcond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
-    inc_run_count();
-    map_annotatble(cx, sp, attr, item, Contract::Postcond)
+fn postcond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    policy::expand(cx, attr, item_ident(&item), |cx| {
+        new_run(cx, |cx| contract(cx, sp, attr, item.clone(), Contract::Postcond))
+    }, vec![item.clone()])
 }
 
-fn invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
-    inc_run_count();
-    map_annotatble(cx, sp, attr, item, Contract::Invariant)
+fn invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if spec::is_spec_only(attr) {
+        return vec![spec::spec_only(cx, sp, attr, item)];
+    }
+    new_run(cx, |cx| {
+        if let Some(item) = refinement::as_struct(&item) {
+            return refinement::struct_invariant(cx, sp, attr, item);
+        }
+        // The methods of a trait and its impls get the invariant, with its level.
+        if let Some(item) = trait_invariant::as_trait(&item) {
+            return trait_invariant::trait_invariant(cx, sp, attr, item);
+        }
+        // Invariants on structs generate items the crate may use, so they are kept
+        // (see refinement.rs), but invariants on functions only add checks.
+        if strip::stripped(cx) || strip::disabled(cx, attr, &item) {
+            return vec![strip::strip(cx, item)];
+        }
+        policy::expand(cx, attr, item_ident(&item), |cx| {
+            let item = item.clone();
+            if incremental::is_incremental(attr) {
+                return vec![incremental::invariant(cx, sp, attr, item)];
+            }
+            let (entry, exit) = match invariant_checks(cx, sp, attr) {
+                Ok(checks) => checks,
+                Err(_) => return vec![item],
+            };
+            if attr.is_meta_item_list() && pred_item(attr).is_none() {
+                return vec![history::invariant(cx, sp, attr, item)];
+            }
+            contract(cx, sp, attr, item, Contract::Invariant { entry: entry, exit: exit })
+        }, vec![item.clone()])
+    })
 }
 
+// Whether the invariant attr is checked on entering and on leaving the
+// function, which `entry_only` and `exit_only` restrict.
+fn invariant_checks(cx: &ExtCtxt, sp: Span, attr: &MetaItem) -> Result<(bool, bool), ()> {
+    let items = attr.meta_item_list().unwrap_or(&[]);
+    let entry_only = items.iter().any(|item| item.is_word() && item.check_name("entry_only"));
+    let exit_only = items.iter().any(|item| item.is_word() && item.check_name("exit_only"));
+    if !entry_only && !exit_only {
+        return Ok((true, true));
+    }
+    if entry_only && exit_only {
+        cx.span_err(sp, "an invariant can't be both `entry_only` and `exit_only`");
+        return Err(());
+    }
+    if pred_item(attr).is_none() {
+        cx.span_err(sp, "expected `pred` with `entry_only` or `exit_only`");
+        return Err(());
+    }
+    Ok((!exit_only, !entry_only))
+}
 
-fn contract_body(
-    ident: ast::Ident,
-    decl: &ast::FnDecl,
-    body: &ast::Block,
+fn is_invariant_checks_item(item: &ast::NestedMetaItem) -> bool {
+    item.is_word() && (item.check_name("entry_only") || item.check_name("exit_only"))
+}
+
+
+// Adds the checks of a pre- or postcondition or invariant to item, or, if the
+// crate's policy is `predicates="fn"`, compiles its predicate to a function (see
+// predicate_fns.rs).
+fn contract(
     cx: &mut ExtCtxt,
     sp: Span,
     attr: &MetaItem,
+    item: Annotatable,
     contract: Contract,
-) -> Result<P<ast::Block>, ()> {
+) -> Vec<Annotatable> {
+    if policy::crate_policy(cx).predicates == policy::Predicates::Fn {
+        return predicate_fns::contract(cx, sp, attr, item, contract);
+    }
+    vec![map_annotatble(cx, sp, attr, item, contract)]
+}
+
+// The predicate of the contract attr, as an expression and as a string, with
+// `return` renamed to the result name for postconditions. Paths to shared
+// predicates are only resolved in the expression (see shared_predicates.rs).
+fn contract_pred(
+    cx: &ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    contract: &Contract,
+) -> Result<(P<ast::Expr>, String), ()> {
     // Parse out the predicate supplied to the syntax extension.
     let pred = try!(make_predicate(cx, sp, attr, contract.short_str()));
     let mut pred_str = pred.to_string();
 
     // Rename `return` to `__result`
-    let result_name = result_name();
     if contract.checks_return() {
-        pred_str = pred_str.replace("return", &result_name.to_string());
+        pred_str = returns::rename(cx, &pred_str);
     }
 
     let pred = cx.parse_expr(pred_str.clone());
+    try!(plugin_args::check_pure(cx, sp, &pred));
+    let pred = shared_predicates::resolve(cx, pred);
+    Ok((pred, pred_str))
+}
 
+// The body of a function with the contract checked. pred is what is checked,
+// pred_str is the predicate for messages. free_fn is the function, if it is not
+// a method, whose body may be moved to a helper (see `body_helper`).
+fn contract_body(
+    free_fn: Option<&P<ast::Item>>,
+    ident: ast::Ident,
+    attrs: &[ast::Attribute],
+    decl: &ast::FnDecl,
+    body: &ast::Block,
+    cx: &mut ExtCtxt,
+    sp: Span,
+    pred: P<ast::Expr>,
+    pred_str: &str,
+    contract: Contract,
+) -> Result<P<ast::Block>, ()> {
     // Construct the new function.
     let fn_name = ident.name.as_str();
 
+    let audited;
+    let body = if audit::enabled(cx) {
+        audited = try!(audit::audit(cx, &fn_name, body));
+        &*audited
+    } else {
+        body
+    };
+
     let mut stmts = Vec::new();
 
     // Check precondition.
@@ -111,21 +517,65 @@ fn contract_body(
             contract.pre_str(),
             &fn_name,
             pred.clone(),
-            &pred_str,
+            pred_str,
+            None,
         ));
     }
 
-    let init_stmt = quote_stmt!(cx, let mut $result_name = None;).unwrap();
-    stmts.push(init_stmt);
+    // Keep the arguments so they can be captured if the postcondition fails.
+    let capture = contract.has_postcond() && capture::enabled(cx, attrs);
+    if capture {
+        stmts.push(try!(capture::snapshot(cx, sp, decl)));
+    }
 
-    stmts.push(make_body(cx, (*body).clone(), sp, &decl.output));
+    // A method which takes `self` by value consumes it, so its postcondition
+    // sees `self` as it was on entry (see history.rs).
+    let pred = if contract.checks_return() && consumes_self(decl) {
+        let (pred, olds) = history::entry_self(cx, pred);
+        // Only transitions pool the values they save.
+        let old = match policy::current(cx).old {
+            policy::Old::Clone => policy::Old::Clone,
+            _ => policy::Old::Snapshot,
+        };
+        stmts.extend(history::save_olds(cx, olds, old));
+        pred
+    } else {
+        pred
+    };
 
-    let unwrap = quote_stmt!(cx, let $result_name = $result_name.unwrap();).unwrap();
-    stmts.push(unwrap);
+    // The arguments are moved into a helper, so nothing after the body may use
+    // them.
+    let helper = match free_fn {
+        Some(_) if capture || contract.has_postcond() && uses_args(decl, &pred) => None,
+        Some(item) => body_helper(cx, sp, item, attrs, body),
+        None => None,
+    };
+    match helper {
+        Some(helper) => stmts.extend(helper),
+        None => stmts.extend(bind_result(cx, body, sp, &decl.output)),
+    }
 
     // Check postcondition.
     if contract.has_postcond() {
-        stmts.push(assert(cx, contract.post_str(), &fn_name, pred, &pred_str));
+        let before_panic = if capture {
+            Some(capture::record(cx, &fn_name))
+        } else {
+            None
+        };
+        let check = assert(
+            cx,
+            contract.post_str(),
+            &fn_name,
+            pred,
+            pred_str,
+            before_panic,
+        );
+        // The value a boxed future resolves to is checked, not the future.
+        if contract.checks_return() && boxed_future::is_boxed_future(&decl.output) {
+            stmts.push(boxed_future::check_item(cx, sp, check));
+        } else {
+            stmts.push(check);
+        }
     }
 
     Ok(fn_body(cx, stmts, sp))
@@ -134,7 +584,8 @@ fn contract_body(
 enum Contract {
     Precond,
     Postcond,
-    Invariant,
+    // Whether the invariant is checked on entering and on leaving the function.
+    Invariant { entry: bool, exit: bool },
 }
 
 impl Contract {
@@ -142,7 +593,7 @@ impl Contract {
         match self {
             &Contract::Precond => "precond",
             &Contract::Postcond => "postcond",
-            &Contract::Invariant => "invariant",
+            &Contract::Invariant { .. } => "invariant",
         }
     }
 
@@ -150,7 +601,7 @@ impl Contract {
         match self {
             &Contract::Precond => "Precondition",
             &Contract::Postcond => "Postcondition",
-            &Contract::Invariant => "Invariant",
+            &Contract::Invariant { .. } => "Invariant",
         }
     }
 
@@ -158,7 +609,7 @@ impl Contract {
         match self {
             &Contract::Precond => "precondition of",
             &Contract::Postcond => panic!(),
-            &Contract::Invariant => "invariant entering",
+            &Contract::Invariant { .. } => "invariant entering",
         }
     }
 
@@ -166,7 +617,7 @@ impl Contract {
         match self {
             &Contract::Precond => panic!(),
             &Contract::Postcond => "postcondition of",
-            &Contract::Invariant => "invariant leaving",
+            &Contract::Invariant { .. } => "invariant leaving",
         }
     }
 
@@ -174,7 +625,7 @@ impl Contract {
         match self {
             &Contract::Precond => true,
             &Contract::Postcond => false,
-            &Contract::Invariant => true,
+            &Contract::Invariant { entry, .. } => entry,
         }
     }
 
@@ -182,13 +633,16 @@ impl Contract {
         match self {
             &Contract::Precond => false,
             &Contract::Postcond => true,
-            &Contract::Invariant => true,
+            &Contract::Invariant { exit, .. } => exit,
         }
     }
 
+    // An invariant only checked on leaving can refer to the returned value,
+    // e.g., that of a constructor.
     fn checks_return(&self) -> bool {
         match self {
             &Contract::Postcond => true,
+            &Contract::Invariant { entry: false, .. } => true,
             _ => false,
         }
     }
@@ -202,11 +656,52 @@ fn map_annotatble(
     item: Annotatable,
     contract: Contract,
 ) -> Annotatable {
-    match item {
+    let kind = contract.long_str();
+    let item = if inlining::enabled(cx) && inlining::wants_hint(&item) {
+        inlining::hint(cx, sp, item)
+    } else {
+        item
+    };
+    let free_fn = match item {
+        Annotatable::Item(ref item) => Some(item.clone()),
+        _ => None,
+    };
+    map_fn_body(cx, sp, item, kind, |cx, ident, attrs, decl, body| {
+        let (pred, pred_str) = try!(contract_pred(cx, sp, attr, &contract));
+        let free_fn = free_fn.as_ref();
+        contract_body(free_fn, ident, attrs, decl, body, cx, sp, pred, &pred_str, contract)
+    })
+}
+
+fn item_ident(item: &Annotatable) -> ast::Ident {
+    match *item {
+        Annotatable::Item(ref item) => item.ident,
+        Annotatable::ImplItem(ref item) => item.ident,
+        Annotatable::TraitItem(ref item) => item.ident,
+    }
+}
+
+// Replaces the body of item, which must be a function-like item-like-thing, with
+// the result of f. kind describes what we are doing for error messages.
+fn map_fn_body<F>(cx: &mut ExtCtxt, sp: Span, item: Annotatable, kind: &str, f: F) -> Annotatable
+where
+    F: FnOnce(&mut ExtCtxt, ast::Ident, &[ast::Attribute], &ast::FnDecl, &ast::Block)
+        -> Result<P<ast::Block>, ()>,
+{
+    let ident = item_ident(&item);
+    // The checks must see the arguments of functions generated by macros.
+    let f = |cx: &mut ExtCtxt,
+             ident,
+             attrs: &[ast::Attribute],
+             decl: &ast::FnDecl,
+             body: &ast::Block| {
+        f(cx, ident, attrs, decl, body).map(|body| hygiene::mark_args(decl, body))
+    };
+    let result = match item {
         Annotatable::Item(item) => {
             match &item.node {
                 &ast::ItemKind::Fn(ref decl, unsafety, constness, abi, ref generics, ref body) => {
-                    match contract_body(item.ident, decl, body, cx, sp, attr, contract) {
+                    match f(cx, item.ident, &item.attrs, decl, body) {
                         Ok(body) => Annotatable::Item(P(Item {
                             node: ast::ItemKind::Fn(
                                 decl.clone(),
@@ -222,15 +717,16 @@ fn map_annotatble(
                     }
                 }
                 _ => {
-                    cx.span_err(sp, &format!("{} on non-function item", contract.long_str()));
-                    Annotatable::Item(item.clone())
+                    let item = Annotatable::Item(item.clone());
+                    unsupported::report(cx, sp, kind, &item);
+                    item
                 }
             }
         }
         Annotatable::ImplItem(item) => {
             match item.node {
                 ast::ImplItemKind::Method(ref sig, ref body) => {
-                    match contract_body(item.ident, &sig.decl, body, cx, sp, attr, contract) {
+                    match f(cx, item.ident, &item.attrs, &sig.decl, body) {
                         Ok(body) => Annotatable::ImplItem(P(ast::ImplItem {
                             node: ast::ImplItemKind::Method(sig.clone(), body),
                             ..(*item).clone()
@@ -239,18 +735,16 @@ fn map_annotatble(
                     }
                 }
                 _ => {
-                    cx.span_err(
-                        sp,
-                        &format!("{} on non-function impl item", contract.long_str()),
-                    );
-                    Annotatable::ImplItem(item.clone())
+                    let item = Annotatable::ImplItem(item.clone());
+                    unsupported::report(cx, sp, kind, &item);
+                    item
                 }
             }
         }
         Annotatable::TraitItem(item) => {
             match item.node {
                 ast::TraitItemKind::Method(ref sig, Some(ref body)) => {
-                    match contract_body(item.ident, &sig.decl, body, cx, sp, attr, contract) {
+                    match f(cx, item.ident, &item.attrs, &sig.decl, body) {
                         Ok(body) => Annotatable::TraitItem(P(ast::TraitItem {
                             node: ast::TraitItemKind::Method(sig.clone(), Some(body)),
                             ..(*item).clone()
@@ -259,48 +753,123 @@ fn map_annotatble(
                     }
                 }
                 _ => {
-                    cx.span_err(
-                        sp,
-                        &format!("{} on non-function trait item", contract.long_str()),
-                    );
-                    Annotatable::TraitItem(item.clone())
+                    let item = Annotatable::TraitItem(item.clone());
+                    unsupported::report(cx, sp, kind, &item);
+                    item
                 }
             }
         }
+    };
+
+    dump::expansion(cx, ident, &result);
+    result
+}
+
+fn debug_precond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if_debug(cx, |cx| precond(cx, sp, attr, item.clone()), vec![item.clone()])
+}
+fn debug_postcond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if_debug(cx, |cx| postcond(cx, sp, attr, item.clone()), vec![item.clone()])
+}
+fn debug_invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if spec::is_spec_only(attr) {
+        return vec![spec::spec_only(cx, sp, attr, item)];
+    }
+    if trait_invariant::as_trait(&item).is_some() {
+        return invariant(cx, sp, attr, item);
     }
+    if_debug(cx, |cx| invariant(cx, sp, attr, item.clone()), vec![item.clone()])
+}
+fn debug_transition(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    if_debug(cx, |cx| history::transition(cx, sp, attr, item.clone()), item.clone())
 }
 
-fn debug_precond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
-    if_debug(cx, |cx| precond(cx, sp, attr, item.clone()), item.clone())
+fn release_precond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if_release(cx, |cx| precond(cx, sp, attr, item.clone()), vec![item.clone()])
 }
-fn debug_postcond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
-    if_debug(cx, |cx| postcond(cx, sp, attr, item.clone()), item.clone())
+fn release_postcond(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if_release(cx, |cx| postcond(cx, sp, attr, item.clone()), vec![item.clone()])
 }
-fn debug_invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
-    if_debug(cx, |cx| invariant(cx, sp, attr, item.clone()), item.clone())
+fn release_invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    if spec::is_spec_only(attr) {
+        return vec![spec::spec_only(cx, sp, attr, item)];
+    }
+    if trait_invariant::as_trait(&item).is_some() {
+        return invariant(cx, sp, attr, item);
+    }
+    if_release(cx, |cx| invariant(cx, sp, attr, item.clone()), vec![item.clone()])
+}
+fn release_transition(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    if_release(cx, |cx| history::transition(cx, sp, attr, item.clone()), item.clone())
 }
 
 // Executes f if we are compiling in debug mode, returns item otherwise.
-fn if_debug<F>(cx: &mut ExtCtxt, f: F, item: Annotatable) -> Annotatable
+fn if_debug<F, T>(cx: &mut ExtCtxt, f: F, item: T) -> T
+where
+    F: Fn(&mut ExtCtxt) -> T,
+{
+    if has_cfg(cx, "debug_assertions") { f(cx) } else { item }
+}
+
+// Executes f if we are not compiling in debug mode, returns item otherwise.
+fn if_release<F, T>(cx: &mut ExtCtxt, f: F, item: T) -> T
 where
-    F: Fn(&mut ExtCtxt) -> Annotatable,
+    F: Fn(&mut ExtCtxt) -> T,
 {
-    if cx.cfg().iter().any(|&(_name, opt)| match opt {
-        Some(interned_string) => interned_string == Symbol::intern("debug_assertions"),
+    if has_cfg(cx, "debug_assertions") { item } else { f(cx) }
+}
+
+// Whether decl is a method which takes `self` by value (including `self: Box<Self>`).
+fn consumes_self(decl: &ast::FnDecl) -> bool {
+    match decl.get_self() {
+        Some(explicit_self) => match explicit_self.node {
+            ast::SelfKind::Value(_) => true,
+            ast::SelfKind::Region(..) => false,
+            ast::SelfKind::Explicit(ref ty, _) => match ty.node {
+                ast::TyKind::Rptr(..) => false,
+                _ => true,
+            },
+        },
         None => false,
-    })
-    {
-        f(cx)
-    } else {
-        item
+    }
+}
+
+fn is_contract(attr: &ast::Attribute) -> bool {
+    CONTRACTS.iter().any(|name| attr.check_name(name))
+}
+
+// Whether `--cfg name` was passed to the compiler.
+fn has_cfg(cx: &ExtCtxt, name: &str) -> bool {
+    let name = Symbol::intern(name);
+    cx.cfg().iter().any(|&(cfg_name, _)| cfg_name == name)
+}
+
+// Whether `--cfg name="value"` was passed to the compiler.
+fn has_cfg_value(cx: &ExtCtxt, name: &str, value: &str) -> bool {
+    let name = Symbol::intern(name);
+    let value = Symbol::intern(value);
+    cx.cfg().iter().any(|&(cfg_name, cfg_value)| cfg_name == name && cfg_value == Some(value))
+}
+
+// Splits pred into the operands of a chain of op at the top level, e.g.,
+// `a || (b || c)` gives `[a, b, c]` for `||`.
+fn split_binary(pred: &P<ast::Expr>, op: ast::BinOpKind) -> Vec<P<ast::Expr>> {
+    match pred.node {
+        ast::ExprKind::Binary(bin_op, ref lhs, ref rhs) if bin_op.node == op => {
+            let mut result = split_binary(lhs, op);
+            result.extend(split_binary(rhs, op).into_iter());
+            result
+        }
+        ast::ExprKind::Paren(ref inner) => split_binary(inner, op),
+        _ => vec![pred.clone()],
     }
 }
 
 // Takes the predicate passed to the syntax extension, checks it and turns it
 // into a string.
 fn make_predicate(cx: &ExtCtxt, sp: Span, attr: &MetaItem, cond_name: &str) -> Result<Symbol, ()> {
-    fn debug_name(cond_name: &str) -> String {
-        let mut result = "debug_".to_string();
+    fn prefixed_name(prefix: &str, cond_name: &str) -> String {
+        let mut result = prefix.to_string();
         result.push_str(cond_name);
         result
     }
@@ -308,7 +877,8 @@ fn make_predicate(cx: &ExtCtxt, sp: Span, attr: &MetaItem, cond_name: &str) -> R
     match &attr.node {
         &ast::MetaItemKind::NameValue(ref lit) => {
             if attr.name.to_string() == cond_name ||
-                attr.name.to_string() == &debug_name(cond_name)[..]
+                attr.name.to_string() == &prefixed_name("debug_", cond_name)[..] ||
+                attr.name.to_string() == &prefixed_name("release_", cond_name)[..]
             {
                 match &lit.node {
                     &ast::LitKind::Str(ref lit, _) => Ok(lit.clone()),
@@ -325,6 +895,51 @@ fn make_predicate(cx: &ExtCtxt, sp: Span, attr: &MetaItem, cond_name: &str) -> R
                 Err(())
             }
         }
+        &ast::MetaItemKind::List(ref items) => {
+            let items: Vec<_> = items
+                .iter()
+                .filter(|item| !policy::is_option(item))
+                .filter(|item| cond_name != "invariant" || !is_invariant_checks_item(item))
+                .cloned()
+                .collect();
+            let pred_items = items.iter().filter(|item| is_pred_item(item)).count();
+            match pred_item(attr) {
+                Some(pred) if items.len() == pred_items => Ok(pred),
+                Some(_) => {
+                    cx.span_err(
+                        sp,
+                        "`pred`, `when`, `then`, `each`, and `of` can only be used with `level`, \
+                         `on_violation`, `old`, `tag`, and, for invariants, `entry_only` or \
+                         `exit_only`",
+                    );
+                    Err(())
+                }
+                None if pred_items > 0 => {
+                    cx.span_err(
+                        sp,
+                        "expected either `pred`, both `when` and `then`, or `each` (with `of`)",
+                    );
+                    Err(())
+                }
+                None => {
+                    let (invariants, clauses): (Vec<_>, Vec<_>) =
+                        items.into_iter().partition(constructors::is_invariant_clause);
+                    let (overflows, clauses): (Vec<_>, Vec<_>) =
+                        clauses.into_iter().partition(overflow::is_overflow_clause);
+                    let mut preds = Vec::new();
+                    for item in &invariants {
+                        preds.push(try!(constructors::invariant_clause(cx, item)));
+                    }
+                    for item in &overflows {
+                        preds.push(try!(overflow::overflow_clause(cx, item)));
+                    }
+                    if preds.is_empty() || !clauses.is_empty() {
+                        preds.push(try!(floats::predicate(cx, sp, &clauses)));
+                    }
+                    Ok(Symbol::intern(&preds.join(" && ")))
+                }
+            }
+        }
         _ => {
             cx.span_err(sp, "unexpected format of condition");
             Err(())
@@ -332,23 +947,212 @@ fn make_predicate(cx: &ExtCtxt, sp: Span, attr: &MetaItem, cond_name: &str) -> R
     }
 }
 
+// The predicate of a contract given as a list, e.g.,
+// `#[precond(pred="x > 0", level="debug")]`, or, for a conditional contract
+// such as `#[postcond(when="x > 0", then="return > 0")]`, `implies!(when, then)`,
+// or, for a contract on each element of a collection such as
+// `#[postcond(each="*elem >= 0", of="return")]`, `each!(of, |elem| each)` (see
+// predicates.rs), where `of` defaults to `return`. None if no form is used, or
+// they are mixed.
+fn pred_item(attr: &MetaItem) -> Option<Symbol> {
+    attr.meta_item_list().and_then(list_pred)
+}
+
+fn list_pred(items: &[ast::NestedMetaItem]) -> Option<Symbol> {
+    let value = |name| {
+        items.iter().find(|item| item.check_name(name)).and_then(|item| item.value_str())
+    };
+    match (value("pred"), value("when"), value("then"), value("each")) {
+        (Some(pred), None, None, None) if value("of").is_none() => Some(pred),
+        (None, Some(when), Some(then), None) if value("of").is_none() => {
+            Some(Symbol::intern(&format!("implies!({}, {})", when, then)))
+        }
+        (None, None, None, Some(each)) => {
+            let of = value("of").map_or("return".to_owned(), |of| of.to_string());
+            Some(Symbol::intern(&format!("each!({}, |elem| {})", of, each)))
+        }
+        _ => None,
+    }
+}
+
+fn is_pred_item(item: &ast::NestedMetaItem) -> bool {
+    ["pred", "when", "then", "each", "of"].iter().any(|name| item.check_name(name))
+}
+
 // Make an assertion. cond_type should be the kind of assertion (precondition
 // postcondition, etc.). fn_name is the name of the function we are operating on.
+// If the assertion fails, before_panic is executed before panicking. If pred is
+// a chain of `&&`, each conjunct is checked in turn, so that the message names
+// the one which failed.
 fn assert(
     cx: &ExtCtxt,
     cond_type: &str,
     fn_name: &InternedString,
     pred: P<ast::Expr>,
     pred_str: &str,
+    before_panic: Option<ast::Stmt>,
 ) -> ast::Stmt {
-    let label = format!(
-        "{} {} ({})",
-        cond_type,
-        fn_name,
-        pred_str.replace("\"", "\\\"")
-    );
-    let label = &label;
-    quote_stmt!(cx, assert!($pred, $label);).unwrap()
+    let label = make_label(cx, cond_type, fn_name, pred_str);
+    if graph::enabled(cx) {
+        graph::contract(cx, cond_type, fn_name, &pred, pred_str);
+    }
+    let pred = if mutation::enabled(cx) {
+        mutation::mutate(cx, cond_type, fn_name, pred)
+    } else {
+        pred
+    };
+    let policy = policy::current(cx);
+    let debug_assert = policy.backend == policy::Backend::DebugAssert;
+    // Whether check is a single `assert!` (or `debug_assert!`).
+    let mut simple = false;
+    let check = if coverage::enabled(cx) {
+        coverage::assert(cx, cond_type, fn_name, pred, pred_str, &label, before_panic)
+    } else {
+        let conjuncts = split_binary(&pred, ast::BinOpKind::And);
+        if conjuncts.len() == 1 {
+            check_pred(cx, &pred, &label, before_panic, debug_assert, &mut simple)
+        } else {
+            let probabilities = if cond_type == "postcondition of" {
+                sample::probabilities(cx, pred.span, conjuncts.len())
+            } else {
+                None
+            };
+            let checks = conjuncts
+                .iter()
+                .enumerate()
+                .map(|(i, conjunct)| {
+                    let label = make_label(cx, cond_type, fn_name, &label::source(cx, conjunct));
+                    let before_panic = match probabilities {
+                        Some(ref probabilities) => {
+                            sample::before_panic(cx, probabilities[i], before_panic.clone())
+                        }
+                        None => before_panic.clone(),
+                    };
+                    let check =
+                        check_pred(cx, conjunct, &label, before_panic, debug_assert, &mut simple);
+                    match probabilities {
+                        Some(ref probabilities) => sample::check(cx, probabilities[i], check),
+                        None => check,
+                    }
+                })
+                .collect();
+            simple = false;
+            cx.stmt_expr(cx.expr_block(cx.block(pred.span, checks)))
+        }
+    };
+    // A panic in the predicate, rather than the check's own, is reported as a
+    // crash of the predicate.
+    let check = if policy.predicate_panics == policy::PredicatePanics::Report {
+        simple = false;
+        let fn_name: &str = fn_name;
+        quote_stmt!(cx,
+            ::libhoare::panics::report_predicate_panics($cond_type, $fn_name, $pred_str, || {
+                $check
+            });
+        ).unwrap()
+    } else {
+        check
+    };
+    let check = if metrics::enabled(cx) {
+        simple = false;
+        metrics::observe(cx, cond_type, fn_name, check)
+    } else {
+        check
+    };
+    let check = match policy.on_violation {
+        policy::OnViolation::Panic => check,
+        policy::OnViolation::Log => {
+            quote_stmt!(cx, ::libhoare::violation::log(|| { $check });).unwrap()
+        }
+        policy::OnViolation::Abort => {
+            quote_stmt!(cx, ::libhoare::violation::abort(|| { $check });).unwrap()
+        }
+    };
+    let check = if hits::enabled(cx) {
+        simple = false;
+        let record = hits::record(cx, cond_type, fn_name);
+        quote_stmt!(cx, { $record $check }).unwrap()
+    } else {
+        check
+    };
+    // Other checks are only made if `debug_assert!` would be.
+    let check = if debug_assert && !simple {
+        quote_stmt!(cx, if cfg!(debug_assertions) { $check }).unwrap()
+    } else {
+        check
+    };
+    match policy.tag {
+        Some(tag) => {
+            let tag = &*tag.as_str();
+            quote_stmt!(cx, if ::libhoare::tags::enabled($tag) { $check }).unwrap()
+        }
+        None => check,
+    }
+}
+
+// The message of a failed assertion.
+fn make_label(cx: &ExtCtxt, cond_type: &str, fn_name: &str, pred_str: &str) -> String {
+    format!("{} {} ({})", cond_type, fn_name, label::render(cx, pred_str))
+}
+
+// The check of pred, if the check fails, before_panic is executed before
+// panicking with label. Sets simple if the check is a single `assert!` (or
+// `debug_assert!`).
+fn check_pred(
+    cx: &ExtCtxt,
+    pred: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+    debug_assert: bool,
+    simple: &mut bool,
+) -> ast::Stmt {
+    if let Some(check) = predicates::assert(cx, pred, label, before_panic.clone()) {
+        return check;
+    }
+    if explain::enabled(cx) {
+        return explain::assert(cx, pred, label, before_panic);
+    }
+    if diff::enabled(cx) && diff::is_comparison(pred) {
+        return diff::assert(cx, pred, label, before_panic);
+    }
+    if inlining::enabled(cx) {
+        return inlining::check(cx, pred, label, before_panic);
+    }
+    match before_panic {
+        Some(before_panic) => {
+            quote_stmt!(cx, if !($pred) { $before_panic panic!($label); }).unwrap()
+        }
+        None => {
+            *simple = true;
+            if debug_assert {
+                quote_stmt!(cx, debug_assert!($pred, $label);).unwrap()
+            } else {
+                quote_stmt!(cx, assert!($pred, $label);).unwrap()
+            }
+        }
+    }
+}
+
+// For impls generated for an item with generics: the parameters with their
+// bounds (e.g., `<'a, T: Clone>`), the arguments (e.g., `<'a, T>`), and the
+// where clause (e.g., ` where T: Debug`). Each is empty if there are none.
+fn generics_strings(generics: &ast::Generics) -> (String, String, String) {
+    let mut generics = generics.clone();
+    // Defaults are not allowed on the parameters of impls.
+    for param in &mut generics.ty_params {
+        param.default = None;
+    }
+    let where_clause = pprust::where_clause_to_string(&generics.where_clause);
+    if !generics.is_parameterized() {
+        return (String::new(), String::new(), where_clause);
+    }
+    let args = generics
+        .lifetimes
+        .iter()
+        .map(|def| pprust::lifetime_to_string(&def.lifetime))
+        .chain(generics.ty_params.iter().map(|param| param.ident.to_string()))
+        .collect::<Vec<_>>();
+    (pprust::generics_to_string(&generics), format!("<{}>", args.join(", ")), where_clause)
 }
 
 fn fn_body(cx: &ExtCtxt, mut stmts: Vec<ast::Stmt>, sp: Span) -> P<ast::Block> {
@@ -368,26 +1172,275 @@ fn result_expr(cx: &ExtCtxt) -> Option<ast::Stmt> {
 }
 
 fn result_name() -> ast::Ident {
-    unsafe { ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__result_{}", RUN_COUNT))) }
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__result_{}", current_run())))
+}
+
+// Points to the result while it is checked, if it is a mutable reference.
+fn ptr_name() -> ast::Ident {
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__hoare_ptr_{}", current_run())))
+}
+
+// Holds the serialized arguments for #[capture_violations].
+fn capture_name() -> ast::Ident {
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__hoare_capture_{}", current_run())))
+}
+
+// Holds the value returned by the reference implementation for #[oracle].
+fn oracle_name() -> ast::Ident {
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__hoare_oracle_{}", current_run())))
+}
+
+// The static counting calls to a function with an incremental invariant, and
+// whether the full invariant is checked on this call.
+fn calls_name() -> ast::Ident {
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__HOARE_CALLS_{}", current_run())))
+}
+
+fn full_check_name() -> ast::Ident {
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__hoare_full_{}", current_run())))
+}
+
+// Holds the i'th value saved on entry to the function, to be compared with its
+// value on exit.
+fn old_name(i: usize) -> ast::Ident {
+    ast::Ident::with_empty_ctxt(Symbol::intern(&format!("__hoare_old_{}_{}", current_run(), i)))
 }
 
 fn loop_label(sp: Span) -> ast::SpannedIdent {
-    unsafe {
-        codemap::Spanned {
-            node: ast::Ident::with_empty_ctxt(Symbol::intern(&format!("'__hoare_{}", RUN_COUNT))),
-            span: sp,
-        }
+    codemap::Spanned {
+        node: ast::Ident::with_empty_ctxt(
+            Symbol::intern(&format!("'__hoare_body_{}", current_run())),
+        ),
+        span: sp,
     }
 }
 
 fn spanned_loop_label() -> Spanned<ast::Ident> {
-    unsafe {
-        dummy_spanned(ast::Ident::with_empty_ctxt(
-            Symbol::intern(&format!("'__hoare_{}", RUN_COUNT)),
-        ))
+    dummy_spanned(ast::Ident::with_empty_ctxt(
+        Symbol::intern(&format!("'__hoare_body_{}", current_run())),
+    ))
+}
+
+// The attributes a function can have for its body to be moved to a helper.
+// Any other may be a contract expanded after this one, which would use the
+// arguments after the body.
+const HELPER_ATTRS: &'static [&'static str] =
+    &["doc", "allow", "warn", "deny", "forbid", "inline", "cold", "must_use", "test"];
+
+// Statements which bind the result name to a call of `__hoare_body_<name>`, a
+// `#[doc(hidden)]` function nested in the contracted one which has its body as
+// it is, if the body returns early (otherwise `bind_result` leaves it as it is
+// anyway). The function, item, can't be a method or unsafe, and its arguments
+// must be plain names (they are moved into the helper) and their types
+// writable.
+fn body_helper(
+    cx: &ExtCtxt,
+    sp: Span,
+    item: &P<ast::Item>,
+    attrs: &[ast::Attribute],
+    body: &ast::Block,
+) -> Option<Vec<ast::Stmt>> {
+    let mut finder = ReturnFinder { found: false };
+    finder.visit_block(body);
+    let other_attrs = attrs
+        .iter()
+        .any(|attr| !HELPER_ATTRS.iter().any(|&name| attr.check_name(name)));
+    if !finder.found || other_attrs {
+        return None;
+    }
+    let (decl, generics) = match item.node {
+        ast::ItemKind::Fn(ref decl, ast::Unsafety::Normal, constness, _, ref generics, _)
+            if constness.node == ast::Constness::NotConst => (decl, generics),
+        _ => return None,
+    };
+    let ty = match decl.output {
+        ast::FunctionRetTy::Ty(ref ty) if !can_annotate(ty) || is_mut_ref(ty) => return None,
+        ast::FunctionRetTy::Ty(ref ty) => Some(ty.clone()),
+        ast::FunctionRetTy::Default(_) => None,
+    };
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        match arg.pat.node {
+            ast::PatKind::Ident(ast::BindingMode::ByValue(_), ident, None)
+                if can_annotate(&arg.ty) => args.push(cx.expr_ident(sp, ident.node)),
+            _ => return None,
+        }
+    }
+
+    let name = ast::Ident::from_str(&format!("__hoare_body_{}", item.ident));
+    let hidden = cx.meta_list(
+        sp,
+        Symbol::intern("doc"),
+        vec![cx.meta_list_item_word(sp, Symbol::intern("hidden"))],
+    );
+    let helper = P(ast::Item {
+        ident: name,
+        attrs: vec![cx.attribute(sp, hidden)],
+        vis: ast::Visibility::Inherited,
+        node: ast::ItemKind::Fn(
+            decl.clone(),
+            ast::Unsafety::Normal,
+            dummy_spanned(ast::Constness::NotConst),
+            abi::Abi::Rust,
+            generics.clone(),
+            P(body.clone()),
+        ),
+        ..(**item).clone()
+    });
+    // Type parameters which only appear in the return type can't be inferred.
+    let types = generics.ty_params.iter().map(|param| cx.ty_ident(sp, param.ident)).collect();
+    let path = cx.path_all(sp, false, vec![name], Vec::new(), types, Vec::new());
+    let call = cx.expr_call(sp, cx.expr_path(path), args);
+    let result_name = result_name();
+    let bind = match ty {
+        Some(ty) => quote_stmt!(cx, let $result_name: $ty = $call;),
+        None => quote_stmt!(cx, let $result_name = $call;),
+    };
+    Some(vec![cx.stmt_item(sp, helper), bind.unwrap()])
+}
+
+// Whether pred uses any of the arguments in decl, or a macro (whose tokens may).
+fn uses_args(decl: &ast::FnDecl, pred: &ast::Expr) -> bool {
+    let mut names = Vec::new();
+    for arg in &decl.inputs {
+        if let ast::PatKind::Ident(_, ref ident, _) = arg.pat.node {
+            names.push(ident.node.name);
+        }
+    }
+    let mut finder = NameFinder {
+        names: &names,
+        found: false,
+    };
+    finder.visit_expr(pred);
+    finder.found
+}
+
+struct NameFinder<'a> {
+    names: &'a [ast::Name],
+    found: bool,
+}
+
+impl<'a, 'b> Visitor<'a> for NameFinder<'b> {
+    fn visit_expr(&mut self, e: &'a ast::Expr) {
+        if let ast::ExprKind::Path(None, ref path) = e.node {
+            if path.segments.len() == 1 && self.names.contains(&path.segments[0].identifier.name) {
+                self.found = true;
+            }
+        }
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_mac(&mut self, _: &'a ast::Mac) {
+        self.found = true;
     }
 }
 
+// Statements which run body and bind its value to the result name. Unless body
+// returns early, it is used as it is. Otherwise, it is put in a loop, its
+// returns are rewritten to assign the result and break out of the loop.
+//
+// The result has the function's return type (where it can be written), so
+// values are coerced as they would be when returned, e.g., `&self.0` to the
+// `&str` returned by `Deref::deref`. A mutable reference (as returned by
+// `IndexMut::index_mut`) usually borrows `self`, which the postcondition and
+// invariant still use, so it is reborrowed through a pointer while they are
+// checked.
+fn bind_result(
+    cx: &ExtCtxt,
+    body: &ast::Block,
+    sp: Span,
+    ret: &ast::FunctionRetTy,
+) -> Vec<ast::Stmt> {
+    let result_name = result_name();
+    let ty = match ret {
+        &ast::FunctionRetTy::Ty(ref ty) if can_annotate(ty) => Some(ty.clone()),
+        _ => None,
+    };
+    let mut finder = ReturnFinder { found: false };
+    finder.visit_block(body);
+    let stmts = if !finder.found {
+        let body = cx.expr_block(P(body.clone()));
+        match ty {
+            Some(ref ty) => vec![quote_stmt!(cx, let $result_name: $ty = $body;).unwrap()],
+            None => vec![quote_stmt!(cx, let $result_name = $body;).unwrap()],
+        }
+    } else {
+        let init = match ty {
+            Some(ref ty) => quote_stmt!(cx, let mut $result_name: Option<$ty> = None;),
+            None => quote_stmt!(cx, let mut $result_name = None;),
+        };
+        vec![
+            init.unwrap(),
+            make_body(cx, body.clone(), sp, ret),
+            quote_stmt!(cx, let $result_name = $result_name.unwrap();).unwrap(),
+        ]
+    };
+
+    match ty {
+        Some(ref ty) if is_mut_ref(ty) => {
+            // The borrow ends with the block.
+            let mut stmts = stmts;
+            stmts.push(cx.stmt_expr(cx.expr_ident(sp, result_name)));
+            let result = cx.expr_block(cx.block(sp, stmts));
+            let ptr_name = ptr_name();
+            vec![
+                quote_stmt!(cx, let $ptr_name: *mut _ = $result;).unwrap(),
+                quote_stmt!(cx, let $result_name = unsafe { &mut *$ptr_name };).unwrap(),
+            ]
+        }
+        _ => stmts,
+    }
+}
+
+// Whether ty can be the type of a local variable.
+fn can_annotate(ty: &ast::Ty) -> bool {
+    let mut finder = ImplTraitFinder { found: false };
+    finder.visit_ty(ty);
+    !finder.found
+}
+
+// Finds `impl Trait` and `!` types.
+struct ImplTraitFinder {
+    found: bool,
+}
+
+impl<'a> Visitor<'a> for ImplTraitFinder {
+    fn visit_ty(&mut self, ty: &'a ast::Ty) {
+        match ty.node {
+            ast::TyKind::ImplTrait(..) | ast::TyKind::Never => self.found = true,
+            _ => visit::walk_ty(self, ty),
+        }
+    }
+
+    fn visit_mac(&mut self, _: &'a ast::Mac) {}
+}
+
+fn is_mut_ref(ty: &ast::Ty) -> bool {
+    match ty.node {
+        ast::TyKind::Rptr(_, ref mut_ty) => mut_ty.mutbl == ast::Mutability::Mutable,
+        _ => false,
+    }
+}
+
+// Finds returns from the function (rather than a closure or a nested item).
+struct ReturnFinder {
+    found: bool,
+}
+
+impl<'a> Visitor<'a> for ReturnFinder {
+    fn visit_expr(&mut self, e: &'a ast::Expr) {
+        match e.node {
+            ast::ExprKind::Ret(_) => self.found = true,
+            ast::ExprKind::Closure(..) => {}
+            _ => visit::walk_expr(self, e),
+        }
+    }
+
+    fn visit_item(&mut self, _: &'a ast::Item) {}
+
+    fn visit_mac(&mut self, _: &'a ast::Mac) {}
+}
+
 fn make_body(cx: &ExtCtxt, mut body: ast::Block, sp: Span, ret: &ast::FunctionRetTy) -> ast::Stmt {
     // Fold return expressions into breaks.
     body.stmts = fold_stmts(cx, &body.stmts);
@@ -396,9 +1449,7 @@ fn make_body(cx: &ExtCtxt, mut body: ast::Block, sp: Span, ret: &ast::FunctionRe
 
     // Turn the optional returned expression into an assignment
     // into __result and a break.
-    body.stmts.extend(
-        terminate_loop(cx, &expr, ret).into_iter(),
-    );
+    body.stmts.extend(terminate_loop(cx, &expr, ret));
     // FIXME Sometimes (e.g., after a return which was converted to a break) this
     // is not necessary, it will then produce unreachable code warnings. Would
     // be better not to generate this code then.
@@ -413,23 +1464,34 @@ fn make_body(cx: &ExtCtxt, mut body: ast::Block, sp: Span, ret: &ast::FunctionRe
     ))
 }
 
+// The last statement of the body, expr, assigning its value to the result. A
+// statement which has no value (a `let`, an item, or a macro with a semicolon)
+// is kept, followed by assigning `()` if the function returns nothing.
 fn terminate_loop(
     cx: &ExtCtxt,
     expr: &Option<ast::Stmt>,
     ret: &ast::FunctionRetTy,
-) -> Option<ast::Stmt> {
+) -> Vec<ast::Stmt> {
     let result_name = result_name();
+    let unit = || quote_stmt!(cx, $result_name = Some(());).unwrap();
     match expr {
         &Some(ref expr) => {
             let expr = expr.clone();
-            match &expr.node {
-                &ast::StmtKind::Expr(ref e) |
-                &ast::StmtKind::Semi(ref e) => quote_stmt!(cx, $result_name = Some($e)),
-                _ => None,
+            match expr.node {
+                ast::StmtKind::Expr(ref e) |
+                ast::StmtKind::Semi(ref e) => {
+                    vec![quote_stmt!(cx, $result_name = Some($e)).unwrap()]
+                }
+                ast::StmtKind::Mac(ref mac) if mac.1 != ast::MacStmtStyle::Semicolon => {
+                    let e = cx.expr(expr.span, ast::ExprKind::Mac(mac.0.clone()));
+                    vec![quote_stmt!(cx, $result_name = Some($e)).unwrap()]
+                }
+                _ if is_void(ret) => vec![expr.clone(), unit()],
+                _ => vec![expr.clone()],
             }
         }
-        &None if is_void(ret) => quote_stmt!(cx, $result_name = Some(())),
-        _ => None,
+        &None if is_void(ret) => vec![unit()],
+        _ => Vec::new(),
     }
 }
 
@@ -447,7 +1509,8 @@ fn is_void(ret: &ast::FunctionRetTy) -> bool {
 }
 
 
-// These folding functions walk the AST replacing any returns with breaks.
+// These folding functions walk the AST replacing any returns with breaks,
+// leaving closures and nested items, whose returns are their own, alone.
 fn fold_stmts(cx: &ExtCtxt, stmts: &[ast::Stmt]) -> Vec<ast::Stmt> {
     let mut result = Vec::new();
     for s in stmts {
@@ -472,9 +1535,8 @@ impl<'a, 'b> Folder for ReturnFolder<'a, 'b> {
         let loop_label = spanned_loop_label();
         match e.node {
             ast::ExprKind::Ret(Some(ref expr)) => {
-                // We should really fold expr here, but you'd have to be pretty
-                // pathalogical to embed a return inside a return.
-                let expr = expr.clone();
+                // The value can return too, e.g., `return loop { .. return x .. }`.
+                let expr = self.fold_expr(expr.clone());
                 // FIXME(#26994) broken quasi-quoting.
                 // return quote_expr!(self.cx, { $result_name = Some($expr); break $loop_label; });
                 let stmts = vec![
@@ -500,11 +1562,16 @@ impl<'a, 'b> Folder for ReturnFolder<'a, 'b> {
                 let expr = self.cx.expr_block(self.cx.block(stmts[0].span, stmts));
                 return expr;
             }
+            ast::ExprKind::Closure(..) => return e,
             _ => {}
         }
         e.map(|e| noop_fold_expr(e, self))
     }
 
+    fn fold_item(&mut self, i: P<ast::Item>) -> SmallVector<P<ast::Item>> {
+        SmallVector::one(i)
+    }
+
     fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
         noop_fold_mac(mac, self)
     }
@@ -0,0 +1,159 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Invariants of data protected by locks, see the `Lock invariants` section of
+// ../readme.md.
+//
+// Attributes on fields are not expanded, so `#[lock_invariant="..."]` is an
+// inert attribute which is read by `#[lock_invariants]` on the struct. For each
+// `Mutex<T>` field with an invariant we generate `lock_<field>`, and for each
+// `RwLock<T>` field `read_<field>` and `write_<field>`, which return the guard
+// wrapped in a `libhoare::lock::Checked`.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+pub fn lock_invariants(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    let generated = match item {
+        Annotatable::Item(ref item) => {
+            match item.node {
+                ast::ItemKind::Struct(ast::VariantData::Struct(ref fields, _), ref generics) => {
+                    if generics.is_parameterized() {
+                        cx.span_err(sp, "#[lock_invariants] can not be used on generic structs");
+                        None
+                    } else {
+                        lock_methods(cx, sp, item.ident, fields)
+                    }
+                }
+                _ => {
+                    cx.span_err(sp, "#[lock_invariants] can only be used on structs with named fields");
+                    None
+                }
+            }
+        }
+        _ => {
+            cx.span_err(sp, "#[lock_invariants] can only be used on structs");
+            None
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.map(Annotatable::Item).into_iter());
+    result
+}
+
+fn lock_methods(
+    cx: &ExtCtxt,
+    sp: Span,
+    struct_name: ast::Ident,
+    fields: &[ast::StructField],
+) -> Option<P<ast::Item>> {
+    let mut methods = String::new();
+    for field in fields {
+        let predicates: Vec<String> = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.check_name("lock_invariant"))
+            .filter_map(|attr| attr.value_str().map(|pred| format!("({})", pred)))
+            .collect();
+        if predicates.is_empty() {
+            continue;
+        }
+
+        let field_name = field.ident.unwrap();
+        let (lock, inner) = match lock_type(&field.ty) {
+            Some(lock_type) => lock_type,
+            None => {
+                cx.span_err(
+                    field.span,
+                    "#[lock_invariant] can only be used on fields of type `Mutex<T>` or `RwLock<T>`",
+                );
+                return None;
+            }
+        };
+        let inner = pprust::ty_to_string(&inner);
+        let pred = predicates.join(" && ");
+        let description = format!("{:?}", format!("{}::{} ({})", struct_name, field_name, pred));
+        let vis = format!("{} ", pprust::vis_to_string(&field.vis).trim());
+        let check = format!("__hoare_lock_invariant_{}", field_name);
+
+        methods.push_str(&format!(
+            "#[doc(hidden)] #[allow(dead_code)] fn {check}({field}: &{inner}) -> bool {{ {pred} }}\n",
+            check = check,
+            field = field_name,
+            inner = inner,
+            pred = pred
+        ));
+        let accessors: &[(&str, &str, &str)] = if lock == "Mutex" {
+            &[("lock", "lock", "MutexGuard")]
+        } else {
+            &[("read", "read", "RwLockReadGuard"), ("write", "write", "RwLockWriteGuard")]
+        };
+        for &(prefix, method, guard) in accessors {
+            methods.push_str(&format!(
+                "#[allow(dead_code)] {vis}fn {prefix}_{field}(&self) \
+                 -> ::libhoare::lock::Checked<::std::sync::{guard}<{inner}>> {{ \
+                 ::libhoare::lock::Checked::new(self.{field}.{method}().unwrap(), \
+                 {struct_name}::{check}, {description}) }}\n",
+                vis = vis,
+                prefix = prefix,
+                field = field_name,
+                guard = guard,
+                inner = inner,
+                method = method,
+                struct_name = struct_name,
+                check = check,
+                description = description
+            ));
+        }
+    }
+
+    if methods.is_empty() {
+        cx.span_warn(sp, "#[lock_invariants] on a struct without #[lock_invariant] fields");
+        return None;
+    }
+
+    Some(cx.parse_item(format!("impl {} {{\n{}}}", struct_name, methods)))
+}
+
+// For `Mutex<T>` or `RwLock<T>` (with any path), the name of the lock type and
+// `T`.
+fn lock_type(ty: &ast::Ty) -> Option<(String, P<ast::Ty>)> {
+    let path = match ty.node {
+        ast::TyKind::Path(None, ref path) => path,
+        _ => return None,
+    };
+    let segment = path.segments.last().unwrap();
+    let name = segment.identifier.to_string();
+    if name != "Mutex" && name != "RwLock" {
+        return None;
+    }
+    match segment.parameters {
+        Some(ref parameters) => {
+            match **parameters {
+                ast::PathParameters::AngleBracketed(ref data) if data.types.len() == 1 => {
+                    Some((name, data.types[0].clone()))
+                }
+                _ => None,
+            }
+        }
+        None => None,
+    }
+}
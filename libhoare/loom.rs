@@ -0,0 +1,156 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Loom tests of invariants under concurrency, see the `Concurrent invariants`
+// section of ../readme.md.
+//
+// `#[loom_model="constructor"]` on an inherent impl looks at the methods which
+// take only `&self` and have invariants. It generates a method which checks all
+// of those invariants and, under `--cfg loom`, a test which passes the methods
+// to `libhoare::loom::explore`. Since the impl is expanded before its methods,
+// the methods' contracts have not been expanded yet.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+pub fn loom_model(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    let init = match attr.value_str() {
+        Some(init) => cx.parse_expr(init.to_string()),
+        None => {
+            cx.span_err(sp, "expected #[loom_model=\"constructor expression\"]");
+            return vec![item];
+        }
+    };
+
+    let generated = match item {
+        Annotatable::Item(ref item) => {
+            match item.node {
+                ast::ItemKind::Impl(_, _, _, ref generics, None, ref ty, ref impl_items) => {
+                    if generics.is_parameterized() {
+                        cx.span_err(sp, "#[loom_model] can not be used on generic impls");
+                        None
+                    } else {
+                        model(cx, sp, init, ty, impl_items)
+                    }
+                }
+                _ => {
+                    cx.span_err(sp, "#[loom_model] can only be used on inherent impls");
+                    None
+                }
+            }
+        }
+        _ => {
+            cx.span_err(sp, "#[loom_model] can only be used on inherent impls");
+            None
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.unwrap_or(Vec::new()).into_iter().map(Annotatable::Item));
+    result
+}
+
+fn model(
+    cx: &ExtCtxt,
+    sp: Span,
+    init: P<ast::Expr>,
+    ty: &P<ast::Ty>,
+    impl_items: &[ast::ImplItem],
+) -> Option<Vec<P<ast::Item>>> {
+    let mut ops = Vec::new();
+    let mut invariants: Vec<String> = Vec::new();
+    for impl_item in impl_items {
+        if let ast::ImplItemKind::Method(ref sig, _) = impl_item.node {
+            let predicates: Vec<String> = impl_item
+                .attrs
+                .iter()
+                .filter(|attr| attr.check_name("invariant") || attr.check_name("debug_invariant"))
+                .filter_map(|attr| attr.value_str().map(|pred| pred.to_string()))
+                .collect();
+            if predicates.is_empty() || !takes_only_ref_self(sig) {
+                continue;
+            }
+
+            let name = &*impl_item.ident.name.as_str();
+            let method = impl_item.ident;
+            ops.push(quote_expr!(cx, ($name, <$ty>::$method as fn(&$ty))));
+            for pred in predicates {
+                if !invariants.contains(&pred) {
+                    invariants.push(pred);
+                }
+            }
+        }
+    }
+
+    if ops.is_empty() {
+        cx.span_err(
+            sp,
+            "#[loom_model] requires a method which takes only `&self` and has an invariant",
+        );
+        return None;
+    }
+
+    let invariant = cx.parse_expr(
+        invariants
+            .iter()
+            .map(|pred| format!("({})", pred))
+            .collect::<Vec<_>>()
+            .join(" && "),
+    );
+    let checker = quote_item!(cx,
+        impl $ty {
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            fn __hoare_loom_invariant(&self) -> bool {
+                $invariant
+            }
+        }
+    ).unwrap();
+
+    let type_name = pprust::ty_to_string(ty);
+    let test_name = ast::Ident::with_empty_ctxt(Symbol::intern(&format!(
+        "loom_{}",
+        type_name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect::<String>()
+    )));
+    let type_name = &*type_name;
+    let ops = cx.expr_vec_slice(sp, ops);
+    let test = quote_item!(cx,
+        #[cfg(loom)]
+        #[test]
+        fn $test_name() {
+            ::libhoare::loom::explore($type_name, || $init, $ops, <$ty>::__hoare_loom_invariant);
+        }
+    ).unwrap();
+
+    Some(vec![checker, test])
+}
+
+fn takes_only_ref_self(sig: &ast::MethodSig) -> bool {
+    if sig.decl.inputs.len() != 1 || sig.generics.is_parameterized() {
+        return false;
+    }
+    match sig.decl.inputs[0].to_self() {
+        Some(ref self_kind) => match self_kind.node {
+            ast::SelfKind::Region(_, ast::Mutability::Immutable) => true,
+            _ => false,
+        },
+        None => false,
+    }
+}
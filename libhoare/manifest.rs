@@ -0,0 +1,81 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Manifests are line-based files which the plugin writes while compiling a
+// crate, describing the contracts it has expanded, for use by tools in the
+// runtime crate. Each manifest is truncated the first time it is written to
+// during a compilation.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use syntax::ext::base::ExtCtxt;
+
+thread_local! {
+    static CREATED: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+}
+
+// The directory given by env_var, or default. Relative paths are relative to
+// the directory the compiler is run in, which for Cargo is the workspace root.
+pub fn dir(env_var: &str, default: &str) -> PathBuf {
+    let dir = PathBuf::from(env::var(env_var).unwrap_or(default.to_owned()));
+    match env::current_dir() {
+        Ok(cwd) => cwd.join(dir),
+        Err(_) => dir,
+    }
+}
+
+// Appends a line to `dir/<crate name>.<extension>`, warning if we can't.
+pub fn append(cx: &ExtCtxt, dir: &Path, extension: &str, line: &str) {
+    let path = dir.join(format!("{}.{}", cx.ecfg.crate_name, extension));
+    let created = CREATED.with(|created| !created.borrow_mut().insert(path.clone()));
+    let file = if created {
+        OpenOptions::new().append(true).open(&path)
+    } else {
+        fs::create_dir_all(dir).and_then(|_| File::create(&path))
+    };
+
+    if let Err(e) = file.and_then(|mut file| writeln!(file, "{}", line)) {
+        cx.span_warn(
+            cx.call_site(),
+            &format!("could not write manifest {}: {}", path.display(), e),
+        );
+    }
+}
+
+// Appends a line to the manifest of this build of the crate:
+// `dir/<crate name>.<extension>`, or `dir/<crate name>.test.<extension>` when
+// compiling its tests (`--test`). `cargo test` compiles a crate both ways, so
+// manifests which describe a build, rather than the crate, need one each, or
+// the build which finishes last would overwrite the other's.
+pub fn append_per_build(cx: &ExtCtxt, dir: &Path, extension: &str, line: &str) {
+    if cx.ecfg.should_test {
+        append(cx, dir, &format!("test.{}", extension), line);
+    } else {
+        append(cx, dir, extension, line);
+    }
+}
+
+// The full path of a function in the current module, starting with the crate
+// name.
+pub fn fn_path(cx: &ExtCtxt, fn_name: &str) -> String {
+    let mut path: Vec<String> = cx.current_expansion
+        .module
+        .mod_path
+        .iter()
+        .map(|ident| ident.to_string())
+        .collect();
+    path.push(fn_name.to_string());
+    path.join("::")
+}
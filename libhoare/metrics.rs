@@ -0,0 +1,43 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Counting checks and violations for monitoring, see the `Contract metrics`
+// section of ../readme.md.
+//
+// When compiling with `--cfg hoare_metrics` (or the crate's feature
+// `hoare-metrics`), each check is run by `observe` on a static
+// `libhoare::metrics::Metric` of its own, inside the handling of violations
+// (see `on_violation` in policy.rs), so the metric sees the check's panic.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+
+use manifest;
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_metrics") || super::has_cfg_value(cx, "feature", "hoare-metrics")
+}
+
+// check, counted by a metric labelled with the function and the kind of
+// contract (cond_type without ` of`, e.g., `precondition`).
+pub fn observe(cx: &ExtCtxt, cond_type: &str, fn_name: &str, check: ast::Stmt) -> ast::Stmt {
+    let fn_path = &manifest::fn_path(cx, fn_name);
+    let kind = cond_type.trim_right_matches(" of");
+    quote_stmt!(cx, {
+        static METRIC: ::libhoare::metrics::Metric = ::libhoare::metrics::Metric {
+            fn_path: $fn_path,
+            kind: $kind,
+            registered: ::std::sync::atomic::AtomicBool::new(false),
+            checks: ::std::sync::atomic::AtomicUsize::new(0),
+            violations: ::std::sync::atomic::AtomicUsize::new(0),
+        };
+        METRIC.observe(|| { $check });
+    }).unwrap()
+}
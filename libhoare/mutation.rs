@@ -0,0 +1,224 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Specification mutation testing, see the `Mutation testing` section of
+// ../readme.md.
+//
+// When compiling with `--cfg hoare_mutants`, we generate a number of mutants of
+// each predicate (by dropping conjuncts and changing comparisons) and compile
+// all of them into the check. Which one is used is decided at runtime by
+// `libhoare::mutation::active`, so the `hoare-mutants` tool can run the test
+// suite once per mutant without recompiling.
+//
+// Each mutant is written to `<crate name>.mutants` (`<crate name>.test.mutants`
+// for the build of the crate's tests, see manifest.rs) as a line of the form
+// `id \t cond_type \t fn_path \t predicate \t mutant`. The id is a hash of
+// the contract and the number of the mutant, not a count, so that the same
+// mutant has the same id in every build of the crate, and `HOARE_MUTANT`
+// activates it in the library and in its tests alike.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+use syntax::fold::{self, Folder};
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::visit::{self, Visitor};
+
+use manifest;
+
+thread_local! {
+    // The number of contracts with each key (see contract_key) so far.
+    static SEEN: RefCell<HashMap<u64, u32>> = RefCell::new(HashMap::new());
+}
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_mutants")
+}
+
+// Returns an expression which evaluates either pred or the active mutant of
+// pred.
+pub fn mutate(cx: &ExtCtxt, cond_type: &str, fn_name: &str, pred: P<ast::Expr>) -> P<ast::Expr> {
+    let dir = manifest::dir("HOARE_MUTANTS_DIR", "target/hoare-mutants");
+    let fn_path = manifest::fn_path(cx, fn_name);
+    let original = pprust::expr_to_string(&pred);
+
+    let key = contract_key(cond_type, &fn_path, &original);
+    let mut arms = Vec::new();
+    for (i, mutant) in mutants(cx, &pred).into_iter().enumerate() {
+        let id = id(key, i);
+        manifest::append_per_build(
+            cx,
+            &dir,
+            "mutants",
+            &format!(
+                "{}\t{}\t{}\t{}\t{}",
+                id,
+                cond_type,
+                fn_path,
+                original,
+                pprust::expr_to_string(&mutant)
+            ),
+        );
+        arms.push(quote_arm!(cx, Some($id) => $mutant,));
+    }
+    arms.push(quote_arm!(cx, _ => $pred,));
+
+    let krate = &*cx.ecfg.crate_name;
+    let active = quote_expr!(cx, ::libhoare::mutation::active($krate));
+    cx.expr_match(cx.call_site(), active, arms)
+}
+
+// A hash of the contract, which is the same in every build of the crate. The
+// rare contracts which are the same check of the same predicate in the same
+// module (e.g., on methods with the same name) are told apart by the order
+// they are expanded in.
+fn contract_key(cond_type: &str, fn_path: &str, pred: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (cond_type, fn_path, pred).hash(&mut hasher);
+    let key = hasher.finish();
+    let seen = SEEN.with(|seen| {
+        let mut seen = seen.borrow_mut();
+        let count = seen.entry(key).or_insert(0);
+        *count += 1;
+        *count
+    });
+    let mut hasher = DefaultHasher::new();
+    (key, seen).hash(&mut hasher);
+    hasher.finish()
+}
+
+// The id of the i'th mutant of the contract with key. Ids fit in 32 bits, so
+// that they are usizes on every target.
+fn id(key: u64, i: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    (key, i).hash(&mut hasher);
+    hasher.finish() as u32 as usize
+}
+
+// Mutants are generated by dropping each conjunct of a top-level `&&` chain
+// (or replacing the whole predicate with `true` if there is only one), and by
+// replacing each comparison operator by its negation and by moving its
+// boundary (e.g., `<` becomes `>=` and `<=`).
+fn mutants(cx: &ExtCtxt, pred: &P<ast::Expr>) -> Vec<P<ast::Expr>> {
+    let mut result = Vec::new();
+
+    let conjuncts = super::split_binary(pred, ast::BinOpKind::And);
+    if conjuncts.len() == 1 {
+        result.push(quote_expr!(cx, true));
+    } else {
+        for i in 0..conjuncts.len() {
+            let rest = conjuncts
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, c)| c.clone());
+            result.push(conjunction(cx, rest.collect()));
+        }
+    }
+
+    let mut comparisons = Comparisons(Vec::new());
+    comparisons.visit_expr(pred);
+    for (target, op) in comparisons.0.into_iter().enumerate() {
+        for &replacement in replacements(op) {
+            let mut folder = ReplaceComparison {
+                target: target,
+                seen: 0,
+                replacement: replacement,
+            };
+            result.push(folder.fold_expr(pred.clone()));
+        }
+    }
+
+    result
+}
+
+fn conjunction(cx: &ExtCtxt, mut conjuncts: Vec<P<ast::Expr>>) -> P<ast::Expr> {
+    let first = conjuncts.remove(0);
+    conjuncts.into_iter().fold(first, |lhs, rhs| {
+        cx.expr_binary(cx.call_site(), ast::BinOpKind::And, lhs, rhs)
+    })
+}
+
+fn replacements(op: ast::BinOpKind) -> &'static [ast::BinOpKind] {
+    use syntax::ast::BinOpKind::*;
+
+    match op {
+        Lt => &[Ge, Le],
+        Le => &[Gt, Lt],
+        Gt => &[Le, Ge],
+        Ge => &[Lt, Gt],
+        Eq => &[Ne],
+        Ne => &[Eq],
+        _ => &[],
+    }
+}
+
+// Collects the comparison operators in an expression, in pre-order.
+struct Comparisons(Vec<ast::BinOpKind>);
+
+impl<'a> Visitor<'a> for Comparisons {
+    fn visit_expr(&mut self, e: &'a ast::Expr) {
+        if let ast::ExprKind::Binary(op, _, _) = e.node {
+            if op.node.is_comparison() {
+                self.0.push(op.node);
+            }
+        }
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_mac(&mut self, _mac: &'a ast::Mac) {
+        // Don't look inside macros, the default implementation panics.
+    }
+}
+
+// Replaces the target'th comparison operator (in pre-order) with replacement.
+struct ReplaceComparison {
+    target: usize,
+    seen: usize,
+    replacement: ast::BinOpKind,
+}
+
+impl Folder for ReplaceComparison {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        let replaced = match e.node {
+            ast::ExprKind::Binary(op, ref lhs, ref rhs) if op.node.is_comparison() => {
+                let replace = self.seen == self.target;
+                self.seen += 1;
+                if replace {
+                    let op = ast::BinOp {
+                        node: self.replacement,
+                        span: op.span,
+                    };
+                    Some(P(ast::Expr {
+                        node: ast::ExprKind::Binary(op, lhs.clone(), rhs.clone()),
+                        ..(*e).clone()
+                    }))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        match replaced {
+            Some(replaced) => replaced,
+            None => e.map(|e| fold::noop_fold_expr(e, self)),
+        }
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
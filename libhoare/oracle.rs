@@ -0,0 +1,98 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Differential testing against a reference implementation, see the
+// `Oracles` section of ../readme.md.
+//
+// In test builds, `#[oracle="reference"]` calls `reference` with (clones of)
+// the function's arguments on entry, runs the function as usual, and asserts
+// that both returned the same value.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::ptr::P;
+
+pub fn oracle(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    if !super::has_cfg(cx, "test") {
+        return item;
+    }
+
+    super::new_run(cx, |cx| {
+        super::map_fn_body(cx, sp, item, "Oracle", |cx, ident, _attrs, decl, body| {
+            oracle_body(ident, decl, body, cx, sp, attr)
+        })
+    })
+}
+
+fn oracle_body(
+    ident: ast::Ident,
+    decl: &ast::FnDecl,
+    body: &ast::Block,
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+) -> Result<P<ast::Block>, ()> {
+    let reference = match attr.value_str() {
+        Some(reference) => reference.to_string(),
+        None => {
+            cx.span_err(sp, "expected #[oracle=\"path::to::reference\"]");
+            return Err(());
+        }
+    };
+
+    // The reference implementation is called first, so it gets its own copy of
+    // each argument. References are copied, mutable references can't be.
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        if let ast::TyKind::Rptr(_, ast::MutTy { mutbl: ast::Mutability::Mutable, .. }) =
+            arg.ty.node
+        {
+            cx.span_err(
+                arg.ty.span,
+                "#[oracle] can't be used with functions which take mutable references",
+            );
+            return Err(());
+        }
+        match arg.pat.node {
+            ast::PatKind::Ident(_, ref ident, None) => {
+                let arg = cx.expr_ident(sp, ident.node);
+                args.push(quote_expr!(cx, ::std::clone::Clone::clone(&$arg)));
+            }
+            _ => {
+                cx.span_err(
+                    arg.pat.span,
+                    "#[oracle] requires function arguments to be plain identifiers",
+                );
+                return Err(());
+            }
+        }
+    }
+
+    let result_name = super::result_name();
+    let oracle_name = super::oracle_name();
+    let label = &format!(
+        "oracle of {} ({})",
+        ident.name.as_str(),
+        reference.replace("\"", "\\\"")
+    );
+    let reference = cx.parse_expr(reference);
+
+    let mut stmts = Vec::new();
+    let call = cx.expr_call(sp, reference, args);
+    stmts.push(quote_stmt!(cx, let $oracle_name = $call;).unwrap());
+    stmts.extend(super::bind_result(cx, body, sp, &decl.output));
+    stmts.push(quote_stmt!(cx, assert_eq!($result_name, $oracle_name, $label);).unwrap());
+
+    Ok(super::fn_body(cx, stmts, sp))
+}
@@ -0,0 +1,111 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Overflow clauses, see the `Arithmetic overflow` section of ../readme.md.
+//
+// `#[precond(no_overflow="a + b * c")]` (and the same for postconditions) is
+// short for a predicate which evaluates the expression with checked
+// arithmetic, `(b).checked_mul(c).and_then(|__hoare_r0| (a).checked_add(
+// __hoare_r0)).is_some()`, so it holds if no operation in it overflows. `+`,
+// `-`, `*`, `/`, `%`, and unary `-` are checked, anything else is an operand.
+
+use syntax::ast;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+
+use returns;
+
+// Whether item is a `no_overflow="e"` clause.
+pub fn is_overflow_clause(item: &ast::NestedMetaItem) -> bool {
+    item.check_name("no_overflow")
+}
+
+// The predicate for a `no_overflow="e"` clause.
+pub fn overflow_clause(cx: &ExtCtxt, item: &ast::NestedMetaItem) -> Result<String, ()> {
+    let expr = match item.value_str() {
+        // `return * 2` would parse as returning `*2`.
+        Some(expr) => cx.parse_expr(returns::rename(cx, &expr.as_str())),
+        None => {
+            cx.span_err(item.span, "expected `no_overflow=\"arithmetic expression\"`");
+            return Err(());
+        }
+    };
+    match checked(&expr, 0) {
+        Checked::Option(checked) => Ok(format!("{}.is_some()", checked)),
+        Checked::Operand(_) => {
+            cx.span_err(item.span, "expected `no_overflow` of `+`, `-`, `*`, `/`, or `%`");
+            Err(())
+        }
+    }
+}
+
+enum Checked {
+    // An expression which is not checked.
+    Operand(String),
+    // An `Option` which is `None` if the expression overflows.
+    Option(String),
+}
+
+// e with checked arithmetic. depth is the number of closures around it.
+fn checked(e: &ast::Expr, depth: usize) -> Checked {
+    let method = |op: ast::BinOpKind| match op {
+        ast::BinOpKind::Add => Some("checked_add"),
+        ast::BinOpKind::Sub => Some("checked_sub"),
+        ast::BinOpKind::Mul => Some("checked_mul"),
+        ast::BinOpKind::Div => Some("checked_div"),
+        ast::BinOpKind::Rem => Some("checked_rem"),
+        _ => None,
+    };
+    match e.node {
+        ast::ExprKind::Paren(ref inner) => checked(inner, depth),
+        ast::ExprKind::Binary(op, ref left, ref right) if method(op.node).is_some() => {
+            let method = method(op.node).unwrap();
+            let left_name = format!("__hoare_l{}", depth);
+            let right_name = format!("__hoare_r{}", depth);
+            // Each operand which can overflow is bound by a closure.
+            let (left, left_value) = match checked(left, depth + 1) {
+                Checked::Operand(left) => (None, format!("({})", left)),
+                Checked::Option(left) => (Some(left), left_name.clone()),
+            };
+            let (right, right_value) = match checked(right, depth + 1) {
+                Checked::Operand(right) => (None, right),
+                Checked::Option(right) => (Some(right), right_name.clone()),
+            };
+            let mut result = format!("{}.{}({})", left_value, method, right_value);
+            if let Some(right) = right {
+                result = format!("{}.and_then(|{}| {})", right, right_name, result);
+            }
+            if let Some(left) = left {
+                result = format!("{}.and_then(|{}| {})", left, left_name, result);
+            }
+            Checked::Option(result)
+        }
+        // A negative literal can't overflow.
+        ast::ExprKind::Unary(ast::UnOp::Neg, ref operand) if is_literal(operand) => {
+            Checked::Operand(pprust::expr_to_string(e))
+        }
+        ast::ExprKind::Unary(ast::UnOp::Neg, ref operand) => match checked(operand, depth + 1) {
+            Checked::Operand(operand) => Checked::Option(format!("({}).checked_neg()", operand)),
+            Checked::Option(operand) => {
+                let name = format!("__hoare_n{}", depth);
+                Checked::Option(format!("{}.and_then(|{}| {}.checked_neg())", operand, name, name))
+            }
+        },
+        _ => Checked::Operand(pprust::expr_to_string(e)),
+    }
+}
+
+fn is_literal(e: &ast::Expr) -> bool {
+    match e.node {
+        ast::ExprKind::Lit(_) => true,
+        _ => false,
+    }
+}
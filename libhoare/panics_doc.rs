@@ -0,0 +1,123 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Generation of a rustdoc `# Panics` section from the preconditions of a
+// function (`#[contract_panics]`), see the `Documenting panics` section of
+// ../readme.md.
+//
+// Like `#[contract_examples]`, this must come before the contract attributes,
+// so that they have not been expanded yet. Each conjunct of each precondition
+// (and invariant, which is checked on entry) is listed, grouped by the builds
+// in which it is checked.
+
+use syntax::ast;
+use syntax::ast::{BinOpKind, MetaItem};
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::symbol::Symbol;
+
+use inputs;
+
+// (contracts, the builds they are checked in)
+const GROUPS: &'static [(&'static [&'static str], Option<&'static str>)] = &[
+    (&["precond", "invariant"], None),
+    (&["debug_precond", "debug_invariant"], Some("debug")),
+    (&["release_precond", "release_invariant"], Some("release")),
+];
+
+pub fn contract_panics(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    let doc = match item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Fn(..) => panics_doc(cx, sp, &item.attrs),
+            _ => {
+                cx.span_err(sp, "#[contract_panics] on non-function item");
+                return Annotatable::Item(item.clone());
+            }
+        },
+        Annotatable::ImplItem(ref item) => match item.node {
+            ast::ImplItemKind::Method(..) => panics_doc(cx, sp, &item.attrs),
+            _ => {
+                cx.span_err(sp, "#[contract_panics] on non-method impl item");
+                return Annotatable::ImplItem(item.clone());
+            }
+        },
+        _ => {
+            cx.span_err(sp, "#[contract_panics] is only supported on functions and methods");
+            return item;
+        }
+    };
+    let doc = match doc {
+        Some(doc) => doc,
+        None => return item,
+    };
+
+    let doc = cx.meta_name_value(
+        sp,
+        Symbol::intern("doc"),
+        ast::LitKind::Str(Symbol::intern(&doc), ast::StrStyle::Cooked),
+    );
+    let doc = cx.attribute(sp, doc);
+    match item {
+        Annotatable::Item(item) => Annotatable::Item(item.map(|mut item| {
+            item.attrs.push(doc);
+            item
+        })),
+        Annotatable::ImplItem(item) => Annotatable::ImplItem(item.map(|mut item| {
+            item.attrs.push(doc);
+            item
+        })),
+        item => item,
+    }
+}
+
+fn panics_doc(cx: &ExtCtxt, sp: Span, attrs: &[ast::Attribute]) -> Option<String> {
+    let mut doc = "# Panics\n".to_owned();
+    let mut found = false;
+    for &(names, builds) in GROUPS {
+        let conditions = conditions(cx, attrs, names);
+        if conditions.is_empty() {
+            continue;
+        }
+        doc.push_str(&match (builds, found) {
+            (None, _) => "\nPanics unless:\n\n".to_owned(),
+            (Some(builds), false) => format!("\nIn {} builds, panics unless:\n\n", builds),
+            (Some(builds), true) => format!("\nIn {} builds, also panics unless:\n\n", builds),
+        });
+        for condition in conditions {
+            doc.push_str(&format!("* `{}`\n", condition));
+        }
+        found = true;
+    }
+    if !found {
+        cx.span_warn(sp, "#[contract_panics]: no preconditions found");
+        return None;
+    }
+    Some(doc)
+}
+
+// The conjuncts of the predicates of the contracts `names` on the item.
+fn conditions(cx: &ExtCtxt, attrs: &[ast::Attribute], names: &[&str]) -> Vec<String> {
+    let mut result = Vec::new();
+    for pred in inputs::entry_predicates(cx, attrs, names) {
+        let pred = cx.parse_expr(pred);
+        for conjunct in super::split_binary(&pred, BinOpKind::And) {
+            result.push(pprust::expr_to_string(&conjunct));
+        }
+    }
+    result
+}
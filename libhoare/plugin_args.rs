@@ -0,0 +1,147 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Arguments of the plugin, see the `Plugin arguments` section of ../readme.md.
+//
+// `#![plugin(hoare(level="debug", backend="debug_assert", strict))]` configures
+// the plugin for the crate. The options of the crate's policy (see policy.rs)
+// set the defaults which `#![hoare_policy(...)]` starts from, and `strict`
+// requires the predicates of contracts to be pure, as far as we can tell from
+// their syntax: they can't assign, borrow mutably, or use unsafe blocks (except
+// in the arguments of macros other than the shorthands of predicates.rs). The
+// arguments are checked when the plugin is registered, so errors are reported
+// once, at the `#![plugin]` attribute.
+
+use std::cell::Cell;
+
+use rustc_plugin::Registry;
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::ExtCtxt;
+use syntax::print::pprust;
+use syntax::visit::{self, Visitor};
+
+use policy::{self, Policy};
+use predicates;
+
+thread_local! {
+    static POLICY: Cell<Policy> = Cell::new(policy::DEFAULT);
+    static STRICT: Cell<bool> = Cell::new(false);
+}
+
+pub fn register(reg: &Registry) {
+    let mut policy = policy::DEFAULT;
+    let mut strict = false;
+    for arg in reg.args() {
+        if arg.is_word() && arg.check_name("strict") {
+            strict = true;
+            continue;
+        }
+        let name = arg.name().map(|name| name.to_string()).unwrap_or_default();
+        let value = arg.value_str().map(|value| value.to_string());
+        match value {
+            Some(ref value) if policy::is_crate_option(arg) => {
+                if !policy::set_option(&name, value, &mut policy) {
+                    reg.sess.span_err(
+                        arg.span,
+                        &format!("invalid value `{}` for the plugin argument `{}`", value, name),
+                    );
+                }
+            }
+            _ => reg.sess.span_err(
+                arg.span,
+                "expected `strict` or a policy option (`level=\"...\"`, `on_violation`, \
+                 `backend`, `old`, `predicates`, `closure_return`, `predicate_panics`, or \
+                 `sample`) in #![plugin(hoare(...))]",
+            ),
+        }
+    }
+    POLICY.with(|p| p.set(policy));
+    STRICT.with(|s| s.set(strict));
+}
+
+// The defaults of the crate's policy.
+pub fn policy() -> Policy {
+    POLICY.with(|policy| policy.get())
+}
+
+// Checks that pred, the predicate of a contract, is pure, if the plugin is
+// strict.
+pub fn check_pure(cx: &ExtCtxt, sp: Span, pred: &ast::Expr) -> Result<(), ()> {
+    if !STRICT.with(|strict| strict.get()) {
+        return Ok(());
+    }
+    let mut finder = ImpureFinder {
+        cx: cx,
+        found: None,
+    };
+    finder.visit_expr(pred);
+    match finder.found {
+        Some((what, e)) => {
+            cx.span_err(
+                sp,
+                &format!(
+                    "the predicate `{}` is not pure, since it {} (`{}`), which is required by \
+                     #![plugin(hoare(strict))]",
+                    pprust::expr_to_string(pred),
+                    what,
+                    e
+                ),
+            );
+            Err(())
+        }
+        None => Ok(()),
+    }
+}
+
+// Finds the first part of an expression which is not pure, with what it does.
+struct ImpureFinder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    found: Option<(&'static str, String)>,
+}
+
+impl<'a, 'b, 'c> Visitor<'c> for ImpureFinder<'a, 'b> {
+    fn visit_expr(&mut self, e: &'c ast::Expr) {
+        if self.found.is_some() {
+            return;
+        }
+        let cx = self.cx;
+        let found = &mut self.found;
+        let shorthand = predicates::map_args(cx, e, |arg| {
+            if found.is_none() {
+                let mut finder = ImpureFinder {
+                    cx: cx,
+                    found: None,
+                };
+                finder.visit_expr(&arg);
+                *found = finder.found;
+            }
+            arg
+        });
+        if shorthand.is_some() {
+            return;
+        }
+        let what = match e.node {
+            ast::ExprKind::Assign(..) | ast::ExprKind::AssignOp(..) => Some("assigns"),
+            ast::ExprKind::AddrOf(ast::Mutability::Mutable, _) => Some("borrows mutably"),
+            ast::ExprKind::Block(ref block) => match block.rules {
+                ast::BlockCheckMode::Unsafe(_) => Some("uses an unsafe block"),
+                ast::BlockCheckMode::Default => None,
+            },
+            _ => None,
+        };
+        match what {
+            Some(what) => self.found = Some((what, pprust::expr_to_string(e))),
+            None => visit::walk_expr(self, e),
+        }
+    }
+
+    fn visit_mac(&mut self, _mac: &'c ast::Mac) {}
+}
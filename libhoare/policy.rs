@@ -0,0 +1,409 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contract policies, see the `Contract policy` section of ../readme.md.
+//
+// `#![hoare_policy(level="debug", on_violation="log", old="clone")]` (see
+// `apply` for all the options) at the crate root sets the defaults for the
+// crate's contracts, starting from those given as arguments of the plugin (see
+// plugin_args.rs). Plugins can not see crate attributes, so we parse the
+// attributes of the crate root the first time a contract is expanded. A
+// contract with a `pred` list (e.g.,
+// `#[precond(pred="x > 0", level="always")]`) can override the defaults, as
+// can the `debug_` and `release_` versions of contracts for the level. A
+// contract with `tag="name"` is removed if the tag is disabled when compiling,
+// and otherwise checks `libhoare::tags::enabled("name")` before its predicate.
+// `predicates="fn"` (see predicate_fns.rs) can only be set for the crate.
+// With `sample="k"`, postconditions check about k of their clauses on each
+// call (see sample.rs), weighted by a contract's `sample_weights`. With
+// `predicate_panics="report"`, a panic in a predicate, other than the check's
+// own, is reported as the predicate crashing (see `libhoare::panics`).
+// A profile (see profile.rs) can override the policy of the contracts of
+// modules and functions. `expand` makes the contract's policy the current one
+// while it is expanded, so that code generating checks can use `current`.
+
+use std::cell::Cell;
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::ExtCtxt;
+use syntax::parse;
+use syntax::symbol::Symbol;
+
+use manifest;
+use plugin_args;
+use profile;
+
+// Which builds check a contract.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Always,
+    Debug,
+    Release,
+    Off,
+}
+
+// What happens when a contract is violated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OnViolation {
+    Panic,
+    Log,
+    Abort,
+}
+
+// What the generated checks are built on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Assert,
+    DebugAssert,
+}
+
+// How `old(e)` saves the value of `e`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Old {
+    Snapshot,
+    Clone,
+    // Copied into a buffer reused across calls (see history.rs).
+    Pooled,
+}
+
+// Whether predicates are checked where they are used or compiled to functions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Predicates {
+    Inline,
+    Fn,
+}
+
+// What `return` in a closure in a postcondition is (see returns.rs).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClosureReturn {
+    Closure,
+    Result,
+}
+
+// What happens when evaluating a predicate panics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PredicatePanics {
+    // The panic unwinds as it is.
+    Propagate,
+    // The check panics again, saying the predicate crashed (see
+    // `libhoare::panics::report_predicate_panics`).
+    Report,
+}
+
+#[derive(Clone, Copy)]
+pub struct Policy {
+    pub level: Level,
+    pub on_violation: OnViolation,
+    pub backend: Backend,
+    pub old: Old,
+    pub predicates: Predicates,
+    pub closure_return: ClosureReturn,
+    pub predicate_panics: PredicatePanics,
+    // The number of clauses of a postcondition to check per call, on average,
+    // see `sample`.
+    pub sample: Option<usize>,
+    // The relative weights of the clauses when sampling.
+    pub sample_weights: Option<Symbol>,
+    pub tag: Option<Symbol>,
+}
+
+pub const DEFAULT: Policy = Policy {
+    level: Level::Always,
+    on_violation: OnViolation::Panic,
+    backend: Backend::Assert,
+    old: Old::Snapshot,
+    predicates: Predicates::Inline,
+    closure_return: ClosureReturn::Closure,
+    predicate_panics: PredicatePanics::Propagate,
+    sample: None,
+    sample_weights: None,
+    tag: None,
+};
+
+// The names of the options which can be given in a policy.
+const OPTIONS: &'static [&'static str] = &[
+    "level",
+    "on_violation",
+    "backend",
+    "old",
+    "predicates",
+    "closure_return",
+    "predicate_panics",
+    "sample",
+    "sample_weights",
+    "tag",
+];
+
+thread_local! {
+    static CRATE_POLICY: Cell<Option<Policy>> = Cell::new(None);
+    static CURRENT: Cell<Option<Policy>> = Cell::new(None);
+}
+
+// The crate's policy, from `#![hoare_policy(...)]` at the crate root.
+pub fn crate_policy(cx: &ExtCtxt) -> Policy {
+    if let Some(policy) = CRATE_POLICY.with(|policy| policy.get()) {
+        return policy;
+    }
+    let policy = read_crate_policy(cx);
+    CRATE_POLICY.with(|p| p.set(Some(policy)));
+    policy
+}
+
+// The policy of the contract being expanded, or the crate's.
+pub fn current(cx: &ExtCtxt) -> Policy {
+    CURRENT.with(|policy| policy.get()).unwrap_or_else(|| crate_policy(cx))
+}
+
+// Whether item is an option of a policy, rather than, e.g., a predicate.
+pub fn is_option(item: &ast::NestedMetaItem) -> bool {
+    OPTIONS.iter().any(|name| item.check_name(name))
+}
+
+// Whether item is an option which can be set for the crate. Tags and weights are
+// only for individual contracts.
+pub fn is_crate_option(item: &ast::NestedMetaItem) -> bool {
+    is_option(item) && !item.check_name("tag") && !item.check_name("sample_weights")
+}
+
+// Expands the contract attr on the function fn_name, using f, with its policy as
+// the current one, if the contract is checked in this build. Otherwise, returns
+// item.
+pub fn expand<F, T>(cx: &mut ExtCtxt, attr: &MetaItem, fn_name: ast::Ident, f: F, item: T) -> T
+where
+    F: Fn(&mut ExtCtxt) -> T,
+{
+    let mut policy = crate_policy(cx);
+    if let Some(items) = attr.meta_item_list() {
+        for item in items.iter().filter(|item| item.check_name("predicates")) {
+            cx.span_err(item.span, "`predicates` can only be set in #![hoare_policy(...)]");
+        }
+        apply(cx, items, &mut policy);
+    }
+    set_level(attr, &mut policy);
+    profile::apply(cx, &manifest::fn_path(cx, &fn_name.name.as_str()), &mut policy);
+    if !checked(cx, &policy) {
+        return item;
+    }
+
+    let outer = CURRENT.with(|current| current.replace(Some(policy)));
+    let result = f(cx);
+    CURRENT.with(|current| current.set(outer));
+    result
+}
+
+// Whether the contract attr on the function fn_name is not checked in this
+// build, because of its level, a disabled tag, or the profile. A contract with
+// invalid options is not disabled, so that expanding it reports them.
+pub fn disabled(cx: &ExtCtxt, attr: &MetaItem, fn_name: ast::Ident) -> bool {
+    let mut policy = crate_policy(cx);
+    if let Some(items) = attr.meta_item_list() {
+        if !set_options(items, &mut policy).is_empty() {
+            return false;
+        }
+    }
+    set_level(attr, &mut policy);
+    profile::apply(cx, &manifest::fn_path(cx, &fn_name.name.as_str()), &mut policy);
+    !checked(cx, &policy)
+}
+
+// The `debug_` and `release_` versions of contracts always use their own level.
+fn set_level(attr: &MetaItem, policy: &mut Policy) {
+    let name = attr.name.as_str();
+    if name.starts_with("debug_") {
+        policy.level = Level::Debug;
+    } else if name.starts_with("release_") {
+        policy.level = Level::Release;
+    }
+}
+
+// Whether a contract with policy is checked in this build.
+fn checked(cx: &ExtCtxt, policy: &Policy) -> bool {
+    let level = match policy.level {
+        Level::Always => true,
+        Level::Debug => super::has_cfg(cx, "debug_assertions"),
+        Level::Release => !super::has_cfg(cx, "debug_assertions"),
+        Level::Off => false,
+    };
+    level && !policy.tag.map_or(false, |tag| tag_disabled(cx, tag))
+}
+
+// Sets the options in items on policy, reporting any which are invalid.
+fn apply(cx: &ExtCtxt, items: &[ast::NestedMetaItem], policy: &mut Policy) {
+    for sp in set_options(items, policy) {
+        cx.span_err(
+            sp,
+            "expected `level=\"always|debug|release|off\"`, \
+             `on_violation=\"panic|log|abort\"`, `backend=\"assert|debug_assert\"`, \
+             `old=\"snapshot|clone|pooled\"`, `predicates=\"inline|fn\"`, \
+             `closure_return=\"closure|result\"`, `predicate_panics=\"propagate|report\"`, \
+             `sample=\"clauses per call\"`, \
+             `sample_weights=\"weight, ...\"`, or `tag=\"name\"`",
+        );
+    }
+}
+
+// Sets the options in items on policy, and returns the spans of those which
+// are invalid.
+fn set_options(items: &[ast::NestedMetaItem], policy: &mut Policy) -> Vec<Span> {
+    let mut invalid = Vec::new();
+    for item in items.iter().filter(|item| is_option(item)) {
+        let name = item.name().map(|name| name.to_string()).unwrap_or_default();
+        let value = item.value_str().map(|value| value.to_string()).unwrap_or_default();
+        if !set_option(&name, &value, policy) {
+            invalid.push(item.span);
+        }
+    }
+    invalid
+}
+
+// Sets the option name to value on policy, or returns false if it is invalid.
+pub fn set_option(name: &str, value: &str, policy: &mut Policy) -> bool {
+    match name {
+        "level" => parse_level(value).map(|level| policy.level = level).is_some(),
+        "on_violation" => {
+            parse_on_violation(value)
+                .map(|on_violation| policy.on_violation = on_violation)
+                .is_some()
+        }
+        "backend" => parse_backend(value).map(|backend| policy.backend = backend).is_some(),
+        "predicates" => {
+            parse_predicates(value).map(|predicates| policy.predicates = predicates).is_some()
+        }
+        "closure_return" => {
+            parse_closure_return(value)
+                .map(|closure_return| policy.closure_return = closure_return)
+                .is_some()
+        }
+        "predicate_panics" => {
+            parse_predicate_panics(value)
+                .map(|predicate_panics| policy.predicate_panics = predicate_panics)
+                .is_some()
+        }
+        "sample" => match value.trim().parse() {
+            Ok(n) if n > 0 => {
+                policy.sample = Some(n);
+                true
+            }
+            _ => false,
+        },
+        "sample_weights" => {
+            policy.sample_weights = Some(Symbol::intern(value));
+            !value.is_empty()
+        }
+        "tag" => {
+            policy.tag = Some(Symbol::intern(value));
+            !value.is_empty()
+        }
+        "old" => parse_old(value).map(|old| policy.old = old).is_some(),
+        _ => false,
+    }
+}
+
+// Whether contracts with tag are disabled when compiling, with
+// `--cfg hoare_disable_tag="tag"` or the crate's feature `hoare-disable-tag`.
+fn tag_disabled(cx: &ExtCtxt, tag: Symbol) -> bool {
+    let tag = tag.as_str();
+    super::has_cfg_value(cx, "hoare_disable_tag", &tag) ||
+        super::has_cfg_value(cx, "feature", &format!("hoare-disable-{}", tag))
+}
+
+fn parse_level(s: &str) -> Option<Level> {
+    match s {
+        "always" => Some(Level::Always),
+        "debug" => Some(Level::Debug),
+        "release" => Some(Level::Release),
+        "off" => Some(Level::Off),
+        _ => None,
+    }
+}
+
+fn parse_on_violation(s: &str) -> Option<OnViolation> {
+    match s {
+        "panic" => Some(OnViolation::Panic),
+        "log" => Some(OnViolation::Log),
+        "abort" => Some(OnViolation::Abort),
+        _ => None,
+    }
+}
+
+fn parse_backend(s: &str) -> Option<Backend> {
+    match s {
+        "assert" => Some(Backend::Assert),
+        "debug_assert" => Some(Backend::DebugAssert),
+        _ => None,
+    }
+}
+
+fn parse_old(s: &str) -> Option<Old> {
+    match s {
+        "snapshot" => Some(Old::Snapshot),
+        "clone" => Some(Old::Clone),
+        "pooled" => Some(Old::Pooled),
+        _ => None,
+    }
+}
+
+fn parse_predicates(s: &str) -> Option<Predicates> {
+    match s {
+        "inline" => Some(Predicates::Inline),
+        "fn" => Some(Predicates::Fn),
+        _ => None,
+    }
+}
+
+fn parse_closure_return(s: &str) -> Option<ClosureReturn> {
+    match s {
+        "closure" => Some(ClosureReturn::Closure),
+        "result" => Some(ClosureReturn::Result),
+        _ => None,
+    }
+}
+
+fn parse_predicate_panics(s: &str) -> Option<PredicatePanics> {
+    match s {
+        "propagate" => Some(PredicatePanics::Propagate),
+        "report" => Some(PredicatePanics::Report),
+        _ => None,
+    }
+}
+
+fn read_crate_policy(cx: &ExtCtxt) -> Policy {
+    let mut policy = plugin_args::policy();
+    // The crate root is the first file parsed.
+    let root = match cx.codemap().files.borrow().first() {
+        Some(root) => root.clone(),
+        None => return policy,
+    };
+    let src = match root.src {
+        Some(ref src) => (**src).clone(),
+        None => return policy,
+    };
+    let attrs = parse::parse_crate_attrs_from_source_str(root.name.clone(), src, cx.parse_sess);
+    let attrs = match attrs {
+        Ok(attrs) => attrs,
+        Err(mut e) => {
+            e.cancel();
+            return policy;
+        }
+    };
+
+    for attr in attrs.iter().filter(|attr| attr.check_name("hoare_policy")) {
+        match attr.meta_item_list() {
+            Some(ref items) if items.iter().all(is_crate_option) => apply(cx, items, &mut policy),
+            _ => cx.span_err(
+                attr.span,
+                "expected #![hoare_policy(level=\"...\", on_violation=\"...\", old=\"...\")]",
+            ),
+        }
+    }
+    policy
+}
@@ -0,0 +1,201 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Compiling predicates to functions, see the `Predicate functions` section of
+// ../readme.md.
+//
+// With `#![hoare_policy(predicates="fn")]`, the predicate of each
+// precondition, postcondition, and invariant of a free function `foo` is
+// compiled to `__hoare_<precond|postcond|invariant>_foo_<n>`, where n counts
+// the contracts of that kind on `foo` in the order they are written. The
+// function takes a reference to each argument of `foo` (and, for a
+// postcondition, to the returned value), and each use of an argument in the
+// predicate is replaced by `(*arg)`. The checks call the function instead of
+// evaluating the predicate, and report the original predicate when it fails.
+//
+// The function of an invariant is always inlined in release builds (see
+// `invariant_method` in refinement.rs).
+//
+// Methods keep their checks inline, since a method's predicate function would
+// have to be in the same impl, and a trait impl can't have extra methods.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::fold::{self, Folder};
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use super::Contract;
+use inputs;
+use manifest;
+use predicates;
+
+thread_local! {
+    // The number of predicate functions generated for each kind of contract
+    // of each function.
+    static COUNTS: RefCell<HashMap<String, usize>> = RefCell::new(HashMap::new());
+}
+
+pub fn contract(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    item: Annotatable,
+    contract: Contract,
+) -> Vec<Annotatable> {
+    let (vis, generics) = match item {
+        Annotatable::Item(ref f) => match f.node {
+            ast::ItemKind::Fn(_, _, _, _, ref generics, _) => (f.vis.clone(), generics.clone()),
+            _ => return vec![super::map_annotatble(cx, sp, attr, item.clone(), contract)],
+        },
+        _ => return vec![super::map_annotatble(cx, sp, attr, item, contract)],
+    };
+
+    let mut pred_fn = None;
+    let kind = contract.long_str();
+    let free_fn = match item {
+        Annotatable::Item(ref item) => Some(item.clone()),
+        _ => None,
+    };
+    let item = super::map_fn_body(cx, sp, item, kind, |cx, ident, attrs, decl, body| {
+        let (pred, pred_str) = try!(super::contract_pred(cx, sp, attr, &contract));
+        let name = fn_name(cx, &contract, ident);
+        let (f, call) = try!(compile(cx, &name, &vis, &generics, decl, &contract, pred));
+        pred_fn = Some(f);
+        let free_fn = free_fn.as_ref();
+        super::contract_body(free_fn, ident, attrs, decl, body, cx, sp, call, &pred_str, contract)
+    });
+
+    let mut result = vec![item];
+    result.extend(pred_fn.map(Annotatable::Item));
+    result
+}
+
+// `__hoare_<kind>_<fn>_<n>`.
+fn fn_name(cx: &ExtCtxt, contract: &Contract, ident: ast::Ident) -> String {
+    let prefix = format!("__hoare_{}_{}", contract.short_str(), ident);
+    let key = manifest::fn_path(cx, &prefix);
+    let n = COUNTS.with(|counts| {
+        let mut counts = counts.borrow_mut();
+        let count = counts.entry(key).or_insert(0);
+        *count += 1;
+        *count - 1
+    });
+    format!("{}_{}", prefix, n)
+}
+
+// The predicate function called name, and a call of it from the function with
+// decl.
+fn compile(
+    cx: &ExtCtxt,
+    name: &str,
+    vis: &ast::Visibility,
+    generics: &ast::Generics,
+    decl: &ast::FnDecl,
+    contract: &Contract,
+    pred: P<ast::Expr>,
+) -> Result<(P<ast::Item>, P<ast::Expr>), ()> {
+    let args = match inputs::fn_args(cx, decl, "`predicates=\"fn\"`") {
+        Some(args) => args,
+        None => return Err(()),
+    };
+    let mut params: Vec<String> = args
+        .iter()
+        .map(|&(ident, ref ty)| format!("{}: &{}", ident, pprust::ty_to_string(ty)))
+        .collect();
+    let mut names: Vec<ast::Name> = args.iter().map(|&(ident, _)| ident.name).collect();
+    if contract.checks_return() {
+        let result_name = super::result_name();
+        let ret = match decl.output {
+            ast::FunctionRetTy::Ty(ref ty) => pprust::ty_to_string(ty),
+            ast::FunctionRetTy::Default(_) => "()".to_owned(),
+        };
+        params.push(format!("{}: &{}", result_name, ret));
+        names.push(result_name.name);
+    }
+
+    let body = DerefArgs { cx: cx, names: names.clone() }.fold_expr(pred);
+    let (generic_params, _, where_clause) = super::generics_strings(generics);
+    let inline = match *contract {
+        Contract::Invariant { .. } => "#[cfg_attr(not(debug_assertions), inline(always))]\n",
+        _ => "",
+    };
+    let f = cx.parse_item(format!(
+        "#[doc(hidden)]\n\
+         #[allow(dead_code, non_snake_case)]\n\
+         {inline}\
+         {vis} fn {name}{params}({args}) -> bool{where_clause} {{ {body} }}",
+        inline = inline,
+        vis = pprust::vis_to_string(vis).trim(),
+        name = name,
+        params = generic_params,
+        args = params.join(", "),
+        where_clause = where_clause,
+        body = pprust::expr_to_string(&body)
+    ));
+
+    // Type parameters which only appear in the return type can't be inferred.
+    let type_args: Vec<String> = generics.ty_params.iter().map(|p| p.ident.to_string()).collect();
+    let call = format!(
+        "{}{}({})",
+        name,
+        if type_args.is_empty() { String::new() } else { format!("::<{}>", type_args.join(", ")) },
+        names.iter().map(|name| format!("&{}", name)).collect::<Vec<_>>().join(", ")
+    );
+    Ok((f, cx.parse_expr(call)))
+}
+
+// Replaces each use of one of names (the arguments, which the predicate
+// function takes by reference) with `(*name)`, except where a closure's
+// argument shadows it.
+struct DerefArgs<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    names: Vec<ast::Name>,
+}
+
+impl<'a, 'b> Folder for DerefArgs<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        match e.node {
+            ast::ExprKind::Path(None, ref path)
+                if path.segments.len() == 1 &&
+                    self.names.contains(&path.segments[0].identifier.name) => {
+                return self.cx.parse_expr(format!("(*{})", path.segments[0].identifier));
+            }
+            ast::ExprKind::Closure(_, ref decl, _, _) => {
+                let outer = self.names.clone();
+                for arg in &decl.inputs {
+                    if let ast::PatKind::Ident(_, ref ident, _) = arg.pat.node {
+                        self.names.retain(|name| *name != ident.node.name);
+                    }
+                }
+                let e = e.clone().map(|e| fold::noop_fold_expr(e, self));
+                self.names = outer;
+                return e;
+            }
+            _ => {}
+        }
+        // The arguments of shorthands such as `in_range!` are expressions.
+        let cx = self.cx;
+        if let Some(e) = predicates::map_args(cx, &e, |arg| self.fold_expr(arg)) {
+            return e;
+        }
+        e.map(|e| fold::noop_fold_expr(e, self))
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        mac
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Predicate shorthands, see the `Predicate shorthands` section of
+// ../readme.md.
+//
+// `in_range!(x, r)`, `non_empty!(c)`, `is_sorted!(s)`, `checked_at!(c, i)`,
+// `valid_index!(i, c)`, `is_ascii!(s)`, `is_char_boundary!(s, i)`, and
+// `valid_utf8!(b)` are macros which can be used anywhere in a predicate,
+// they call the functions of the same name in `libhoare::predicates` and check
+// the result is `Ok`. If the whole predicate
+// is one of these macros, the check instead panics with the `Err` message,
+// which describes the value which violated the predicate.
+//
+// `increased!(a, b)`, `decreased!(a, b)`, `increased_by!(a, b, n)`,
+// `decreased_by!(a, b, n)`, `grew_by!(a, b, n)`, and `shrank_by!(a, b, n)`
+// compare an old value `a` (usually `old(e)` in a transition) with a new value
+// `b` in the same way. `old(e)` in the arguments of any shorthand is found by
+// history.rs using `map_args`.
+//
+// `implies!(a, b)` and `iff!(a, b)` are logical connectives, they expand to
+// `!(a) || (b)` and `(a) == (b)` (so `implies!` only evaluates `b` if `a`
+// holds). If the whole predicate is one of them, the message says which side
+// held.
+//
+// `each!(c, |x| pred)` checks pred holds for each element of the collection c,
+// it expands to `(&c).into_iter().all(|x| pred)`, so x is a reference to the
+// element (unless the closure's pattern dereferences it, e.g., `|&x|`). If the
+// whole predicate is `each!`, the elements are checked in a loop, and the
+// message gives the index of the first which violates pred.
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::{DummyResult, ExtCtxt, MacEager, MacResult};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::parse::token;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::tokenstream::TokenTree;
+
+// (name, number of arguments)
+const SHORTHANDS: &'static [(&'static str, usize)] = &[
+    ("in_range", 2),
+    ("non_empty", 1),
+    ("is_sorted", 1),
+    ("checked_at", 2),
+    ("valid_index", 2),
+    ("is_ascii", 1),
+    ("is_char_boundary", 2),
+    ("valid_utf8", 1),
+    ("increased", 2),
+    ("decreased", 2),
+    ("increased_by", 3),
+    ("decreased_by", 3),
+    ("grew_by", 3),
+    ("shrank_by", 3),
+    ("implies", 2),
+    ("iff", 2),
+    ("each", 2),
+];
+
+fn is_connective(name: &str) -> bool {
+    name == "implies" || name == "iff"
+}
+
+pub fn in_range(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "in_range", tts)
+}
+
+pub fn non_empty(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "non_empty", tts)
+}
+
+pub fn is_sorted(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "is_sorted", tts)
+}
+
+pub fn checked_at(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "checked_at", tts)
+}
+
+pub fn valid_index(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "valid_index", tts)
+}
+
+pub fn is_ascii(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "is_ascii", tts)
+}
+
+pub fn is_char_boundary(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "is_char_boundary", tts)
+}
+
+pub fn valid_utf8(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "valid_utf8", tts)
+}
+
+pub fn increased(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "increased", tts)
+}
+
+pub fn decreased(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "decreased", tts)
+}
+
+pub fn increased_by(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "increased_by", tts)
+}
+
+pub fn decreased_by(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "decreased_by", tts)
+}
+
+pub fn grew_by(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "grew_by", tts)
+}
+
+pub fn shrank_by(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    expand(cx, sp, "shrank_by", tts)
+}
+
+pub fn implies(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    match args(cx, sp, "implies", tts) {
+        Some(args) => {
+            let (a, b) = (&args[0], &args[1]);
+            MacEager::expr(quote_expr!(cx, (!($a) || ($b))))
+        }
+        None => DummyResult::expr(sp),
+    }
+}
+
+pub fn iff(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    match args(cx, sp, "iff", tts) {
+        Some(args) => {
+            let (a, b) = (&args[0], &args[1]);
+            MacEager::expr(quote_expr!(cx, (($a) == ($b))))
+        }
+        None => DummyResult::expr(sp),
+    }
+}
+
+pub fn each(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    match args(cx, sp, "each", tts) {
+        Some(ref args) if closure(cx, sp, &args[1]).is_some() => {
+            let (c, f) = (&args[0], &args[1]);
+            MacEager::expr(quote_expr!(cx, (&$c).into_iter().all($f)))
+        }
+        _ => DummyResult::expr(sp),
+    }
+}
+
+fn expand(cx: &mut ExtCtxt, sp: Span, name: &str, tts: &[TokenTree]) -> Box<MacResult> {
+    match call(cx, sp, name, tts) {
+        Some(call) => MacEager::expr(quote_expr!(cx, ::std::result::Result::is_ok(&$call))),
+        None => DummyResult::expr(sp),
+    }
+}
+
+// If pred is an invocation of one of the shorthands, returns a check which
+// panics with label and the reason the predicate failed, otherwise None. If the check fails,
+// before_panic is executed before panicking.
+pub fn assert(
+    cx: &ExtCtxt,
+    pred: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> Option<ast::Stmt> {
+    let (name, tts) = match pred.node {
+        ast::ExprKind::Paren(ref inner) => return assert(cx, inner, label, before_panic),
+        ast::ExprKind::Mac(ref mac) if mac.node.path.segments.len() == 1 => {
+            let name = mac.node.path.segments[0].identifier.name.as_str();
+            if !SHORTHANDS.iter().any(|&(s, _)| s == &*name) {
+                return None;
+            }
+            (name, mac.node.stream().trees().collect::<Vec<_>>())
+        }
+        _ => return None,
+    };
+
+    if is_connective(&name) {
+        return Some(match args(cx, pred.span, &name, &tts) {
+            Some(args) => connective(cx, &name, &args[0], &args[1], label, before_panic),
+            None => cx.stmt_expr(DummyResult::raw_expr(pred.span)),
+        });
+    }
+
+    if name == "each" {
+        let check = args(cx, pred.span, &name, &tts).and_then(|args| {
+            closure(cx, pred.span, &args[1])
+                .map(|(pat, body)| each_check(cx, &args[0], pat, &body, label, before_panic))
+        });
+        return Some(check.unwrap_or_else(|| cx.stmt_expr(DummyResult::raw_expr(pred.span))));
+    }
+
+    let call = match call(cx, pred.span, &name, &tts) {
+        Some(call) => call,
+        None => return Some(cx.stmt_expr(DummyResult::raw_expr(pred.span))),
+    };
+    let before_panic = before_panic.into_iter().collect::<Vec<_>>();
+    quote_stmt!(cx,
+        if let ::std::result::Result::Err(__hoare_reason) = $call {
+            $before_panic
+            panic!("{}: {}", $label, __hoare_reason);
+        }
+    )
+}
+
+// The check for `implies!(a, b)` or `iff!(a, b)`, the message says which of a
+// and b held.
+fn connective(
+    cx: &ExtCtxt,
+    name: &str,
+    a: &P<ast::Expr>,
+    b: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> ast::Stmt {
+    let (a_str, b_str) = (pprust::expr_to_string(a), pprust::expr_to_string(b));
+    let a_not_b = format!("{} holds but {} does not", a_str, b_str);
+    let before_panic = before_panic.into_iter().collect::<Vec<_>>();
+    if name == "implies" {
+        return quote_stmt!(cx,
+            if ($a) && !($b) {
+                $before_panic
+                panic!("{}: {}", $label, $a_not_b);
+            }
+        ).unwrap();
+    }
+
+    let b_not_a = format!("{} holds but {} does not", b_str, a_str);
+    quote_stmt!(cx, {
+        let __hoare_a: bool = $a;
+        let __hoare_b: bool = $b;
+        if __hoare_a != __hoare_b {
+            $before_panic
+            panic!("{}: {}", $label, if __hoare_a { $a_not_b } else { $b_not_a });
+        }
+    }).unwrap()
+}
+
+// The pattern and body of f, the closure of one argument which `each!` takes,
+// or None (after reporting an error) if it isn't one.
+fn closure(cx: &ExtCtxt, sp: Span, f: &ast::Expr) -> Option<(P<ast::Pat>, P<ast::Expr>)> {
+    match f.node {
+        ast::ExprKind::Closure(_, ref decl, ref body, _) if decl.inputs.len() == 1 => {
+            Some((decl.inputs[0].pat.clone(), body.clone()))
+        }
+        ast::ExprKind::Paren(ref inner) => closure(cx, sp, inner),
+        _ => {
+            cx.span_err(sp, "the second argument of each! must be a closure of one argument");
+            None
+        }
+    }
+}
+
+// The check for `each!(c, |pat| body)`, the message gives the index of the
+// first element for which body does not hold.
+fn each_check(
+    cx: &ExtCtxt,
+    c: &P<ast::Expr>,
+    pat: P<ast::Pat>,
+    body: &P<ast::Expr>,
+    label: &str,
+    before_panic: Option<ast::Stmt>,
+) -> ast::Stmt {
+    let body_str = pprust::expr_to_string(body);
+    let before_panic = before_panic.into_iter().collect::<Vec<_>>();
+    quote_stmt!(cx,
+        for (__hoare_index, $pat) in (&$c).into_iter().enumerate() {
+            if !($body) {
+                $before_panic
+                panic!("{}: element {} does not satisfy {}", $label, __hoare_index, $body_str);
+            }
+        }
+    ).unwrap()
+}
+
+// If e is an invocation of one of the shorthands, the invocation with f
+// applied to each of its arguments, otherwise None.
+pub fn map_args<F>(cx: &ExtCtxt, e: &ast::Expr, mut f: F) -> Option<P<ast::Expr>>
+where
+    F: FnMut(P<ast::Expr>) -> P<ast::Expr>,
+{
+    let (name, tts) = match e.node {
+        ast::ExprKind::Mac(ref mac) if mac.node.path.segments.len() == 1 => {
+            let name = mac.node.path.segments[0].identifier.name.as_str();
+            if !SHORTHANDS.iter().any(|&(s, _)| s == &*name) {
+                return None;
+            }
+            (name, mac.node.stream().trees().collect::<Vec<_>>())
+        }
+        _ => return None,
+    };
+    let args = match args(cx, e.span, &name, &tts) {
+        Some(args) => args,
+        None => return None,
+    };
+    let args = args
+        .into_iter()
+        .map(|arg| pprust::expr_to_string(&f(arg)))
+        .collect::<Vec<_>>();
+    Some(cx.parse_expr(format!("{}!({})", name, args.join(", "))))
+}
+
+// `::libhoare::predicates::name(&arg, ...)`, or None (after reporting an
+// error) if the arguments are wrong.
+fn call(cx: &ExtCtxt, sp: Span, name: &str, tts: &[TokenTree]) -> Option<P<ast::Expr>> {
+    let args = match args(cx, sp, name, tts) {
+        Some(args) => args.into_iter().map(|arg| cx.expr_addr_of(sp, arg)).collect(),
+        None => return None,
+    };
+    let path = cx.path_global(
+        sp,
+        vec![cx.ident_of("libhoare"), cx.ident_of("predicates"), cx.ident_of(name)],
+    );
+    Some(cx.expr_call(sp, cx.expr_path(path), args))
+}
+
+// The arguments of the shorthand name, or None (after reporting an error) if
+// they are wrong.
+fn args(cx: &ExtCtxt, sp: Span, name: &str, tts: &[TokenTree]) -> Option<Vec<P<ast::Expr>>> {
+    let arity = SHORTHANDS.iter().find(|&&(s, _)| s == name).unwrap().1;
+    let mut parser = cx.new_parser_from_tts(tts);
+    let mut args = Vec::new();
+    while parser.token != token::Eof {
+        match parser.parse_expr() {
+            Ok(arg) => args.push(arg),
+            Err(mut e) => {
+                e.emit();
+                return None;
+            }
+        }
+        if !parser.eat(&token::Comma) && parser.token != token::Eof {
+            cx.span_err(sp, &format!("expected `,` in arguments to {}!", name));
+            return None;
+        }
+    }
+    if args.len() != arity {
+        cx.span_err(
+            sp,
+            &format!("{}! takes {} argument{}", name, arity, if arity == 1 { "" } else { "s" }),
+        );
+        return None;
+    }
+    Some(args)
+}
@@ -0,0 +1,207 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Contract profiles, see the `Contract profiles` section of ../readme.md.
+//
+// A profile is a `hoare.toml` file (next to the crate's Cargo.toml, or the
+// file named by `HOARE_PROFILE`) which overrides the policies of the contracts
+// of modules and functions, without changing their attributes:
+//
+// [module."my_crate::parser"]
+// level = "debug"
+//
+// [fn."my_crate::parser::tokenize"]
+// enabled = false
+//
+// We read a small subset of TOML: tables named `module."path"` or `fn."path"`,
+// `key = value` lines with string, integer, and boolean values, and comments.
+// The profile is read the first time a contract is expanded. The tables of the
+// modules containing a function apply first, from the outermost, then its own.
+// They are applied after the options of each contract, so they override them.
+
+use std::cell::RefCell;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use syntax::ext::base::ExtCtxt;
+
+use policy::{self, Policy};
+
+pub const FILE_VAR: &'static str = "HOARE_PROFILE";
+
+// The options a profile can set, other than `enabled`.
+const OPTIONS: &'static [&'static str] = &["level", "on_violation", "sample"];
+
+thread_local! {
+    static PROFILE: RefCell<Option<Rc<Vec<Table>>>> = RefCell::new(None);
+}
+
+struct Table {
+    // Whether the table is for a module (and its submodules), or a function.
+    module: bool,
+    path: String,
+    options: Vec<(String, String)>,
+}
+
+impl Table {
+    fn applies_to(&self, fn_path: &str) -> bool {
+        if self.module {
+            fn_path.starts_with(&format!("{}::", self.path))
+        } else {
+            fn_path == self.path
+        }
+    }
+}
+
+// Applies the profile's overrides for the function at fn_path (see
+// manifest::fn_path) to policy.
+pub fn apply(cx: &ExtCtxt, fn_path: &str, policy: &mut Policy) {
+    for (name, value) in overrides(cx, fn_path) {
+        if name == "enabled" {
+            if value == "false" {
+                policy.level = policy::Level::Off;
+            } else if policy.level == policy::Level::Off {
+                policy.level = policy::Level::Always;
+            }
+        } else {
+            policy::set_option(&name, &value, policy);
+        }
+    }
+}
+
+// The overrides for the function at fn_path, in the order they apply.
+pub fn overrides(cx: &ExtCtxt, fn_path: &str) -> Vec<(String, String)> {
+    let profile = profile(cx);
+    let mut tables: Vec<&Table> =
+        profile.iter().filter(|table| table.applies_to(fn_path)).collect();
+    // The sort is stable, so later tables for the same path win.
+    tables.sort_by_key(|table| (!table.module, table.path.len()));
+    tables.into_iter().flat_map(|table| table.options.iter().cloned()).collect()
+}
+
+fn profile(cx: &ExtCtxt) -> Rc<Vec<Table>> {
+    if let Some(profile) = PROFILE.with(|profile| profile.borrow().clone()) {
+        return profile;
+    }
+    let profile = Rc::new(read_profile(cx));
+    PROFILE.with(|p| *p.borrow_mut() = Some(profile.clone()));
+    profile
+}
+
+fn read_profile(cx: &ExtCtxt) -> Vec<Table> {
+    let (path, required) = match env::var_os(FILE_VAR) {
+        Some(path) => (PathBuf::from(path), true),
+        None => {
+            let dir = env::var_os("CARGO_MANIFEST_DIR").map_or(PathBuf::new(), PathBuf::from);
+            (dir.join("hoare.toml"), false)
+        }
+    };
+    let mut src = String::new();
+    if let Err(e) = File::open(&path).and_then(|mut file| file.read_to_string(&mut src)) {
+        if required {
+            cx.span_err(
+                cx.call_site(),
+                &format!("could not read contract profile {}: {}", path.display(), e),
+            );
+        }
+        return Vec::new();
+    }
+    match parse(&src) {
+        Ok(tables) => tables,
+        Err((line, msg)) => {
+            cx.span_err(cx.call_site(), &format!("{}:{}: {}", path.display(), line, msg));
+            Vec::new()
+        }
+    }
+}
+
+// The tables of src, or the line number of the first error and a message.
+fn parse(src: &str) -> Result<Vec<Table>, (usize, String)> {
+    let mut tables: Vec<Table> = Vec::new();
+    for (i, line) in src.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(comment) if !line[..comment].contains('"') => &line[..comment],
+            _ => line,
+        }.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            tables.push(try!(header(&line[1..line.len() - 1]).map_err(|msg| (i + 1, msg))));
+            continue;
+        }
+
+        let table = match tables.last_mut() {
+            Some(table) => table,
+            None => return Err((i + 1, "expected a table before the first option".to_owned())),
+        };
+        let (name, value) = match line.find('=') {
+            Some(eq) => (line[..eq].trim(), line[eq + 1..].trim()),
+            None => return Err((i + 1, "expected `key = value`".to_owned())),
+        };
+        let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            value[1..value.len() - 1].to_owned()
+        } else {
+            value.to_owned()
+        };
+        let valid = if name == "enabled" {
+            value == "true" || value == "false"
+        } else {
+            let mut scratch = policy::DEFAULT;
+            OPTIONS.contains(&name) && policy::set_option(name, &value, &mut scratch)
+        };
+        if !valid {
+            return Err((
+                i + 1,
+                format!(
+                    "invalid `{} = {}`, expected `enabled = true|false`, \
+                     `level = \"always|debug|release|off\"`, \
+                     `on_violation = \"panic|log|abort\"`, or `sample = clauses per call`",
+                    name,
+                    value
+                ),
+            ));
+        }
+        table.options.push((name.to_owned(), value));
+    }
+    Ok(tables)
+}
+
+// The table with header `module."path"` or `fn."path"`.
+fn header(header: &str) -> Result<Table, String> {
+    let error = || format!("expected `[module.\"path\"]` or `[fn.\"path\"]`, found `[{}]`", header);
+    let dot = match header.find('.') {
+        Some(dot) => dot,
+        None => return Err(error()),
+    };
+    let module = match header[..dot].trim() {
+        "module" => true,
+        "fn" => false,
+        _ => return Err(error()),
+    };
+    let path = header[dot + 1..].trim();
+    let path = if path.len() >= 2 && path.starts_with('"') && path.ends_with('"') {
+        &path[1..path.len() - 1]
+    } else {
+        path
+    };
+    if path.is_empty() {
+        return Err(error());
+    }
+    Ok(Table {
+        module: module,
+        path: path.to_owned(),
+        options: Vec::new(),
+    })
+}
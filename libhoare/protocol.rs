@@ -0,0 +1,286 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Protocols (typestate checked at runtime), see the `Protocols` section of
+// ../readme.md.
+//
+// `#[protocol]` on a struct adds a `__hoare_state: libhoare::protocol::State`
+// field. `#[protocol(initial="a", m="a | b -> c", ..)]` on its inherent impl
+// initializes that field in every `Self { .. }` literal in the impl and, in
+// debug builds, makes each method `m` check that it is called in state `a` or
+// `b` and then move to state `c`. States are numbered in order of appearance,
+// starting with the initial state. The field is added in every build, so that
+// struct literals outside the impl which initialize it compile in release builds
+// too; it costs a `usize` per value.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::fold::{self, Folder};
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+use strip;
+
+const STATE_FIELD: &'static str = "__hoare_state";
+
+struct Transition {
+    span: Span,
+    method: String,
+    from: Vec<usize>,
+    to: usize,
+}
+
+pub fn protocol(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[protocol] can only be used on structs and impls");
+            return item;
+        }
+    };
+
+    let result = match item.node {
+        ast::ItemKind::Struct(ast::VariantData::Struct(ref fields, id), ref generics)
+            if attr.is_word() => {
+            let mut fields = fields.clone();
+            fields.push(ast::StructField {
+                span: sp,
+                ident: Some(state_field()),
+                vis: ast::Visibility::Inherited,
+                id: ast::DUMMY_NODE_ID,
+                ty: quote_ty!(cx, ::libhoare::protocol::State),
+                attrs: Vec::new(),
+            });
+            Some(ast::ItemKind::Struct(ast::VariantData::Struct(fields, id), generics.clone()))
+        }
+        ast::ItemKind::Impl(unsafety, polarity, defaultness, ref generics, None, ref ty, ref impl_items)
+            if attr.is_meta_item_list() => {
+            parse_protocol(cx, sp, attr).map(|(states, transitions)| {
+                for transition in &transitions {
+                    let defined = impl_items.iter().any(|impl_item| {
+                        *impl_item.ident.name.as_str() == *transition.method
+                    });
+                    if !defined {
+                        cx.span_err(
+                            transition.span,
+                            &format!(
+                                "the protocol's method `{}` is not defined in the impl",
+                                transition.method
+                            ),
+                        );
+                    }
+                }
+                let impl_items = impl_items
+                    .iter()
+                    .map(|impl_item| {
+                        super::new_run(cx, |cx| {
+                            check_method(cx, sp, ty, &states, &transitions, impl_item)
+                        })
+                    })
+                    .collect();
+                ast::ItemKind::Impl(
+                    unsafety,
+                    polarity,
+                    defaultness,
+                    generics.clone(),
+                    None,
+                    ty.clone(),
+                    impl_items,
+                )
+            })
+        }
+        _ => {
+            cx.span_err(
+                sp,
+                "#[protocol] must be used on a struct with named fields, and \
+                 #[protocol(...)] on its inherent impl",
+            );
+            None
+        }
+    };
+
+    match result {
+        Some(node) => Annotatable::Item(P(ast::Item {
+            node: node,
+            ..(*item).clone()
+        })),
+        None => Annotatable::Item(item),
+    }
+}
+
+// Parses `initial="a", m="a | b -> c", ..` into the names of the states and the
+// transitions.
+fn parse_protocol(cx: &ExtCtxt, sp: Span, attr: &MetaItem) -> Option<(Vec<String>, Vec<Transition>)> {
+    let mut states: Vec<String> = Vec::new();
+    let mut transitions = Vec::new();
+    fn index(states: &mut Vec<String>, state: &str) -> usize {
+        match states.iter().position(|s| s == state) {
+            Some(i) => i,
+            None => {
+                states.push(state.to_owned());
+                states.len() - 1
+            }
+        }
+    }
+
+    let items = attr.meta_item_list().unwrap();
+    for item in items.iter().filter(|item| item.check_name("initial")) {
+        match item.value_str() {
+            Some(initial) => {
+                index(&mut states, initial.as_str().trim());
+            }
+            None => cx.span_err(item.span, "expected `initial=\"state\"`"),
+        }
+    }
+    if states.len() != 1 {
+        cx.span_err(sp, "a protocol must have exactly one `initial=\"state\"`");
+        return None;
+    }
+
+    for item in items.iter().filter(|item| !item.check_name("initial")) {
+        let (method, spec) = match (item.name(), item.value_str()) {
+            (Some(method), Some(spec)) => (method.to_string(), spec.to_string()),
+            _ => {
+                cx.span_err(item.span, "expected `method=\"from | from -> to\"`");
+                return None;
+            }
+        };
+        let parts: Vec<&str> = spec.split("->").map(|s| s.trim()).collect();
+        if parts.len() != 2 || parts[1].is_empty() {
+            cx.span_err(item.span, "expected `method=\"from | from -> to\"`");
+            return None;
+        }
+        let from = parts[0]
+            .split('|')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| index(&mut states, s))
+            .collect();
+        let to = index(&mut states, parts[1]);
+        transitions.push(Transition {
+            span: item.span,
+            method: method,
+            from: from,
+            to: to,
+        });
+    }
+
+    Some((states, transitions))
+}
+
+// Initializes the state in struct literals in impl_item and, if it is a method
+// in the protocol, checks the protocol.
+fn check_method(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    ty: &P<ast::Ty>,
+    states: &[String],
+    transitions: &[Transition],
+    impl_item: &ast::ImplItem,
+) -> ast::ImplItem {
+    let (sig, body) = match impl_item.node {
+        ast::ImplItemKind::Method(ref sig, ref body) => (sig, body),
+        _ => return impl_item.clone(),
+    };
+
+    let type_name = pprust::ty_to_string(ty);
+    let body = InitState {
+        cx: cx,
+        type_name: &type_name,
+    }.fold_block(body.clone());
+
+    let name = impl_item.ident.name.as_str();
+    let transition = transitions.iter().find(|t| *t.method == *name);
+    let body = match transition {
+        Some(transition) if super::has_cfg(cx, "debug_assertions") && !strip::stripped(cx) => {
+            let self_kind = sig.decl.inputs.first().and_then(|arg| arg.to_self());
+            let by_value = match self_kind {
+                Some(ref self_kind) => match self_kind.node {
+                    ast::SelfKind::Value(_) => true,
+                    _ => false,
+                },
+                None => {
+                    cx.span_err(impl_item.span, "methods in a protocol must take `self`");
+                    return impl_item.clone();
+                }
+            };
+
+            let states = cx.expr_vec_slice(
+                sp,
+                states.iter().map(|s| cx.expr_str(sp, Symbol::intern(s))).collect(),
+            );
+            let allowed = cx.expr_vec_slice(
+                sp,
+                transition.from.iter().map(|&s| cx.expr_usize(sp, s)).collect(),
+            );
+            let type_name = &*type_name;
+            let method = &*name;
+            let to = transition.to;
+
+            let mut stmts = Vec::new();
+            stmts.push(quote_stmt!(cx,
+                self.__hoare_state.check($states, $allowed, $type_name, $method);
+            ).unwrap());
+            stmts.extend(super::bind_result(cx, body, sp, &sig.decl.output));
+            // A method which takes self by value consumes it, so there is no
+            // state to change.
+            if !by_value {
+                stmts.push(quote_stmt!(cx, self.__hoare_state.set($to);).unwrap());
+            }
+            super::fn_body(cx, stmts, sp)
+        }
+        _ => body,
+    };
+
+    ast::ImplItem {
+        node: ast::ImplItemKind::Method(sig.clone(), body),
+        ..impl_item.clone()
+    }
+}
+
+fn state_field() -> ast::Ident {
+    ast::Ident::from_str(STATE_FIELD)
+}
+
+// Adds `__hoare_state: Default::default()` (the initial state) to struct
+// literals of the type (`Self { .. }` or `Type { .. }`) without a base.
+struct InitState<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    type_name: &'a str,
+}
+
+impl<'a, 'b> Folder for InitState<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        let e = e.map(|e| fold::noop_fold_expr(e, self));
+        match e.node {
+            ast::ExprKind::Struct(ref path, ref fields, None) => {
+                let name = path.to_string();
+                let is_self = name == "Self" || name == self.type_name;
+                if !is_self || fields.iter().any(|f| f.ident.node == state_field()) {
+                    return e.clone();
+                }
+
+                let mut fields = fields.clone();
+                let init = quote_expr!(self.cx, ::std::default::Default::default());
+                fields.push(self.cx.field_imm(e.span, state_field(), init));
+                self.cx.expr_struct(e.span, path.clone(), fields)
+            }
+            _ => e.clone(),
+        }
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
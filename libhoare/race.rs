@@ -0,0 +1,126 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Race checks, see the `Race checks` section of ../readme.md.
+//
+// `#[race_checks]` on a struct wraps the type `T` of each field marked with the
+// inert `#[race_check]` in `libhoare::race::Canary<T>`, and generates methods
+// to take a snapshot of the canaries and to check them against it.
+// `#[race_free]` on a method (in debug builds) takes a snapshot on entry and
+// checks it on exit.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::ptr::P;
+
+pub fn race_checks(cx: &mut ExtCtxt, sp: Span, _attr: &MetaItem, item: Annotatable) -> Vec<Annotatable> {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[race_checks] can only be used on structs");
+            return vec![item];
+        }
+    };
+
+    let (fields, id) = match item.node {
+        ast::ItemKind::Struct(ast::VariantData::Struct(ref fields, id), ref generics)
+            if !generics.is_parameterized() => (fields.clone(), id),
+        _ => {
+            cx.span_err(sp, "#[race_checks] can only be used on non-generic structs with named fields");
+            return vec![Annotatable::Item(item)];
+        }
+    };
+
+    let mut checked = Vec::new();
+    let fields = fields
+        .into_iter()
+        .map(|mut field| {
+            if field.attrs.iter().any(|attr| attr.check_name("race_check")) {
+                let ty = field.ty;
+                field.ty = quote_ty!(cx, ::libhoare::race::Canary<$ty>);
+                checked.push(field.ident.unwrap());
+            }
+            field
+        })
+        .collect();
+    if checked.is_empty() {
+        cx.span_warn(sp, "#[race_checks] on a struct without #[race_check] fields");
+        return vec![Annotatable::Item(item)];
+    }
+
+    let methods = methods(cx, item.ident, &checked);
+    let item = item.map(|mut item| {
+        let generics = match item.node {
+            ast::ItemKind::Struct(_, ref generics) => generics.clone(),
+            _ => unreachable!(),
+        };
+        item.node = ast::ItemKind::Struct(ast::VariantData::Struct(fields, id), generics);
+        item
+    });
+    vec![Annotatable::Item(item), Annotatable::Item(methods)]
+}
+
+fn methods(cx: &ExtCtxt, struct_name: ast::Ident, fields: &[ast::Ident]) -> P<ast::Item> {
+    let snapshots = fields
+        .iter()
+        .map(|field| format!("self.{}.snapshot()", field))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let checks = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            format!(
+                "self.{field}.check(snapshots[{i}], \"{struct_name}::{field}\", method);\n",
+                field = field,
+                i = i,
+                struct_name = struct_name
+            )
+        })
+        .collect::<String>();
+    cx.parse_item(format!(
+        "impl {struct_name} {{\n\
+         #[doc(hidden)] #[allow(dead_code)]\n\
+         fn __hoare_race_snapshot(&self) -> Vec<::libhoare::race::Snapshot> {{ vec![{snapshots}] }}\n\
+         #[doc(hidden)] #[allow(dead_code)]\n\
+         fn __hoare_race_check(&self, snapshots: Vec<::libhoare::race::Snapshot>, method: &str) {{\n\
+         {checks}}}\n\
+         }}",
+        struct_name = struct_name,
+        snapshots = snapshots,
+        checks = checks
+    ))
+}
+
+pub fn race_free(cx: &mut ExtCtxt, sp: Span, _attr: &MetaItem, item: Annotatable) -> Annotatable {
+    super::if_debug(cx, |cx| check_method(cx, sp, item.clone()), item.clone())
+}
+
+fn check_method(cx: &mut ExtCtxt, sp: Span, item: Annotatable) -> Annotatable {
+    super::new_run(cx, |cx| {
+        super::map_fn_body(cx, sp, item, "Race free", |cx, ident, _attrs, decl, body| {
+            if !decl.has_self() {
+                cx.span_err(sp, "#[race_free] can only be used on methods which take `self`");
+                return Err(());
+            }
+
+            let fn_name = &*ident.name.as_str();
+            let snapshot = super::old_name(0);
+            let mut stmts = Vec::new();
+            stmts.push(quote_stmt!(cx, let $snapshot = self.__hoare_race_snapshot();).unwrap());
+            stmts.extend(super::bind_result(cx, body, sp, &decl.output));
+            stmts.push(quote_stmt!(cx, self.__hoare_race_check($snapshot, $fn_name);).unwrap());
+            Ok(super::fn_body(cx, stmts, sp))
+        })
+    })
+}
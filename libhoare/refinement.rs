@@ -0,0 +1,475 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Invariants on structs, see the `Refinement types` and `Struct invariants`
+// sections of ../readme.md.
+//
+// `#[invariant="..."]` on a struct does not add checks to methods (those need
+// their own invariants), instead we generate a method which checks the
+// invariant, an impl of `libhoare::HasInvariant` and, for a newtype (a tuple
+// struct with one field), `try_new` (which checks the invariant),
+// `new_unchecked`, and `Deref` to the wrapped type, or, for a struct with named
+// fields and `#[invariant_setters]`, setters which check the invariant. With
+// `#[invariant_deserialize]`, we also implement `Deserialize`, checking the
+// invariant, with `#[check_invariants]`, a `check_invariants` method which
+// returns an `Err` rather than panicking, and with `#[invariant_suspension]`,
+// `with_invariant_suspended`, which suspends the invariant of a value for the
+// duration of a closure. The first invariant to be expanded handles (and
+// removes) any others on the struct.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+use graph;
+
+pub fn as_struct(item: &Annotatable) -> Option<P<ast::Item>> {
+    match *item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Struct(..) => Some(item.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub fn struct_invariant(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: P<ast::Item>) -> Vec<Annotatable> {
+    if derives_invariant(&item) {
+        cx.span_err(
+            sp,
+            "use #[check(pred=\"...\")] rather than #[invariant] with #[derive(Invariant)]",
+        );
+        return vec![Annotatable::Item(item)];
+    }
+    let mut preds = Vec::new();
+    match attr.value_str() {
+        Some(pred) => preds.push(pred.to_string()),
+        None => {
+            cx.span_err(sp, "expected #[invariant=\"predicate\"]");
+            return vec![Annotatable::Item(item)];
+        }
+    }
+    for attr in &item.attrs {
+        if attr.check_name("invariant") {
+            match attr.value_str() {
+                Some(pred) => preds.push(pred.to_string()),
+                None => cx.span_err(attr.span, "expected #[invariant=\"predicate\"]"),
+            }
+        } else if attr.check_name("debug_invariant") || attr.check_name("release_invariant") {
+            cx.span_err(
+                attr.span,
+                &format!("use #[invariant] rather than #[{}] on structs", attr.name()),
+            );
+        }
+    }
+    let item = item.map(|mut item| {
+        item.attrs.retain(|attr| {
+            !attr.check_name("invariant") && !attr.check_name("debug_invariant") &&
+                !attr.check_name("release_invariant")
+        });
+        item
+    });
+    checked_struct(cx, sp, item, &preds)
+}
+
+// The struct item, with the items generated for its invariant, preds.
+pub fn checked_struct(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    item: P<ast::Item>,
+    preds: &[String],
+) -> Vec<Annotatable> {
+    let generated = match item.node {
+        ast::ItemKind::Struct(ast::VariantData::Tuple(ref fields, _), _) if fields.len() == 1 => {
+            newtype(cx, &item, &fields[0].ty, preds)
+        }
+        ast::ItemKind::Struct(ast::VariantData::Struct(ref fields, _), _) => {
+            named_fields(cx, sp, &item, fields, preds)
+        }
+        _ => {
+            cx.span_err(
+                sp,
+                "invariants can only be used on newtypes (tuple structs with one field) and structs \
+                 with named fields",
+            );
+            return vec![Annotatable::Item(item.clone())];
+        }
+    };
+
+    if let ast::ItemKind::Struct(ref data, _) = item.node {
+        audit_fields(cx, &item, data.fields());
+    }
+    if graph::enabled(cx) {
+        graph::invariant(cx, &item.ident.name.as_str(), preds);
+    }
+    let mut result = vec![Annotatable::Item(item.clone())];
+    result.extend(generated.into_iter().map(Annotatable::Item));
+    if item.attrs.iter().any(|attr| attr.check_name("invariant_deserialize")) {
+        if is_generic(&item) {
+            cx.span_err(sp, "#[invariant_deserialize] can not be used on generic structs");
+            return result;
+        }
+        result.extend(deserialize(cx, &item, preds).into_iter().map(Annotatable::Item));
+    }
+    result.push(Annotatable::Item(has_invariant(cx, &item, preds)));
+    if item.attrs.iter().any(|attr| attr.check_name("check_invariants")) {
+        result.push(Annotatable::Item(check_invariants(cx, &item)));
+    }
+    if is_suspendable(&item) {
+        result.extend(suspension(cx, &item, preds).into_iter().map(Annotatable::Item));
+    }
+    result
+}
+
+fn is_suspendable(item: &ast::Item) -> bool {
+    item.attrs.iter().any(|attr| attr.check_name("invariant_suspension"))
+}
+
+// The thread local which holds the addresses of the values of the struct whose
+// invariant is suspended.
+fn suspended_static(item: &ast::Item) -> String {
+    format!("__HOARE_SUSPENDED_{}", item.ident.name.as_str().to_uppercase())
+}
+
+fn is_generic(item: &ast::Item) -> bool {
+    match item.node {
+        ast::ItemKind::Struct(_, ref generics) => generics.is_parameterized(),
+        _ => false,
+    }
+}
+
+// The start of an impl of trait_ (or an inherent impl) for the struct, with
+// its generic parameters and where clause.
+pub fn impl_header(item: &ast::Item, trait_: Option<&str>) -> String {
+    let (params, args, where_clause) = match item.node {
+        ast::ItemKind::Struct(_, ref generics) => super::generics_strings(generics),
+        _ => (String::new(), String::new(), String::new()),
+    };
+    format!(
+        "impl{} {}{}{}{}",
+        params,
+        trait_.map_or(String::new(), |trait_| format!("{} for ", trait_)),
+        item.ident,
+        args,
+        where_clause
+    )
+}
+
+// Whether the struct has `#[derive(Invariant)]` (see derive_invariant.rs).
+fn derives_invariant(item: &ast::Item) -> bool {
+    item.attrs.iter().any(|attr| {
+        attr.check_name("derive_Invariant") ||
+            attr.check_name("derive") &&
+                attr.meta_item_list().map_or(false, |items| {
+                    items.iter().any(|item| item.check_name("Invariant"))
+                })
+    })
+}
+
+// Fields which are visible outside the struct's module (`pub`, `pub(crate)`,
+// etc.) can be written without checking the invariant, so we warn about them,
+// listing them all in one warning.
+fn audit_fields(cx: &ExtCtxt, item: &ast::Item, fields: &[ast::StructField]) {
+    let visible: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .filter(|&(_, field)| field.vis != ast::Visibility::Inherited)
+        .map(|(i, field)| match field.ident {
+            Some(ident) => format!("`{}`", ident),
+            None => format!("`{}`", i),
+        })
+        .collect();
+    if visible.is_empty() {
+        return;
+    }
+
+    let setters = item.attrs.iter().any(|attr| attr.check_name("invariant_setters"));
+    let is_newtype = fields.len() == 1 && fields[0].ident.is_none();
+    let advice = if is_newtype {
+        "make the field private and use `try_new`"
+    } else if setters {
+        "make them private and use the setters"
+    } else {
+        "make them private and consider #[invariant_setters]"
+    };
+    cx.span_warn(
+        item.span,
+        &format!(
+            "the invariant of {} is not enforced, since {} {} can be written outside its \
+             module without checking it; {}",
+            item.ident,
+            if visible.len() == 1 { "the field" } else { "the fields" },
+            visible.join(", "),
+            advice
+        ),
+    );
+}
+
+// The method which checks the invariant. It is in the struct's module, so the
+// predicates can use the module's private items, and visible to the whole
+// crate, so that constructors and builders in other modules can call it (see
+// constructors.rs). It is always inlined in release builds, so that when a
+// method checks the invariant on leaving and the next one on entering, the
+// optimizer can see they are the same check and remove the second.
+//
+// With `#[invariant_suspension]`, the invariant holds (as far as its checks are
+// concerned) while it is suspended, so only those structs pay for looking up
+// whether it is.
+fn invariant_method(item: &ast::Item, preds: &[String]) -> String {
+    let suspended = if is_suspendable(item) {
+        format!(
+            "{}.with(|suspended| suspended.borrow().contains(&(self as *const Self as usize))) || ",
+            suspended_static(item)
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "#[doc(hidden)] #[allow(dead_code)]\n\
+         #[cfg_attr(not(debug_assertions), inline(always))]\n\
+         pub(crate) fn __hoare_invariant(&self) -> bool {{ {}{} }}\n",
+        suspended,
+        preds.iter().map(|pred| format!("({})", pred)).collect::<Vec<_>>().join(" && ")
+    )
+}
+
+fn newtype(cx: &ExtCtxt, item: &ast::Item, inner: &ast::Ty, preds: &[String]) -> Vec<P<ast::Item>> {
+    let inner = pprust::ty_to_string(inner);
+    let name = item.ident;
+    let vis = format!("{} ", pprust::vis_to_string(&item.vis).trim());
+    let methods = cx.parse_item(format!(
+        "{header} {{\n\
+         {invariant}\
+         /// Creates a `{name}` if `value` satisfies its invariant, otherwise returns\n\
+         /// `value`.\n\
+         #[allow(dead_code)]\n\
+         {vis}fn try_new(value: {inner}) -> ::std::result::Result<Self, {inner}> {{\n\
+         let candidate = {name}(value);\n\
+         if candidate.__hoare_invariant() {{ Ok(candidate) }} else {{ Err(candidate.0) }}\n\
+         }}\n\
+         /// Creates a `{name}` without checking its invariant, the caller must\n\
+         /// ensure that it holds.\n\
+         #[allow(dead_code)]\n\
+         {vis}fn new_unchecked(value: {inner}) -> Self {{ {name}(value) }}\n\
+         }}",
+        header = impl_header(item, None),
+        name = name,
+        invariant = invariant_method(item, preds),
+        vis = vis,
+        inner = inner
+    ));
+    let deref = cx.parse_item(format!(
+        "{header} {{\n\
+         type Target = {inner};\n\
+         fn deref(&self) -> &{inner} {{ &self.0 }}\n\
+         }}",
+        header = impl_header(item, Some("::std::ops::Deref")),
+        inner = inner
+    ));
+    vec![methods, deref]
+}
+
+// For a struct with named fields, we generate a method to check the invariant
+// and, if the struct has `#[invariant_setters]`, a setter for each field which
+// checks the invariant after setting it.
+fn named_fields(
+    cx: &ExtCtxt,
+    sp: Span,
+    item: &ast::Item,
+    fields: &[ast::StructField],
+    preds: &[String],
+) -> Vec<P<ast::Item>> {
+    let setters = item.attrs.iter().any(|attr| attr.check_name("invariant_setters"));
+    let vis = format!("{} ", pprust::vis_to_string(&item.vis).trim());
+    let pred = preds.join(" && ");
+
+    let mut methods = invariant_method(item, preds);
+    for field in fields {
+        let field_name = field.ident.unwrap();
+        if !setters {
+            continue;
+        }
+        let setter = format!("set_{}", field_name);
+        methods.push_str(&format!(
+            "#[allow(dead_code)]\n\
+             {vis}fn {setter}(&mut self, value: {ty}) {{\n\
+             self.{field} = value;\n\
+             if !self.__hoare_invariant() {{ panic!(\"{{}}\", {label:?}); }}\n\
+             }}\n",
+            vis = vis,
+            setter = setter,
+            ty = pprust::ty_to_string(&field.ty),
+            field = field_name,
+            label = format!("invariant leaving {} ({})", setter, pred)
+        ));
+    }
+    if setters && fields.is_empty() {
+        cx.span_warn(sp, "#[invariant_setters] on a struct without fields");
+    }
+
+    vec![cx.parse_item(format!("{} {{\n{}}}", impl_header(item, None), methods))]
+}
+
+// `check_invariants`, for callers which want to check the invariant without
+// panicking, which calls that of `HasInvariant`.
+fn check_invariants(cx: &ExtCtxt, item: &ast::Item) -> P<ast::Item> {
+    cx.parse_item(format!(
+        "{header} {{\n\
+         /// Checks the invariant of `{name}`, returning the first predicate which does\n\
+         /// not hold as an `Err`.\n\
+         #[allow(dead_code)]\n\
+         {vis}fn check_invariants(&self)\n\
+         -> ::std::result::Result<(), ::libhoare::InvariantError> {{\n\
+         ::libhoare::HasInvariant::check_invariants(self)\n\
+         }}\n\
+         }}",
+        header = impl_header(item, None),
+        name = item.ident,
+        vis = format!("{} ", pprust::vis_to_string(&item.vis).trim())
+    ))
+}
+
+// `with_invariant_suspended(f)`, which calls `f` with the invariant of `self`
+// suspended, for batches of changes which break it in between. The address of
+// `self` is recorded in a thread local (so methods called by `f` on other
+// values of the struct still check their invariants), and removed when `f`
+// returns or panics. The invariant is checked once, when `f` returns, unless
+// the value is still suspended by an enclosing call.
+fn suspension(cx: &ExtCtxt, item: &ast::Item, preds: &[String]) -> Vec<P<ast::Item>> {
+    let suspended = suspended_static(item);
+    let thread_local = cx.parse_item(format!(
+        "thread_local! {{\n\
+         #[doc(hidden)]\n\
+         static {suspended}: ::std::cell::RefCell<Vec<usize>> =\n\
+         ::std::cell::RefCell::new(Vec::new());\n\
+         }}",
+        suspended = suspended
+    ));
+    let method = cx.parse_item(format!(
+        "{header} {{\n\
+         /// Calls `f` with the invariant of `self` suspended, so that it is not checked\n\
+         /// until `f` returns, and then checks it once.\n\
+         #[allow(dead_code)]\n\
+         {vis}fn with_invariant_suspended<__R, __F>(&mut self, f: __F) -> __R\n\
+         where __F: FnOnce(&mut Self) -> __R {{\n\
+         struct Resume(usize);\n\
+         impl Drop for Resume {{\n\
+         fn drop(&mut self) {{\n\
+         let key = self.0;\n\
+         {suspended}.with(|suspended| {{\n\
+         let mut suspended = suspended.borrow_mut();\n\
+         if let Some(i) = suspended.iter().rposition(|&k| k == key) {{\n\
+         suspended.remove(i);\n\
+         }}\n\
+         }});\n\
+         }}\n\
+         }}\n\
+         let key = self as *const Self as usize;\n\
+         {suspended}.with(|suspended| suspended.borrow_mut().push(key));\n\
+         let result = {{\n\
+         let _resume = Resume(key);\n\
+         f(self)\n\
+         }};\n\
+         if !self.__hoare_invariant() {{ panic!(\"{{}}\", {label:?}); }}\n\
+         result\n\
+         }}\n\
+         }}",
+        header = impl_header(item, None),
+        vis = format!("{} ", pprust::vis_to_string(&item.vis).trim()),
+        suspended = suspended,
+        label = format!("invariant leaving with_invariant_suspended ({})", preds.join(" && "))
+    ));
+    vec![thread_local, method]
+}
+
+// `HasInvariant`, for `libhoare::Enforced` and other generic code, whose
+// `check_invariants` returns the first predicate of the invariant which does not
+// hold as an `Err`.
+fn has_invariant(cx: &ExtCtxt, item: &ast::Item, preds: &[String]) -> P<ast::Item> {
+    let name = item.ident;
+    let checks = preds
+        .iter()
+        .map(|pred| {
+            format!(
+                "if !({pred}) {{\n\
+                 return Err(::libhoare::InvariantError::new({name:?}, {pred:?}));\n\
+                 }}\n",
+                pred = pred,
+                name = name.to_string()
+            )
+        })
+        .collect::<String>();
+    cx.parse_item(format!(
+        "{header} {{\n\
+         fn check_invariants(&self)\n\
+         -> ::std::result::Result<(), ::libhoare::InvariantError> {{\n\
+         {checks}\
+         Ok(())\n\
+         }}\n\
+         }}",
+        header = impl_header(item, Some("::libhoare::HasInvariant")),
+        checks = checks
+    ))
+}
+
+// With `#[invariant_deserialize]`, we implement `Deserialize` by deserializing
+// a copy of the struct (with any `#[serde(...)]` attributes) and checking the
+// invariant of the result, so that deserialization can not produce a value
+// which violates it.
+fn deserialize(cx: &ExtCtxt, item: &ast::Item, preds: &[String]) -> Vec<P<ast::Item>> {
+    let name = item.ident;
+    let unchecked_name = format!("__HoareUnchecked{}", name);
+    let (construct, fields) = match item.node {
+        ast::ItemKind::Struct(ast::VariantData::Struct(ref fields, _), _) => {
+            let fields = fields
+                .iter()
+                .map(|field| format!("{0}: unchecked.{0}", field.ident.unwrap()))
+                .collect::<Vec<_>>();
+            (format!("{} {{ {} }}", name, fields.join(", ")), fields.len())
+        }
+        _ => (format!("{}(unchecked.0)", name), 1),
+    };
+    if fields == 0 {
+        return Vec::new();
+    }
+
+    let mut unchecked = item.clone();
+    unchecked.ident = ast::Ident::from_str(&unchecked_name);
+    unchecked.vis = ast::Visibility::Inherited;
+    unchecked.attrs.retain(|attr| attr.check_name("serde"));
+    let unchecked = cx.parse_item(format!(
+        "#[derive(Deserialize)] #[doc(hidden)] #[allow(non_camel_case_types)] {}",
+        pprust::item_to_string(&unchecked)
+    ));
+
+    let label = format!("invariant of {} ({})", name, preds.join(" && "));
+    let deserialize = cx.parse_item(format!(
+        "impl<'de> ::serde::Deserialize<'de> for {name} {{\n\
+         fn deserialize<D>(deserializer: D) -> ::std::result::Result<{name}, D::Error>\n\
+         where D: ::serde::Deserializer<'de> {{\n\
+         let unchecked: {unchecked} = try!(::serde::Deserialize::deserialize(deserializer));\n\
+         let value = {construct};\n\
+         if !value.__hoare_invariant() {{\n\
+         return Err(<D::Error as ::serde::de::Error>::custom({label:?}));\n\
+         }}\n\
+         Ok(value)\n\
+         }}\n\
+         }}",
+        name = name,
+        unchecked = unchecked_name,
+        construct = construct,
+        label = label
+    ));
+    vec![unchecked, deserialize]
+}
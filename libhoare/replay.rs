@@ -0,0 +1,128 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Replaying captured inputs, see the `Capturing violating inputs` section of
+// ../readme.md.
+//
+// `#[replay_violations]` on a function `foo(a: A, b: &B)` generates, next to
+// it,
+//
+// #[cfg(test)]
+// fn replay_foo(__hoare_args: &str) {
+//     let (a, b): (A, B) = ::libhoare::corpus::deserialize(__hoare_args);
+//     foo(a, &b);
+// }
+//
+// which calls `foo` with arguments serialized as by `#[capture_violations]` (a
+// JSON array), so that a captured input is replayed, with all of foo's
+// contracts checked, by a one line test. An argument of type `&T` or `&mut T`
+// is deserialized as a `T` (`String` for `&str` and `Vec<T>` for `&[T]`) and
+// passed by reference. Methods can't be replayed, since `self` isn't captured.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+
+const USAGE: &'static str = "#[replay_violations] can only be used on non-generic functions";
+
+pub fn replay_violations(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    let generated = match item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _)
+                if !generics.is_parameterized() => {
+                replay_fn(cx, item, decl)
+            }
+            _ => {
+                cx.span_err(sp, USAGE);
+                None
+            }
+        },
+        _ => {
+            cx.span_err(sp, USAGE);
+            None
+        }
+    };
+
+    let mut result = vec![item];
+    result.extend(generated.into_iter().map(Annotatable::Item));
+    result
+}
+
+fn replay_fn(cx: &ExtCtxt, item: &ast::Item, decl: &ast::FnDecl) -> Option<P<ast::Item>> {
+    let mut pats = Vec::new();
+    let mut tys = Vec::new();
+    let mut args = Vec::new();
+    for arg in &decl.inputs {
+        let ident = match arg.pat.node {
+            ast::PatKind::Ident(_, ref ident, None) => ident.node,
+            _ => {
+                cx.span_err(
+                    arg.pat.span,
+                    "#[replay_violations] requires function arguments to be plain identifiers",
+                );
+                return None;
+            }
+        };
+        let (ty, by) = owned(&arg.ty);
+        pats.push(if by == "&mut " { format!("mut {}", ident) } else { ident.to_string() });
+        tys.push(ty);
+        args.push(format!("{}{}", by, ident));
+    }
+    // A tuple of one needs a trailing comma.
+    let comma = if decl.inputs.len() == 1 { "," } else { "" };
+
+    Some(cx.parse_item(format!(
+        "/// Calls `{name}` with arguments serialized by `#[capture_violations]`.\n\
+         #[cfg(test)]\n\
+         #[allow(dead_code)]\n\
+         {vis}fn replay_{name}(__hoare_args: &str) {{\n\
+         let ({pats}{comma}): ({tys}{comma}) = ::libhoare::corpus::deserialize(__hoare_args);\n\
+         {name}({args});\n\
+         }}",
+        name = item.ident,
+        vis = format!("{} ", pprust::vis_to_string(&item.vis).trim()),
+        pats = pats.join(", "),
+        tys = tys.join(", "),
+        comma = comma,
+        args = args.join(", ")
+    )))
+}
+
+// The type an argument of type ty is deserialized as, and how it is passed
+// (`&`, `&mut `, or by value).
+fn owned(ty: &ast::Ty) -> (String, &'static str) {
+    match ty.node {
+        ast::TyKind::Rptr(_, ref mt) => {
+            let by = match mt.mutbl {
+                ast::Mutability::Mutable => "&mut ",
+                ast::Mutability::Immutable => "&",
+            };
+            let owned = match mt.ty.node {
+                ast::TyKind::Path(None, ref path)
+                    if path.segments.len() == 1 && path.segments[0].identifier.name == "str" => {
+                    "String".to_owned()
+                }
+                ast::TyKind::Slice(ref elem) => format!("Vec<{}>", pprust::ty_to_string(elem)),
+                _ => pprust::ty_to_string(&mt.ty),
+            };
+            (owned, by)
+        }
+        _ => (pprust::ty_to_string(ty), ""),
+    }
+}
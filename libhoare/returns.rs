@@ -0,0 +1,133 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// `return` in postconditions, see the `Closures in predicates` section of
+// ../readme.md.
+//
+// In a postcondition (and a transition, or an incremental invariant), `return`
+// is the returned value, so it is renamed to the result name. Rather than
+// replacing the text, which would also rename `return` in a closure (e.g.,
+// `xs.iter().all(|&x| { if x < 0 { return false; } ... })`) and in identifiers
+// such as `returned`, we parse the predicate and rename only the `return`s
+// outside closure bodies. Parsing `return - 1` gives a return of `-1`, so each
+// renamed `return` is the start of a return expression, and we rename just the
+// keyword, by its position in the predicate. The arguments of macros are not
+// parsed by the parser, so we parse them as expressions, or, if they are not,
+// rename every `return` in them.
+//
+// With `closure_return="result"` (see policy.rs), `return` in a closure is also
+// the returned value.
+
+use syntax::ast;
+use syntax::codemap::{BytePos, Pos};
+use syntax::ext::base::ExtCtxt;
+use syntax::parse::{self, token};
+use syntax::symbol::keywords;
+use syntax::tokenstream::{TokenStream, TokenTree};
+use syntax::visit::{self, Visitor};
+
+use policy;
+
+// pred with each `return` which is the returned value renamed to the result
+// name.
+pub fn rename(cx: &ExtCtxt, pred: &str) -> String {
+    if !pred.contains("return") {
+        return pred.to_owned();
+    }
+    // Errors are reported when the renamed predicate is parsed.
+    let expr = match parse::parse_expr_from_source_str(
+        "<predicate>".to_owned(),
+        pred.to_owned(),
+        cx.parse_sess,
+    ) {
+        Ok(expr) => expr,
+        Err(mut e) => {
+            e.cancel();
+            return pred.to_owned();
+        }
+    };
+
+    let mut finder = ResultFinder {
+        cx: cx,
+        closures: policy::current(cx).closure_return == policy::ClosureReturn::Result,
+        returns: Vec::new(),
+    };
+    finder.visit_expr(&expr);
+    finder.returns.sort();
+
+    let result_name = super::result_name().to_string();
+    let mut renamed = String::new();
+    let mut last = 0;
+    for pos in finder.returns {
+        let offset = cx.codemap().lookup_byte_offset(pos).pos.to_usize();
+        renamed.push_str(&pred[last..offset]);
+        renamed.push_str(&result_name);
+        last = offset + "return".len();
+    }
+    renamed.push_str(&pred[last..]);
+    renamed
+}
+
+// Finds the positions of the `return`s which are the returned value.
+struct ResultFinder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    // Whether to look in closures.
+    closures: bool,
+    returns: Vec<BytePos>,
+}
+
+impl<'a, 'b, 'c> Visitor<'c> for ResultFinder<'a, 'b> {
+    fn visit_expr(&mut self, e: &'c ast::Expr) {
+        match e.node {
+            ast::ExprKind::Ret(_) => self.returns.push(e.span.lo),
+            ast::ExprKind::Closure(..) if !self.closures => return,
+            _ => {}
+        }
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_mac(&mut self, mac: &'c ast::Mac) {
+        let tts = mac.node.stream().trees().collect::<Vec<_>>();
+        let mut parser = self.cx.new_parser_from_tts(&tts);
+        let mut args = Vec::new();
+        while parser.token != token::Eof {
+            match parser.parse_expr() {
+                Ok(arg) => args.push(arg),
+                Err(mut e) => {
+                    e.cancel();
+                    keywords_in(mac.node.stream(), &mut self.returns);
+                    return;
+                }
+            }
+            if !parser.eat(&token::Comma) && parser.token != token::Eof {
+                keywords_in(mac.node.stream(), &mut self.returns);
+                return;
+            }
+        }
+        for arg in &args {
+            self.visit_expr(arg);
+        }
+    }
+}
+
+// Adds the positions of the `return` keywords in tts to returns.
+fn keywords_in(tts: TokenStream, returns: &mut Vec<BytePos>) {
+    for tt in tts.trees() {
+        match tt {
+            TokenTree::Token(sp, token::Ident(ident)) => {
+                if ident.name == keywords::Return.name() {
+                    returns.push(sp.lo);
+                }
+            }
+            TokenTree::Token(..) => {}
+            TokenTree::Delimited(_, delimited) => keywords_in(delimited.stream(), returns),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Round-trip contracts for conversions, see the `Round trips` section of
+// ../readme.md.
+//
+// `#[round_trip]` on an impl of `From`, `TryFrom`, or `FromStr` makes its
+// conversion function (`from`, `try_from`, or `from_str`), in debug builds,
+// save its input and pass it and the result to the matching function in
+// `libhoare::round_trip`, which converts the result back and compares.
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::print::pprust;
+
+// The traits which can be checked, the function which converts, and the
+// function in `libhoare::round_trip` which checks it.
+const CONVERSIONS: &'static [(&'static str, &'static str, &'static str)] = &[
+    ("From", "from", "check_from"),
+    ("TryFrom", "try_from", "check_try_from"),
+    ("FromStr", "from_str", "check_from_str"),
+];
+
+pub fn round_trip(cx: &mut ExtCtxt, sp: Span, _attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[round_trip] can only be used on impls of From, TryFrom, or FromStr");
+            return item;
+        }
+    };
+    let (trait_ref, ty) = match item.node {
+        ast::ItemKind::Impl(_, _, _, _, Some(ref trait_ref), ref ty, _) => {
+            (trait_ref.clone(), ty.clone())
+        }
+        _ => {
+            cx.span_err(sp, "#[round_trip] can only be used on impls of From, TryFrom, or FromStr");
+            return Annotatable::Item(item);
+        }
+    };
+    let trait_name = trait_ref.path.segments.last().unwrap().identifier.name.to_string();
+    let (method, check) = match CONVERSIONS.iter().find(|c| c.0 == trait_name) {
+        Some(&(_, method, check)) => (method, check),
+        None => {
+            cx.span_err(sp, "#[round_trip] can only be used on impls of From, TryFrom, or FromStr");
+            return Annotatable::Item(item);
+        }
+    };
+    if !super::has_cfg(cx, "debug_assertions") {
+        return Annotatable::Item(item);
+    }
+
+    let conversion = format!(
+        "{} for {}",
+        pprust::path_to_string(&trait_ref.path),
+        pprust::ty_to_string(&ty)
+    );
+    Annotatable::Item(item.map(|mut item| {
+        if let ast::ItemKind::Impl(_, _, _, _, _, _, ref mut impl_items) = item.node {
+            *impl_items = impl_items
+                .drain(..)
+                .map(|impl_item| {
+                    if &*impl_item.ident.name.as_str() == method {
+                        super::new_run(cx, |cx| check_method(cx, impl_item, check, &conversion))
+                    } else {
+                        impl_item
+                    }
+                })
+                .collect();
+        }
+        item
+    }))
+}
+
+// Wraps the body of the conversion function to save its input and check the
+// conversion round-trips.
+fn check_method(
+    cx: &mut ExtCtxt,
+    impl_item: ast::ImplItem,
+    check: &str,
+    conversion: &str,
+) -> ast::ImplItem {
+    let (sig, body) = match impl_item.node {
+        ast::ImplItemKind::Method(ref sig, ref body) => (sig.clone(), body.clone()),
+        _ => return impl_item,
+    };
+    let sp = impl_item.span;
+    let input = match sig.decl.inputs.first().map(|arg| &arg.pat.node) {
+        Some(&ast::PatKind::Ident(_, ref ident, None)) => ident.node,
+        _ => {
+            cx.span_err(sp, "#[round_trip] requires the argument of the conversion to be a name");
+            return impl_item;
+        }
+    };
+
+    let result_name = super::result_name();
+
+    let mut stmts = Vec::new();
+    stmts.push(quote_stmt!(cx, let __hoare_input = ::std::clone::Clone::clone(&$input);).unwrap());
+    stmts.extend(super::bind_result(cx, &body, sp, &sig.decl.output));
+    // The runtime can't use `TryInto`, which is unstable, so it is passed in
+    // from the impl's crate, which has the `try_from` feature.
+    stmts.push(if check == "check_try_from" {
+        quote_stmt!(cx,
+            ::libhoare::round_trip::check_try_from(
+                &__hoare_input,
+                &$result_name,
+                $conversion,
+                ::std::convert::TryInto::try_into,
+            );
+        ).unwrap()
+    } else {
+        let check = ast::Ident::from_str(check);
+        quote_stmt!(cx,
+            ::libhoare::round_trip::$check(&__hoare_input, &$result_name, $conversion);
+        ).unwrap()
+    });
+    let body = super::fn_body(cx, stmts, sp);
+
+    ast::ImplItem {
+        node: ast::ImplItemKind::Method(sig, body),
+        ..impl_item
+    }
+}
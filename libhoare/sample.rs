@@ -0,0 +1,89 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Sampling the clauses of postconditions, see the `Sampling postconditions`
+// section of ../readme.md.
+//
+// With the policy `sample="k"`, each clause (top-level conjunct) of a
+// postcondition with more than k clauses is checked on each call with
+// probability k * w / total, where w is its weight (from `sample_weights`, or
+// 1) and total is the sum of the weights, and at most 1. So about k clauses
+// are checked per call, and each one is checked eventually. The generated code
+// asks `libhoare::sample::selected` whether to check a clause, and, if a
+// sampled clause fails, calls `libhoare::sample::report_seed` before panicking,
+// so that the same clauses can be checked again (see runtime/sample.rs).
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::ExtCtxt;
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+
+use policy;
+
+// The probability of checking each of the clauses of a postcondition, or None
+// if all of them are always checked.
+pub fn probabilities(cx: &ExtCtxt, sp: Span, clauses: usize) -> Option<Vec<f64>> {
+    let policy = policy::current(cx);
+    let sample = match policy.sample {
+        Some(sample) if sample < clauses => sample,
+        _ => return None,
+    };
+    let weights = match policy.sample_weights {
+        Some(weights) => {
+            let weights: Result<Vec<f64>, _> =
+                weights.as_str().split(',').map(|w| w.trim().parse::<f64>()).collect();
+            match weights {
+                Ok(ref weights)
+                    if weights.len() == clauses && weights.iter().all(|&w| w > 0.0) => {
+                    weights.clone()
+                }
+                _ => {
+                    cx.span_err(
+                        sp,
+                        &format!(
+                            "expected `sample_weights` to be {} positive numbers, one for each \
+                             clause of the postcondition",
+                            clauses
+                        ),
+                    );
+                    return None;
+                }
+            }
+        }
+        None => vec![1.0; clauses],
+    };
+    let total: f64 = weights.iter().sum();
+    Some(weights.iter().map(|w| (sample as f64 * w / total).min(1.0)).collect())
+}
+
+// check, made only if it is selected with probability.
+pub fn check(cx: &ExtCtxt, probability: f64, check: ast::Stmt) -> ast::Stmt {
+    if probability >= 1.0 {
+        return check;
+    }
+    let probability = cx.parse_expr(format!("{:?}f64", probability));
+    quote_stmt!(cx, if ::libhoare::sample::selected($probability) { $check }).unwrap()
+}
+
+// What a clause checked with probability does before panicking, before_panic
+// and, if it is sampled, reporting the seed.
+pub fn before_panic(
+    cx: &ExtCtxt,
+    probability: f64,
+    before_panic: Option<ast::Stmt>,
+) -> Option<ast::Stmt> {
+    if probability >= 1.0 {
+        return before_panic;
+    }
+    let report = quote_stmt!(cx, ::libhoare::sample::report_seed();).unwrap();
+    let stmts = before_panic.into_iter().chain(Some(report)).collect();
+    Some(cx.stmt_expr(cx.expr_block(cx.block(cx.call_site(), stmts))))
+}
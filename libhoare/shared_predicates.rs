@@ -0,0 +1,216 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Predicates shared between crates, see the `Shared predicates` section of
+// ../readme.md.
+//
+// `#[predicate]` on a public function which returns `bool` makes it a
+// predicate for the contracts of other crates. It is made `#[inline]`, so that
+// its calls from other crates can be inlined, and `name \t path` is written to
+// a manifest (`<crate name>.predicates` in the predicates directory).
+// `#[predicate(path="..")]` gives the path, from the crate root, it is
+// re-exported at, e.g., for a predicate in a private module.
+//
+// In the predicate of a function's contract, a path `krate::name`, where
+// `krate` is another crate with a manifest, is a path from the crate root
+// wherever the contract is, so it is rewritten to `::krate::<path of name>`
+// (or to `::krate::name`, if name isn't one of krate's predicates), including
+// in the arguments of shorthands (see predicates.rs). Only the predicate which
+// is checked is rewritten, failures report it as it is written.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use syntax::ast::{self, MetaItem};
+use syntax::attr;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::fold::{self, Folder};
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+
+use manifest;
+use predicates;
+
+const USAGE: &'static str = "#[predicate] can only be used on public functions which return bool";
+
+// The predicates of other crates, by crate and name, to their paths.
+type Predicates = HashMap<String, HashMap<String, String>>;
+
+thread_local! {
+    static PREDICATES: RefCell<Option<Rc<Predicates>>> = RefCell::new(None);
+}
+
+fn dir() -> PathBuf {
+    manifest::dir("HOARE_PREDICATES_DIR", "target/hoare-predicates")
+}
+
+// `#[predicate]`
+pub fn predicate(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let path = if attr.is_word() {
+        None
+    } else {
+        match attr.meta_item_list() {
+            Some(items) if items.len() == 1 && items[0].check_name("path") &&
+                items[0].value_str().is_some() => {
+                items[0].value_str()
+            }
+            _ => {
+                cx.span_err(sp, "expected #[predicate] or #[predicate(path=\"...\")]");
+                return item;
+            }
+        }
+    };
+    let item = match item {
+        Annotatable::Item(item) => item,
+        _ => {
+            cx.span_err(sp, USAGE);
+            return item;
+        }
+    };
+    let returns_bool = match item.node {
+        ast::ItemKind::Fn(ref decl, ..) => match decl.output {
+            ast::FunctionRetTy::Ty(ref ty) => pprust::ty_to_string(ty) == "bool",
+            ast::FunctionRetTy::Default(_) => false,
+        },
+        _ => false,
+    };
+    if !returns_bool || item.vis != ast::Visibility::Public {
+        cx.span_err(sp, USAGE);
+        return Annotatable::Item(item);
+    }
+
+    let name = item.ident.name.as_str();
+    let path = match path {
+        Some(path) => format!("{}::{}", cx.ecfg.crate_name, path),
+        None => manifest::fn_path(cx, &name),
+    };
+    manifest::append(cx, &dir(), "predicates", &format!("{}\t{}", name, path));
+
+    Annotatable::Item(item.map(|mut item| {
+        if !attr::contains_name(&item.attrs, "inline") {
+            item.attrs.push(cx.attribute(sp, cx.meta_word(sp, Symbol::intern("inline"))));
+        }
+        item
+    }))
+}
+
+// pred, with its paths from crates with shared predicates made global.
+pub fn resolve(cx: &ExtCtxt, pred: P<ast::Expr>) -> P<ast::Expr> {
+    let predicates = shared_predicates(cx);
+    if predicates.is_empty() {
+        return pred;
+    }
+    let mut folder = PathFolder {
+        cx: cx,
+        predicates: &predicates,
+    };
+    folder.fold_expr(pred)
+}
+
+// The predicates of other crates, as sorted `(crate, name, path)`. Cached
+// expansions (see cache.rs) depend on them, since resolve does.
+pub fn manifests(cx: &ExtCtxt) -> Vec<(String, String, String)> {
+    let mut result = Vec::new();
+    for (krate, names) in shared_predicates(cx).iter() {
+        for (name, path) in names {
+            result.push((krate.clone(), name.clone(), path.clone()));
+        }
+    }
+    result.sort();
+    result
+}
+
+fn shared_predicates(cx: &ExtCtxt) -> Rc<Predicates> {
+    if let Some(predicates) = PREDICATES.with(|predicates| predicates.borrow().clone()) {
+        return predicates;
+    }
+    let predicates = Rc::new(read_predicates(cx));
+    PREDICATES.with(|p| *p.borrow_mut() = Some(predicates.clone()));
+    predicates
+}
+
+// The manifests of all crates other than this one.
+fn read_predicates(cx: &ExtCtxt) -> Predicates {
+    let mut predicates = Predicates::new();
+    let entries = match fs::read_dir(dir()) {
+        Ok(entries) => entries,
+        Err(_) => return predicates,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().map_or(true, |ext| ext != "predicates") {
+            continue;
+        }
+        let krate = match path.file_stem() {
+            Some(krate) if *krate != *cx.ecfg.crate_name => krate.to_string_lossy().into_owned(),
+            _ => continue,
+        };
+        let mut src = String::new();
+        if let Err(e) = File::open(&path).and_then(|mut file| file.read_to_string(&mut src)) {
+            cx.span_warn(
+                cx.call_site(),
+                &format!("could not read predicates {}: {}", path.display(), e),
+            );
+            continue;
+        }
+        let names = predicates.entry(krate).or_insert(HashMap::new());
+        for line in src.lines() {
+            let fields: Vec<&str> = line.splitn(2, '\t').collect();
+            if fields.len() == 2 {
+                names.insert(fields[0].to_owned(), fields[1].to_owned());
+            }
+        }
+    }
+    predicates
+}
+
+struct PathFolder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    predicates: &'a Predicates,
+}
+
+impl<'a, 'b> Folder for PathFolder<'a, 'b> {
+    fn fold_expr(&mut self, e: P<ast::Expr>) -> P<ast::Expr> {
+        let cx = self.cx;
+        if let Some(e) = predicates::map_args(cx, &e, |arg| self.fold_expr(arg)) {
+            return e;
+        }
+        e.map(|e| fold::noop_fold_expr(e, self))
+    }
+
+    fn fold_path(&mut self, path: ast::Path) -> ast::Path {
+        let path = fold::noop_fold_path(path, self);
+        // Global paths start with the crate root.
+        if path.segments.len() != 2 {
+            return path;
+        }
+        let krate = path.segments[0].identifier.to_string();
+        let name = path.segments[1].identifier.to_string();
+        let full = match self.predicates.get(&krate) {
+            Some(names) => names.get(&name).cloned().unwrap_or(format!("{}::{}", krate, name)),
+            None => return path,
+        };
+        let idents = full.split("::").map(|segment| self.cx.ident_of(segment)).collect();
+        let mut global = self.cx.path_global(path.span, idents);
+        global.segments.last_mut().unwrap().parameters = path.segments[1].parameters.clone();
+        global
+    }
+
+    fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
+        fold::noop_fold_mac(mac, self)
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Specification-only clauses, see the `Specification-only clauses` section of
+// ../readme.md.
+//
+// A contract whose clauses are all `spec_only="text"` generates no checks, in
+// any build. Each clause is added to the item's documentation, and, when
+// compiling with `--cfg hoare_graph`, to the graph manifest (see graph.rs).
+
+use syntax::ast::{self, MetaItem};
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::symbol::Symbol;
+
+use graph;
+
+// Whether attr is a contract with `spec_only` clauses.
+pub fn is_spec_only(attr: &MetaItem) -> bool {
+    super::CONTRACTS.iter().any(|name| attr.check_name(name)) &&
+        attr.meta_item_list().map_or(false, |items| {
+            items.iter().any(|item| item.check_name("spec_only"))
+        })
+}
+
+// item, documented with the clauses of the spec-only contract attr.
+pub fn spec_only(cx: &mut ExtCtxt, sp: Span, attr: &MetaItem, item: Annotatable) -> Annotatable {
+    let mut clauses = Vec::new();
+    for clause in attr.meta_item_list().unwrap_or(&[]) {
+        match clause.value_str() {
+            Some(text) if clause.check_name("spec_only") => clauses.push(text),
+            _ => {
+                cx.span_err(
+                    sp,
+                    "`spec_only` can only be used with other `spec_only=\"text\"` clauses",
+                );
+                return item;
+            }
+        }
+    }
+
+    let name = attr.name.as_str();
+    let kind = match name.trim_left_matches("debug_").trim_left_matches("release_") {
+        "precond" => "precondition",
+        "postcond" => "postcondition",
+        kind => kind,
+    };
+    let ident = super::item_ident(&item);
+    let mut docs = Vec::new();
+    for text in clauses {
+        let text = text.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+        if graph::enabled(cx) {
+            graph::spec(cx, kind, &ident.name.as_str(), &text);
+        }
+        let mut capitalized = kind[..1].to_uppercase();
+        capitalized.push_str(&kind[1..]);
+        let doc = cx.meta_name_value(
+            sp,
+            Symbol::intern("doc"),
+            ast::LitKind::Str(
+                Symbol::intern(&format!("\n**{}** (not checked): {}", capitalized, text)),
+                ast::StrStyle::Cooked,
+            ),
+        );
+        docs.push(cx.attribute(sp, doc));
+    }
+
+    match item {
+        Annotatable::Item(item) => Annotatable::Item(item.map(|mut item| {
+            item.attrs.extend(docs);
+            item
+        })),
+        Annotatable::ImplItem(item) => Annotatable::ImplItem(item.map(|mut item| {
+            item.attrs.extend(docs);
+            item
+        })),
+        Annotatable::TraitItem(item) => Annotatable::TraitItem(item.map(|mut item| {
+            item.attrs.extend(docs);
+            item
+        })),
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Specification coverage, see the `Specification coverage` section of
+// ../readme.md.
+//
+// When compiling with `--cfg hoare_spec_coverage`, the first attribute of the
+// plugin to be expanded parses the whole crate again, from its root file,
+// removes what is not compiled in this configuration (see configure.rs), and
+// writes every public function and struct to a manifest (`<crate
+// name>.specs` in the specification coverage directory), which
+// `hoare-spec-coverage` in the runtime crate summarizes.
+//
+// Manifest lines have one of the forms
+//  * `fn \t module_path \t name \t specified`, for each `pub fn` and each
+//    `pub` method of an inherent impl (named `Type::method`), where specified
+//    is 1 if it has a contract (or `#[apply_contract_set]`, on it or its impl)
+//    and 0 otherwise,
+//  * `type \t module_path \t name \t specified`, for each `pub struct`, where
+//    specified is 1 if it has an invariant (or `#[derive(Invariant)]`).
+//
+// Items generated by macros are not in the parsed source, so they are not
+// counted.
+
+use std::cell::Cell;
+use std::path::Path;
+
+use syntax::ast;
+use syntax::attr;
+use syntax::ext::base::ExtCtxt;
+use syntax::parse;
+use syntax::print::pprust;
+
+use configure;
+use manifest;
+
+thread_local! {
+    static RECORDED: Cell<bool> = Cell::new(false);
+}
+
+pub fn enabled(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_spec_coverage")
+}
+
+// Writes the manifest of the crate, the first time it is called in a
+// compilation.
+pub fn record(cx: &ExtCtxt) {
+    if RECORDED.with(|recorded| recorded.replace(true)) {
+        return;
+    }
+    // The crate root is the first file parsed.
+    let root = match cx.codemap().files.borrow().first() {
+        Some(root) => root.name.clone(),
+        None => return,
+    };
+    let krate = match parse::parse_crate_from_file(Path::new(&root), cx.parse_sess) {
+        Ok(krate) => configure::configure_crate(cx, krate),
+        Err(mut e) => {
+            e.cancel();
+            cx.span_warn(
+                cx.call_site(),
+                &format!("could not parse {} for its specification coverage", root),
+            );
+            return;
+        }
+    };
+
+    let mut lines = Vec::new();
+    module(&cx.ecfg.crate_name, &krate.module, &mut lines);
+    let dir = manifest::dir("HOARE_SPEC_COVERAGE_DIR", "target/hoare-spec-coverage");
+    for line in lines {
+        manifest::append(cx, &dir, "specs", &line);
+    }
+}
+
+// Adds the lines for the items of m, whose path is path, and of its
+// submodules.
+fn module(path: &str, m: &ast::Mod, lines: &mut Vec<String>) {
+    for item in &m.items {
+        let public = item.vis == ast::Visibility::Public;
+        match item.node {
+            ast::ItemKind::Fn(..) if public => {
+                lines.push(line("fn", path, &item.ident.to_string(), has_contract(&item.attrs)));
+            }
+            ast::ItemKind::Struct(..) if public => {
+                lines.push(line("type", path, &item.ident.to_string(), has_invariant(&item.attrs)));
+            }
+            ast::ItemKind::Impl(.., None, ref self_ty, ref items) => {
+                // Drop any generic arguments, e.g., `Bounds<T>` is the type `Bounds`.
+                let ty = pprust::ty_to_string(self_ty);
+                let ty = ty.split('<').next().unwrap().trim();
+                let set = attr::contains_name(&item.attrs, "apply_contract_set");
+                for method in items {
+                    if let ast::ImplItemKind::Method(..) = method.node {
+                        if method.vis == ast::Visibility::Public {
+                            let name = format!("{}::{}", ty, method.ident);
+                            let specified = set || has_contract(&method.attrs);
+                            lines.push(line("fn", path, &name, specified));
+                        }
+                    }
+                }
+            }
+            ast::ItemKind::Mod(ref m) => {
+                module(&format!("{}::{}", path, item.ident), m, lines);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn line(kind: &str, path: &str, name: &str, specified: bool) -> String {
+    format!("{}\t{}\t{}\t{}", kind, path, name, specified as u8)
+}
+
+fn has_contract(attrs: &[ast::Attribute]) -> bool {
+    attrs.iter().any(|attr| super::is_contract(attr) || attr.check_name("apply_contract_set"))
+}
+
+fn has_invariant(attrs: &[ast::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.check_name("derive") {
+            let items = attr.meta_item_list().unwrap_or_default();
+            items.iter().any(|item| item.check_name("Invariant"))
+        } else {
+            ["invariant", "debug_invariant", "release_invariant"]
+                .iter()
+                .any(|name| attr.check_name(name))
+        }
+    })
+}
@@ -0,0 +1,262 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Enum-based state machines with contracts, see the `State machines` section of
+// ../readme.md.
+//
+//     state_machine! {
+//         enum Door { Open { angle: u32 }, Closed, Locked(u32) }
+//         states {
+//             Open => "*angle <= 90",
+//             Locked => "*_0 != 0",
+//         }
+//         transitions {
+//             #[from = "Open"]
+//             #[to = "Closed"]
+//             fn close(self) -> Door { Door::Closed }
+//         }
+//     }
+//
+// expands to the enum and an impl containing the transitions, each with a
+// precondition that the machine is in a `from` state whose invariant holds and
+// a postcondition that it ends up in the `to` state and its invariant holds
+// (for the returned value if the transition takes `self` by value). The
+// contracts are expanded as usual, so transitions can have their own.
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::{DummyResult, ExtCtxt, MacEager, MacResult};
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::parse::parser::Parser;
+use syntax::parse::token;
+use syntax::parse::PResult;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::tokenstream::TokenTree;
+use syntax::util::small_vector::SmallVector;
+
+struct Machine {
+    item: P<ast::Item>,
+    // (variant, invariant)
+    invariants: Vec<(ast::Ident, String)>,
+    transitions: Vec<P<ast::Item>>,
+}
+
+pub fn state_machine(cx: &mut ExtCtxt, sp: Span, tts: &[TokenTree]) -> Box<MacResult> {
+    let machine = {
+        let mut parser = cx.new_parser_from_tts(tts);
+        match parse_machine(&mut parser) {
+            Ok(machine) => machine,
+            Err(mut e) => {
+                e.emit();
+                return DummyResult::any(sp);
+            }
+        }
+    };
+
+    match expand(cx, sp, machine) {
+        Some(items) => MacEager::items(SmallVector::many(items)),
+        None => DummyResult::any(sp),
+    }
+}
+
+fn parse_machine<'a>(parser: &mut Parser<'a>) -> PResult<'a, Machine> {
+    let item = match try!(parser.parse_item()) {
+        Some(item) => item,
+        None => return Err(parser.fatal("expected the enum of the state machine")),
+    };
+
+    try!(expect_keyword(parser, "states"));
+    try!(parser.expect(&token::OpenDelim(token::Brace)));
+    let mut invariants = Vec::new();
+    while !parser.eat(&token::CloseDelim(token::Brace)) {
+        let variant = try!(parser.parse_ident());
+        try!(parser.expect(&token::FatArrow));
+        let (pred, _) = try!(parser.parse_str());
+        invariants.push((variant, pred.to_string()));
+        if !parser.eat(&token::Comma) {
+            try!(parser.expect(&token::CloseDelim(token::Brace)));
+            break;
+        }
+    }
+
+    try!(expect_keyword(parser, "transitions"));
+    try!(parser.expect(&token::OpenDelim(token::Brace)));
+    let mut transitions = Vec::new();
+    while !parser.eat(&token::CloseDelim(token::Brace)) {
+        match try!(parser.parse_item()) {
+            Some(item) => transitions.push(item),
+            None => return Err(parser.fatal("expected a transition function")),
+        }
+    }
+
+    if parser.token != token::Eof {
+        return Err(parser.fatal("unexpected tokens after transitions"));
+    }
+    Ok(Machine {
+        item: item,
+        invariants: invariants,
+        transitions: transitions,
+    })
+}
+
+fn expect_keyword<'a>(parser: &mut Parser<'a>, keyword: &str) -> PResult<'a, ()> {
+    let ident = try!(parser.parse_ident());
+    if ident.name != keyword {
+        return Err(parser.fatal(&format!("expected `{}`, found `{}`", keyword, ident)));
+    }
+    Ok(())
+}
+
+fn expand(cx: &ExtCtxt, sp: Span, machine: Machine) -> Option<Vec<P<ast::Item>>> {
+    let (enum_name, variants) = match machine.item.node {
+        ast::ItemKind::Enum(ref def, ref generics) if !generics.is_parameterized() => {
+            (machine.item.ident, &def.variants)
+        }
+        _ => {
+            cx.span_err(machine.item.span, "a state machine must be a non-generic enum");
+            return None;
+        }
+    };
+
+    let is_variant = |name: &str| variants.iter().any(|v| v.node.name.name == name);
+    let mut ok = true;
+
+    // Which states we are in and whether their invariants hold.
+    let mut names = String::new();
+    let mut invariants = String::new();
+    for variant in variants {
+        let name = variant.node.name;
+        let (fields, pattern) = match variant.node.data {
+            ast::VariantData::Struct(ref fields, _) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|f| format!("ref {}", f.ident.unwrap()))
+                    .collect();
+                (fields.join(", "), format!("{}::{} {{ .. }}", enum_name, name))
+            }
+            ast::VariantData::Tuple(ref fields, _) => {
+                let fields: Vec<String> = (0..fields.len()).map(|i| format!("ref _{}", i)).collect();
+                (fields.join(", "), format!("{}::{}(..)", enum_name, name))
+            }
+            ast::VariantData::Unit(_) => (String::new(), format!("{}::{}", enum_name, name)),
+        };
+        names.push_str(&format!("{} => \"{}\",\n", pattern, name));
+
+        for &(ref state, ref pred) in &machine.invariants {
+            if state.name != name.name {
+                continue;
+            }
+            let binding = match variant.node.data {
+                ast::VariantData::Struct(..) => format!("{}::{} {{ {} }}", enum_name, name, fields),
+                ast::VariantData::Tuple(..) => format!("{}::{}({})", enum_name, name, fields),
+                ast::VariantData::Unit(_) => pattern.clone(),
+            };
+            invariants.push_str(&format!("{} => {},\n", binding, pred));
+        }
+    }
+    for &(ref state, _) in &machine.invariants {
+        if !is_variant(&state.name.as_str()) {
+            cx.span_err(sp, &format!("`{}` is not a state of {}", state, enum_name));
+            ok = false;
+        }
+    }
+
+    let mut methods = format!(
+        "#[doc(hidden)] #[allow(dead_code)] fn __hoare_state_name(&self) -> &'static str {{\n\
+         match *self {{\n{}}}\n}}\n\
+         #[doc(hidden)] #[allow(dead_code, unused_variables)] \
+         fn __hoare_in_state(&self, states: &[&str]) -> bool {{\n\
+         states.contains(&self.__hoare_state_name()) && match *self {{\n{}_ => true,\n}}\n}}\n",
+        names,
+        invariants
+    );
+
+    for transition in &machine.transitions {
+        match transition_method(cx, transition, &is_variant) {
+            Some(method) => methods.push_str(&method),
+            None => ok = false,
+        }
+    }
+
+    if !ok {
+        return None;
+    }
+    let methods = cx.parse_item(format!("impl {} {{\n{}}}", enum_name, methods));
+    Some(vec![machine.item.clone(), methods])
+}
+
+// The source of a transition, with `from` and `to` replaced by contracts.
+fn transition_method<F>(cx: &ExtCtxt, transition: &P<ast::Item>, is_variant: &F) -> Option<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let decl = match transition.node {
+        ast::ItemKind::Fn(ref decl, ..) => decl,
+        _ => {
+            cx.span_err(transition.span, "expected a transition function");
+            return None;
+        }
+    };
+    let by_value = match decl.inputs.first().and_then(|arg| arg.to_self()) {
+        Some(ref self_kind) => match self_kind.node {
+            ast::SelfKind::Value(_) => true,
+            _ => false,
+        },
+        None => {
+            cx.span_err(transition.span, "a transition must take `self`");
+            return None;
+        }
+    };
+
+    let states = |name: &str| -> Option<Vec<String>> {
+        let attr = transition.attrs.iter().find(|attr| attr.check_name(name));
+        let value = attr.and_then(|attr| attr.value_str());
+        match value {
+            Some(value) => {
+                let states: Vec<String> =
+                    value.as_str().split('|').map(|s| s.trim().to_owned()).collect();
+                for state in &states {
+                    if !is_variant(state) {
+                        cx.span_err(transition.span, &format!("`{}` is not a state", state));
+                        return None;
+                    }
+                }
+                Some(states)
+            }
+            None => {
+                cx.span_err(
+                    transition.span,
+                    &format!("a transition needs `#[{}=\"State\"]`", name),
+                );
+                None
+            }
+        }
+    };
+    let (from, to) = match (states("from"), states("to")) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return None,
+    };
+
+    let quoted = |states: Vec<String>| {
+        states.iter().map(|s| format!("\\\"{}\\\"", s)).collect::<Vec<_>>().join(", ")
+    };
+    let target = if by_value { "return" } else { "self" };
+    let mut item = (**transition).clone();
+    item.attrs.retain(|attr| !attr.check_name("from") && !attr.check_name("to"));
+    Some(format!(
+        "#[precond=\"self.__hoare_in_state(&[{}])\"]\n\
+         #[postcond=\"{}.__hoare_in_state(&[{}])\"]\n{}\n",
+        quoted(from),
+        target,
+        quoted(to),
+        pprust::item_to_string(&item)
+    ))
+}
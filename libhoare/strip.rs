@@ -0,0 +1,84 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Removing contracts entirely, see the `Stripping contracts` section of
+// ../readme.md.
+//
+// When compiling with `--cfg hoare_strip`, attributes which only add checks
+// are wrapped in `Strippable`, which returns the item unchanged (and dumps it,
+// see dump.rs, so the expansion can be checked for residue). Attributes which
+// generate items the crate may use (constructors of refinement types, protocol
+// state, etc.) check `stripped` themselves and only skip their checks.
+//
+// A contract which is disabled in this build (by its level or a disabled tag,
+// see policy.rs) is removed in the same way, before it is expanded, so a
+// function whose contracts are all disabled is exactly the original function:
+// none of the result variable, the loop the body is wrapped in for early
+// returns, snapshots of `old` values, etc. are generated.
+
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt, MultiItemModifier};
+
+use cache;
+use call_checks;
+use dump;
+use policy;
+use spec;
+
+pub fn stripped(cx: &ExtCtxt) -> bool {
+    super::has_cfg(cx, "hoare_strip")
+}
+
+// Wraps the expander of an attribute so that it does nothing if contracts are
+// stripped or it is a disabled contract (and so its expansion can be cached,
+// see cache.rs).
+pub struct Strippable<F>(pub F);
+
+impl<F, T> MultiItemModifier for Strippable<F>
+where
+    F: Fn(&mut ExtCtxt, Span, &MetaItem, Annotatable) -> T,
+    T: Into<Vec<Annotatable>>,
+{
+    fn expand(
+        &self,
+        cx: &mut ExtCtxt,
+        sp: Span,
+        attr: &MetaItem,
+        item: Annotatable,
+    ) -> Vec<Annotatable> {
+        // Preconditions are exported whether or not they are checked here.
+        if call_checks::enabled(cx) {
+            call_checks::export(cx, attr, &item);
+        }
+        if spec::is_spec_only(attr) {
+            vec![spec::spec_only(cx, sp, attr, item)]
+        } else if stripped(cx) || disabled(cx, attr, &item) {
+            vec![strip(cx, item)]
+        } else {
+            cache::expand(cx, sp, attr, item, |cx, sp, attr, item| {
+                (self.0)(cx, sp, attr, item).into()
+            })
+        }
+    }
+}
+
+// Whether attr is a contract on item which is not checked in this build.
+pub fn disabled(cx: &ExtCtxt, attr: &MetaItem, item: &Annotatable) -> bool {
+    super::CONTRACTS.iter().any(|name| attr.check_name(name)) &&
+        policy::disabled(cx, attr, super::item_ident(item))
+}
+
+// Returns item, which has had its contract removed, unchanged (after dumping
+// it).
+pub fn strip(cx: &ExtCtxt, item: Annotatable) -> Annotatable {
+    dump::expansion(cx, super::item_ident(&item), &item);
+    item
+}
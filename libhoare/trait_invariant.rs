@@ -0,0 +1,230 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Trait invariants, see the `Trait invariants` section of ../readme.md.
+//
+// `#[invariant="pred"]` on a trait is recorded (by the trait's name), and
+// copied to each of the trait's provided methods. `#[check_trait_invariant]`
+// on an impl of the trait copies it to each method of the impl. So the
+// invariant is expanded as usual, with its policy, on every method, except:
+//
+// * methods which do not take `&self` or `&mut self`, and
+// * methods called in pred, since checking the invariant in them would
+//   recurse.
+//
+// Plugins see one item at a time, so the trait must be declared in the same
+// crate, and expanded before the impl, i.e., earlier in the crate.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use syntax::ast;
+use syntax::ast::MetaItem;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+use syntax::ext::build::AstBuilder;
+use syntax::ext::quote::rt::ExtParseUtils;
+use syntax::print::pprust;
+use syntax::ptr::P;
+use syntax::symbol::Symbol;
+use syntax::visit::{self, Visitor};
+
+use predicates;
+
+thread_local! {
+    // The invariant of each trait with one, by the trait's name.
+    static INVARIANTS: RefCell<HashMap<String, MetaItem>> = RefCell::new(HashMap::new());
+}
+
+pub fn as_trait(item: &Annotatable) -> Option<P<ast::Item>> {
+    match *item {
+        Annotatable::Item(ref item) => match item.node {
+            ast::ItemKind::Trait(..) => Some(item.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// The predicate of the invariant of the trait item, from its attributes if it
+// has not been expanded yet, or as recorded if it has.
+pub fn invariant_pred(item: &ast::Item) -> Option<Symbol> {
+    let names = ["invariant", "debug_invariant", "release_invariant"];
+    let attr = item.attrs.iter().find(|attr| names.iter().any(|name| attr.check_name(name)));
+    let attr = match attr {
+        Some(attr) => attr.meta(),
+        None => {
+            let name = item.ident.to_string();
+            INVARIANTS.with(|invariants| invariants.borrow().get(&name).cloned())
+        }
+    };
+    attr.and_then(|attr| attr.value_str().or_else(|| super::pred_item(&attr)))
+}
+
+// The recorded invariants, as (trait name, attribute), sorted by name.
+pub fn recorded() -> Vec<(String, String)> {
+    let mut recorded: Vec<(String, String)> = INVARIANTS.with(|invariants| {
+        invariants
+            .borrow()
+            .iter()
+            .map(|(name, attr)| (name.clone(), pprust::to_string(|s| s.print_meta_item(attr))))
+            .collect()
+    });
+    recorded.sort();
+    recorded
+}
+
+// Records the invariant attr of the trait item and adds it to the trait's
+// provided methods.
+pub fn trait_invariant(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    attr: &MetaItem,
+    item: P<ast::Item>,
+) -> Vec<Annotatable> {
+    let observers = match observers(cx, sp, attr) {
+        Some(observers) => observers,
+        None => return vec![Annotatable::Item(item)],
+    };
+    INVARIANTS.with(|invariants| {
+        invariants.borrow_mut().insert(item.ident.to_string(), attr.clone())
+    });
+
+    let item = item.map(|mut item| {
+        if let ast::ItemKind::Trait(_, _, _, ref mut items) = item.node {
+            for trait_item in items.iter_mut() {
+                if let ast::TraitItemKind::Method(ref sig, Some(_)) = trait_item.node {
+                    if checked(&sig.decl, trait_item.ident, &observers) {
+                        trait_item.attrs.push(cx.attribute(sp, attr.clone()));
+                    }
+                }
+            }
+        }
+        item
+    });
+    vec![Annotatable::Item(item)]
+}
+
+pub fn check_trait_invariant(
+    cx: &mut ExtCtxt,
+    sp: Span,
+    _attr: &MetaItem,
+    item: Annotatable,
+) -> Annotatable {
+    let item = match item {
+        Annotatable::Item(item) => item,
+        item => {
+            cx.span_err(sp, "#[check_trait_invariant] can only be used on impls of traits");
+            return item;
+        }
+    };
+    let trait_name = match item.node {
+        ast::ItemKind::Impl(_, _, _, _, Some(ref trait_ref), _, _) => {
+            trait_ref.path.segments.last().unwrap().identifier.to_string()
+        }
+        _ => {
+            cx.span_err(sp, "#[check_trait_invariant] can only be used on impls of traits");
+            return Annotatable::Item(item);
+        }
+    };
+    let attr = match INVARIANTS.with(|invariants| invariants.borrow().get(&trait_name).cloned()) {
+        Some(attr) => attr,
+        None => {
+            cx.span_err(
+                sp,
+                &format!(
+                    "{} has no #[invariant], or it is declared after this impl or in another \
+                     crate",
+                    trait_name
+                ),
+            );
+            return Annotatable::Item(item);
+        }
+    };
+    let observers = match observers(cx, sp, &attr) {
+        Some(observers) => observers,
+        None => return Annotatable::Item(item),
+    };
+
+    Annotatable::Item(item.map(|mut item| {
+        if let ast::ItemKind::Impl(_, _, _, _, _, _, ref mut items) = item.node {
+            for impl_item in items.iter_mut() {
+                if let ast::ImplItemKind::Method(ref sig, _) = impl_item.node {
+                    if checked(&sig.decl, impl_item.ident, &observers) {
+                        impl_item.attrs.push(cx.attribute(sp, attr.clone()));
+                    }
+                }
+            }
+        }
+        item
+    }))
+}
+
+// Whether the invariant is checked in the method name with decl.
+pub fn checked(decl: &ast::FnDecl, name: ast::Ident, observers: &[ast::Name]) -> bool {
+    if observers.contains(&name.name) {
+        return false;
+    }
+    match decl.get_self() {
+        Some(explicit_self) => match explicit_self.node {
+            ast::SelfKind::Region(..) => true,
+            ast::SelfKind::Explicit(ref ty, _) => match ty.node {
+                ast::TyKind::Rptr(..) => true,
+                _ => false,
+            },
+            ast::SelfKind::Value(_) => false,
+        },
+        None => false,
+    }
+}
+
+// The names of the methods called in the predicate of the invariant attr, or
+// None (after reporting an error) if it has none.
+pub fn observers(cx: &ExtCtxt, sp: Span, attr: &MetaItem) -> Option<Vec<ast::Name>> {
+    let pred = match attr.value_str().or_else(|| super::pred_item(attr)) {
+        Some(pred) => pred,
+        None => {
+            cx.span_err(sp, "expected #[invariant=\"predicate\"] on a trait");
+            return None;
+        }
+    };
+    let mut finder = MethodFinder {
+        cx: cx,
+        methods: Vec::new(),
+    };
+    finder.visit_expr(&cx.parse_expr(pred.to_string()));
+    Some(finder.methods)
+}
+
+struct MethodFinder<'a, 'b: 'a> {
+    cx: &'a ExtCtxt<'b>,
+    methods: Vec<ast::Name>,
+}
+
+impl<'a, 'b, 'c> Visitor<'c> for MethodFinder<'a, 'b> {
+    fn visit_expr(&mut self, e: &'c ast::Expr) {
+        if let ast::ExprKind::MethodCall(ref method, _) = e.node {
+            self.methods.push(method.identifier.name);
+        }
+        // The arguments of shorthands such as `iff!` are expressions.
+        let cx = self.cx;
+        let mut args = Vec::new();
+        predicates::map_args(cx, e, |arg| {
+            args.push(arg.clone());
+            arg
+        });
+        for arg in &args {
+            self.visit_expr(arg);
+        }
+        visit::walk_expr(self, e)
+    }
+
+    fn visit_mac(&mut self, _: &'c ast::Mac) {}
+}
@@ -0,0 +1,108 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Errors for contracts on items which are not functions, see the `Unsupported
+// items` section of ../readme.md.
+//
+// Contracts are checked when functions are called, so a contract on anything
+// else (a struct, a static, an impl, ...) is an error. Often it is one of the
+// features for types, traits, or impls which was meant, so the error names
+// what the contract is on and, where there is one, suggests that feature:
+//
+// error: Precondition on a struct, which is not a function
+//   = help: an invariant on a struct, `#[invariant="..."]`, is checked on entry
+//           and exit of its methods (see `Struct invariants` in libhoare's
+//           readme)
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::ext::base::{Annotatable, ExtCtxt};
+
+// Reports kind (e.g., "Precondition") on item, which is not a function with a
+// body.
+pub fn report(cx: &ExtCtxt, sp: Span, kind: &str, item: &Annotatable) {
+    let invariant = kind == "Invariant";
+    let (what, help) = match *item {
+        Annotatable::Item(ref item) => match item.node {
+            // Invariants on structs don't get here, unless they are of a kind
+            // (e.g., incremental) which structs can't have.
+            ast::ItemKind::Struct(..) if invariant => ("a struct", None),
+            ast::ItemKind::Struct(..) => ("a struct", Some(STRUCT_INVARIANT)),
+            ast::ItemKind::Enum(..) => ("an enum", Some(WRAP_IN_STRUCT)),
+            ast::ItemKind::Union(..) => ("a union", Some(WRAP_IN_STRUCT)),
+            ast::ItemKind::Static(..) => ("a static", Some(REFINEMENT)),
+            ast::ItemKind::Const(..) => ("a const", Some(REFINEMENT)),
+            ast::ItemKind::Impl(.., Some(_), _, _) if invariant => {
+                ("a trait impl", Some(TRAIT_IMPL_INVARIANT))
+            }
+            ast::ItemKind::Impl(..) => ("an impl", Some(CONTRACT_SET)),
+            ast::ItemKind::Trait(..) => ("a trait", Some(CONTRACT_SET)),
+            ast::ItemKind::Mod(..) => ("a module", Some(PROFILE)),
+            ast::ItemKind::Ty(..) => ("a type alias", None),
+            _ => ("an item", None),
+        },
+        Annotatable::ImplItem(ref item) => match item.node {
+            ast::ImplItemKind::Const(..) => ("an associated const", Some(ON_METHODS)),
+            ast::ImplItemKind::Type(..) => ("an associated type", Some(ON_METHODS)),
+            _ => ("an impl item", None),
+        },
+        Annotatable::TraitItem(ref item) => match item.node {
+            ast::TraitItemKind::Method(_, None) => {
+                let mut err = cx.struct_span_err(
+                    sp,
+                    &format!("{} on a required trait method, which has no body", kind),
+                );
+                err.help(CONTRACTED);
+                err.emit();
+                return;
+            }
+            ast::TraitItemKind::Const(..) => ("an associated const", Some(ON_METHODS)),
+            ast::TraitItemKind::Type(..) => ("an associated type", Some(ON_METHODS)),
+            _ => ("a trait item", None),
+        },
+    };
+    let mut err = cx.struct_span_err(sp, &format!("{} on {}, which is not a function", kind, what));
+    if let Some(help) = help {
+        err.help(help);
+    }
+    err.emit();
+}
+
+const STRUCT_INVARIANT: &'static str =
+    "an invariant on a struct, `#[invariant=\"...\"]`, is checked on entry and exit of its \
+     methods (see `Struct invariants` in libhoare's readme)";
+
+const WRAP_IN_STRUCT: &'static str =
+    "only structs can have invariants, so wrap it in a struct with `#[invariant=\"...\"]` \
+     (see `Struct invariants` in libhoare's readme), or put the contract on its methods";
+
+const REFINEMENT: &'static str =
+    "contracts are checked when functions are called, so to constrain a value, give it a \
+     newtype with an invariant, `#[invariant=\"self.0 > 0\"] struct Positive(i32);` (see \
+     `Refinement types` in libhoare's readme)";
+
+const TRAIT_IMPL_INVARIANT: &'static str =
+    "put the invariant on the trait, and `#[check_trait_invariant]` on the impl (see `Trait \
+     invariants` in libhoare's readme), or put it on the struct";
+
+const CONTRACT_SET: &'static str =
+    "to add a contract to each method, define it in `contract_set!` and use \
+     `#[apply_contract_set(name)]` (see `Contract sets` in libhoare's readme)";
+
+const PROFILE: &'static str =
+    "a contract profile, `hoare.toml`, can set the policy of the contracts in a module (see \
+     `Contract profiles` in libhoare's readme)";
+
+const ON_METHODS: &'static str = "put the contract on the methods instead";
+
+const CONTRACTED: &'static str =
+    "give the method a default body, or put `#[contracted]` on the trait to check the \
+     contracts of its required methods on trait objects (see `Contracts on trait objects` in \
+     libhoare's readme)";
@@ -0,0 +1,105 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Counting allocations for `#[no_alloc]`.
+//!
+//! Functions with `#[no_alloc]` check, in debug builds, that the current thread
+//! made no allocations during the call. Allocations can only be counted if the
+//! global allocator calls `record_allocation` for each allocation. With the
+//! `counting-alloc` feature (which needs Rust 1.28 or later, for
+//! `std::alloc::GlobalAlloc`), `CountingAllocator` does that:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: libhoare::alloc::CountingAllocator = libhoare::alloc::CountingAllocator;
+//! ```
+//!
+//! On the nightly the plugin builds with, the global allocator has to be
+//! written against `std::heap::Alloc` instead, see tests/test_no_alloc.rs.
+
+#[cfg(feature = "counting-alloc")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// A global allocator which counts the allocations (and reallocations) made by
+/// each thread, and otherwise uses the system allocator.
+#[cfg(feature = "counting-alloc")]
+pub struct CountingAllocator;
+
+#[cfg(feature = "counting-alloc")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Counts an allocation by the current thread. To be called by the global
+/// allocator.
+///
+/// # Panics
+///
+/// If the thread locals of the current thread have already been destroyed.
+pub fn record_allocation() {
+    INSTALLED.store(true, Ordering::Relaxed);
+    ALLOCATIONS.with(|n| n.set(n.get() + 1));
+}
+
+/// The number of allocations made by the current thread so far.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Checks the current thread made no allocations since `allocations()` returned
+/// `before`.
+///
+/// # Panics
+///
+/// If there were allocations, or if no global allocator calls
+/// `record_allocation`.
+pub fn check_no_alloc(before: usize, fn_name: &str) {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        panic!(
+            "no_alloc of {} can not be checked: the global allocator does not call \
+             libhoare::alloc::record_allocation",
+            fn_name
+        );
+    }
+    let allocations = allocations() - before;
+    if allocations != 0 {
+        panic!(
+            "no_alloc of {} violated: {} allocation{}",
+            fn_name,
+            allocations,
+            if allocations == 1 { "" } else { "s" }
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Overhead of contracts.
+//!
+//! The benchmarks generated by the plugin's `#[bench_contracts]` are criterion
+//! benchmark groups named `hoare/<function>`, with a benchmark of the function
+//! without contracts (`unchecked`), with all of them (`checked`), and with each
+//! contract on its own (named by the contract's attribute). `Report` reads
+//! criterion's results for these groups and computes the overhead of each
+//! variant relative to `unchecked`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{self, Value};
+
+use files;
+
+const GROUP_PREFIX: &str = "hoare/";
+
+/// Mean times of the benchmarks of contracted functions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// For each function, the mean time in nanoseconds of each variant
+    /// (`unchecked`, `checked`, or a contract).
+    pub functions: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// The cost of one variant of a function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overhead {
+    /// `checked` or a contract.
+    pub variant: String,
+    pub time_ns: f64,
+    /// The difference from the function without contracts.
+    pub overhead_ns: f64,
+    /// The overhead as a percentage of the time without contracts.
+    pub overhead_percent: f64,
+}
+
+impl Report {
+    /// Reads the results of criterion in `dir` (usually `target/criterion`).
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Report> {
+        let mut report = Report::default();
+        report.load_dir(dir.as_ref())?;
+        Ok(report)
+    }
+
+    // Criterion keeps the latest results of each benchmark in `new`.
+    fn load_dir(&mut self, dir: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if path.file_name().and_then(|name| name.to_str()) == Some("new") {
+                let benchmark = path.join("benchmark.json");
+                let estimates = path.join("estimates.json");
+                if benchmark.is_file() && estimates.is_file() {
+                    let benchmark = files::read_to_string(benchmark)?;
+                    self.add(&benchmark, &files::read_to_string(estimates)?);
+                }
+            } else {
+                self.load_dir(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the result of one benchmark, given criterion's `benchmark.json`
+    /// and `estimates.json`. Benchmarks not generated by `#[bench_contracts]`
+    /// and malformed results are ignored.
+    pub fn add(&mut self, benchmark: &str, estimates: &str) {
+        let (benchmark, estimates): (Value, Value) =
+            match (serde_json::from_str(benchmark), serde_json::from_str(estimates)) {
+                (Ok(benchmark), Ok(estimates)) => (benchmark, estimates),
+                _ => return,
+            };
+        let function = match benchmark["group_id"].as_str() {
+            Some(group) if group.starts_with(GROUP_PREFIX) => &group[GROUP_PREFIX.len()..],
+            _ => return,
+        };
+        let variant = benchmark["function_id"].as_str();
+        let mean = estimates["mean"]["point_estimate"].as_f64();
+        if let (Some(variant), Some(mean)) = (variant, mean) {
+            self.functions
+                .entry(function.to_owned())
+                .or_insert_with(BTreeMap::new)
+                .insert(variant.to_owned(), mean);
+        }
+    }
+
+    /// The overhead of each variant of `function`, other than `unchecked`,
+    /// with `checked` first. Empty if the function was not benchmarked
+    /// without contracts.
+    pub fn overheads(&self, function: &str) -> Vec<Overhead> {
+        let variants = match self.functions.get(function) {
+            Some(variants) => variants,
+            None => return Vec::new(),
+        };
+        let unchecked = match variants.get("unchecked") {
+            Some(&unchecked) => unchecked,
+            None => return Vec::new(),
+        };
+        let mut result: Vec<Overhead> = variants
+            .iter()
+            .filter(|&(variant, _)| variant != "unchecked")
+            .map(|(variant, &time_ns)| Overhead {
+                variant: variant.clone(),
+                time_ns,
+                overhead_ns: time_ns - unchecked,
+                overhead_percent: if unchecked > 0.0 {
+                    (time_ns - unchecked) / unchecked * 100.0
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+        result.sort_by_key(|overhead| overhead.variant != "checked");
+        result
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (function, variants) in &self.functions {
+            match variants.get("unchecked") {
+                Some(unchecked) => {
+                    writeln!(f, "{}: {:.1} ns without contracts", function, unchecked)?
+                }
+                None => {
+                    writeln!(f, "{}: not benchmarked without contracts", function)?;
+                    continue;
+                }
+            }
+            for overhead in self.overheads(function) {
+                writeln!(
+                    f,
+                    "    {:+10.1} ns {:+7.1}%  {}",
+                    overhead.overhead_ns,
+                    overhead.overhead_percent,
+                    overhead.variant
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Prints the overhead of contracts measured by the benchmarks generated by
+// `#[bench_contracts]`. Usage: `hoare-bench [dir]`, where dir defaults to
+// `target/criterion`.
+
+extern crate libhoare;
+
+use std::env;
+use std::process;
+
+use libhoare::bench::Report;
+
+fn main() {
+    let dir = env::args().nth(1).unwrap_or("target/criterion".to_owned());
+    let report = match Report::load(&dir) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("could not read benchmark results from {}: {}", dir, e);
+            process::exit(2);
+        }
+    };
+
+    if report.functions.is_empty() {
+        eprintln!("no benchmarks generated by #[bench_contracts] in {}", dir);
+        process::exit(1);
+    }
+    print!("{}", report);
+}
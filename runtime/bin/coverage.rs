@@ -0,0 +1,36 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Prints a contract coverage report. Usage: `hoare-coverage [dir]`, where dir
+// defaults to `target/hoare-coverage`. Exits with an error if any contract was
+// never evaluated.
+
+extern crate libhoare;
+
+use std::env;
+use std::process;
+
+use libhoare::coverage::Report;
+
+fn main() {
+    let dir = env::args().nth(1).unwrap_or("target/hoare-coverage".to_owned());
+    let report = match Report::load(&dir) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("could not read contract coverage from {}: {}", dir, e);
+            process::exit(2);
+        }
+    };
+
+    print!("{}", report);
+    if !report.unevaluated().is_empty() {
+        process::exit(1);
+    }
+}
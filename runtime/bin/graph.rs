@@ -0,0 +1,45 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Prints a graph of the contracts in a crate. Usage: `hoare-graph [--json]
+// [dir]`, where dir defaults to `target/hoare-graph`. The graph is printed in
+// graphviz's DOT language, or as JSON with `--json`.
+
+extern crate libhoare;
+
+use std::env;
+use std::process;
+
+use libhoare::graph::Graph;
+
+fn main() {
+    let mut json = false;
+    let mut dir = "target/hoare-graph".to_owned();
+    for arg in env::args().skip(1) {
+        if arg == "--json" {
+            json = true;
+        } else {
+            dir = arg;
+        }
+    }
+
+    let graph = match Graph::load(&dir) {
+        Ok(graph) => graph,
+        Err(e) => {
+            eprintln!("could not read contract graph from {}: {}", dir, e);
+            process::exit(2);
+        }
+    };
+    if json {
+        println!("{}", graph.to_json());
+    } else {
+        print!("{}", graph.to_dot());
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Runs a test suite once for each mutant of the contracts in a crate and
+// reports the mutants which no test kills.
+//
+// Usage: `hoare-mutants [--dir DIR] [-- COMMAND ARGS...]`, where DIR defaults
+// to `target/hoare-mutants` and the command defaults to `cargo test`. The
+// command is run with `--cfg hoare_mutants` added to `RUSTFLAGS`.
+
+extern crate libhoare;
+
+use std::env;
+use std::process::{self, Command, Stdio};
+
+use libhoare::mutation::{Mutant, ACTIVE_VAR};
+
+fn main() {
+    let mut dir = "target/hoare-mutants".to_owned();
+    let mut command = vec!["cargo".to_owned(), "test".to_owned()];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--dir" => match args.next() {
+                Some(arg) => dir = arg,
+                None => usage(),
+            },
+            "--" => {
+                command = args.collect();
+                break;
+            }
+            _ => usage(),
+        }
+    }
+    if command.is_empty() {
+        usage();
+    }
+
+    let rustflags = match env::var("RUSTFLAGS") {
+        Ok(flags) => format!("{} --cfg hoare_mutants", flags),
+        Err(_) => "--cfg hoare_mutants".to_owned(),
+    };
+
+    // Also builds the crate, which writes the manifests.
+    if !run(&command, &rustflags, None) {
+        eprintln!("the test suite fails without any mutants, fix it first");
+        process::exit(2);
+    }
+
+    let mutants = match Mutant::load_all(&dir) {
+        Ok(mutants) => mutants,
+        Err(e) => {
+            eprintln!("could not read mutants from {}: {}", dir, e);
+            process::exit(2);
+        }
+    };
+
+    let mut survivors = Vec::new();
+    for mutant in &mutants {
+        if run(&command, &rustflags, Some(mutant)) {
+            survivors.push(mutant);
+        }
+    }
+
+    println!(
+        "mutation testing: {} of {} mutants killed",
+        mutants.len() - survivors.len(),
+        mutants.len()
+    );
+    for mutant in &survivors {
+        println!(
+            "survived: {} {}: `{}` mutated to `{}`",
+            mutant.cond_type, mutant.fn_path, mutant.predicate, mutant.mutant
+        );
+    }
+    if !survivors.is_empty() {
+        process::exit(1);
+    }
+}
+
+// Whether the command succeeds.
+fn run(command: &[String], rustflags: &str, mutant: Option<&Mutant>) -> bool {
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..])
+        .env("RUSTFLAGS", rustflags)
+        .env_remove(ACTIVE_VAR)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(mutant) = mutant {
+        cmd.env(ACTIVE_VAR, mutant.selector());
+    }
+    match cmd.status() {
+        Ok(status) => status.success(),
+        Err(e) => {
+            eprintln!("could not run {}: {}", command[0], e);
+            process::exit(2);
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: hoare-mutants [--dir DIR] [-- COMMAND ARGS...]");
+    process::exit(2);
+}
@@ -0,0 +1,46 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Prints a specification coverage report. Usage: `hoare-spec-coverage [--json |
+// --badge] [dir]`, where dir defaults to `target/hoare-spec-coverage`. The
+// report is a Markdown table, JSON with `--json`, or a shields.io endpoint
+// badge with `--badge`.
+
+extern crate libhoare;
+
+use std::env;
+use std::process;
+
+use libhoare::spec_coverage::SpecCoverage;
+
+fn main() {
+    let mut format = "markdown".to_owned();
+    let mut dir = "target/hoare-spec-coverage".to_owned();
+    for arg in env::args().skip(1) {
+        if arg == "--json" || arg == "--badge" {
+            format = arg[2..].to_owned();
+        } else {
+            dir = arg;
+        }
+    }
+
+    let coverage = match SpecCoverage::load(&dir) {
+        Ok(coverage) => coverage,
+        Err(e) => {
+            eprintln!("could not read specification coverage from {}: {}", dir, e);
+            process::exit(2);
+        }
+    };
+    match &*format {
+        "json" => println!("{}", coverage.to_json()),
+        "badge" => println!("{}", coverage.to_badge()),
+        _ => print!("{}", coverage.to_markdown()),
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Wrapper for trait objects whose trait has contracts, see the `Contracts on
+// trait objects` section of ../readme.md.
+
+/// Checks the contracts of a `#[contracted]` trait on every call.
+///
+/// The plugin implements `Trait` for `Contracted<Box<T>>` (for any
+/// `T: Trait + ?Sized`, including `dyn Trait`) by checking the contracts
+/// declared on the trait around a call to the wrapped object.
+pub struct Contracted<T>(pub T);
+
+impl<T> Contracted<T> {
+    pub fn new(inner: T) -> Contracted<T> {
+        Contracted(inner)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
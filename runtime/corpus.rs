@@ -0,0 +1,101 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A corpus of inputs which violated postconditions.
+//!
+//! Functions marked `#[capture_violations]` serialize their arguments (as a
+//! JSON array) on entry, and write them to `<dir>/<fn path>/<hash>.json` if a
+//! postcondition fails. `inputs` reads them back so that the failures can be
+//! replayed as regression tests, and `deserialize` reads one input, for the
+//! functions generated by `#[replay_violations]`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+
+use files;
+
+/// Serializes a tuple of references to a function's arguments. Called by code
+/// generated by the plugin. Returns `None` if the arguments can't be
+/// serialized.
+pub fn serialize<T: Serialize>(args: &T) -> Option<String> {
+    serde_json::to_string(args).ok()
+}
+
+/// Deserializes the arguments of a function, as serialized by `serialize`.
+/// Called by code generated by the plugin to replay a captured input.
+///
+/// # Panics
+///
+/// If `args` can't be deserialized as a `T`.
+pub fn deserialize<T: DeserializeOwned>(args: &str) -> T {
+    serde_json::from_str(args)
+        .unwrap_or_else(|e| panic!("could not deserialize the arguments {}: {}", args, e))
+}
+
+/// Writes serialized arguments to the corpus. Called by code generated by the
+/// plugin when a postcondition fails, so it reports problems rather than
+/// panicking.
+pub fn capture(dir: &str, fn_path: &str, args: &Option<String>) {
+    let args = match *args {
+        Some(ref args) => args,
+        None => {
+            eprintln!("could not capture the arguments of {}: serialization failed", fn_path);
+            return;
+        }
+    };
+
+    let mut hasher = DefaultHasher::new();
+    args.hash(&mut hasher);
+    let fn_dir = fn_dir(dir, fn_path);
+    let path = fn_dir.join(format!("{:016x}.json", hasher.finish()));
+    match fs::create_dir_all(&fn_dir).and_then(|_| files::write(&path, args)) {
+        Ok(()) => eprintln!("captured violating input of {} in {}", fn_path, path.display()),
+        Err(e) => eprintln!("could not capture the arguments of {}: {}", fn_path, e),
+    }
+}
+
+/// The captured inputs of the function `fn_path` (e.g., `my_crate::foo`) in
+/// the corpus in `dir`, as tuples of arguments.
+///
+/// # Panics
+///
+/// If a captured input can't be read or deserialized as a `T`.
+pub fn inputs<T: DeserializeOwned, P: AsRef<Path>>(dir: P, fn_path: &str) -> Vec<T> {
+    let fn_dir = fn_dir(dir, fn_path);
+    let entries = match fs::read_dir(&fn_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let json = files::read_to_string(path)
+                .unwrap_or_else(|e| panic!("could not read {}: {}", path.display(), e));
+            serde_json::from_str(&json)
+                .unwrap_or_else(|e| panic!("could not deserialize {}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+fn fn_dir<P: AsRef<Path>>(dir: P, fn_path: &str) -> PathBuf {
+    dir.as_ref().join(fn_path.replace("::", "."))
+}
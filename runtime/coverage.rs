@@ -0,0 +1,192 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Contract coverage.
+//!
+//! When a crate is compiled with `--cfg hoare_coverage`, the plugin writes a
+//! manifest of all its contracts (`<crate>.contracts`, or
+//! `<crate>.test.contracts` for the build of its tests) to the coverage
+//! directory and each evaluation of a contract calls `record`. The first time
+//! each contract and branch is seen by a process, it is appended to a `.hits`
+//! file of the process in the same directory. `Report` combines the two.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use files;
+
+// (cond_type, fn_path, predicate, branch), where branch is the index of the
+// disjunct which held or None if the predicate was false.
+type Hit = (&'static str, &'static str, &'static str, Option<usize>);
+
+lazy_static! {
+    static ref SEEN: Mutex<BTreeSet<Hit>> = Mutex::new(BTreeSet::new());
+    // `std::process::id` is not stable on the plugin's nightly, so the name is
+    // random.
+    static ref HITS_FILE: String =
+        format!("{:016x}.hits", RandomState::new().build_hasher().finish());
+}
+
+/// Records the evaluation of a contract. Called by code generated by the
+/// plugin; `dir` is the coverage directory.
+pub fn record(
+    dir: &'static str,
+    cond_type: &'static str,
+    fn_path: &'static str,
+    pred: &'static str,
+    branch: Option<usize>,
+) {
+    let mut seen = SEEN.lock().unwrap_or_else(|e| e.into_inner());
+    if !seen.insert((cond_type, fn_path, pred, branch)) {
+        return;
+    }
+
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => "-".to_owned(),
+    };
+    let path = Path::new(dir).join(&*HITS_FILE);
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut file| writeln!(file, "{}\t{}\t{}\t{}", cond_type, fn_path, branch, pred));
+    // Never fail the program being tested because of coverage.
+    if let Err(e) = result {
+        eprintln!("could not record contract coverage in {}: {}", path.display(), e);
+    }
+}
+
+/// Coverage of a single contract check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractCoverage {
+    /// The kind of check, e.g., `precondition of` or `invariant leaving`.
+    pub cond_type: String,
+    /// The path of the function, starting with the crate name.
+    pub fn_path: String,
+    pub predicate: String,
+    /// The number of top-level disjuncts (`||`) in the predicate.
+    pub branches: usize,
+    /// The disjuncts which were the first to hold in some evaluation.
+    pub branches_hit: BTreeSet<usize>,
+    /// Whether the predicate was ever false.
+    pub violated: bool,
+}
+
+impl ContractCoverage {
+    /// Whether the contract was evaluated at all.
+    pub fn evaluated(&self) -> bool {
+        self.violated || !self.branches_hit.is_empty()
+    }
+
+    /// Whether every disjunct of the predicate was exercised.
+    pub fn fully_covered(&self) -> bool {
+        self.branches_hit.len() == self.branches
+    }
+}
+
+/// A coverage report for all contracts in a coverage directory.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub contracts: Vec<ContractCoverage>,
+}
+
+impl Report {
+    /// Reads all manifests and hits in `dir`. A contract in the manifests of
+    /// more than one build of a crate is reported once.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Report> {
+        let mut contracts = BTreeMap::new();
+        let mut hits = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("contracts") => {
+                    for line in files::read_to_string(&path)?.lines() {
+                        let fields: Vec<&str> = line.splitn(4, '\t').collect();
+                        if fields.len() != 4 {
+                            continue;
+                        }
+                        let key = (fields[0].to_owned(), fields[1].to_owned(), fields[3].to_owned());
+                        contracts.insert(
+                            key,
+                            ContractCoverage {
+                                cond_type: fields[0].to_owned(),
+                                fn_path: fields[1].to_owned(),
+                                predicate: fields[3].to_owned(),
+                                branches: fields[2].parse().unwrap_or(1),
+                                branches_hit: BTreeSet::new(),
+                                violated: false,
+                            },
+                        );
+                    }
+                }
+                Some("hits") => hits.push(files::read_to_string(&path)?),
+                _ => {}
+            }
+        }
+
+        for line in hits.iter().flat_map(|hits| hits.lines()) {
+            let fields: Vec<&str> = line.splitn(4, '\t').collect();
+            if fields.len() != 4 {
+                continue;
+            }
+            let key = (fields[0].to_owned(), fields[1].to_owned(), fields[3].to_owned());
+            // Hits for contracts which are no longer in a manifest are stale.
+            if let Some(contract) = contracts.get_mut(&key) {
+                match fields[2].parse() {
+                    Ok(branch) => {
+                        contract.branches_hit.insert(branch);
+                    }
+                    Err(_) => contract.violated = true,
+                }
+            }
+        }
+
+        Ok(Report {
+            contracts: contracts.into_iter().map(|(_, contract)| contract).collect(),
+        })
+    }
+
+    /// Contracts which were never evaluated.
+    pub fn unevaluated(&self) -> Vec<&ContractCoverage> {
+        self.contracts.iter().filter(|c| !c.evaluated()).collect()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let evaluated = self.contracts.iter().filter(|c| c.evaluated()).count();
+        writeln!(
+            f,
+            "contract coverage: {} of {} contracts evaluated",
+            evaluated,
+            self.contracts.len()
+        )?;
+        for c in &self.contracts {
+            let status = if !c.evaluated() {
+                "never"
+            } else if c.fully_covered() {
+                "covered"
+            } else {
+                "partial"
+            };
+            write!(f, "{:8} {} {} ({})", status, c.cond_type, c.fn_path, c.predicate)?;
+            if c.branches > 1 {
+                write!(f, " [{} of {} branches]", c.branches_hit.len(), c.branches)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Messages for failed comparisons.
+//!
+//! In test builds, if the predicate of a contract is a comparison (e.g.,
+//! `return == x + 1`), the plugin evaluates each operand once and, if the
+//! comparison fails, calls `comparison_failed` with their `Debug`
+//! representations. Operands whose type does not implement `Debug` are
+//! handled using `Operand`, see below.
+
+use std::env;
+use std::fmt::Debug;
+
+/// Wraps an operand of a comparison so that `(&Operand(&x)).debug_operand()`
+/// is `Some` debug representation of `x` if its type implements `Debug`, and
+/// `None` otherwise. This relies on method resolution trying methods of
+/// `Operand` (`DebugOperand`) before those of `&Operand` (`NoDebugOperand`),
+/// so both traits must be in scope.
+pub struct Operand<'a, T: ?Sized + 'a>(pub &'a T);
+
+pub trait DebugOperand {
+    fn debug_operand(&self) -> Option<String>;
+}
+
+impl<'a, T: Debug + ?Sized> DebugOperand for Operand<'a, T> {
+    fn debug_operand(&self) -> Option<String> {
+        Some(format!("{:#?}", self.0))
+    }
+}
+
+pub trait NoDebugOperand {
+    fn debug_operand(&self) -> Option<String>;
+}
+
+impl<'a, 'b, T: ?Sized> NoDebugOperand for &'b Operand<'a, T> {
+    fn debug_operand(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Panics with `label`, the operands of the failed comparison, and (for `==`)
+/// a diff of the operands. Called by code generated by the plugin. The diff is
+/// colored unless the `NO_COLOR` environment variable is set.
+pub fn comparison_failed(label: &str, op: &str, left: Option<String>, right: Option<String>) -> ! {
+    panic!("{}", comparison_message(label, op, left, right, env::var_os("NO_COLOR").is_none()))
+}
+
+/// The message used by `comparison_failed`.
+pub fn comparison_message(
+    label: &str,
+    op: &str,
+    left: Option<String>,
+    right: Option<String>,
+    color: bool,
+) -> String {
+    let mut message = format!("{}\n", label);
+    let (left, right) = match (left, right) {
+        (Some(left), Some(right)) => (left, right),
+        (left, right) => {
+            let missing = || "(does not implement Debug)".to_owned();
+            message.push_str(&format!(" left: {}\n", left.unwrap_or_else(missing)));
+            message.push_str(&format!("right: {}\n", right.unwrap_or_else(missing)));
+            return message;
+        }
+    };
+
+    if op != "==" || (!left.contains('\n') && !right.contains('\n')) {
+        message.push_str(&format!(" left: {}\n", left));
+        message.push_str(&format!("right: {}\n", right));
+        if op != "==" {
+            return message;
+        }
+    }
+
+    let left_lines: Vec<&str> = left.lines().collect();
+    let right_lines: Vec<&str> = right.lines().collect();
+    message.push_str("diff (- left, + right):\n");
+    for (tag, line) in line_diff(&left_lines, &right_lines) {
+        let line = format!("{}{}", tag, line);
+        match (tag, color) {
+            ('-', true) => message.push_str(&format!("\x1b[31m{}\x1b[0m\n", line)),
+            ('+', true) => message.push_str(&format!("\x1b[32m{}\x1b[0m\n", line)),
+            _ => message.push_str(&format!("{}\n", line)),
+        }
+    }
+    message
+}
+
+/// A line diff using the longest common subsequence, each line is tagged with
+/// `' '`, `'-'` (only in `old`), or `'+'` (only in `new`).
+pub fn line_diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    // lcs[i][j] is the length of the LCS of old[i..] and new[j..].
+    let mut lcs = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            result.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            result.push(('-', old[i]));
+            i += 1;
+        } else {
+            result.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    result
+}
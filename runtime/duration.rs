@@ -0,0 +1,30 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking `#[max_duration]`.
+
+use std::time::{Duration, Instant};
+
+/// Checks less than `max_nanos` nanoseconds have passed since `start`. `limit`
+/// is the limit as written in the contract.
+///
+/// # Panics
+///
+/// If the limit was exceeded.
+pub fn check_max_duration(start: Instant, max_nanos: u64, fn_name: &str, limit: &str) {
+    let elapsed = start.elapsed();
+    let max = Duration::new(max_nanos / 1_000_000_000, (max_nanos % 1_000_000_000) as u32);
+    if elapsed > max {
+        panic!(
+            "max_duration of {} violated: took {:?}, limit {}",
+            fn_name, elapsed, limit
+        );
+    }
+}
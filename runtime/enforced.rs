@@ -0,0 +1,116 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A wrapper which checks the invariant of the value it owns.
+//!
+//! `Enforced<T>` checks the invariant of `T` (see `HasInvariant`) when it is
+//! created and cloned, and when an `EnforcedMut`, which gives mutable access to
+//! the value, is dropped. So the invariant is enforced even if `T`'s methods
+//! have no contracts of their own. Shared access, through `Deref`, is not
+//! checked, since it can't break the invariant (unless `T` has interior
+//! mutability).
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+
+use invariant::HasInvariant;
+
+/// A value whose invariant is checked whenever it may have changed.
+pub struct Enforced<T: HasInvariant> {
+    value: T,
+}
+
+impl<T: HasInvariant> Enforced<T> {
+    /// Wraps `value`.
+    ///
+    /// # Panics
+    ///
+    /// If the invariant of `value` does not hold.
+    pub fn new(value: T) -> Enforced<T> {
+        check(&value, "Enforced::new");
+        Enforced { value }
+    }
+
+    /// Mutable access to the value, whose invariant is checked when the
+    /// returned guard is dropped.
+    pub fn get_mut<'a>(&'a mut self) -> EnforcedMut<'a, T> {
+        EnforcedMut {
+            value: &mut self.value,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: HasInvariant> Deref for Enforced<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: HasInvariant + Clone> Clone for Enforced<T> {
+    /// # Panics
+    ///
+    /// If the invariant of the clone does not hold.
+    fn clone(&self) -> Enforced<T> {
+        let value = self.value.clone();
+        check(&value, "Enforced::clone");
+        Enforced { value }
+    }
+}
+
+impl<T: HasInvariant + fmt::Debug> fmt::Debug for Enforced<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Enforced").field(&self.value).finish()
+    }
+}
+
+/// Mutable access to the value of an `Enforced`.
+///
+/// # Panics
+///
+/// On drop, if the invariant of the value does not hold (unless the thread is
+/// already panicking).
+pub struct EnforcedMut<'a, T: HasInvariant + 'a> {
+    value: &'a mut T,
+}
+
+impl<'a, T: HasInvariant> Deref for EnforcedMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, T: HasInvariant> DerefMut for EnforcedMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<'a, T: HasInvariant> Drop for EnforcedMut<'a, T> {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            check(self.value, "EnforcedMut::drop");
+        }
+    }
+}
+
+fn check<T: HasInvariant>(value: &T, place: &str) {
+    if let Err(error) = value.check_invariants() {
+        panic!("{} in {}", error, place);
+    }
+}
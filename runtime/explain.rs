@@ -0,0 +1,36 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Explanations of failed predicates.
+//!
+//! With `--cfg hoare_explain`, if a predicate fails the plugin re-evaluates
+//! its leaf subexpressions (e.g., `self.len()` and `cap` in
+//! `self.len() <= cap`) and calls `failed` with their source and `Debug`
+//! representations (see `diff::Operand`).
+
+/// Panics with `label` followed by the value of each subexpression. Called by
+/// code generated by the plugin.
+pub fn failed(label: &str, values: &[(&str, Option<String>)]) -> ! {
+    panic!("{}", message(label, values))
+}
+
+/// The message used by `failed`, one line per subexpression, `source = value`.
+/// Multi-line values are indented.
+pub fn message(label: &str, values: &[(&str, Option<String>)]) -> String {
+    let mut message = label.to_owned();
+    for &(source, ref value) in values {
+        let value = match *value {
+            Some(ref value) => value.replace('\n', "\n    "),
+            None => "(does not implement Debug)".to_owned(),
+        };
+        message.push_str(&format!("\n  {} = {}", source, value));
+    }
+    message
+}
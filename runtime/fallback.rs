@@ -0,0 +1,140 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Contracts on stable Rust.
+//!
+//! `precond!` and `postcond!` are `macro_rules!` macros which check pre- and
+//! postconditions of a function without the plugin, so contracts keep
+//! compiling (and being checked) when the plugin doesn't build:
+//!
+//! ```
+//! #[macro_use]
+//! extern crate libhoare;
+//!
+//! precond! {
+//!     /// Divides `x` by `y`.
+//!     pub fn div(x: i32, y: i32) -> i32
+//!         requires (y != 0)
+//!     {
+//!         x / y
+//!     }
+//! }
+//!
+//! postcond! {
+//!     fn abs(x: i32) -> i32
+//!         ensures |result| (*result >= 0)
+//!     {
+//!         if x < 0 { return -x; }
+//!         x
+//!     }
+//! }
+//! # fn main() { assert_eq!(div(abs(-6), 2), 3); }
+//! ```
+//!
+//! Both macros take any number of `requires (pred)` and
+//! `ensures |result| (pred)` clauses, in any order, between the signature and
+//! the body; `result` names a reference to the value the body returns. Failed
+//! checks panic with the same messages as the plugin's. Generic functions and
+//! `where` clauses are not supported.
+
+/// A function with preconditions, see the module documentation of
+/// `libhoare::fallback`.
+#[macro_export]
+macro_rules! precond {
+    ($($fn:tt)*) => {
+        __hoare_contract!(@start $($fn)*);
+    };
+}
+
+/// A function with postconditions, see the module documentation of
+/// `libhoare::fallback`.
+#[macro_export]
+macro_rules! postcond {
+    ($($fn:tt)*) => {
+        __hoare_contract!(@start $($fn)*);
+    };
+}
+
+// Splits a function into its signature, name, return type (including the
+// `->`), preconditions, postconditions, and body, then generates the checked
+// function. The postconditions need the return type, so it is collected up to
+// the first clause one token at a time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __hoare_contract {
+    (@start $(#[$attr:meta])* pub $(($($vis:tt)*))* fn $name:ident ($($args:tt)*)
+     $($rest:tt)*) => {
+        __hoare_contract!(
+            @ret [$(#[$attr])* pub $(($($vis)*))* fn $name($($args)*)] $name [] $($rest)*
+        );
+    };
+    (@start $(#[$attr:meta])* fn $name:ident ($($args:tt)*) $($rest:tt)*) => {
+        __hoare_contract!(@ret [$(#[$attr])* fn $name($($args)*)] $name [] $($rest)*);
+    };
+
+    (@ret $head:tt $name:ident $ret:tt requires $($rest:tt)*) => {
+        __hoare_contract!(@clauses $head $name $ret [] [] requires $($rest)*);
+    };
+    (@ret $head:tt $name:ident $ret:tt ensures $($rest:tt)*) => {
+        __hoare_contract!(@clauses $head $name $ret [] [] ensures $($rest)*);
+    };
+    (@ret $head:tt $name:ident [$($ret:tt)*] $t:tt $($rest:tt)*) => {
+        __hoare_contract!(@ret $head $name [$($ret)* $t] $($rest)*);
+    };
+
+    (@clauses $head:tt $name:ident $ret:tt [$($pre:tt)*] $post:tt
+     requires ($($pred:tt)*) $($rest:tt)*) => {
+        __hoare_contract!(
+            @clauses $head $name $ret [$($pre)* ($($pred)*)] $post $($rest)*
+        );
+    };
+    (@clauses $head:tt $name:ident $ret:tt $pre:tt [$($post:tt)*]
+     ensures |$result:ident| ($($pred:tt)*) $($rest:tt)*) => {
+        __hoare_contract!(
+            @clauses $head $name $ret $pre [$($post)* [$result ($($pred)*)]] $($rest)*
+        );
+    };
+    (@clauses [$($head:tt)*] $name:ident [$($ret:tt)*] [$(($($pre:tt)*))*] []
+     $body:block) => {
+        $($head)* $($ret)* {
+            $(assert!(
+                $($pre)*,
+                concat!("precondition of ", stringify!($name), " (", stringify!($($pre)*), ")")
+            );)*
+            $body
+        }
+    };
+    // The body is run in a closure, so that a `return` in it still reaches
+    // the postconditions.
+    (@clauses [$($head:tt)*] $name:ident [$($ret:tt)*] [$(($($pre:tt)*))*]
+     [$([$result:ident ($($post:tt)*)])+] $body:block) => {
+        $($head)* $($ret)* {
+            $(assert!(
+                $($pre)*,
+                concat!("precondition of ", stringify!($name), " (", stringify!($($pre)*), ")")
+            );)*
+            let __hoare_result = (|| $($ret)* { $body })();
+            $({
+                let $result = &__hoare_result;
+                assert!(
+                    $($post)*,
+                    concat!(
+                        "postcondition of ",
+                        stringify!($name),
+                        " (",
+                        stringify!($($post)*),
+                        ")"
+                    )
+                );
+            })+
+            __hoare_result
+        }
+    };
+}
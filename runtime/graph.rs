@@ -0,0 +1,232 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Graphs of contracts.
+//!
+//! When a crate is compiled with `--cfg hoare_graph`, the plugin writes a
+//! manifest (`<crate>.graph`) to the graph directory describing the crate's
+//! contracted functions, the types with invariants, the named predicates
+//! (functions, methods, and macros) their contracts call, and the
+//! constructors which establish invariants, and the specification-only
+//! clauses of functions and types. `Graph` reads these and renders
+//! them as DOT or JSON.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json;
+
+use files;
+
+/// The contracts in a graph directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Graph {
+    /// The paths of contracted functions, with their contracts as
+    /// `(cond_type, predicate)`.
+    pub functions: BTreeMap<String, BTreeSet<(String, String)>>,
+    /// The paths of types with invariants, with the invariants' predicates.
+    pub types: BTreeMap<String, BTreeSet<String>>,
+    /// `(function or type, named predicate)` for each named predicate called
+    /// in a contract.
+    pub uses: BTreeSet<(String, String)>,
+    /// `(function, type)` for each constructor which establishes the invariant
+    /// of a type.
+    pub establishes: BTreeSet<(String, String)>,
+    /// The paths of functions and types with specification-only clauses, with
+    /// the clauses as `(kind, text)`.
+    pub specs: BTreeMap<String, BTreeSet<(String, String)>>,
+}
+
+impl Graph {
+    /// Reads all graph manifests in `dir`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Graph> {
+        let mut graph = Graph::default();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("graph") {
+                continue;
+            }
+            for line in files::read_to_string(&path)?.lines() {
+                graph.add(line);
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Adds a line of a manifest to the graph, ignoring malformed lines.
+    pub fn add(&mut self, line: &str) {
+        let fields: Vec<&str> = line.splitn(4, '\t').collect();
+        match (fields[0], fields.len()) {
+            ("contract", 4) => {
+                self.functions
+                    .entry(fields[1].to_owned())
+                    .or_insert_with(BTreeSet::new)
+                    .insert((fields[2].to_owned(), fields[3].to_owned()));
+            }
+            ("invariant", 3) => {
+                self.types
+                    .entry(fields[1].to_owned())
+                    .or_insert_with(BTreeSet::new)
+                    .insert(fields[2].to_owned());
+            }
+            ("uses", 3) => {
+                self.uses.insert((fields[1].to_owned(), fields[2].to_owned()));
+            }
+            ("establishes", 3) => {
+                self.establishes.insert((fields[1].to_owned(), fields[2].to_owned()));
+            }
+            ("spec", 4) => {
+                self.specs
+                    .entry(fields[1].to_owned())
+                    .or_insert_with(BTreeSet::new)
+                    .insert((fields[2].to_owned(), fields[3].to_owned()));
+            }
+            _ => {}
+        }
+    }
+
+    /// The named predicates, with the functions and types which use them.
+    pub fn predicates(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut result: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for &(ref user, ref pred) in &self.uses {
+            result.entry(pred).or_insert_with(Vec::new).push(user);
+        }
+        result
+    }
+
+    /// The paths of the functions: those with contracts, and those with only
+    /// specification-only clauses.
+    pub fn function_paths(&self) -> BTreeSet<&str> {
+        let specified = self.specs.keys().filter(|path| !self.types.contains_key(*path));
+        self.functions.keys().chain(specified).map(|path| &**path).collect()
+    }
+
+    // The lines of the specification-only clauses of path in a DOT label.
+    fn spec_label(&self, path: &str) -> String {
+        let mut label = String::new();
+        for &(ref kind, ref text) in self.specs.get(path).into_iter().flat_map(|specs| specs) {
+            label += &format!("\nspec {}: {}", kind, text);
+        }
+        label
+    }
+
+    // The specification-only clauses of path as JSON.
+    fn spec_json(&self, path: &str) -> Vec<serde_json::Value> {
+        self.specs
+            .get(path)
+            .into_iter()
+            .flat_map(|specs| specs)
+            .map(|&(ref kind, ref text)| json!({ "kind": kind, "text": text }))
+            .collect()
+    }
+
+    /// The graph in graphviz's DOT language. Functions and types are boxes
+    /// listing their contracts, named predicates are ellipses.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph contracts {\n    node [shape=box];\n".to_owned();
+        for function in self.function_paths() {
+            let mut label = function.to_owned();
+            for &(ref cond_type, ref pred) in
+                self.functions.get(function).into_iter().flat_map(|contracts| contracts)
+            {
+                label += &format!("\n{}: {}", cond_type, pred);
+            }
+            label += &self.spec_label(function);
+            dot += &format!("    {} [label={}];\n", quote(function), quote(&label));
+        }
+        for (ty, invariants) in &self.types {
+            let mut label = format!("invariant of {}", ty);
+            for pred in invariants {
+                label += &format!("\n{}", pred);
+            }
+            label += &self.spec_label(ty);
+            dot += &format!(
+                "    {} [label={}, style=rounded];\n",
+                quote(ty),
+                quote(&label)
+            );
+        }
+        for pred in self.predicates().keys() {
+            dot += &format!("    {} [shape=ellipse];\n", quote(pred));
+        }
+        for &(ref user, ref pred) in &self.uses {
+            dot += &format!("    {} -> {};\n", quote(user), quote(pred));
+        }
+        for &(ref function, ref ty) in &self.establishes {
+            dot += &format!(
+                "    {} -> {} [style=dashed, label=\"establishes\"];\n",
+                quote(function),
+                quote(ty)
+            );
+        }
+        dot + "}\n"
+    }
+
+    /// The graph as JSON, with `functions`, `types`, and `predicates` arrays.
+    pub fn to_json(&self) -> String {
+        let uses = |user: &str| -> Vec<&str> {
+            self.uses.iter().filter(|u| u.0 == user).map(|u| &*u.1).collect()
+        };
+        let functions: Vec<_> = self
+            .function_paths()
+            .into_iter()
+            .map(|function| {
+                let contracts: Vec<_> = self
+                    .functions
+                    .get(function)
+                    .into_iter()
+                    .flat_map(|contracts| contracts)
+                    .map(|&(ref cond_type, ref pred)| {
+                        json!({ "kind": cond_type, "predicate": pred })
+                    })
+                    .collect();
+                let establishes: Vec<&str> = self
+                    .establishes
+                    .iter()
+                    .filter(|e| e.0 == function)
+                    .map(|e| &*e.1)
+                    .collect();
+                json!({
+                    "path": function,
+                    "contracts": contracts,
+                    "uses": uses(function),
+                    "establishes": establishes,
+                    "specs": self.spec_json(function),
+                })
+            })
+            .collect();
+        let types: Vec<_> = self
+            .types
+            .iter()
+            .map(|(ty, invariants)| {
+                json!({
+                    "path": ty,
+                    "invariants": invariants,
+                    "uses": uses(ty),
+                    "specs": self.spec_json(ty),
+                })
+            })
+            .collect();
+        let predicates: Vec<_> = self
+            .predicates()
+            .into_iter()
+            .map(|(pred, used_by)| json!({ "name": pred, "used_by": used_by }))
+            .collect();
+        json!({ "functions": functions, "types": types, "predicates": predicates })
+            .to_string()
+    }
+}
+
+// s as a DOT string.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
@@ -0,0 +1,214 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Saving the values of `old(e)` in transitions.
+//!
+//! The value of `e` is saved on entry to the function using its `Snapshot`
+//! implementation if it has one, and otherwise by cloning it. Like
+//! `diff::Operand`, this relies on method resolution: the plugin generates
+//! `(&Old(&e)).old()`, which finds `SnapshotOld` (implemented for `Old`)
+//! before `CloneOld` (implemented for `&Old`), so both traits must be in scope.
+//!
+//! With `old = "pooled"`, the plugin generates `pooled(&e)` instead, which
+//! copies `e` into a buffer reused across calls (see `ReusableSnapshot`).
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::ops::Deref;
+
+/// A value saved by `old(e)` in place of `e` itself, e.g., a projection of a
+/// value which is expensive (or impossible) to clone.
+///
+/// ```
+/// # use libhoare::Snapshot;
+/// struct Log {
+///     lines: Vec<String>,
+/// }
+///
+/// impl Snapshot for Log {
+///     type Snapshot = usize;
+///     fn snapshot(&self) -> usize {
+///         self.lines.len()
+///     }
+/// }
+/// ```
+pub trait Snapshot {
+    type Snapshot;
+    fn snapshot(&self) -> Self::Snapshot;
+}
+
+// So that `old(self)` works in methods.
+impl<T: Snapshot + ?Sized> Snapshot for &T {
+    type Snapshot = T::Snapshot;
+
+    fn snapshot(&self) -> T::Snapshot {
+        (**self).snapshot()
+    }
+}
+
+impl<T: Snapshot + ?Sized> Snapshot for &mut T {
+    type Snapshot = T::Snapshot;
+
+    fn snapshot(&self) -> T::Snapshot {
+        (**self).snapshot()
+    }
+}
+
+/// Wraps the argument of `old`.
+pub struct Old<'a, T: ?Sized + 'a>(pub &'a T);
+
+pub trait SnapshotOld {
+    type Output;
+    fn old(&self) -> Self::Output;
+}
+
+impl<'a, T: Snapshot + ?Sized> SnapshotOld for Old<'a, T> {
+    type Output = T::Snapshot;
+
+    fn old(&self) -> T::Snapshot {
+        self.0.snapshot()
+    }
+}
+
+pub trait CloneOld {
+    type Output;
+    fn old(&self) -> Self::Output;
+}
+
+impl<'a, 'b, T: Clone> CloneOld for &'b Old<'a, T> {
+    type Output = T;
+
+    fn old(&self) -> T {
+        self.0.clone()
+    }
+}
+
+/// A value which `old(e)` can copy into a buffer reused across calls, with
+/// `old = "pooled"`, rather than allocating a new clone on every call.
+///
+/// ```
+/// # use libhoare::history::ReusableSnapshot;
+/// struct Lines(Vec<String>);
+///
+/// impl ReusableSnapshot for Lines {
+///     fn empty() -> Lines {
+///         Lines(Vec::new())
+///     }
+///     fn copy_from(&mut self, source: &Lines) {
+///         self.0.clone_from(&source.0);
+///     }
+/// }
+/// ```
+pub trait ReusableSnapshot: 'static {
+    /// A new buffer, which will be overwritten by `copy_from`.
+    fn empty() -> Self;
+    /// Makes `self` a copy of `source`, reusing the allocations of `self`.
+    fn copy_from(&mut self, source: &Self);
+}
+
+impl<T: Clone + 'static> ReusableSnapshot for Vec<T> {
+    fn empty() -> Vec<T> {
+        Vec::new()
+    }
+
+    fn copy_from(&mut self, source: &Vec<T>) {
+        self.clone_from(source);
+    }
+}
+
+impl<T: Clone + 'static> ReusableSnapshot for VecDeque<T> {
+    fn empty() -> VecDeque<T> {
+        VecDeque::new()
+    }
+
+    fn copy_from(&mut self, source: &VecDeque<T>) {
+        self.clone_from(source);
+    }
+}
+
+impl ReusableSnapshot for String {
+    fn empty() -> String {
+        String::new()
+    }
+
+    fn copy_from(&mut self, source: &String) {
+        self.clone_from(source);
+    }
+}
+
+impl<K, V, S> ReusableSnapshot for HashMap<K, V, S>
+where
+    K: Clone + Eq + Hash + 'static,
+    V: Clone + 'static,
+    S: BuildHasher + Clone + Default + 'static,
+{
+    fn empty() -> HashMap<K, V, S> {
+        HashMap::default()
+    }
+
+    fn copy_from(&mut self, source: &HashMap<K, V, S>) {
+        self.clone_from(source);
+    }
+}
+
+// The most buffers of each type kept by each thread.
+const POOL_SIZE: usize = 4;
+
+thread_local! {
+    // Buffers which are not in use, by type.
+    static POOLS: RefCell<HashMap<TypeId, Vec<Box<Any>>>> = RefCell::new(HashMap::new());
+}
+
+/// A copy of a value in a pooled buffer, which is returned to the pool when
+/// this is dropped.
+pub struct Pooled<T: ReusableSnapshot> {
+    buffer: Option<Box<T>>,
+}
+
+/// Copies `value` into a buffer from the current thread's pool (or a new one if
+/// the pool is empty). Called by code generated by the plugin.
+pub fn pooled<T: ReusableSnapshot>(value: &T) -> Pooled<T> {
+    let buffer = POOLS
+        .with(|pools| {
+            pools
+                .borrow_mut()
+                .get_mut(&TypeId::of::<T>())
+                .and_then(|pool| pool.pop())
+        })
+        .and_then(|buffer| buffer.downcast::<T>().ok());
+    let mut buffer = buffer.unwrap_or_else(|| Box::new(T::empty()));
+    buffer.copy_from(value);
+    Pooled {
+        buffer: Some(buffer),
+    }
+}
+
+impl<T: ReusableSnapshot> Deref for Pooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.buffer.as_ref().unwrap()
+    }
+}
+
+impl<T: ReusableSnapshot> Drop for Pooled<T> {
+    fn drop(&mut self) {
+        let buffer: Box<Any> = self.buffer.take().unwrap();
+        POOLS.with(|pools| {
+            let mut pools = pools.borrow_mut();
+            let pool = pools.entry(TypeId::of::<T>()).or_insert_with(Vec::new);
+            if pool.len() < POOL_SIZE {
+                pool.push(buffer);
+            }
+        });
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking that contracts were evaluated.
+//!
+//! When a crate is compiled with `--cfg hoare_hits`, each evaluation of a
+//! contract calls `record`, and tests can use `assert_contract_hit` to check
+//! that a contract was evaluated at least once by the current process.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// The kind of a contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContractKind {
+    Precond,
+    Postcond,
+    /// An invariant, checked on entering or leaving the function.
+    Invariant,
+}
+
+lazy_static! {
+    static ref HITS: Mutex<BTreeSet<(&'static str, ContractKind)>> = Mutex::new(BTreeSet::new());
+}
+
+/// Records the evaluation of a contract of the function `fn_path`. Called by
+/// code generated by the plugin.
+pub fn record(fn_path: &'static str, kind: ContractKind) {
+    HITS.lock().unwrap_or_else(|e| e.into_inner()).insert((fn_path, kind));
+}
+
+/// Whether a contract of kind `kind` on the function `fn_path` has been
+/// evaluated. `fn_path` should start with the crate name (e.g.,
+/// `my_crate::foo::bar`), but a path within the crate (`foo::bar`) also
+/// matches.
+pub fn was_hit(fn_path: &str, kind: ContractKind) -> bool {
+    HITS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|&(hit_path, hit_kind)| hit_kind == kind && path_matches(hit_path, fn_path))
+}
+
+/// Checks that a contract of kind `kind` on the function `fn_path` has been
+/// evaluated, see `was_hit`.
+///
+/// # Panics
+///
+/// If the contract has not been evaluated.
+pub fn assert_contract_hit(fn_path: &str, kind: ContractKind) {
+    if was_hit(fn_path, kind) {
+        return;
+    }
+
+    if HITS.lock().unwrap_or_else(|e| e.into_inner()).is_empty() {
+        panic!(
+            "{:?} of {} was never evaluated (no contract evaluations were recorded, \
+             is the crate compiled with --cfg hoare_hits?)",
+            kind, fn_path
+        );
+    }
+    panic!("{:?} of {} was never evaluated", kind, fn_path);
+}
+
+fn path_matches(hit_path: &str, fn_path: &str) -> bool {
+    hit_path == fn_path
+        || (hit_path.ends_with(fn_path) && hit_path[..hit_path.len() - fn_path.len()].ends_with("::"))
+}
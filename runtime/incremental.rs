@@ -0,0 +1,45 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Scheduling the full checks of incremental invariants.
+//!
+//! A method with `#[invariant(incremental="...", full="...", every="n")]`
+//! checks the incremental predicate on every call, but the full one only on
+//! every `n`th call. The generated code counts calls in a static and asks
+//! `full_check_due` whether this call is one of them.
+
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// If set, the number of calls between full checks for every incremental
+/// invariant, overriding the `every` of each invariant. `1` makes every check a
+/// full check and `0` disables full checks.
+pub const EVERY_VAR: &str = "HOARE_FULL_CHECK_EVERY";
+
+/// Counts a call and returns whether the full check should be done for it. The
+/// full check is done on the first call and then on every `every`th call (never
+/// if `every` is `0`).
+pub fn full_check_due(calls: &AtomicUsize, every: usize) -> bool {
+    let every = every_override().unwrap_or(every);
+    let call = calls.fetch_add(1, Ordering::Relaxed);
+    every != 0 && call % every == 0
+}
+
+fn every_override() -> Option<usize> {
+    lazy_static! {
+        static ref EVERY: Option<usize> = env::var(EVERY_VAR).ok().map(|every| {
+            every
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a number, found `{}`", EVERY_VAR, every))
+        });
+    }
+    *EVERY
+}
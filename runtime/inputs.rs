@@ -0,0 +1,94 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use arbitrary::Unstructured;
+
+// The number of random bytes each decoding attempt starts from.
+const INPUT_LEN: usize = 256;
+const DEFAULT_ATTEMPTS: usize = 10_000;
+
+/// An endless iterator of random arguments for a function which satisfy its
+/// preconditions. Created by `generate_valid_input!`.
+///
+/// Each call to `next` decodes candidate arguments from fresh random data until
+/// they satisfy the function's preconditions, and panics if that takes more
+/// than `attempts` tries.
+pub struct ValidInputs<T> {
+    fn_name: &'static str,
+    decode: fn(&mut Unstructured) -> Option<T>,
+    attempts: usize,
+    rng: XorShift,
+}
+
+impl<T> ValidInputs<T> {
+    pub fn new(fn_name: &'static str, decode: fn(&mut Unstructured) -> Option<T>) -> ValidInputs<T> {
+        ValidInputs {
+            fn_name,
+            decode,
+            attempts: DEFAULT_ATTEMPTS,
+            rng: XorShift::new(),
+        }
+    }
+
+    /// Sets the number of candidates to try for each input before giving up.
+    pub fn attempts(mut self, attempts: usize) -> ValidInputs<T> {
+        self.attempts = attempts;
+        self
+    }
+}
+
+impl<T> Iterator for ValidInputs<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut data = [0; INPUT_LEN];
+        for _ in 0..self.attempts {
+            self.rng.fill(&mut data);
+            if let Some(args) = (self.decode)(&mut Unstructured::new(&data)) {
+                return Some(args);
+            }
+        }
+        panic!(
+            "could not generate an input satisfying the precondition of {} in {} attempts",
+            self.fn_name,
+            self.attempts
+        );
+    }
+}
+
+// Xorshift64*, seeded from the standard library's per-process random keys.
+// Good enough for generating test inputs and saves a dependency on `rand`.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> XorShift {
+        let seed = RandomState::new().build_hasher().finish();
+        XorShift(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn fill(&mut self, data: &mut [u8]) {
+        for chunk in data.chunks_mut(8) {
+            let bytes = self.next_u64();
+            for (i, b) in chunk.iter_mut().enumerate() {
+                *b = (bytes >> (i * 8)) as u8;
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking struct invariants without panicking.
+//!
+//! For a struct with `#[invariant]`, the plugin implements `HasInvariant`, whose
+//! `check_invariants` returns the first predicate of the invariant which does not
+//! hold, so the struct can be used with `Enforced`. With `#[check_invariants]`,
+//! it also generates an inherent `check_invariants` method.
+
+use std::error::Error;
+use std::fmt;
+
+/// A type whose invariant can be checked on demand.
+///
+/// The plugin implements this for structs with `#[invariant]`.
+pub trait HasInvariant {
+    /// Returns the first predicate of the invariant which does not hold as an
+    /// `Err`.
+    fn check_invariants(&self) -> Result<(), InvariantError>;
+}
+
+/// A violated struct invariant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantError {
+    type_name: &'static str,
+    predicate: &'static str,
+}
+
+impl InvariantError {
+    pub fn new(type_name: &'static str, predicate: &'static str) -> InvariantError {
+        InvariantError {
+            type_name,
+            predicate,
+        }
+    }
+
+    /// The name of the struct whose invariant was violated.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The predicate which does not hold.
+    pub fn predicate(&self) -> &'static str {
+        self.predicate
+    }
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invariant of {} ({}) violated", self.type_name, self.predicate)
+    }
+}
+
+impl Error for InvariantError {}
@@ -0,0 +1,170 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking the laws of operator and comparison traits.
+//!
+//! The tests generated for `#[laws(...)]` call these functions, each of which
+//! checks a law holds for every pair (or triple) of sample values.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul};
+
+/// Checks `partial_cmp` agrees with `==` and with itself when its arguments are
+/// swapped.
+///
+/// # Panics
+///
+/// If the law does not hold for some samples.
+pub fn partial_ord<T: PartialOrd + Debug>(type_name: &str, samples: &[T]) {
+    for a in samples {
+        for b in samples {
+            let ord = a.partial_cmp(b);
+            if (ord == Some(Ordering::Equal)) != (a == b) {
+                violated(
+                    type_name,
+                    "partial_ord",
+                    &format!("{:?}.partial_cmp(&{:?}) is {:?}, but == is {}", a, b, ord, a == b),
+                );
+            }
+            let reversed = b.partial_cmp(a);
+            if reversed != ord.map(Ordering::reverse) {
+                violated(
+                    type_name,
+                    "partial_ord",
+                    &format!(
+                        "{:?}.partial_cmp(&{:?}) is {:?}, but the reverse is {:?}",
+                        a, b, ord, reversed
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Checks `cmp` agrees with `partial_cmp` and is transitive.
+///
+/// # Panics
+///
+/// If the law does not hold for some samples.
+pub fn ord<T: Ord + Debug>(type_name: &str, samples: &[T]) {
+    partial_ord(type_name, samples);
+    for a in samples {
+        for b in samples {
+            if Some(a.cmp(b)) != a.partial_cmp(b) {
+                violated(
+                    type_name,
+                    "ord",
+                    &format!(
+                        "{:?}.cmp(&{:?}) is {:?}, but partial_cmp is {:?}",
+                        a,
+                        b,
+                        a.cmp(b),
+                        a.partial_cmp(b)
+                    ),
+                );
+            }
+            for c in samples {
+                if a < b && b < c && a >= c {
+                    violated(
+                        type_name,
+                        "ord",
+                        &format!("{:?} < {:?} < {:?}, but not {:?} < {:?}", a, b, c, a, c),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Checks equal values have equal hashes.
+///
+/// # Panics
+///
+/// If the law does not hold for some samples.
+pub fn hash<T: Hash + Eq + Debug>(type_name: &str, samples: &[T]) {
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    for a in samples {
+        for b in samples {
+            if a == b && hash_of(a) != hash_of(b) {
+                violated(
+                    type_name,
+                    "hash",
+                    &format!("{:?} == {:?}, but their hashes differ", a, b),
+                );
+            }
+        }
+    }
+}
+
+/// Checks `(a + b) + c == a + (b + c)`.
+///
+/// # Panics
+///
+/// If the law does not hold for some samples.
+pub fn add_assoc<T>(type_name: &str, samples: &[T])
+where
+    T: Add<Output = T> + Clone + PartialEq + Debug,
+{
+    associative(type_name, "add_assoc", "+", samples, |a, b| a + b);
+}
+
+/// Checks `(a * b) * c == a * (b * c)`.
+///
+/// # Panics
+///
+/// If the law does not hold for some samples.
+pub fn mul_assoc<T>(type_name: &str, samples: &[T])
+where
+    T: Mul<Output = T> + Clone + PartialEq + Debug,
+{
+    associative(type_name, "mul_assoc", "*", samples, |a, b| a * b);
+}
+
+fn associative<T, F>(type_name: &str, law: &str, op_str: &str, samples: &[T], op: F)
+where
+    T: Clone + PartialEq + Debug,
+    F: Fn(T, T) -> T,
+{
+    for a in samples {
+        for b in samples {
+            for c in samples {
+                let left = op(op(a.clone(), b.clone()), c.clone());
+                let right = op(a.clone(), op(b.clone(), c.clone()));
+                if left != right {
+                    violated(
+                        type_name,
+                        law,
+                        &format!(
+                            "({a:?} {op} {b:?}) {op} {c:?} is {:?}, but {a:?} {op} ({b:?} {op} {c:?}) is {:?}",
+                            left,
+                            right,
+                            a = a,
+                            b = b,
+                            c = c,
+                            op = op_str
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn violated(type_name: &str, law: &str, reason: &str) -> ! {
+    panic!("law {} of {} violated: {}", law, type_name, reason);
+}
@@ -0,0 +1,68 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Runtime support for code generated by the hoare plugin. Crates only need to
+// depend on this crate if they use a feature of the plugin which requires it,
+// see ../readme.md.
+
+pub extern crate arbitrary;
+pub extern crate backtrace;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(feature = "loom")]
+extern crate loom as loom_crate;
+extern crate serde;
+#[macro_use]
+extern crate serde_json;
+
+pub mod alloc;
+pub mod bench;
+mod contracted;
+pub mod corpus;
+pub mod coverage;
+pub mod diff;
+pub mod duration;
+pub mod enforced;
+pub mod explain;
+pub mod fallback;
+mod files;
+pub mod graph;
+pub mod hits;
+pub mod history;
+pub mod incremental;
+pub mod invariant;
+pub mod laws;
+mod inputs;
+pub mod lock;
+#[cfg(feature = "loom")]
+pub mod loom;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod mutation;
+pub mod panics;
+pub mod predicates;
+pub mod protocol;
+pub mod race;
+pub mod reentrancy;
+pub mod round_trip;
+pub mod sample;
+pub mod snapshot;
+pub mod spec_coverage;
+pub mod strip;
+pub mod tags;
+pub mod violation;
+
+pub use contracted::Contracted;
+pub use enforced::{Enforced, EnforcedMut};
+pub use hits::{assert_contract_hit, ContractKind};
+pub use history::Snapshot;
+pub use inputs::ValidInputs;
+pub use invariant::{HasInvariant, InvariantError};
+pub use violation::{Location, Violation, ViolationKind};
@@ -0,0 +1,76 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Guards which check an invariant of the data protected by a lock.
+//!
+//! For each field of a `#[lock_invariants]` struct with a `#[lock_invariant]`,
+//! the plugin generates methods which lock the field and wrap the guard in a
+//! `Checked`.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+
+/// A lock guard whose invariant is checked when it is created (i.e., when the
+/// lock is acquired) and when it is dropped (when the lock is released).
+pub struct Checked<G: Deref> {
+    guard: G,
+    check: fn(&G::Target) -> bool,
+    // e.g., `Account::balance (balance.total >= 0)`
+    description: &'static str,
+}
+
+impl<G: Deref> Checked<G> {
+    /// # Panics
+    ///
+    /// If the invariant does not hold.
+    pub fn new(guard: G, check: fn(&G::Target) -> bool, description: &'static str) -> Checked<G> {
+        if !check(&*guard) {
+            panic!("lock invariant acquiring {}", description);
+        }
+        Checked {
+            guard,
+            check,
+            description,
+        }
+    }
+}
+
+impl<G: Deref> Deref for Checked<G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &G::Target {
+        &self.guard
+    }
+}
+
+impl<G: DerefMut> DerefMut for Checked<G> {
+    fn deref_mut(&mut self) -> &mut G::Target {
+        &mut self.guard
+    }
+}
+
+impl<G: Deref> Drop for Checked<G> {
+    fn drop(&mut self) {
+        // Panicking while panicking would abort.
+        if !thread::panicking() && !(self.check)(&*self.guard) {
+            panic!("lock invariant releasing {}", self.description);
+        }
+    }
+}
+
+impl<G: Deref> fmt::Debug for Checked<G>
+where
+    G::Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.guard, f)
+    }
+}
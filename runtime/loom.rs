@@ -0,0 +1,71 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking invariants under concurrency with [loom](https://crates.io/crates/loom).
+//!
+//! The tests generated for `#[loom_model]` call `explore`, which requires the
+//! `loom` feature of this crate.
+
+use std::sync::Arc;
+
+use loom_crate::{model, thread};
+
+/// A method to explore and its name.
+pub type Op<T> = (&'static str, fn(&T));
+
+/// Explores the interleavings of every pair of `ops` (including an op with
+/// itself), each called on the same value from its own thread, and checks
+/// `invariant` after each op and once both have finished.
+///
+/// # Panics
+///
+/// If the invariant does not hold in some interleaving (or an op panics).
+pub fn explore<T>(
+    type_name: &'static str,
+    init: fn() -> T,
+    ops: &[Op<T>],
+    invariant: fn(&T) -> bool,
+) where
+    T: Send + Sync + 'static,
+{
+    for (i, &first) in ops.iter().enumerate() {
+        for &second in &ops[i..] {
+            model(move || {
+                let value = Arc::new(init());
+                let threads: Vec<_> = [(first, second.0), (second, first.0)]
+                    .iter()
+                    .map(|&((name, op), other)| {
+                        let value = value.clone();
+                        thread::spawn(move || {
+                            op(&value);
+                            assert!(
+                                invariant(&value),
+                                "invariant of {} violated after {} (concurrently with {})",
+                                type_name,
+                                name,
+                                other
+                            );
+                        })
+                    })
+                    .collect();
+                for thread in threads {
+                    thread.join().unwrap();
+                }
+                assert!(
+                    invariant(&value),
+                    "invariant of {} violated after {} and {}",
+                    type_name,
+                    first.0,
+                    second.0
+                );
+            });
+        }
+    }
+}
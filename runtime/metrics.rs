@@ -0,0 +1,139 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Counting contract checks and violations, for monitoring.
+//!
+//! When a crate is compiled with `--cfg hoare_metrics` (or its feature
+//! `hoare-metrics`), each contract check has a static `Metric`, and runs in
+//! `Metric::observe`, which counts the check, and the violation if the check
+//! panics (including when the panic is then caught by `on_violation = "log"`).
+//! A metric registers itself the first time it observes a check. `snapshot`
+//! and `prometheus_text` report the counts of all registered metrics. This
+//! module requires the `metrics` feature of this crate.
+
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref METRICS: Mutex<Vec<&'static Metric>> = Mutex::new(Vec::new());
+}
+
+/// The counters of one contract check, for a check of kind `kind` (e.g.,
+/// `precondition`) on the function `fn_path`. Created by code generated by the
+/// plugin, as a static with all counters zero (`const fn` is not stable on
+/// the nightly the plugin builds with, so there is no constructor).
+#[derive(Debug)]
+pub struct Metric {
+    pub fn_path: &'static str,
+    pub kind: &'static str,
+    #[doc(hidden)]
+    pub registered: AtomicBool,
+    #[doc(hidden)]
+    pub checks: AtomicUsize,
+    #[doc(hidden)]
+    pub violations: AtomicUsize,
+}
+
+impl Metric {
+    /// Runs `check`, counting it, and counting a violation if it panics.
+    pub fn observe<F: FnOnce()>(&'static self, check: F) {
+        if !self.registered.swap(true, Ordering::Relaxed) {
+            METRICS.lock().unwrap_or_else(|e| e.into_inner()).push(self);
+        }
+        self.checks.fetch_add(1, Ordering::Relaxed);
+        // Only dropped if check unwinds.
+        let violated = Violated(self);
+        check();
+        mem::forget(violated);
+    }
+}
+
+struct Violated(&'static Metric);
+
+impl Drop for Violated {
+    fn drop(&mut self) {
+        self.0.violations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The counts of the checks of one kind of contract on a function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractMetrics {
+    pub fn_path: &'static str,
+    /// `precondition`, `postcondition`, `invariant entering`, etc.
+    pub kind: &'static str,
+    pub checks: u64,
+    pub violations: u64,
+}
+
+/// The counts of all the contracts which have been checked, ordered by
+/// function and kind. The counts of several contracts of the same kind on a
+/// function are added up.
+pub fn snapshot() -> Vec<ContractMetrics> {
+    let mut counts: BTreeMap<(&'static str, &'static str), (u64, u64)> = BTreeMap::new();
+    for metric in METRICS.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        let count = counts.entry((metric.fn_path, metric.kind)).or_insert((0, 0));
+        count.0 += metric.checks.load(Ordering::Relaxed) as u64;
+        count.1 += metric.violations.load(Ordering::Relaxed) as u64;
+    }
+    counts
+        .into_iter()
+        .map(|((fn_path, kind), (checks, violations))| ContractMetrics {
+            fn_path,
+            kind,
+            checks,
+            violations,
+        })
+        .collect()
+}
+
+/// The counts of `snapshot` in the Prometheus text exposition format, as the
+/// counters `hoare_contract_checks_total` and
+/// `hoare_contract_violations_total`, labelled with the `function` and the
+/// `kind` of contract.
+pub fn prometheus_text() -> String {
+    let metrics = snapshot();
+    let mut text = String::new();
+    counter(&mut text, &metrics, "checks", "Number of times a contract was checked.", |m| {
+        m.checks
+    });
+    counter(&mut text, &metrics, "violations", "Number of times a contract was violated.", |m| {
+        m.violations
+    });
+    text
+}
+
+// Appends the counter `hoare_contract_<name>_total` of each of metrics to text.
+fn counter(
+    text: &mut String,
+    metrics: &[ContractMetrics],
+    name: &str,
+    help: &str,
+    count: fn(&ContractMetrics) -> u64,
+) {
+    text.push_str(&format!("# HELP hoare_contract_{}_total {}\n", name, help));
+    text.push_str(&format!("# TYPE hoare_contract_{}_total counter\n", name));
+    for metric in metrics {
+        text.push_str(&format!(
+            "hoare_contract_{}_total{{function=\"{}\",kind=\"{}\"}} {}\n",
+            name,
+            escape(metric.fn_path),
+            escape(metric.kind),
+            count(metric)
+        ));
+    }
+}
+
+// s as a label value.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
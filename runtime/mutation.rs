@@ -0,0 +1,103 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Specification mutation testing.
+//!
+//! When a crate is compiled with `--cfg hoare_mutants`, the plugin compiles a
+//! number of mutants of every contract into the crate and writes them to
+//! `<crate>.mutants` in the mutants directory (`<crate>.test.mutants` for the
+//! build of its tests). A mutant has the same id in every build of a crate. At
+//! most one mutant is active in a process, selected by the `HOARE_MUTANT`
+//! environment variable.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use files;
+
+/// The environment variable which selects the active mutant, given as
+/// `<crate>:<id>`.
+pub const ACTIVE_VAR: &str = "HOARE_MUTANT";
+
+/// The id of the active mutant in `krate`, if any. Called by code generated by
+/// the plugin.
+pub fn active(krate: &str) -> Option<usize> {
+    lazy_static! {
+        static ref ACTIVE: Option<(String, usize)> = env::var(ACTIVE_VAR).ok().and_then(|selector| {
+            let colon = selector.rfind(':')?;
+            let id = selector[colon + 1..].parse().ok()?;
+            Some((selector[..colon].to_owned(), id))
+        });
+    }
+
+    match *ACTIVE {
+        Some((ref active_krate, id)) if active_krate == krate => Some(id),
+        _ => None,
+    }
+}
+
+/// A mutant of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mutant {
+    pub krate: String,
+    pub id: usize,
+    /// The kind of check, e.g., `precondition of` or `invariant leaving`.
+    pub cond_type: String,
+    pub fn_path: String,
+    pub predicate: String,
+    pub mutant: String,
+}
+
+impl Mutant {
+    /// Reads all mutants from the manifests in `dir`. A mutant in the manifests
+    /// of more than one build of a crate is only returned once.
+    pub fn load_all<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Mutant>> {
+        let mut result = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("mutants") {
+                continue;
+            }
+            let krate = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(stem) if stem.ends_with(".test") => stem[..stem.len() - 5].to_owned(),
+                Some(stem) => stem.to_owned(),
+                None => continue,
+            };
+            for line in files::read_to_string(&path)?.lines() {
+                let fields: Vec<&str> = line.splitn(5, '\t').collect();
+                if fields.len() != 5 {
+                    continue;
+                }
+                let id = match fields[0].parse() {
+                    Ok(id) => id,
+                    Err(_) => continue,
+                };
+                result.push(Mutant {
+                    krate: krate.clone(),
+                    id,
+                    cond_type: fields[1].to_owned(),
+                    fn_path: fields[2].to_owned(),
+                    predicate: fields[3].to_owned(),
+                    mutant: fields[4].to_owned(),
+                });
+            }
+        }
+        result.sort_by(|a, b| (&a.krate, a.id).cmp(&(&b.krate, b.id)));
+        result.dedup_by(|a, b| (&a.krate, a.id) == (&b.krate, b.id));
+        Ok(result)
+    }
+
+    /// The value of `HOARE_MUTANT` which activates this mutant.
+    pub fn selector(&self) -> String {
+        format!("{}:{}", self.krate, self.id)
+    }
+}
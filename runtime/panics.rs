@@ -0,0 +1,71 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reporting violations of `#[no_panic]`, and panics in predicates.
+//!
+//! With the policy `predicate_panics="report"`, each check is passed to
+//! `report_predicate_panics`, so that a predicate which panics itself (e.g.,
+//! indexing out of bounds) is reported as a crash of the contract, rather than
+//! looking like a panic of the function it is on.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Called with the payload of a panic caught in a function with
+/// `#[no_panic]`.
+///
+/// # Panics
+///
+/// Always, with a message including that of the original panic.
+pub fn no_panic_violated(fn_name: &str, payload: Box<Any + Send>) -> ! {
+    panic!(
+        "panic-freedom contract violated in {}: {}",
+        fn_name,
+        message(&*payload)
+    );
+}
+
+/// Runs `check`, the check of the `contract` (e.g., `precondition of`) of
+/// `fn_name` with `predicate`.
+///
+/// # Panics
+///
+/// If the check fails, with its message. If anything else in the check panics,
+/// with `contract predicate crashed in <fn_name> (<predicate>): ` and the
+/// message of the original panic.
+pub fn report_predicate_panics<F: FnOnce()>(
+    contract: &str,
+    fn_name: &str,
+    predicate: &str,
+    check: F,
+) {
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(check)) {
+        // The messages of a failed check start with its contract and function.
+        let message = message(&*payload);
+        if message.starts_with(&format!("{} {} (", contract, fn_name)) {
+            panic::resume_unwind(payload);
+        }
+        panic!(
+            "contract predicate crashed in {} ({}): {}",
+            fn_name, predicate, message
+        );
+    }
+}
+
+/// The message of a panic, if it has one.
+pub fn message(payload: &(Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&'static str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "(no message)"
+    }
+}
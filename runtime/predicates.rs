@@ -0,0 +1,383 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Predicate shorthands.
+//!
+//! The plugin's `in_range!`, `non_empty!`, `is_sorted!`, `checked_at!`,
+//! `valid_index!`, `is_ascii!`, `is_char_boundary!`, `valid_utf8!`,
+//! `increased!`, `decreased!`, `increased_by!`, `decreased_by!`, `grew_by!`,
+//! and `shrank_by!` macros call the functions in this module. Each returns
+//! `Ok(())` if the predicate holds and otherwise an `Err` describing the value
+//! which violated it, which is used in the failure message of a contract.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Range, RangeFrom, RangeFull, RangeTo, Sub};
+use std::str;
+
+/// Checks `value` is in `range`, e.g., `in_range(&x, &(0..101))`.
+pub fn in_range<T, R>(value: &T, range: &R) -> Result<(), String>
+where
+    T: Debug,
+    R: Bounds<T> + Debug,
+{
+    if range.includes(value) {
+        Ok(())
+    } else {
+        Err(format!("{:?} is not in {:?}", value, range))
+    }
+}
+
+/// Ranges which `in_range` can check. `std::ops::RangeBounds` and inclusive
+/// ranges are unstable on the nightly the plugin builds with.
+pub trait Bounds<T> {
+    fn includes(&self, value: &T) -> bool;
+}
+
+impl<T: PartialOrd> Bounds<T> for Range<T> {
+    fn includes(&self, value: &T) -> bool {
+        self.start <= *value && *value < self.end
+    }
+}
+
+impl<T: PartialOrd> Bounds<T> for RangeFrom<T> {
+    fn includes(&self, value: &T) -> bool {
+        self.start <= *value
+    }
+}
+
+impl<T: PartialOrd> Bounds<T> for RangeTo<T> {
+    fn includes(&self, value: &T) -> bool {
+        *value < self.end
+    }
+}
+
+impl<T> Bounds<T> for RangeFull {
+    fn includes(&self, _value: &T) -> bool {
+        true
+    }
+}
+
+/// Checks `collection` has at least one element.
+pub fn non_empty<C: Collection + ?Sized>(collection: &C) -> Result<(), String> {
+    if collection.is_empty() {
+        Err("the collection is empty".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks the elements of `slice` are in non-decreasing order.
+pub fn is_sorted<S, T>(slice: &S) -> Result<(), String>
+where
+    S: AsRef<[T]> + ?Sized,
+    T: PartialOrd + Debug,
+{
+    let slice = slice.as_ref();
+    for i in 1..slice.len() {
+        match slice[i - 1].partial_cmp(&slice[i]) {
+            Some(Ordering::Less) | Some(Ordering::Equal) => {}
+            // Greater, or incomparable (e.g., NaN).
+            _ => {
+                return Err(format!(
+                    "not sorted at index {}: {:?} is not <= {:?}",
+                    i,
+                    slice[i - 1],
+                    slice[i]
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks `index` is in bounds for `collection`, e.g., `checked_at(&v, &i)`.
+pub fn checked_at<C: Indexed + ?Sized>(collection: &C, index: &usize) -> Result<(), String> {
+    let len = collection.len();
+    if *index < len {
+        Ok(())
+    } else {
+        Err(format!("index {} is out of bounds for a length of {}", index, len))
+    }
+}
+
+/// Checks `index` (a `usize`, or an `Option<usize>` which may be `None`) is in
+/// bounds for `collection`, e.g., `valid_index(&return, &self.items)` for a
+/// search which returns an index into `self.items`.
+pub fn valid_index<I, C>(index: &I, collection: &C) -> Result<(), String>
+where
+    I: MaybeIndex + ?Sized,
+    C: Indexed + ?Sized,
+{
+    match index.index() {
+        Some(index) => checked_at(collection, &index),
+        None => Ok(()),
+    }
+}
+
+/// Checks the bytes of `s` (e.g., a string or a byte slice) are all ASCII.
+pub fn is_ascii<S: AsRef<[u8]> + ?Sized>(s: &S) -> Result<(), String> {
+    match s.as_ref().iter().position(|b| !b.is_ascii()) {
+        Some(offset) => Err(format!(
+            "non-ASCII byte {:#04x} at offset {}",
+            s.as_ref()[offset],
+            offset
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks `offset` is on a char boundary of `s` (or is its length), so `s` can
+/// be sliced there.
+pub fn is_char_boundary<S: AsRef<str> + ?Sized>(s: &S, offset: &usize) -> Result<(), String> {
+    let s = s.as_ref();
+    if s.is_char_boundary(*offset) {
+        return Ok(());
+    }
+    if *offset > s.len() {
+        return Err(format!("offset {} is past the end, at {}", offset, s.len()));
+    }
+    // The start of the char the offset is in.
+    let start = (0..*offset).rev().find(|&i| s.is_char_boundary(i)).unwrap_or(0);
+    let c = s[start..].chars().next().unwrap();
+    Err(format!(
+        "offset {} is not a char boundary, it is inside {:?} at {}..{}",
+        offset,
+        c,
+        start,
+        start + c.len_utf8()
+    ))
+}
+
+/// Checks `bytes` are valid UTF-8.
+pub fn valid_utf8<B: AsRef<[u8]> + ?Sized>(bytes: &B) -> Result<(), String> {
+    match str::from_utf8(bytes.as_ref()) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let offset = e.valid_up_to();
+            Err(match e.error_len() {
+                Some(len) => format!(
+                    "invalid UTF-8 at offset {}: {:02x?}",
+                    offset,
+                    &bytes.as_ref()[offset..offset + len]
+                ),
+                None => format!("incomplete UTF-8 character at offset {}", offset),
+            })
+        }
+    }
+}
+
+/// Checks `new` is greater than `old`, e.g., `increased(&old(x), &x)`.
+pub fn increased<T: PartialOrd + Debug>(old: &T, new: &T) -> Result<(), String> {
+    if new > old {
+        Ok(())
+    } else {
+        Err(format!("did not increase: {:?} became {:?}", old, new))
+    }
+}
+
+/// Checks `new` is less than `old`.
+pub fn decreased<T: PartialOrd + Debug>(old: &T, new: &T) -> Result<(), String> {
+    if new < old {
+        Ok(())
+    } else {
+        Err(format!("did not decrease: {:?} became {:?}", old, new))
+    }
+}
+
+/// Checks `new` is `old + n`, e.g., `increased_by(&old(x), &x, &5)`.
+pub fn increased_by<T>(old: &T, new: &T, n: &T) -> Result<(), String>
+where
+    T: PartialOrd + Sub<Output = T> + Clone + Debug,
+{
+    changed_by(old, new, n, "an increase of", true)
+}
+
+/// Checks `new` is `old - n`.
+pub fn decreased_by<T>(old: &T, new: &T, n: &T) -> Result<(), String>
+where
+    T: PartialOrd + Sub<Output = T> + Clone + Debug,
+{
+    changed_by(old, new, n, "a decrease of", false)
+}
+
+/// Checks a length or size grew by `n`, e.g., `grew_by(&old(v.len()), &v.len(), &1)`.
+pub fn grew_by<T>(old: &T, new: &T, n: &T) -> Result<(), String>
+where
+    T: PartialOrd + Sub<Output = T> + Clone + Debug,
+{
+    changed_by(old, new, n, "to grow by", true)
+}
+
+/// Checks a length or size shrank by `n`.
+pub fn shrank_by<T>(old: &T, new: &T, n: &T) -> Result<(), String>
+where
+    T: PartialOrd + Sub<Output = T> + Clone + Debug,
+{
+    changed_by(old, new, n, "to shrink by", false)
+}
+
+// Checks new is old + n (or old - n if not increase). The difference is only
+// taken in the direction of the change, so unsigned values can't underflow.
+fn changed_by<T>(old: &T, new: &T, n: &T, expected: &str, increase: bool) -> Result<(), String>
+where
+    T: PartialOrd + Sub<Output = T> + Clone + Debug,
+{
+    let holds = if increase {
+        new >= old && new.clone() - old.clone() == *n
+    } else {
+        new <= old && old.clone() - new.clone() == *n
+    };
+    if holds {
+        Ok(())
+    } else {
+        Err(format!("expected {} {:?} from {:?}, but it became {:?}", expected, n, old, new))
+    }
+}
+
+/// Types which `non_empty` can check.
+pub trait Collection {
+    fn is_empty(&self) -> bool;
+}
+
+impl Collection for str {
+    fn is_empty(&self) -> bool {
+        str::is_empty(self)
+    }
+}
+
+impl Collection for String {
+    fn is_empty(&self) -> bool {
+        String::is_empty(self)
+    }
+}
+
+impl<T> Collection for [T] {
+    fn is_empty(&self) -> bool {
+        <[T]>::is_empty(self)
+    }
+}
+
+impl<T> Collection for Vec<T> {
+    fn is_empty(&self) -> bool {
+        Vec::is_empty(self)
+    }
+}
+
+impl<T> Collection for VecDeque<T> {
+    fn is_empty(&self) -> bool {
+        VecDeque::is_empty(self)
+    }
+}
+
+impl<K: Eq + Hash, V> Collection for HashMap<K, V> {
+    fn is_empty(&self) -> bool {
+        HashMap::is_empty(self)
+    }
+}
+
+impl<T: Eq + Hash> Collection for HashSet<T> {
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+impl<K, V> Collection for BTreeMap<K, V> {
+    fn is_empty(&self) -> bool {
+        BTreeMap::is_empty(self)
+    }
+}
+
+impl<T> Collection for BTreeSet<T> {
+    fn is_empty(&self) -> bool {
+        BTreeSet::is_empty(self)
+    }
+}
+
+/// Types which `checked_at` and `valid_index` can check indices into.
+pub trait Indexed: Collection {
+    fn len(&self) -> usize;
+}
+
+/// Indices are byte offsets.
+impl Indexed for str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+}
+
+/// Indices are byte offsets.
+impl Indexed for String {
+    fn len(&self) -> usize {
+        String::len(self)
+    }
+}
+
+impl<T> Indexed for [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+impl<T> Indexed for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+impl<T> Indexed for VecDeque<T> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+}
+
+// Arrays of up to 32 elements, like the standard library's impls (there are
+// no const generics).
+macro_rules! array_impls {
+    ($($n:expr)*) => {
+        $(
+            impl<T> Collection for [T; $n] {
+                fn is_empty(&self) -> bool {
+                    $n == 0
+                }
+            }
+
+            impl<T> Indexed for [T; $n] {
+                fn len(&self) -> usize {
+                    $n
+                }
+            }
+        )*
+    };
+}
+
+array_impls! {
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+}
+
+/// Values which `valid_index` can check: an index, or maybe one.
+pub trait MaybeIndex {
+    fn index(&self) -> Option<usize>;
+}
+
+impl MaybeIndex for usize {
+    fn index(&self) -> Option<usize> {
+        Some(*self)
+    }
+}
+
+impl MaybeIndex for Option<usize> {
+    fn index(&self) -> Option<usize> {
+        *self
+    }
+}
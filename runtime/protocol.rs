@@ -0,0 +1,108 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The state of a type with a `#[protocol]`.
+//!
+//! The plugin adds a `State` field to the struct. In debug builds, each method
+//! named in the protocol checks that it is called in one of the states it
+//! allows and then moves to its target state. States are numbered in the order
+//! they appear in the protocol, starting with the initial state, so
+//! `State::default()` is the initial state.
+
+use std::cmp::Ordering as CmpOrdering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The current state of a protocol. It does not affect comparison or hashing
+/// of the value it is part of.
+#[derive(Default)]
+pub struct State(AtomicUsize);
+
+impl State {
+    pub fn new(state: usize) -> State {
+        State(AtomicUsize::new(state))
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, state: usize) {
+        self.0.store(state, Ordering::SeqCst);
+    }
+
+    /// Checks that the current state is one of `allowed`. Called by code
+    /// generated by the plugin; `states` are the names of all the states.
+    ///
+    /// # Panics
+    ///
+    /// If the current state is not allowed.
+    pub fn check(&self, states: &[&str], allowed: &[usize], type_name: &str, method: &str) {
+        let current = self.get();
+        if allowed.contains(&current) {
+            return;
+        }
+
+        let name = |state: usize| states.get(state).cloned().unwrap_or("?");
+        let expected: Vec<&str> = allowed.iter().map(|&state| name(state)).collect();
+        if expected.is_empty() {
+            panic!(
+                "protocol of {} violated: {} called in state {}, it can't be called in any state",
+                type_name,
+                method,
+                name(current)
+            );
+        }
+        panic!(
+            "protocol of {} violated: {} called in state {}, expected {}",
+            type_name,
+            method,
+            name(current),
+            expected.join(" or ")
+        );
+    }
+}
+
+impl Clone for State {
+    fn clone(&self) -> State {
+        State::new(self.get())
+    }
+}
+
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "State({})", self.get())
+    }
+}
+
+impl PartialEq for State {
+    fn eq(&self, _other: &State) -> bool {
+        true
+    }
+}
+
+impl Eq for State {}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &State) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for State {
+    fn cmp(&self, _other: &State) -> CmpOrdering {
+        CmpOrdering::Equal
+    }
+}
+
+impl Hash for State {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
@@ -0,0 +1,141 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Detecting concurrent modification of fields.
+//!
+//! `#[race_checks]` on a struct wraps each field marked `#[race_check]` in a
+//! `Canary`, which counts modifications made through `Canary::write`. Methods
+//! with `#[race_free]` take a `Snapshot` of each canary on entry and check on
+//! exit that every modification during the call was made by the calling
+//! thread.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+thread_local! {
+    // The number of writes made by this thread to each canary, by address.
+    static OWN_WRITES: RefCell<HashMap<usize, usize>> = RefCell::new(HashMap::new());
+}
+
+/// A value whose modifications through shared references are counted.
+///
+/// Reads go through `Deref`. Modifications through `&self` (e.g., of an
+/// atomic or a value behind a lock) should go through `write`, so they are
+/// counted. Modifications through `&mut self` can not race, so `DerefMut` does
+/// not count them.
+pub struct Canary<T> {
+    value: T,
+    writes: AtomicUsize,
+}
+
+/// The number of writes to a canary, in total and by the current thread, when
+/// the snapshot was taken.
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    writes: usize,
+    own_writes: usize,
+}
+
+impl<T> Canary<T> {
+    pub fn new(value: T) -> Canary<T> {
+        Canary {
+            value,
+            writes: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Records a modification by the current thread and returns the value to
+    /// modify.
+    pub fn write(&self) -> &T {
+        OWN_WRITES.with(|own| *own.borrow_mut().entry(self.address()).or_insert(0) += 1);
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        &self.value
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            writes: self.writes.load(Ordering::SeqCst),
+            own_writes: self.own_writes(),
+        }
+    }
+
+    /// Checks that every write since `snapshot` was by the current thread.
+    ///
+    /// # Panics
+    ///
+    /// If another thread wrote to the canary, with a message naming the field
+    /// (`description`) and the method which was running (`method`).
+    pub fn check(&self, snapshot: Snapshot, description: &str, method: &str) {
+        let writes = self.writes.load(Ordering::SeqCst) - snapshot.writes;
+        let own_writes = self.own_writes() - snapshot.own_writes;
+        if writes != own_writes {
+            panic!(
+                "race check of {} failed: modified by another thread during {} ({} of {} writes)",
+                description,
+                method,
+                writes - own_writes,
+                writes
+            );
+        }
+    }
+
+    fn own_writes(&self) -> usize {
+        OWN_WRITES.with(|own| own.borrow().get(&self.address()).cloned().unwrap_or(0))
+    }
+
+    fn address(&self) -> usize {
+        self as *const Canary<T> as usize
+    }
+}
+
+impl<T> From<T> for Canary<T> {
+    fn from(value: T) -> Canary<T> {
+        Canary::new(value)
+    }
+}
+
+impl<T: Default> Default for Canary<T> {
+    fn default() -> Canary<T> {
+        Canary::new(T::default())
+    }
+}
+
+impl<T: Clone> Clone for Canary<T> {
+    fn clone(&self) -> Canary<T> {
+        Canary::new(self.value.clone())
+    }
+}
+
+impl<T> Deref for Canary<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Canary<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Canary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
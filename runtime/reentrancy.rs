@@ -0,0 +1,64 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking `#[non_reentrant]`.
+//!
+//! Each thread keeps track of the functions with `#[non_reentrant]` it is
+//! executing (and, for `#[non_reentrant(per_object)]`, the address of the
+//! object each was called on).
+
+use std::cell::RefCell;
+
+thread_local! {
+    static EXECUTING: RefCell<Vec<(&'static str, usize)>> = RefCell::new(Vec::new());
+}
+
+/// Records that the current thread is executing the function identified by
+/// `key` (on the object at `address`, or `0` for any object) until the result
+/// is dropped.
+///
+/// # Panics
+///
+/// If the current thread is already executing the function (on that object).
+pub fn enter(key: &'static str, address: usize, fn_name: &str) -> Executing {
+    EXECUTING.with(|executing| {
+        let mut executing = executing.borrow_mut();
+        if executing.contains(&(key, address)) {
+            // Don't hold the borrow while panicking.
+            drop(executing);
+            panic!(
+                "non_reentrant of {} violated: entered again while already executing{}",
+                fn_name,
+                if address == 0 { "" } else { " on the same object" }
+            );
+        }
+        executing.push((key, address));
+    });
+    Executing { key, address }
+}
+
+/// Marks a function as executing until dropped, see `enter`.
+#[must_use]
+#[derive(Debug)]
+pub struct Executing {
+    key: &'static str,
+    address: usize,
+}
+
+impl Drop for Executing {
+    fn drop(&mut self) {
+        EXECUTING.with(|executing| {
+            let mut executing = executing.borrow_mut();
+            if let Some(i) = executing.iter().rposition(|&e| e == (self.key, self.address)) {
+                executing.remove(i);
+            }
+        });
+    }
+}
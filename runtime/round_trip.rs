@@ -0,0 +1,130 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking conversions round-trip.
+//!
+//! Conversions with `#[round_trip]` call these functions with their input and
+//! output. The check converts the output back, which may call another
+//! conversion with `#[round_trip]`; checks are not made while a check is
+//! running, so this does not recurse.
+
+use std::cell::Cell;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+thread_local! {
+    static CHECKING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Checks that converting `output` (the result of converting `input` with
+/// `From`) back gives `input`.
+///
+/// # Panics
+///
+/// If the output converts back to a different value.
+pub fn check_from<A, B>(input: &A, output: &B, conversion: &str)
+where
+    A: PartialEq + Debug,
+    B: Clone + Debug + Into<A>,
+{
+    checking(|| {
+        let back: A = output.clone().into();
+        if back != *input {
+            violated(conversion, input, output, &format!("{:?}", back));
+        }
+    });
+}
+
+/// Checks that converting `output` (the result of converting `input` with
+/// `TryFrom`) back with `try_into` gives `input`, if the conversion succeeded.
+/// `TryInto` is unstable on the nightly the plugin builds with, so the
+/// generated code passes `TryInto::try_into` from the crate being checked.
+///
+/// # Panics
+///
+/// If the output can not be converted back or converts back to a different
+/// value.
+pub fn check_try_from<A, B, E, R, F>(
+    input: &A,
+    output: &Result<B, E>,
+    conversion: &str,
+    try_into: F,
+) where
+    A: PartialEq + Debug,
+    B: Clone + Debug,
+    F: FnOnce(B) -> Result<A, R>,
+{
+    if let Ok(ref output) = *output {
+        checking(|| match try_into(output.clone()) {
+            Ok(back) => {
+                if back != *input {
+                    violated(conversion, input, output, &format!("{:?}", back));
+                }
+            }
+            Err(_) => violated(conversion, input, output, "an error"),
+        });
+    }
+}
+
+/// Checks that parsing the `Display` of `output` (the result of parsing
+/// `input`) gives `output`, if parsing succeeded. The `Display` need not be the
+/// same as the input, e.g., it can normalise whitespace.
+///
+/// # Panics
+///
+/// If the output's `Display` can not be parsed or parses to a different value.
+pub fn check_from_str<T, E>(input: &str, output: &Result<T, E>, conversion: &str)
+where
+    T: Display + Debug + FromStr + PartialEq,
+{
+    if let Ok(ref output) = *output {
+        checking(|| {
+            let displayed = output.to_string();
+            match displayed.parse::<T>() {
+                Ok(back) => {
+                    if back != *output {
+                        panic!(
+                            "round trip of {} violated: {:?} parsed to {:?}, displayed as {:?}, which \
+                             parses to {:?}",
+                            conversion, input, output, displayed, back
+                        );
+                    }
+                }
+                Err(_) => panic!(
+                    "round trip of {} violated: {:?} parsed to {:?}, displayed as {:?}, which does \
+                     not parse",
+                    conversion, input, output, displayed
+                ),
+            }
+        });
+    }
+}
+
+// Runs check unless a check is already running on this thread.
+fn checking<F: FnOnce()>(check: F) {
+    if CHECKING.with(|checking| checking.replace(true)) {
+        return;
+    }
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CHECKING.with(|checking| checking.set(false));
+        }
+    }
+    let _reset = Reset;
+    check();
+}
+
+fn violated<A: Debug, B: Debug>(conversion: &str, input: &A, output: &B, back: &str) -> ! {
+    panic!(
+        "round trip of {} violated: {:?} converted to {:?}, which converts back to {}",
+        conversion, input, output, back
+    );
+}
@@ -0,0 +1,117 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Sampling the clauses of postconditions.
+//!
+//! With the policy `sample="k"`, a postcondition with more than `k` clauses
+//! checks each clause only with some probability (computed by the plugin from
+//! `k` and the clause's weight), so that about `k` are checked per call. The
+//! generated code asks `selected` whether to check a clause. Each thread has
+//! its own generator, so this is cheap and needs no locking.
+//!
+//! The generators are seeded from one number, `seed`, which is random unless
+//! `HOARE_SAMPLE_SEED` is set, mixed with the name of the thread (which, for
+//! tests, is the name of the test). A failed sampled clause calls
+//! `report_seed`, which prints the seed, so that rerunning with it set checks
+//! the same clauses on each call of the same thread.
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+
+/// If set (to anything), every clause is checked, as if there was no `sample`.
+pub const ALL_VAR: &str = "HOARE_SAMPLE_ALL";
+
+/// If set (to a number), the seed of the generators, otherwise it is random.
+pub const SEED_VAR: &str = "HOARE_SAMPLE_SEED";
+
+thread_local! {
+    static STATE: Cell<u64> = Cell::new(thread_seed());
+}
+
+/// Whether to check a clause which is checked with `probability`.
+pub fn selected(probability: f64) -> bool {
+    if probability >= 1.0 || check_all() {
+        return true;
+    }
+    // The top 53 bits, as a number in [0, 1).
+    let x = (next() >> 11) as f64 / (1u64 << 53) as f64;
+    x < probability
+}
+
+fn check_all() -> bool {
+    lazy_static! {
+        static ref ALL: bool = env::var_os(ALL_VAR).is_some();
+    }
+    *ALL
+}
+
+// xorshift64*.
+fn next() -> u64 {
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    })
+}
+
+/// The seed of the generators of all threads.
+///
+/// # Panics
+///
+/// If `HOARE_SAMPLE_SEED` is set, but not to a number.
+pub fn seed() -> u64 {
+    lazy_static! {
+        static ref SEED: u64 = match env::var(SEED_VAR) {
+            Ok(seed) => seed
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("expected {} to be a number, not `{}`", SEED_VAR, seed)),
+            // Each `RandomState` has random keys.
+            Err(_) => RandomState::new().build_hasher().finish(),
+        };
+    }
+    *SEED
+}
+
+/// Prints the seed, to reproduce the clauses which were checked. Called before
+/// a sampled clause panics.
+pub fn report_seed() {
+    eprintln!(
+        "note: the clauses checked were sampled, rerun with {}={} to check the same ones",
+        SEED_VAR,
+        seed()
+    );
+}
+
+// The seed of this thread's generator, from `seed` and the thread's name.
+fn thread_seed() -> u64 {
+    // FNV-1a.
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for byte in thread::current().name().unwrap_or("").bytes() {
+        hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+    }
+    // The finalizer of splitmix64, so that similar names give unrelated seeds.
+    let mut x = seed() ^ hash;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    // The state must not be zero.
+    if x == 0 {
+        1
+    } else {
+        x
+    }
+}
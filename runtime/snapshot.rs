@@ -0,0 +1,150 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Snapshot tests of the code generated by the plugin.
+//!
+//! When compiling with `HOARE_DUMP_EXPANSION=<dir>`, the plugin writes each
+//! contracted function, after expansion, to `<dir>/<fn path>.rs`. `check`
+//! compares such a directory with a directory of reviewed snapshots.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use diff;
+use files;
+
+/// If this environment variable is set, `check` writes the snapshots rather
+/// than comparing against them.
+pub const BLESS_VAR: &str = "HOARE_BLESS";
+
+/// A snapshot which differs from the dumped expansion.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub name: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// Compares every file in `actual` with the file of the same name in
+/// `expected`.
+pub fn compare<P: AsRef<Path>, Q: AsRef<Path>>(actual: P, expected: Q) -> io::Result<Vec<Mismatch>> {
+    let (actual, expected) = (actual.as_ref(), expected.as_ref());
+    let mut names = file_names(actual)?;
+    names.extend(file_names(expected)?);
+
+    let mut result = Vec::new();
+    for name in names {
+        let actual = read(&actual.join(&name))?;
+        let expected = read(&expected.join(&name))?;
+        if actual != expected {
+            result.push(Mismatch {
+                name,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Checks the dumped expansions in `actual` against the snapshots in
+/// `expected`. If `HOARE_BLESS` is set, the snapshots are written, updated, or
+/// removed to match instead.
+///
+/// # Panics
+///
+/// If a snapshot is missing or differs from the dumped expansion (and
+/// `HOARE_BLESS` is not set), or if either directory can't be read.
+pub fn check<P: AsRef<Path>, Q: AsRef<Path>>(actual: P, expected: Q) {
+    let (actual, expected) = (actual.as_ref(), expected.as_ref());
+    let mismatches = compare(actual, expected)
+        .unwrap_or_else(|e| panic!("could not compare snapshots: {}", e));
+    let bless = env::var_os(BLESS_VAR).is_some();
+
+    let mut failed = Vec::new();
+    for mismatch in mismatches {
+        if !bless {
+            failed.push(mismatch);
+            continue;
+        }
+
+        let path = expected.join(&mismatch.name);
+        let updated = match mismatch.actual {
+            Some(ref actual) => {
+                fs::create_dir_all(expected).and_then(|_| files::write(&path, actual))
+            }
+            None => fs::remove_file(&path),
+        };
+        match updated {
+            Ok(()) => eprintln!("updated snapshot {}", path.display()),
+            Err(e) => panic!("could not update snapshot {}: {}", path.display(), e),
+        }
+    }
+
+    if !failed.is_empty() {
+        let mut message = String::new();
+        for mismatch in &failed {
+            message.push_str(&mismatch.to_string());
+        }
+        panic!(
+            "{} snapshot(s) differ, set {} to update them:\n{}",
+            failed.len(),
+            BLESS_VAR,
+            message
+        );
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "--- expected/{}", self.name)?;
+        writeln!(f, "+++ actual/{}", self.name)?;
+        for (tag, line) in diff::line_diff(&lines(&self.expected), &lines(&self.actual)) {
+            writeln!(f, "{}{}", tag, line)?;
+        }
+        Ok(())
+    }
+}
+
+fn lines(contents: &Option<String>) -> Vec<&str> {
+    match *contents {
+        Some(ref contents) => contents.lines().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn file_names(dir: &Path) -> io::Result<BTreeSet<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut result = BTreeSet::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            result.insert(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(result)
+}
+
+fn read(path: &Path) -> io::Result<Option<String>> {
+    match files::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Specification coverage.
+//!
+//! When a crate is compiled with `--cfg hoare_spec_coverage`, the plugin
+//! writes a manifest (`<crate>.specs`) to the specification coverage directory
+//! listing the crate's public functions and structs, and whether each has a
+//! contract (for functions) or an invariant (for structs). `SpecCoverage`
+//! reads these and summarizes them per module, as Markdown, JSON, or a badge.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json;
+
+use files;
+
+/// The public functions and structs of a module, each with whether it is
+/// specified.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Module {
+    /// Functions, and methods as `Type::method`, with whether they have a
+    /// contract.
+    pub functions: BTreeMap<String, bool>,
+    /// Structs, with whether they have an invariant.
+    pub types: BTreeMap<String, bool>,
+}
+
+/// `(specified, total)` of the items in `items`.
+pub fn fraction(items: &BTreeMap<String, bool>) -> (usize, usize) {
+    (items.values().filter(|specified| **specified).count(), items.len())
+}
+
+/// The specification coverage in a directory, by module path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpecCoverage {
+    pub modules: BTreeMap<String, Module>,
+}
+
+impl SpecCoverage {
+    /// Reads all specification coverage manifests in `dir`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<SpecCoverage> {
+        let mut coverage = SpecCoverage::default();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("specs") {
+                continue;
+            }
+            for line in files::read_to_string(&path)?.lines() {
+                coverage.add(line);
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Adds a line of a manifest, ignoring malformed lines.
+    pub fn add(&mut self, line: &str) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4 || !["fn", "type"].contains(&fields[0]) {
+            return;
+        }
+        let specified = match fields[3] {
+            "0" => false,
+            "1" => true,
+            _ => return,
+        };
+        let module = self.modules.entry(fields[1].to_owned()).or_insert_with(Module::default);
+        let items = if fields[0] == "fn" { &mut module.functions } else { &mut module.types };
+        items.insert(fields[2].to_owned(), specified);
+    }
+
+    /// `(specified, total)` of the functions of all modules.
+    pub fn functions(&self) -> (usize, usize) {
+        self.total(|module| fraction(&module.functions))
+    }
+
+    /// `(specified, total)` of the types of all modules.
+    pub fn types(&self) -> (usize, usize) {
+        self.total(|module| fraction(&module.types))
+    }
+
+    fn total<F: Fn(&Module) -> (usize, usize)>(&self, f: F) -> (usize, usize) {
+        self.modules.values().map(f).fold((0, 0), |(s, t), (ds, dt)| (s + ds, t + dt))
+    }
+
+    /// A Markdown table with a row for each module and one for the total.
+    pub fn to_markdown(&self) -> String {
+        let mut md = "| Module | Functions with contracts | Types with invariants |\n\
+                      |---|---|---|\n"
+            .to_owned();
+        for (path, module) in &self.modules {
+            md += &format!(
+                "| `{}` | {} | {} |\n",
+                path,
+                cell(fraction(&module.functions)),
+                cell(fraction(&module.types))
+            );
+        }
+        md + &format!("| **Total** | {} | {} |\n", cell(self.functions()), cell(self.types()))
+    }
+
+    /// The coverage as JSON, with a `modules` array and the `total`. Each
+    /// module lists its unspecified functions and types.
+    pub fn to_json(&self) -> String {
+        let unspecified = |items: &BTreeMap<String, bool>| -> Vec<String> {
+            items.iter().filter(|item| !item.1).map(|item| item.0.clone()).collect()
+        };
+        let modules: Vec<_> = self
+            .modules
+            .iter()
+            .map(|(path, module)| {
+                json!({
+                    "path": path,
+                    "functions": json_fraction(fraction(&module.functions)),
+                    "types": json_fraction(fraction(&module.types)),
+                    "unspecified_functions": unspecified(&module.functions),
+                    "unspecified_types": unspecified(&module.types),
+                })
+            })
+            .collect();
+        json!({
+            "modules": modules,
+            "total": {
+                "functions": json_fraction(self.functions()),
+                "types": json_fraction(self.types()),
+            },
+        })
+        .to_string()
+    }
+
+    /// A shields.io endpoint badge showing the percentage of functions with
+    /// contracts: green from 80%, yellow from 50%, and red below.
+    pub fn to_badge(&self) -> String {
+        let (message, color) = match percent(self.functions()) {
+            Some(p) if p >= 80 => (format!("{}%", p), "green"),
+            Some(p) if p >= 50 => (format!("{}%", p), "yellow"),
+            Some(p) => (format!("{}%", p), "red"),
+            None => ("none".to_owned(), "lightgrey"),
+        };
+        json!({
+            "schemaVersion": 1,
+            "label": "contracts",
+            "message": message,
+            "color": color,
+        })
+        .to_string()
+    }
+}
+
+// The percentage, rounded down, or None if there are no items.
+fn percent((specified, total): (usize, usize)) -> Option<usize> {
+    (specified * 100).checked_div(total)
+}
+
+// A fraction in a Markdown table, e.g., `3/4 (75%)`.
+fn cell(fraction: (usize, usize)) -> String {
+    match percent(fraction) {
+        Some(p) => format!("{}/{} ({}%)", fraction.0, fraction.1, p),
+        None => "-".to_owned(),
+    }
+}
+
+fn json_fraction((specified, total): (usize, usize)) -> serde_json::Value {
+    json!({ "specified": specified, "total": total })
+}
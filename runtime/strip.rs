@@ -0,0 +1,96 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checking that compiling with `--cfg hoare_strip`, or with contracts which
+//! are disabled in the build, leaves no contract code.
+//!
+//! Generated checks use the runtime crate, names starting with `__hoare` (or
+//! `__result`, for the returned value), and failure messages such as
+//! `precondition of f`, so finding none of these in the expanded code (dumped
+//! with `HOARE_DUMP_EXPANSION`) or in the compiled binary shows the contracts
+//! were removed.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use files;
+
+/// The strings which only appear where there is contract code.
+pub const MARKERS: &[&str] = &[
+    "libhoare",
+    "__hoare",
+    "__result",
+    "precondition of",
+    "postcondition of",
+    "invariant entering",
+    "invariant leaving",
+];
+
+/// Returns the markers which appear in `bytes`.
+pub fn residue(bytes: &[u8]) -> Vec<&'static str> {
+    MARKERS
+        .iter()
+        .cloned()
+        .filter(|marker| bytes.windows(marker.len()).any(|window| window == marker.as_bytes()))
+        .collect()
+}
+
+/// Checks the compiled binary (or library) at `path` contains no contract
+/// code.
+///
+/// # Panics
+///
+/// If any marker appears in the file, or it can not be read.
+pub fn check_binary<P: AsRef<Path>>(path: P) {
+    let path = path.as_ref();
+    let bytes =
+        files::read(path).unwrap_or_else(|e| panic!("could not read {}: {}", path.display(), e));
+    let found = residue(&bytes);
+    if !found.is_empty() {
+        panic!("contract code left in {}: found {:?}", path.display(), found);
+    }
+}
+
+/// Checks the expanded code dumped to `dir` (with `HOARE_DUMP_EXPANSION`)
+/// contains no contract code.
+///
+/// # Panics
+///
+/// If any marker appears in a dumped file, if no files were dumped (then the
+/// check would show nothing), or if the files can not be read.
+pub fn check_expansion<P: AsRef<Path>>(dir: P) {
+    let dir = dir.as_ref();
+    let (files, found) = expansion_residue(dir)
+        .unwrap_or_else(|e| panic!("could not read {}: {}", dir.display(), e));
+    if files == 0 {
+        panic!("no expanded code in {}", dir.display());
+    }
+    if !found.is_empty() {
+        panic!("contract code left in the expansion of:\n{}", found.join("\n"));
+    }
+}
+
+// Returns the number of files in dir and, for each which contains markers, its
+// name and those markers.
+fn expansion_residue(dir: &Path) -> io::Result<(usize, Vec<String>)> {
+    let mut files = 0;
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        files += 1;
+        let markers = residue(&files::read(entry.path())?);
+        if !markers.is_empty() {
+            found.push(format!("{}: {:?}", entry.file_name().to_string_lossy(), markers));
+        }
+    }
+    found.sort();
+    Ok((files, found))
+}
@@ -0,0 +1,37 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Disabling tagged contracts at runtime.
+//!
+//! A contract with `tag="name"` only checks its predicate if `enabled("name")`.
+
+use std::env;
+
+/// A comma-separated list of the tags of contracts which should not be
+/// checked, e.g., `expensive,slow`.
+pub const DISABLE_VAR: &str = "HOARE_DISABLE_TAGS";
+
+/// Whether contracts with `tag` should be checked, i.e., it is not listed in
+/// `HOARE_DISABLE_TAGS` (which is read once, on the first call).
+pub fn enabled(tag: &str) -> bool {
+    !disabled().iter().any(|disabled| disabled == tag)
+}
+
+fn disabled() -> &'static [String] {
+    lazy_static! {
+        static ref DISABLED: Vec<String> = env::var(DISABLE_VAR)
+            .unwrap_or_default()
+            .split(',')
+            .map(|tag| tag.trim().to_owned())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+    }
+    &DISABLED
+}
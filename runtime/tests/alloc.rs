@@ -0,0 +1,45 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "counting-alloc")]
+
+extern crate libhoare;
+
+use std::panic;
+
+use libhoare::alloc::{allocations, check_no_alloc, CountingAllocator};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn sum(xs: &[u64]) -> u64 {
+    xs.iter().sum()
+}
+
+#[test]
+fn test_no_alloc() {
+    let xs = vec![1, 2, 3];
+    let before = allocations();
+    assert_eq!(sum(&xs), 6);
+    check_no_alloc(before, "sum");
+}
+
+#[test]
+fn test_alloc() {
+    let before = allocations();
+    let xs = vec![1u64, 2, 3];
+    let mut ys = xs.clone();
+    ys.push(4);
+    let err = panic::catch_unwind(|| check_no_alloc(before, "sum")).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<String>().unwrap(),
+        "no_alloc of sum violated: 3 allocations"
+    );
+}
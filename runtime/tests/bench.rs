@@ -0,0 +1,71 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::fs;
+
+use libhoare::bench::Report;
+
+mod common;
+
+fn benchmark(group: &str, function: &str) -> String {
+    format!(
+        r#"{{"group_id":"{}","function_id":"{}","value_str":null}}"#,
+        group,
+        function.replace('"', "\\\"")
+    )
+}
+
+fn estimates(mean: f64) -> String {
+    format!(r#"{{"mean":{{"point_estimate":{}}},"median":{{"point_estimate":0.0}}}}"#, mean)
+}
+
+#[test]
+fn test_overheads() {
+    let mut report = Report::default();
+    report.add(&benchmark("hoare/my_crate::foo", "unchecked"), &estimates(10.0));
+    report.add(&benchmark("hoare/my_crate::foo", "#[precond = \"x > 0\"]"), &estimates(11.0));
+    report.add(&benchmark("hoare/my_crate::foo", "checked"), &estimates(15.0));
+    report.add(&benchmark("other", "checked"), &estimates(1.0));
+    report.add("not json", &estimates(1.0));
+
+    assert_eq!(report.functions.len(), 1);
+    let overheads = report.overheads("my_crate::foo");
+    assert_eq!(overheads.len(), 2);
+    assert_eq!(overheads[0].variant, "checked");
+    assert_eq!(overheads[0].overhead_ns, 5.0);
+    assert_eq!(overheads[0].overhead_percent, 50.0);
+    assert_eq!(overheads[1].variant, "#[precond = \"x > 0\"]");
+    assert_eq!(overheads[1].overhead_ns, 1.0);
+    assert!(report.overheads("my_crate::bar").is_empty());
+
+    let text = report.to_string();
+    assert!(text.starts_with("my_crate::foo: 10.0 ns without contracts\n"));
+    assert!(text.contains("+5.0 ns   +50.0%  checked\n"));
+}
+
+#[test]
+fn test_load() {
+    let dir = common::temp_dir("bench-test");
+    let new = dir.join("hoare_foo").join("unchecked").join("new");
+    fs::create_dir_all(&new).unwrap();
+    common::write(new.join("benchmark.json"), benchmark("hoare/foo", "unchecked"));
+    common::write(new.join("estimates.json"), estimates(2.0));
+    // Criterion's previous results are not used.
+    let base = dir.join("hoare_foo").join("unchecked").join("base");
+    fs::create_dir_all(&base).unwrap();
+    common::write(base.join("benchmark.json"), benchmark("hoare/foo", "unchecked"));
+    common::write(base.join("estimates.json"), estimates(3.0));
+
+    let report = Report::load(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(report.functions["foo"]["unchecked"], 2.0);
+}
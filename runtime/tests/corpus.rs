@@ -0,0 +1,52 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::fs;
+
+use libhoare::corpus;
+
+mod common;
+
+#[test]
+fn test_capture_and_replay() {
+    let dir = common::temp_dir("corpus-test");
+    let dir_str = dir.to_string_lossy().into_owned();
+
+    let x = 5;
+    let s = "foo".to_owned();
+    let args = corpus::serialize(&(&x, &s));
+    corpus::capture(&dir_str, "krate::foo", &args);
+    // Capturing the same input twice doesn't duplicate it.
+    corpus::capture(&dir_str, "krate::foo", &args);
+    corpus::capture(&dir_str, "krate::foo", &corpus::serialize(&(&-1, &String::new())));
+
+    let mut inputs: Vec<(i32, String)> = corpus::inputs(&dir, "krate::foo");
+    inputs.sort();
+    assert_eq!(inputs, vec![(-1, String::new()), (5, "foo".to_owned())]);
+    assert!(corpus::inputs::<(i32, String), _>(&dir, "krate::bar").is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_deserialize() {
+    let args = corpus::serialize(&(&3, &"foo", &vec![1, 2])).unwrap();
+    let (x, s, xs): (i32, String, Vec<i32>) = corpus::deserialize(&args);
+    assert_eq!((x, s.as_str(), xs), (3, "foo", vec![1, 2]));
+    let () = corpus::deserialize(&corpus::serialize(&()).unwrap());
+}
+
+#[test]
+#[should_panic(expected = "could not deserialize the arguments [1]")]
+fn test_deserialize_wrong_arguments() {
+    let _: (String,) = corpus::deserialize("[1]");
+}
@@ -0,0 +1,71 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::fs;
+
+use libhoare::coverage::{self, Report};
+
+mod common;
+
+#[test]
+fn test_report() {
+    let dir = common::temp_dir("coverage-test");
+    common::write(
+        dir.join("krate.contracts"),
+        "precondition of\tkrate::foo\t1\tx > 0\n\
+         postcondition of\tkrate::foo\t2\ta || b\n\
+         precondition of\tkrate::bar\t1\ttrue\n",
+    );
+
+    let dir_str = common::leak(dir.to_string_lossy().into_owned());
+    coverage::record(dir_str, "precondition of", "krate::foo", "x > 0", Some(0));
+    coverage::record(dir_str, "precondition of", "krate::foo", "x > 0", Some(0));
+    coverage::record(dir_str, "postcondition of", "krate::foo", "a || b", Some(1));
+    coverage::record(dir_str, "postcondition of", "krate::stale", "true", Some(0));
+
+    let report = Report::load(&dir).unwrap();
+    assert_eq!(report.contracts.len(), 3);
+    let unevaluated = report.unevaluated();
+    assert_eq!(unevaluated.len(), 1);
+    assert_eq!(unevaluated[0].fn_path, "krate::bar");
+
+    let text = report.to_string();
+    assert!(text.starts_with("contract coverage: 2 of 3 contracts evaluated\n"));
+    assert!(text.contains("covered  precondition of krate::foo (x > 0)\n"));
+    assert!(text.contains("partial  postcondition of krate::foo (a || b) [1 of 2 branches]\n"));
+    assert!(text.contains("never    precondition of krate::bar (true)\n"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_builds() {
+    let dir = common::temp_dir("coverage-builds");
+    // The library, and its test harness, which also has the tests' contracts.
+    common::write(dir.join("krate.contracts"), "precondition of\tkrate::foo\t1\tx > 0\n");
+    common::write(
+        dir.join("krate.test.contracts"),
+        "precondition of\tkrate::foo\t1\tx > 0\n\
+         precondition of\tkrate::tests::bar\t1\ty > 0\n",
+    );
+
+    let dir_str = common::leak(dir.to_string_lossy().into_owned());
+    coverage::record(dir_str, "precondition of", "krate::tests::bar", "y > 0", Some(0));
+
+    let report = Report::load(&dir).unwrap();
+    assert_eq!(report.contracts.len(), 2);
+    let unevaluated = report.unevaluated();
+    assert_eq!(unevaluated.len(), 1);
+    assert_eq!(unevaluated[0].fn_path, "krate::foo");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
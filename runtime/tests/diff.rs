@@ -0,0 +1,43 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use libhoare::diff::{comparison_message, DebugOperand, NoDebugOperand, Operand};
+
+struct NoDebug;
+
+// The borrows are needed to fall back to NoDebugOperand.
+#[test]
+fn test_operand() {
+    assert_eq!((&Operand(&5)).debug_operand(), Some("5".to_owned()));
+    assert_eq!((&Operand("a")).debug_operand(), Some("\"a\"".to_owned()));
+    assert_eq!((&Operand(&NoDebug)).debug_operand(), None);
+}
+
+#[test]
+fn test_comparison_message() {
+    assert_eq!(
+        comparison_message("postcondition of foo (x < 3)", "<", Some("4".to_owned()), Some("3".to_owned()), false),
+        "postcondition of foo (x < 3)\n left: 4\nright: 3\n"
+    );
+    assert_eq!(
+        comparison_message("precondition of foo (a == b)", "==", Some("[\n    1,\n    2,\n]".to_owned()), Some("[\n    1,\n    3,\n]".to_owned()), false),
+        "precondition of foo (a == b)\ndiff (- left, + right):\n [\n     1,\n-    2,\n+    3,\n ]\n"
+    );
+    assert_eq!(
+        comparison_message("precondition of foo (a == b)", "==", Some("1".to_owned()), Some("2".to_owned()), true),
+        "precondition of foo (a == b)\n left: 1\nright: 2\ndiff (- left, + right):\n\x1b[31m-1\x1b[0m\n\x1b[32m+2\x1b[0m\n"
+    );
+    assert_eq!(
+        comparison_message("precondition of foo (a == b)", "==", None, Some("2".to_owned()), false),
+        "precondition of foo (a == b)\n left: (does not implement Debug)\nright: 2\n"
+    );
+}
@@ -0,0 +1,33 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::panic;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use libhoare::duration::check_max_duration;
+
+#[test]
+fn test_within_limit() {
+    check_max_duration(Instant::now(), 60_000_000_000, "f", "60s");
+}
+
+#[test]
+fn test_exceeded() {
+    let start = Instant::now();
+    thread::sleep(Duration::from_millis(2));
+    let err = panic::catch_unwind(|| check_max_duration(start, 1_000_000, "f", "1ms")).unwrap_err();
+    let message = err.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("max_duration of f violated: took "), "{}", message);
+    assert!(message.ends_with("ms, limit 1ms"), "{}", message);
+}
@@ -0,0 +1,66 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use libhoare::{Enforced, HasInvariant, InvariantError, Violation, ViolationKind};
+
+#[derive(Clone, Debug)]
+struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+impl HasInvariant for Bounds {
+    fn check_invariants(&self) -> Result<(), InvariantError> {
+        if self.min <= self.max {
+            Ok(())
+        } else {
+            Err(InvariantError::new("Bounds", "self.min <= self.max"))
+        }
+    }
+}
+
+#[test]
+fn test_enforced() {
+    let mut bounds = Enforced::new(Bounds { min: 0, max: 2 });
+    {
+        let mut b = bounds.get_mut();
+        // Broken while the guard is alive.
+        b.min = 5;
+        b.max = 7;
+    }
+    assert_eq!(bounds.min, 5);
+    let copy = bounds.clone();
+    assert_eq!(copy.into_inner().max, 7);
+    assert_eq!(format!("{:?}", bounds), "Enforced(Bounds { min: 5, max: 7 })");
+}
+
+#[test]
+#[should_panic(expected = "invariant of Bounds (self.min <= self.max) violated in Enforced::new")]
+fn test_construction() {
+    Enforced::new(Bounds { min: 3, max: 2 });
+}
+
+#[test]
+#[should_panic(expected = "violated in EnforcedMut::drop")]
+fn test_guard_drop() {
+    let mut bounds = Enforced::new(Bounds { min: 0, max: 2 });
+    bounds.get_mut().min = 3;
+}
+
+#[test]
+fn test_violation() {
+    let violation =
+        Violation::parse("invariant of Bounds (self.min <= self.max) violated in Enforced::clone");
+    assert_eq!(violation.kind(), ViolationKind::Invariant);
+    assert_eq!(violation.fn_name(), Some("Enforced::clone"));
+    assert_eq!(violation.predicate(), Some("self.min <= self.max"));
+}
@@ -0,0 +1,46 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use libhoare::diff::{DebugOperand, NoDebugOperand, Operand};
+use libhoare::explain;
+
+struct Opaque;
+
+#[test]
+fn test_message() {
+    let values = [("self.len()", Some("5".to_owned())), ("cap", Some("4".to_owned()))];
+    assert_eq!(
+        explain::message("precondition of push (self.len() < cap)", &values),
+        "precondition of push (self.len() < cap)\n  self.len() = 5\n  cap = 4"
+    );
+}
+
+// The borrows are needed to fall back to NoDebugOperand.
+#[test]
+fn test_operands() {
+    let xs = vec![1, 2];
+    let values = [
+        ("xs", (&Operand(&xs)).debug_operand()),
+        ("opaque", (&Operand(&Opaque)).debug_operand()),
+    ];
+    assert_eq!(
+        explain::message("postcondition of f (g(xs, opaque))", &values),
+        "postcondition of f (g(xs, opaque))\n  xs = [\n        1,\n        2,\n    ]\n  \
+         opaque = (does not implement Debug)"
+    );
+}
+
+#[test]
+#[should_panic(expected = "invariant entering f\n  x = 3")]
+fn test_failed() {
+    explain::failed("invariant entering f", &[("x", Some("3".to_owned()))]);
+}
@@ -0,0 +1,120 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[macro_use]
+extern crate libhoare;
+
+use std::panic;
+
+precond! {
+    /// Divides `x` by `y`.
+    pub fn div(x: i32, y: i32) -> i32
+        requires (y != 0)
+        requires (x >= 0)
+    {
+        x / y
+    }
+}
+
+postcond! {
+    fn abs(x: i32) -> i32
+        ensures |result| (*result >= 0)
+    {
+        if x < 0 {
+            return -x;
+        }
+        x
+    }
+}
+
+postcond! {
+    fn parse(s: &str) -> Result<Vec<u32>, std::num::ParseIntError>
+        requires (!s.is_empty())
+        ensures |result| (result.as_ref().map(|v| !v.is_empty()).unwrap_or(true))
+    {
+        let mut values = Vec::new();
+        for part in s.split(',') {
+            values.push(part.trim().parse()?);
+        }
+        Ok(values)
+    }
+}
+
+postcond! {
+    fn wrong(x: u32) -> u32
+        ensures |result| (*result > x)
+    {
+        x
+    }
+}
+
+precond! {
+    fn no_result(v: &mut Vec<u32>, x: u32)
+        requires (x > 0)
+    {
+        v.push(x);
+    }
+}
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    postcond! {
+        fn incr(&mut self) -> u32
+            requires (self.count < 10)
+            ensures |result| (*result == self.count)
+        {
+            self.count += 1;
+            self.count
+        }
+    }
+}
+
+fn message<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    match err.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => err.downcast_ref::<String>().unwrap().clone(),
+    }
+}
+
+#[test]
+fn test_precond() {
+    assert_eq!(div(6, 2), 3);
+    assert_eq!(message(|| { div(6, 0); }), "precondition of div (y != 0)");
+    assert_eq!(message(|| { div(-6, 2); }), "precondition of div (x >= 0)");
+
+    let mut v = Vec::new();
+    no_result(&mut v, 3);
+    assert_eq!(v, [3]);
+}
+
+#[test]
+fn test_postcond() {
+    // Early returns are checked too.
+    assert_eq!(abs(-4), 4);
+    assert_eq!(abs(4), 4);
+    assert_eq!(parse("1, 2").unwrap(), [1, 2]);
+    assert!(parse("x").is_err());
+    assert_eq!(message(|| { wrong(1); }), "postcondition of wrong (*result > x)");
+}
+
+#[test]
+fn test_method() {
+    let mut counter = Counter { count: 0 };
+    assert_eq!(counter.incr(), 1);
+    counter.count = 10;
+    assert_eq!(
+        message(move || { counter.incr(); }),
+        "precondition of incr (self.count < 10)"
+    );
+}
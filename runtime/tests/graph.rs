@@ -0,0 +1,93 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+extern crate serde_json;
+
+use std::fs;
+
+use libhoare::graph::Graph;
+
+mod common;
+
+const MANIFEST: &str = "contract\tkrate::foo\tprecondition of\tis_valid(x) && x > 0\n\
+                        uses\tkrate::foo\tis_valid\n\
+                        contract\tkrate::new\tprecondition of\tlo <= hi\n\
+                        establishes\tkrate::new\tkrate::Bounds\n\
+                        invariant\tkrate::Bounds\tself.lo <= self.hi\n\
+                        invariant\tkrate::Bounds\tis_valid(self.lo)\n\
+                        uses\tkrate::Bounds\tis_valid\n\
+                        spec\tkrate::sqrt\tpostcondition\treturn is the closest root\n\
+                        spec\tkrate::Bounds\tinvariant\tlo is the least bound\n\
+                        not a manifest line\n";
+
+fn graph() -> Graph {
+    let mut graph = Graph::default();
+    for line in MANIFEST.lines() {
+        graph.add(line);
+    }
+    graph
+}
+
+#[test]
+fn test_load() {
+    let dir = common::temp_dir("graph-test");
+    common::write(dir.join("krate.graph"), MANIFEST);
+    common::write(dir.join("krate.contracts"), "precondition of\tkrate::bar\t1\ttrue\n");
+
+    let graph = Graph::load(&dir).unwrap();
+    assert_eq!(graph, self::graph());
+    assert_eq!(graph.functions.len(), 2);
+    assert_eq!(graph.function_paths().len(), 3);
+    assert_eq!(graph.types["krate::Bounds"].len(), 2);
+    let predicates = graph.predicates();
+    assert_eq!(predicates["is_valid"], vec!["krate::Bounds", "krate::foo"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_dot() {
+    let dot = graph().to_dot();
+    assert!(dot.starts_with("digraph contracts {\n"));
+    assert!(dot.contains(
+        "    \"krate::foo\" [label=\"krate::foo\\nprecondition of: is_valid(x) && x > 0\"];\n"
+    ));
+    assert!(dot.contains(
+        "    \"krate::Bounds\" [label=\"invariant of krate::Bounds\\nis_valid(self.lo)\\n\
+         self.lo <= self.hi\\nspec invariant: lo is the least bound\", style=rounded];\n"
+    ));
+    assert!(dot.contains(
+        "    \"krate::sqrt\" [label=\"krate::sqrt\\nspec postcondition: return is the closest \
+         root\"];\n"
+    ));
+    assert!(dot.contains("    \"is_valid\" [shape=ellipse];\n"));
+    assert!(dot.contains("    \"krate::foo\" -> \"is_valid\";\n"));
+    assert!(dot.contains(
+        "    \"krate::new\" -> \"krate::Bounds\" [style=dashed, label=\"establishes\"];\n"
+    ));
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn test_json() {
+    let json: serde_json::Value = serde_json::from_str(&graph().to_json()).unwrap();
+    assert_eq!(json["functions"][0]["path"], "krate::foo");
+    assert_eq!(json["functions"][0]["uses"][0], "is_valid");
+    assert_eq!(json["functions"][1]["establishes"][0], "krate::Bounds");
+    assert_eq!(json["types"][0]["invariants"].as_array().unwrap().len(), 2);
+    assert_eq!(json["functions"][2]["path"], "krate::sqrt");
+    assert_eq!(json["functions"][2]["contracts"].as_array().unwrap().len(), 0);
+    assert_eq!(json["functions"][2]["specs"][0]["kind"], "postcondition");
+    assert_eq!(json["functions"][2]["specs"][0]["text"], "return is the closest root");
+    assert_eq!(json["types"][0]["specs"][0]["text"], "lo is the least bound");
+    assert_eq!(json["predicates"][0]["name"], "is_valid");
+    assert_eq!(json["predicates"][0]["used_by"].as_array().unwrap().len(), 2);
+}
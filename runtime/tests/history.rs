@@ -0,0 +1,91 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use libhoare::history::{pooled, CloneOld, Old, SnapshotOld};
+use libhoare::Snapshot;
+
+// Can not be cloned.
+struct Log {
+    lines: Vec<String>,
+}
+
+impl Snapshot for Log {
+    type Snapshot = usize;
+    fn snapshot(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+// Can be cloned, but has a cheaper snapshot.
+#[derive(Clone)]
+struct Doc {
+    text: String,
+    version: u32,
+}
+
+impl Snapshot for Doc {
+    type Snapshot = u32;
+    fn snapshot(&self) -> u32 {
+        self.version
+    }
+}
+
+// The borrow is needed to select the impl, see libhoare::history.
+#[test]
+fn test_old() {
+    let x = 42;
+    assert_eq!((&Old(&x)).old(), 42);
+
+    let s = "hello".to_owned();
+    assert_eq!((&Old(&s)).old(), "hello");
+
+    let log = Log {
+        lines: vec!["a".to_owned()],
+    };
+    assert_eq!((&Old(&log)).old(), 1);
+    let log_ref = &log;
+    assert_eq!((&Old(&log_ref)).old(), 1);
+
+    let doc = Doc {
+        text: String::new(),
+        version: 3,
+    };
+    assert_eq!((&Old(&doc)).old(), 3);
+    assert!(doc.clone().text.is_empty());
+}
+
+#[test]
+fn test_pooled_reuses_buffers() {
+    let v = vec![1, 2, 3];
+    let first = pooled(&v);
+    assert_eq!(*first, v);
+    let buffer = first.as_ptr();
+    drop(first);
+
+    // The buffer is reused, without allocating.
+    let second = pooled(&vec![4, 5]);
+    assert_eq!(*second, vec![4, 5]);
+    assert_eq!(second.as_ptr(), buffer);
+
+    // The buffer is in use, so another is allocated.
+    let third = pooled(&v);
+    assert!(third.as_ptr() != buffer);
+    assert_eq!(*third, v);
+}
+
+#[test]
+fn test_pooled_string() {
+    let s = "hello".to_owned();
+    assert_eq!(&*pooled(&s), "hello");
+    assert_eq!(&*pooled(&String::new()), "");
+}
@@ -0,0 +1,31 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::panic;
+
+use libhoare::hits;
+use libhoare::{assert_contract_hit, ContractKind};
+
+#[test]
+fn test_hits() {
+    assert!(panic::catch_unwind(|| assert_contract_hit("krate::foo", ContractKind::Precond)).is_err());
+
+    hits::record("krate::foo", ContractKind::Precond);
+    hits::record("krate::bar::baz", ContractKind::Invariant);
+
+    assert_contract_hit("krate::foo", ContractKind::Precond);
+    assert_contract_hit("bar::baz", ContractKind::Invariant);
+    assert_contract_hit("baz", ContractKind::Invariant);
+    assert!(!hits::was_hit("krate::foo", ContractKind::Postcond));
+    assert!(!hits::was_hit("az", ContractKind::Invariant));
+    assert!(panic::catch_unwind(|| assert_contract_hit("krate::foo", ContractKind::Postcond)).is_err());
+}
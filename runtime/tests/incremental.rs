@@ -0,0 +1,29 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::sync::atomic::AtomicUsize;
+
+use libhoare::incremental::full_check_due;
+
+#[test]
+fn test_full_check_due() {
+    let calls = AtomicUsize::new(0);
+    let due: Vec<bool> = (0..7).map(|_| full_check_due(&calls, 3)).collect();
+    assert_eq!(due, vec![true, false, false, true, false, false, true]);
+
+    let calls = AtomicUsize::new(0);
+    assert!((0..3).all(|_| full_check_due(&calls, 1)));
+
+    let calls = AtomicUsize::new(0);
+    assert!((0..3).all(|_| !full_check_due(&calls, 0)));
+}
@@ -0,0 +1,23 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use libhoare::InvariantError;
+
+#[test]
+fn test_invariant_error() {
+    let error = InvariantError::new("Bounds", "self.min <= self.max");
+    assert_eq!(error.type_name(), "Bounds");
+    assert_eq!(error.predicate(), "self.min <= self.max");
+    assert_eq!(error.to_string(), "invariant of Bounds (self.min <= self.max) violated");
+    let error: Box<std::error::Error> = Box::new(error);
+    assert!(error.source().is_none());
+}
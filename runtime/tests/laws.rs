@@ -0,0 +1,104 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::Add;
+use std::panic;
+
+use libhoare::laws;
+
+// Compares by key, but == compares everything.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct ByKey(u32, &'static str);
+
+impl PartialOrd for ByKey {
+    fn partial_cmp(&self, other: &ByKey) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+// Hashes a field which == ignores.
+#[derive(Debug)]
+struct Loose(u32, u32);
+
+impl PartialEq for Loose {
+    fn eq(&self, other: &Loose) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Loose {}
+
+impl Hash for Loose {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+        self.1.hash(state);
+    }
+}
+
+// Addition which rounds each step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rounded(i32);
+
+impl Add for Rounded {
+    type Output = Rounded;
+    fn add(self, other: Rounded) -> Rounded {
+        Rounded((self.0 + other.0) / 2 * 2)
+    }
+}
+
+fn panic_message<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    err.downcast_ref::<String>().unwrap().clone()
+}
+
+#[test]
+fn test_laws_hold() {
+    let ints = [-3, 0, 1, 7, 7];
+    laws::partial_ord("i32", &ints);
+    laws::ord("i32", &ints);
+    laws::hash("i32", &ints);
+    laws::add_assoc("i32", &ints);
+    laws::mul_assoc("i32", &ints);
+    laws::partial_ord("f64", &[0.5, -1.0, f64::NAN]);
+}
+
+#[test]
+fn test_partial_ord() {
+    let message = panic_message(|| laws::partial_ord("ByKey", &[ByKey(1, "a"), ByKey(1, "b")]));
+    assert_eq!(
+        message,
+        "law partial_ord of ByKey violated: ByKey(1, \"a\").partial_cmp(&ByKey(1, \"b\")) is \
+         Some(Equal), but == is false"
+    );
+}
+
+#[test]
+fn test_hash() {
+    let message = panic_message(|| laws::hash("Loose", &[Loose(1, 2), Loose(1, 3)]));
+    assert_eq!(
+        message,
+        "law hash of Loose violated: Loose(1, 2) == Loose(1, 3), but their hashes differ"
+    );
+}
+
+#[test]
+fn test_add_assoc() {
+    let message = panic_message(|| laws::add_assoc("Rounded", &[Rounded(1), Rounded(2)]));
+    assert_eq!(
+        message,
+        "law add_assoc of Rounded violated: (Rounded(1) + Rounded(1)) + Rounded(2) is Rounded(4), \
+         but Rounded(1) + (Rounded(1) + Rounded(2)) is Rounded(2)"
+    );
+}
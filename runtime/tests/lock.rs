@@ -0,0 +1,51 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::panic;
+use std::sync::{Mutex, RwLock};
+
+use libhoare::lock::Checked;
+
+// Takes a &Vec to match the target of the guards.
+fn sorted(v: &Vec<i32>) -> bool {
+    v.windows(2).all(|w| w[0] <= w[1])
+}
+
+#[test]
+fn test_checked() {
+    let m = Mutex::new(vec![1, 2]);
+    {
+        let mut v = Checked::new(m.lock().unwrap(), sorted, "v (sorted)");
+        v.push(0);
+        v.sort();
+    }
+
+    let l = RwLock::new(vec![1, 2]);
+    assert_eq!(Checked::new(l.read().unwrap(), sorted, "v (sorted)").len(), 2);
+}
+
+#[test]
+fn test_checked_violation() {
+    let m = Mutex::new(vec![2, 1]);
+    let acquire = panic::catch_unwind(|| {
+        Checked::new(m.lock().unwrap(), sorted, "v (sorted)");
+    });
+    assert!(acquire.is_err());
+
+    let m = Mutex::new(vec![1, 2]);
+    let release = panic::catch_unwind(|| {
+        let mut v = Checked::new(m.lock().unwrap(), sorted, "v (sorted)");
+        v.push(0);
+    });
+    let message = release.unwrap_err();
+    assert_eq!(message.downcast_ref::<String>().unwrap(), "lock invariant releasing v (sorted)");
+}
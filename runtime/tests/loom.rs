@@ -0,0 +1,89 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "loom")]
+
+extern crate libhoare;
+extern crate loom;
+
+use std::panic;
+
+use loom::sync::atomic::{AtomicUsize, Ordering};
+use loom::sync::Mutex;
+
+// Keeps lo <= hi, but only while holding the lock.
+struct Locked {
+    bounds: Mutex<(usize, usize)>,
+}
+
+impl Locked {
+    fn new() -> Locked {
+        Locked {
+            bounds: Mutex::new((0, 0)),
+        }
+    }
+
+    fn inc(&self) {
+        let mut bounds = self.bounds.lock().unwrap();
+        bounds.1 += 1;
+        bounds.0 += 1;
+    }
+
+    fn invariant(&self) -> bool {
+        let bounds = self.bounds.lock().unwrap();
+        bounds.0 <= bounds.1
+    }
+}
+
+// Tries to keep lo <= hi, but updates lo first.
+struct Racy {
+    lo: AtomicUsize,
+    hi: AtomicUsize,
+}
+
+impl Racy {
+    fn new() -> Racy {
+        Racy {
+            lo: AtomicUsize::new(0),
+            hi: AtomicUsize::new(0),
+        }
+    }
+
+    fn inc(&self) {
+        self.lo.fetch_add(1, Ordering::SeqCst);
+        self.hi.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn read(&self) {
+        self.lo.load(Ordering::SeqCst);
+    }
+
+    fn invariant(&self) -> bool {
+        self.lo.load(Ordering::SeqCst) <= self.hi.load(Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn test_explore() {
+    libhoare::loom::explore("Locked", Locked::new, &[("inc", Locked::inc)], Locked::invariant);
+}
+
+#[test]
+fn test_explore_violation() {
+    let result = panic::catch_unwind(|| {
+        libhoare::loom::explore(
+            "Racy",
+            Racy::new,
+            &[("inc", Racy::inc), ("read", Racy::read)],
+            Racy::invariant,
+        )
+    });
+    assert!(result.is_err());
+}
@@ -0,0 +1,87 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "metrics")]
+
+extern crate libhoare;
+
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+use libhoare::metrics::{self, ContractMetrics, Metric};
+use libhoare::violation;
+
+// A metric as generated by the plugin.
+macro_rules! metric {
+    ($fn_path:expr, $kind:expr) => {
+        Metric {
+            fn_path: $fn_path,
+            kind: $kind,
+            registered: AtomicBool::new(false),
+            checks: AtomicUsize::new(0),
+            violations: AtomicUsize::new(0),
+        }
+    };
+}
+
+static PRE: Metric = metric!("krate::sqrt", "precondition");
+static POST: Metric = metric!("krate::sqrt", "postcondition");
+static POST_2: Metric = metric!("krate::sqrt", "postcondition");
+static LOGGED: Metric = metric!("krate::log\"ged", "precondition");
+
+fn counts(fn_path: &str) -> Vec<ContractMetrics> {
+    metrics::snapshot().into_iter().filter(|m| m.fn_path == fn_path).collect()
+}
+
+#[test]
+fn test_counts() {
+    PRE.observe(|| {});
+    PRE.observe(|| {});
+    assert!(panic::catch_unwind(|| PRE.observe(|| panic!("precondition of sqrt (x >= 0.0)")))
+        .is_err());
+    POST.observe(|| {});
+    POST_2.observe(|| {});
+
+    assert_eq!(
+        counts("krate::sqrt"),
+        vec![
+            ContractMetrics {
+                fn_path: "krate::sqrt",
+                kind: "postcondition",
+                checks: 2,
+                violations: 0,
+            },
+            ContractMetrics {
+                fn_path: "krate::sqrt",
+                kind: "precondition",
+                checks: 3,
+                violations: 1,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_logged_violations_and_prometheus_text() {
+    violation::expect_violations(1, || {
+        violation::log(|| LOGGED.observe(|| panic!("precondition of logged (false)")));
+        violation::log(|| LOGGED.observe(|| {}));
+    });
+    assert_eq!(counts("krate::log\"ged")[0].violations, 1);
+
+    let text = metrics::prometheus_text();
+    assert!(text.contains("# TYPE hoare_contract_checks_total counter\n"));
+    assert!(text.contains(
+        "hoare_contract_checks_total{function=\"krate::log\\\"ged\",kind=\"precondition\"} 2\n"
+    ));
+    assert!(text.contains(
+        "hoare_contract_violations_total{function=\"krate::log\\\"ged\",kind=\"precondition\"} 1\n"
+    ));
+}
@@ -0,0 +1,53 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::env;
+use std::fs;
+
+use libhoare::mutation::{self, Mutant};
+
+mod common;
+
+#[test]
+fn test_load_all() {
+    let dir = common::temp_dir("mutants-test");
+    common::write(
+        dir.join("krate.mutants"),
+        "1\tprecondition of\tkrate::foo\tx > 0\tx <= 0\n\
+         0\tprecondition of\tkrate::foo\tx > 0\ttrue\n",
+    );
+
+    // The build of the crate's tests has the same mutants, and more.
+    common::write(
+        dir.join("krate.test.mutants"),
+        "1\tprecondition of\tkrate::foo\tx > 0\tx <= 0\n\
+         2\tprecondition of\tkrate::tests::bar\ty > 0\ttrue\n",
+    );
+
+    let mutants = Mutant::load_all(&dir).unwrap();
+    assert_eq!(mutants.len(), 3);
+    assert_eq!(mutants[0].id, 0);
+    assert_eq!(mutants[0].mutant, "true");
+    assert_eq!(mutants[1].predicate, "x > 0");
+    assert_eq!(mutants[1].selector(), "krate:1");
+    assert_eq!(mutants[2].fn_path, "krate::tests::bar");
+    assert_eq!(mutants[2].selector(), "krate:2");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_active() {
+    env::set_var(mutation::ACTIVE_VAR, "krate:3");
+    assert_eq!(mutation::active("krate"), Some(3));
+    assert_eq!(mutation::active("other"), None);
+}
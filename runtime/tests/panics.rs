@@ -0,0 +1,65 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::panic;
+
+use libhoare::panics::{message, no_panic_violated, report_predicate_panics};
+
+#[test]
+fn test_no_panic_violated() {
+    let payload = panic::catch_unwind(|| panic!("index {} out of bounds", 3)).unwrap_err();
+    let err = panic::catch_unwind(panic::AssertUnwindSafe(|| no_panic_violated("get", payload)))
+        .unwrap_err();
+    assert_eq!(
+        message(&*err),
+        "panic-freedom contract violated in get: index 3 out of bounds"
+    );
+}
+
+#[test]
+fn test_message() {
+    let payload = panic::catch_unwind(|| panic!("static")).unwrap_err();
+    assert_eq!(message(&*payload), "static");
+    let payload = panic::catch_unwind(|| panic::panic_any(42)).unwrap_err();
+    assert_eq!(message(&*payload), "(no message)");
+}
+
+#[test]
+fn test_predicate_crashed() {
+    let v: Vec<i32> = Vec::new();
+    let err = panic::catch_unwind(|| {
+        report_predicate_panics("precondition of", "get", "v[0] > 0", || {
+            assert!(v[0] > 0, "precondition of get (v[0] > 0)");
+        })
+    })
+    .unwrap_err();
+    let message = message(&*err);
+    assert!(
+        message.starts_with("contract predicate crashed in get (v[0] > 0): index out of bounds"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_predicate_violated() {
+    let x = 0;
+    let err = panic::catch_unwind(|| {
+        report_predicate_panics("precondition of", "get", "x > 0", || {
+            assert!(x > 0, "precondition of get (x > 0)");
+        })
+    })
+    .unwrap_err();
+    assert_eq!(message(&*err), "precondition of get (x > 0)");
+    report_predicate_panics("precondition of", "get", "x == 0", || assert!(x == 0));
+}
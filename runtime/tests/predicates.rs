@@ -0,0 +1,140 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::collections::HashMap;
+
+use libhoare::predicates::{
+    checked_at, decreased, decreased_by, grew_by, in_range, increased, increased_by, is_ascii,
+    is_char_boundary, is_sorted, non_empty, shrank_by, valid_index, valid_utf8,
+};
+
+#[test]
+fn test_in_range() {
+    assert_eq!(in_range(&5, &(0..101)), Ok(()));
+    assert_eq!(in_range(&100, &(0..101)), Ok(()));
+    assert_eq!(in_range(&0.5, &(0.0..1.0)), Ok(()));
+    assert_eq!(in_range(&101, &(0..101)), Err("101 is not in 0..101".to_owned()));
+    assert_eq!(in_range(&101, &(..101)), Err("101 is not in ..101".to_owned()));
+    assert_eq!(in_range(&101, &(..)), Ok(()));
+    assert_eq!(in_range(&10, &(0..10)), Err("10 is not in 0..10".to_owned()));
+    assert_eq!(in_range(&-1, &(0..)), Err("-1 is not in 0..".to_owned()));
+}
+
+#[test]
+fn test_non_empty() {
+    assert_eq!(non_empty(&vec![1]), Ok(()));
+    assert_eq!(non_empty("a"), Ok(()));
+    assert_eq!(non_empty(&[1, 2][..]), Ok(()));
+    assert!(non_empty(&Vec::<u8>::new()).is_err());
+    assert!(non_empty(&String::new()).is_err());
+    assert!(non_empty(&HashMap::<u8, u8>::new()).is_err());
+    assert_eq!(non_empty(&[0u8; 0]), Err("the collection is empty".to_owned()));
+}
+
+#[test]
+fn test_is_sorted() {
+    assert_eq!(is_sorted(&Vec::<u8>::new()), Ok(()));
+    assert_eq!(is_sorted(&vec![1, 1, 2, 5]), Ok(()));
+    assert!(is_sorted(&[1.0, f64::NAN]).is_err());
+    assert_eq!(is_sorted(&[1, 3, 2]), Err("not sorted at index 2: 3 is not <= 2".to_owned()));
+    assert_eq!(
+        is_sorted(&["a", "c", "b"][..]),
+        Err("not sorted at index 2: \"c\" is not <= \"b\"".to_owned())
+    );
+}
+
+#[test]
+fn test_indices() {
+    let v = vec![1, 2, 3];
+    assert_eq!(checked_at(&v, &2), Ok(()));
+    assert_eq!(checked_at("abc", &1), Ok(()));
+    assert_eq!(
+        checked_at(&v, &3),
+        Err("index 3 is out of bounds for a length of 3".to_owned())
+    );
+    assert!(checked_at(&[0u8; 0], &0).is_err());
+    assert_eq!(valid_index(&0, &v[..]), Ok(()));
+    assert_eq!(valid_index(&v.iter().position(|&x| x == 3), &v), Ok(()));
+    assert_eq!(valid_index(&None, &v), Ok(()));
+    assert_eq!(
+        valid_index(&Some(7), &v),
+        Err("index 7 is out of bounds for a length of 3".to_owned())
+    );
+}
+
+#[test]
+fn test_strings() {
+    assert_eq!(is_ascii("abc"), Ok(()));
+    assert_eq!(is_ascii(&b"ab\xff"[..]), Err("non-ASCII byte 0xff at offset 2".to_owned()));
+    assert_eq!(is_ascii("aé"), Err("non-ASCII byte 0xc3 at offset 1".to_owned()));
+
+    let s = "aé!";
+    assert_eq!(is_char_boundary(s, &0), Ok(()));
+    assert_eq!(is_char_boundary(s, &3), Ok(()));
+    assert_eq!(is_char_boundary(s, &4), Ok(()));
+    assert_eq!(
+        is_char_boundary(s, &2),
+        Err("offset 2 is not a char boundary, it is inside 'é' at 1..3".to_owned())
+    );
+    assert_eq!(
+        is_char_boundary(&s.to_owned(), &5),
+        Err("offset 5 is past the end, at 4".to_owned())
+    );
+
+    assert_eq!(valid_utf8("é".as_bytes()), Ok(()));
+    assert_eq!(
+        valid_utf8(&b"ab\xffc"[..]),
+        Err("invalid UTF-8 at offset 2: [ff]".to_owned())
+    );
+    assert_eq!(
+        valid_utf8(&vec![b'a', 0xc3]),
+        Err("incomplete UTF-8 character at offset 1".to_owned())
+    );
+}
+
+#[test]
+fn test_increased() {
+    assert_eq!(increased(&1, &2), Ok(()));
+    assert_eq!(increased(&2, &2), Err("did not increase: 2 became 2".to_owned()));
+    assert_eq!(decreased(&2.5, &1.0), Ok(()));
+    assert_eq!(decreased(&1u8, &3), Err("did not decrease: 1 became 3".to_owned()));
+}
+
+#[test]
+fn test_changed_by() {
+    assert_eq!(increased_by(&3, &8, &5), Ok(()));
+    assert_eq!(decreased_by(&8u32, &3, &5), Ok(()));
+    assert_eq!(
+        increased_by(&3, &7, &5),
+        Err("expected an increase of 5 from 3, but it became 7".to_owned())
+    );
+    // The difference is not taken the wrong way, so this doesn't underflow.
+    assert_eq!(
+        decreased_by(&3u32, &8, &5),
+        Err("expected a decrease of 5 from 3, but it became 8".to_owned())
+    );
+}
+
+#[test]
+fn test_grew_by() {
+    assert_eq!(grew_by(&0usize, &1, &1), Ok(()));
+    assert_eq!(shrank_by(&4usize, &2, &2), Ok(()));
+    assert_eq!(
+        grew_by(&2usize, &2, &1),
+        Err("expected to grow by 1 from 2, but it became 2".to_owned())
+    );
+    assert_eq!(
+        shrank_by(&2usize, &3, &1),
+        Err("expected to shrink by 1 from 2, but it became 3".to_owned())
+    );
+}
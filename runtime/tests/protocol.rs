@@ -0,0 +1,34 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::panic;
+
+use libhoare::protocol::State;
+
+const STATES: &[&str] = &["closed", "open"];
+
+#[test]
+fn test_state() {
+    let state = State::default();
+    assert_eq!(state.get(), 0);
+    state.check(STATES, &[0], "File", "open");
+    state.set(1);
+    assert_eq!(state.clone().get(), 1);
+    assert!(state == State::default());
+
+    let result = panic::catch_unwind(|| state.check(STATES, &[0], "File", "open"));
+    let message = result.unwrap_err();
+    assert_eq!(
+        message.downcast_ref::<String>().unwrap(),
+        "protocol of File violated: open called in state open, expected closed"
+    );
+}
@@ -0,0 +1,62 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+use libhoare::race::Canary;
+
+#[test]
+fn test_own_writes() {
+    let counter = Canary::new(AtomicUsize::new(0));
+    let snapshot = counter.snapshot();
+    let n = counter.load(Ordering::SeqCst);
+    counter.write().store(n + 1, Ordering::SeqCst);
+    counter.write().fetch_add(1, Ordering::SeqCst);
+    counter.check(snapshot, "Stats::hits", "record");
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_concurrent_write() {
+    let counter = Arc::new(Canary::new(AtomicUsize::new(0)));
+    let snapshot = counter.snapshot();
+    counter.write().fetch_add(1, Ordering::SeqCst);
+
+    let (done, wait) = mpsc::channel();
+    let other = counter.clone();
+    thread::spawn(move || {
+        other.write().fetch_add(1, Ordering::SeqCst);
+        done.send(()).unwrap();
+    });
+    wait.recv().unwrap();
+
+    let err = panic::catch_unwind(|| counter.check(snapshot, "Stats::hits", "record")).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<String>().unwrap(),
+        "race check of Stats::hits failed: modified by another thread during record (1 of 2 writes)"
+    );
+}
+
+#[test]
+fn test_separate_canaries() {
+    let a = Canary::new(AtomicUsize::new(0));
+    let b = Canary::new(AtomicUsize::new(0));
+    let snapshot = a.snapshot();
+    b.write().fetch_add(1, Ordering::SeqCst);
+    a.check(snapshot, "a", "m");
+    assert_eq!(b.into_inner().into_inner(), 1);
+}
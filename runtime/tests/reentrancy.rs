@@ -0,0 +1,56 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+
+use std::panic;
+
+use libhoare::panics::message;
+use libhoare::reentrancy::enter;
+
+#[test]
+fn test_not_reentered() {
+    {
+        let _f = enter("m::f", 0, "f");
+        let _g = enter("m::g", 0, "g");
+    }
+    let _f = enter("m::f", 0, "f");
+}
+
+#[test]
+fn test_reentered() {
+    let _f = enter("m::f", 0, "f");
+    let err = panic::catch_unwind(|| enter("m::f", 0, "f")).unwrap_err();
+    assert_eq!(
+        message(&*err),
+        "non_reentrant of f violated: entered again while already executing"
+    );
+}
+
+#[test]
+fn test_per_object() {
+    let _a = enter("m::f", 1, "f");
+    let _b = enter("m::f", 2, "f");
+    let err = panic::catch_unwind(|| enter("m::f", 1, "f")).unwrap_err();
+    assert_eq!(
+        message(&*err),
+        "non_reentrant of f violated: entered again while already executing on the same object"
+    );
+}
+
+#[test]
+fn test_unwinding() {
+    let _ = panic::catch_unwind(|| {
+        let _f = enter("m::f", 0, "f");
+        panic!("in f");
+    });
+    let _f = enter("m::f", 0, "f");
+}
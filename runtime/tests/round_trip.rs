@@ -0,0 +1,110 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::fmt;
+use std::str::FromStr;
+
+use libhoare::round_trip::{check_from, check_from_str, check_try_from};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Cents(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Dollars(f64);
+
+impl From<Dollars> for Cents {
+    fn from(d: Dollars) -> Cents {
+        Cents((d.0 * 100.0) as u64)
+    }
+}
+
+impl From<Cents> for Dollars {
+    fn from(c: Cents) -> Dollars {
+        let dollars = Dollars(c.0 as f64 / 100.0);
+        check_from(&c, &dollars, "From<Cents> for Dollars");
+        dollars
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Digit(u8);
+
+impl Digit {
+    fn try_from(n: u8) -> Result<Digit, ()> {
+        if n < 10 { Ok(Digit(n)) } else { Err(()) }
+    }
+}
+
+fn try_into(d: Digit) -> Result<u8, ()> {
+    Ok(u8::from(d))
+}
+
+impl From<Digit> for u8 {
+    fn from(d: Digit) -> u8 {
+        d.0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Name(String);
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Name {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Name, ()> {
+        let name = s.trim();
+        if name.is_empty() || name.contains(' ') { Err(()) } else { Ok(Name(name.to_owned())) }
+    }
+}
+
+#[test]
+fn test_from() {
+    assert_eq!(Dollars::from(Cents(250)), Dollars(2.5));
+}
+
+#[test]
+#[should_panic(expected = "round trip of From<Cents> for Dollars violated: Cents(29) converted to \
+                           Dollars(0.29), which converts back to Cents(28)")]
+fn test_from_violated() {
+    let _ = Dollars::from(Cents(29));
+}
+
+#[test]
+fn test_try_from() {
+    check_try_from(&3, &Digit::try_from(3), "TryFrom<u8> for Digit", try_into);
+    check_try_from(&30, &Digit::try_from(30), "TryFrom<u8> for Digit", try_into);
+}
+
+#[test]
+#[should_panic(expected = "round trip of TryFrom<u8> for Digit violated: 4 converted to Digit(5), \
+                           which converts back to 5")]
+fn test_try_from_violated() {
+    check_try_from(&4, &Ok::<_, ()>(Digit(5)), "TryFrom<u8> for Digit", try_into);
+}
+
+#[test]
+fn test_from_str() {
+    check_from_str(" ada ", &" ada ".parse::<Name>(), "FromStr for Name");
+    check_from_str("a b", &"a b".parse::<Name>(), "FromStr for Name");
+}
+
+#[test]
+#[should_panic(expected = "round trip of FromStr for Name violated: \"x\" parsed to Name(\"a b\"), \
+                           displayed as \"a b\", which does not parse")]
+fn test_from_str_violated() {
+    check_from_str("x", &Ok::<_, ()>(Name("a b".to_owned())), "FromStr for Name");
+}
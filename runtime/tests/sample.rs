@@ -0,0 +1,45 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::thread;
+
+use libhoare::sample::{seed, selected};
+
+#[test]
+fn test_certain() {
+    assert!((0..100).all(|_| selected(1.0)));
+    assert!((0..100).all(|_| !selected(0.0)));
+}
+
+#[test]
+fn test_probability() {
+    let n = 10_000;
+    let hits = (0..n).filter(|_| selected(0.25)).count();
+    assert!(hits > n / 5 && hits < n * 3 / 10, "{} of {}", hits, n);
+}
+
+fn selections(name: &str) -> Vec<bool> {
+    thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(|| (0..64).map(|_| selected(0.5)).collect())
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+#[test]
+fn test_reproducible() {
+    assert_eq!(seed(), seed());
+    // Threads with the same name make the same choices.
+    assert_eq!(selections("same"), selections("same"));
+    assert!(selections("same") != selections("other"));
+}
@@ -0,0 +1,55 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::env;
+use std::fs;
+use std::panic;
+
+use libhoare::snapshot::{self, Mismatch};
+
+mod common;
+
+#[test]
+fn test_snapshots() {
+    let root = common::temp_dir("snapshot-test");
+    let (actual, expected) = (root.join("actual"), root.join("expected"));
+    fs::create_dir_all(&actual).unwrap();
+    common::write(actual.join("krate.foo.rs"), "fn foo() {\n    bar();\n}\n");
+
+    // A missing snapshot fails the check, and is only written when blessing.
+    assert!(panic::catch_unwind(|| snapshot::check(&actual, &expected)).is_err());
+    assert!(!expected.join("krate.foo.rs").exists());
+    env::set_var(snapshot::BLESS_VAR, "1");
+    snapshot::check(&actual, &expected);
+    env::remove_var(snapshot::BLESS_VAR);
+    assert!(snapshot::compare(&actual, &expected).unwrap().is_empty());
+
+    common::write(actual.join("krate.foo.rs"), "fn foo() {\n    baz();\n}\n");
+    let mismatches = snapshot::compare(&actual, &expected).unwrap();
+    assert_eq!(
+        mismatches,
+        vec![Mismatch {
+            name: "krate.foo.rs".to_owned(),
+            expected: Some("fn foo() {\n    bar();\n}\n".to_owned()),
+            actual: Some("fn foo() {\n    baz();\n}\n".to_owned()),
+        }]
+    );
+    assert_eq!(
+        mismatches[0].to_string(),
+        "--- expected/krate.foo.rs\n+++ actual/krate.foo.rs\n fn foo() {\n-    bar();\n+    baz();\n }\n"
+    );
+
+    // Changed snapshots fail the check.
+    assert!(panic::catch_unwind(|| snapshot::check(&actual, &expected)).is_err());
+
+    fs::remove_dir_all(&root).unwrap();
+}
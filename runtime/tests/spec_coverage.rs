@@ -0,0 +1,91 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+extern crate serde_json;
+
+use std::fs;
+
+use libhoare::spec_coverage::{fraction, SpecCoverage};
+
+mod common;
+
+const MANIFEST: &str = "fn\tkrate\tnew\t1\n\
+                        fn\tkrate\tfoo\t0\n\
+                        fn\tkrate\tBounds::lo\t1\n\
+                        type\tkrate\tBounds\t1\n\
+                        fn\tkrate::io\tread\t0\n\
+                        type\tkrate::io\tHandle\t0\n\
+                        type\tkrate::io\tBuffer\t1\n\
+                        fn\tkrate::util\tclamp\t1\n\
+                        fn\tkrate\tbar\tyes\n\
+                        not a manifest line\n";
+
+fn coverage() -> SpecCoverage {
+    let mut coverage = SpecCoverage::default();
+    for line in MANIFEST.lines() {
+        coverage.add(line);
+    }
+    coverage
+}
+
+#[test]
+fn test_load() {
+    let dir = common::temp_dir("spec-coverage-test");
+    common::write(dir.join("krate.specs"), MANIFEST);
+    common::write(dir.join("krate.graph"), "invariant\tkrate::Other\ttrue\n");
+
+    let coverage = SpecCoverage::load(&dir).unwrap();
+    assert_eq!(coverage, self::coverage());
+    assert_eq!(coverage.modules.len(), 3);
+    assert_eq!(fraction(&coverage.modules["krate"].functions), (2, 3));
+    assert_eq!(fraction(&coverage.modules["krate::io"].types), (1, 2));
+    assert_eq!(coverage.functions(), (3, 5));
+    assert_eq!(coverage.types(), (2, 3));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_markdown() {
+    let md = coverage().to_markdown();
+    assert!(md.starts_with(
+        "| Module | Functions with contracts | Types with invariants |\n|---|---|---|\n"
+    ));
+    assert!(md.contains("| `krate` | 2/3 (66%) | 1/1 (100%) |\n"));
+    assert!(md.contains("| `krate::io` | 0/1 (0%) | 1/2 (50%) |\n"));
+    assert!(md.contains("| `krate::util` | 1/1 (100%) | - |\n"));
+    assert!(md.ends_with("| **Total** | 3/5 (60%) | 2/3 (66%) |\n"));
+}
+
+#[test]
+fn test_json() {
+    let json: serde_json::Value = serde_json::from_str(&coverage().to_json()).unwrap();
+    assert_eq!(json["modules"][0]["path"], "krate");
+    assert_eq!(json["modules"][0]["functions"]["specified"], 2);
+    assert_eq!(json["modules"][0]["functions"]["total"], 3);
+    assert_eq!(json["modules"][0]["unspecified_functions"][0], "foo");
+    assert_eq!(json["modules"][1]["unspecified_types"][0], "Handle");
+    assert_eq!(json["total"]["functions"]["specified"], 3);
+    assert_eq!(json["total"]["types"]["total"], 3);
+}
+
+#[test]
+fn test_badge() {
+    let badge: serde_json::Value = serde_json::from_str(&coverage().to_badge()).unwrap();
+    assert_eq!(badge["schemaVersion"], 1);
+    assert_eq!(badge["label"], "contracts");
+    assert_eq!(badge["message"], "60%");
+    assert_eq!(badge["color"], "yellow");
+
+    let badge: serde_json::Value =
+        serde_json::from_str(&SpecCoverage::default().to_badge()).unwrap();
+    assert_eq!(badge["message"], "none");
+}
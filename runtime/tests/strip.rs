@@ -0,0 +1,76 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::fs;
+use std::path::PathBuf;
+
+use libhoare::strip::{check_binary, check_expansion, residue};
+
+mod common;
+
+fn temp_dir(name: &str) -> PathBuf {
+    common::temp_dir(&format!("strip-{}", name))
+}
+
+#[test]
+fn test_residue() {
+    assert!(residue(b"fn f(x: i32) -> i32 { x }").is_empty());
+    assert_eq!(
+        residue(b"\x00_ZN8libhoare5alloc\x00precondition of f (x > 0)\x00"),
+        vec!["libhoare", "precondition of"]
+    );
+}
+
+#[test]
+fn test_result_residue() {
+    let code = b"fn f(x: i32) -> i32 { let mut __result_1 = None; '__hoare_body_1: loop {} }";
+    assert_eq!(residue(code), vec!["__hoare", "__result"]);
+}
+
+#[test]
+fn test_check_binary() {
+    let dir = temp_dir("binary");
+    common::write(dir.join("clean"), b"\x7fELF\x00main\x00");
+    check_binary(dir.join("clean"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "found [\"invariant leaving\"]")]
+fn test_check_binary_residue() {
+    let dir = temp_dir("residue");
+    common::write(dir.join("bin"), b"\x7fELF\x00invariant leaving baz\x00");
+    check_binary(dir.join("bin"));
+}
+
+#[test]
+fn test_check_expansion() {
+    let dir = temp_dir("expansion");
+    common::write(dir.join("c.f.rs"), "pub fn f(x: i32) -> i32 { x }\n");
+    check_expansion(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "contract code left in the expansion of:\nc.g.rs: [\"__hoare\"]")]
+fn test_check_expansion_residue() {
+    let dir = temp_dir("expansion-residue");
+    common::write(dir.join("c.f.rs"), "pub fn f(x: i32) -> i32 { x }\n");
+    common::write(dir.join("c.g.rs"), "pub fn g() { let __hoare_result_1 = 0; }\n");
+    check_expansion(&dir);
+}
+
+#[test]
+#[should_panic(expected = "no expanded code in")]
+fn test_check_expansion_empty() {
+    check_expansion(temp_dir("empty"));
+}
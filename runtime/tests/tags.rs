@@ -0,0 +1,25 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::env;
+
+use libhoare::tags::{enabled, DISABLE_VAR};
+
+// The variable is read once, so this is the only test in this file.
+#[test]
+fn test_enabled() {
+    env::set_var(DISABLE_VAR, "expensive, slow,");
+    assert!(enabled("fast"));
+    assert!(!enabled("expensive"));
+    assert!(!enabled("slow"));
+    assert!(enabled(""));
+}
@@ -0,0 +1,138 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate libhoare;
+
+use std::panic;
+
+use libhoare::violation::{abort, catch, expect_violations, log, violations};
+use libhoare::{Violation, ViolationKind};
+
+#[test]
+fn test_log() {
+    let mut checked = 0;
+    log(|| checked += 1);
+    log(|| assert!(checked == 2, "precondition of f (x == 2)"));
+    assert_eq!(checked, 1);
+}
+
+#[test]
+fn test_abort_ok() {
+    let x = 2;
+    abort(|| assert!(x == 2, "precondition of f (x == 2)"));
+}
+
+#[test]
+fn test_other_panics() {
+    log(|| {});
+    // Panics outside a check still panic.
+    let result = panic::catch_unwind(|| panic!("not a contract"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_catch() {
+    assert!(catch(|| {}).is_none());
+    let y = 0;
+    let violation = catch(|| assert!(y > 0, "postcondition of g (y > 0)")).unwrap();
+    assert_eq!(violation.message(), "postcondition of g (y > 0)");
+    assert_eq!(violation.kind(), ViolationKind::Post);
+    assert_eq!(violation.fn_name(), Some("g"));
+    assert_eq!(violation.predicate(), Some("y > 0"));
+    let location = violation.location().unwrap();
+    assert!(location.file.ends_with("violation.rs"));
+    assert_eq!(location.line, line!() - 7);
+}
+
+#[test]
+fn test_parse() {
+    let violation = Violation::parse("precondition of foo ((x + 1) > y): reason");
+    assert_eq!(violation.kind(), ViolationKind::Pre);
+    assert_eq!(violation.fn_name(), Some("foo"));
+    assert_eq!(violation.predicate(), Some("(x + 1) > y"));
+    assert!(violation.location().is_none());
+
+    let violation = Violation::parse("invariant leaving set_x (self.x < self.y)");
+    assert_eq!(violation.kind(), ViolationKind::Invariant);
+    assert_eq!(violation.fn_name(), Some("set_x"));
+
+    let violation = Violation::parse("invariant of Bounds not established by new");
+    assert_eq!(violation.kind(), ViolationKind::Invariant);
+    assert_eq!(violation.fn_name(), Some("new"));
+    assert_eq!(violation.predicate(), None);
+
+    let violation = Violation::parse("loop invariant of sum (i <= n)");
+    assert_eq!(violation.kind(), ViolationKind::LoopInvariant);
+
+    let violation = Violation::parse("panic-freedom contract violated in get: oops");
+    assert_eq!(violation.kind(), ViolationKind::Other);
+    assert_eq!(violation.fn_name(), None);
+    assert_eq!(violation.message(), "panic-freedom contract violated in get: oops");
+
+    let violation = Violation::parse("contract predicate crashed in get (v[i] > 0): oops");
+    assert_eq!(violation.kind(), ViolationKind::Other);
+    assert_eq!(violation.fn_name(), Some("get"));
+    assert_eq!(violation.predicate(), Some("v[i] > 0"));
+}
+
+#[test]
+fn test_values() {
+    let violation =
+        Violation::parse("precondition of f (a.len() < n)\n  a.len() = 3\n  a = [\n    1,\n    ]");
+    assert_eq!(
+        violation.values(),
+        &[
+            ("a.len()".to_owned(), "3".to_owned()),
+            ("a".to_owned(), "[\n1,\n]".to_owned()),
+        ]
+    );
+
+    let violation = Violation::parse("postcondition of f (x == y)\n left: 1\nright: 2\n");
+    assert_eq!(
+        violation.values(),
+        &[("left".to_owned(), "1".to_owned()), ("right".to_owned(), "2".to_owned())]
+    );
+}
+
+#[test]
+fn test_json() {
+    let json = Violation::parse("postcondition of f (x == y)\n left: 1\nright: 2\n").to_json();
+    assert_eq!(
+        json,
+        "{\"fn_name\":\"f\",\"kind\":\"post\",\"location\":null,\
+         \"message\":\"postcondition of f (x == y)\\n left: 1\\nright: 2\\n\",\
+         \"predicate\":\"x == y\",\"values\":[{\"source\":\"left\",\"value\":\"1\"},\
+         {\"source\":\"right\",\"value\":\"2\"}]}"
+    );
+}
+
+#[test]
+fn test_violation_counts() {
+    let before = violations();
+    let y = 1;
+    log(|| assert!(y > 2, "precondition of f (y > 2)"));
+    assert_eq!(violations(), before + 1);
+
+    let x = 0;
+    let result = expect_violations(2, || {
+        log(|| assert!(x > 0, "precondition of f (x > 0)"));
+        log(|| assert!(x == 0, "precondition of f (x == 0)"));
+        log(|| assert!(x < 0, "postcondition of f (x < 0)"));
+        x + 1
+    });
+    assert_eq!(result, 1);
+    expect_violations(0, || log(|| {}));
+}
+
+#[test]
+#[should_panic(expected = "expected 1 contract violations, but there were 0")]
+fn test_missing_violation() {
+    expect_violations(1, || log(|| {}));
+}
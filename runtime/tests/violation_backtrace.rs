@@ -0,0 +1,33 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Separate from violation.rs, since whether backtraces are captured is decided
+// (from the environment) once per process.
+
+extern crate libhoare;
+
+use std::env;
+
+use libhoare::violation::catch;
+
+#[inline(never)]
+fn failing_check() {
+    panic!("precondition of f (x == 2)");
+}
+
+#[test]
+fn test_backtrace() {
+    env::set_var("RUST_LIB_BACKTRACE", "1");
+    let violation = catch(failing_check).unwrap();
+    assert_eq!(violation.message(), "precondition of f (x == 2)");
+    assert!(violation.backtrace().is_some());
+    let report = violation.to_string();
+    assert!(report.starts_with("contract violated: precondition of f (x == 2)\nstack backtrace:\n"));
+}
@@ -0,0 +1,376 @@
+// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Handling contract violations other than by panicking.
+//!
+//! With `on_violation = "log"` or `"abort"`, each check is passed to `log` or
+//! `abort`, which catch the panic of a failed check. This needs panics to
+//! unwind.
+//!
+//! A backtrace is captured where the check panics (before unwinding), if
+//! enabled by the `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment
+//! variables (with the same meaning as for `std::backtrace` in later versions
+//! of Rust), and printed with the message.
+//!
+//! Each thread counts the violations `log` has caught on it, see `violations`
+//! and `expect_violations`, so tests can check that invalid uses are detected
+//! without catching panics.
+//!
+//! `Violation` describes any failed contract. Every check the plugin (or
+//! `precond!` and `postcond!`) generates panics with a message of the form
+//! `<kind> <function> (<predicate>)`, maybe followed by the values involved,
+//! which `Violation::parse` splits into its parts, so that anything handling
+//! violations shares one description of them.
+
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+
+use backtrace::Backtrace;
+
+use panics::message;
+
+thread_local! {
+    static CHECKING: Cell<bool> = Cell::new(false);
+    // The backtrace and location of the last panic in a check.
+    static BACKTRACE: RefCell<Option<Backtrace>> = RefCell::new(None);
+    static LOCATION: RefCell<Option<Location>> = RefCell::new(None);
+    // The number of violations logged, and whether we are in `expect_violations`.
+    static LOGGED: Cell<usize> = Cell::new(0);
+    static EXPECTING: Cell<bool> = Cell::new(false);
+}
+
+/// The kind of contract which was violated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// A precondition (`precondition of`).
+    Pre,
+    /// A postcondition or transition (`postcondition of`, `transition of`), or
+    /// a check of a value a generator yields or completes with.
+    Post,
+    /// An invariant of a function or type, checked on entering or leaving a
+    /// function, by a setter, or after a constructor.
+    Invariant,
+    /// A loop invariant (`loop invariant of`).
+    LoopInvariant,
+    /// Any other contract, e.g., a protocol or `#[no_panic]`, or a predicate
+    /// which panicked (`contract predicate crashed in`).
+    Other,
+}
+
+// The start of the messages of each kind of contract which is followed by the
+// name of the function. Longer prefixes come first.
+const PREFIXES: &[(&str, ViolationKind)] = &[
+    ("precondition of ", ViolationKind::Pre),
+    ("postcondition of ", ViolationKind::Post),
+    ("transition of ", ViolationKind::Post),
+    ("yield of ", ViolationKind::Post),
+    ("completion of ", ViolationKind::Post),
+    ("loop invariant of ", ViolationKind::LoopInvariant),
+    ("invariant entering ", ViolationKind::Invariant),
+    ("invariant leaving ", ViolationKind::Invariant),
+    ("invariant suspending ", ViolationKind::Invariant),
+    ("invariant after atomic section in ", ViolationKind::Invariant),
+    ("monotonic invariant of ", ViolationKind::Invariant),
+    ("set-once invariant of ", ViolationKind::Invariant),
+    ("incremental invariant of ", ViolationKind::Invariant),
+    ("contract predicate crashed in ", ViolationKind::Other),
+];
+
+impl ViolationKind {
+    /// The name of the kind in JSON, e.g., `pre` or `loop_invariant`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ViolationKind::Pre => "pre",
+            ViolationKind::Post => "post",
+            ViolationKind::Invariant => "invariant",
+            ViolationKind::LoopInvariant => "loop_invariant",
+            ViolationKind::Other => "other",
+        }
+    }
+}
+
+/// Where a check failed in the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// A violated contract.
+#[derive(Debug)]
+pub struct Violation {
+    kind: ViolationKind,
+    fn_name: Option<String>,
+    predicate: Option<String>,
+    location: Option<Location>,
+    values: Vec<(String, String)>,
+    message: String,
+    backtrace: Option<Backtrace>,
+}
+
+impl Violation {
+    /// Describes a failed check from its panic message, e.g.,
+    /// `precondition of foo (x > 0)`. Messages which are not in the form of a
+    /// contract's are violations of kind `Other` with only a message.
+    pub fn parse(message: &str) -> Violation {
+        let mut violation = Violation {
+            kind: ViolationKind::Other,
+            fn_name: None,
+            predicate: None,
+            location: None,
+            values: Vec::new(),
+            message: message.to_owned(),
+            backtrace: None,
+        };
+        let mut lines = message.lines();
+        let first = lines.next().unwrap_or("");
+
+        if let Some(&(prefix, kind)) = PREFIXES.iter().find(|p| first.starts_with(p.0)) {
+            let rest = &first[prefix.len()..];
+            violation.kind = kind;
+            let (fn_name, rest) = rest.split_at(rest.find(' ').unwrap_or(rest.len()));
+            violation.fn_name = Some(fn_name.to_owned());
+            violation.predicate = parenthesized(rest.trim()).map(str::to_owned);
+        } else if let Some(rest) = strip_prefix(first, "invariant of ") {
+            // `invariant of T not established by f`
+            if let Some(i) = rest.find(" not established by ") {
+                violation.kind = ViolationKind::Invariant;
+                violation.fn_name = Some(rest[i + " not established by ".len()..].to_owned());
+            }
+            // `invariant of T (pred) violated in f` (see checked.rs)
+            if let Some(i) = rest.rfind(" violated in ") {
+                violation.kind = ViolationKind::Invariant;
+                violation.fn_name = Some(rest[i + " violated in ".len()..].to_owned());
+                let (_, rest) = rest.split_at(rest.find(' ').unwrap_or(rest.len()));
+                violation.predicate = parenthesized(rest.trim()).map(str::to_owned);
+            }
+        }
+
+        // Values are given as `  src = value` (see explain.rs, continuation
+        // lines are indented further) or ` left: value` and `right: value`
+        // (see diff.rs).
+        for line in lines {
+            if let Some(value) = strip_prefix(line, " left: ") {
+                violation.values.push(("left".to_owned(), value.to_owned()));
+            } else if let Some(value) = strip_prefix(line, "right: ") {
+                violation.values.push(("right".to_owned(), value.to_owned()));
+            } else if let Some(continuation) = strip_prefix(line, "    ") {
+                if let Some(last) = violation.values.last_mut() {
+                    last.1.push('\n');
+                    last.1.push_str(continuation);
+                }
+            } else if let Some(value) = strip_prefix(line, "  ") {
+                if let Some(i) = value.find(" = ") {
+                    let (source, value) = (&value[..i], &value[i + " = ".len()..]);
+                    violation.values.push((source.to_owned(), value.to_owned()));
+                }
+            }
+        }
+        violation
+    }
+
+    /// The kind of contract.
+    pub fn kind(&self) -> ViolationKind {
+        self.kind
+    }
+
+    /// The name of the function (or method) whose contract was violated.
+    pub fn fn_name(&self) -> Option<&str> {
+        self.fn_name.as_ref().map(|fn_name| &**fn_name)
+    }
+
+    /// The predicate which did not hold, as written in the contract.
+    pub fn predicate(&self) -> Option<&str> {
+        self.predicate.as_ref().map(|predicate| &**predicate)
+    }
+
+    /// Where the check failed, if the violation was caught by `catch`.
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    /// The values of the parts of the predicate, as `(source, value)`, if the
+    /// message includes them (see the `diff` and `explain` modules).
+    pub fn values(&self) -> &[(String, String)] {
+        &self.values
+    }
+
+    /// The panic message of the failed check.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Where the check failed, if backtraces are enabled.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+
+    /// The violation as a JSON object, with the fields `kind`, `fn_name`,
+    /// `predicate`, `location` (with `file` and `line`), `values`
+    /// (objects with `source` and `value`), and `message`. Missing parts are
+    /// `null`.
+    pub fn to_json(&self) -> String {
+        let location = self.location.as_ref().map(|location| {
+            json!({
+                "file": location.file,
+                "line": location.line,
+            })
+        });
+        let values: Vec<_> = self
+            .values
+            .iter()
+            .map(|&(ref source, ref value)| json!({ "source": source, "value": value }))
+            .collect();
+        json!({
+            "kind": self.kind.as_str(),
+            "fn_name": self.fn_name,
+            "predicate": self.predicate,
+            "location": location,
+            "values": values,
+            "message": self.message,
+        })
+        .to_string()
+    }
+}
+
+// s without prefix, if it starts with it.
+fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.starts_with(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+// The text inside the parentheses s starts with, e.g., `x > (y + 1)` for
+// `(x > (y + 1)): reason`.
+fn parenthesized(s: &str) -> Option<&str> {
+    if !s.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&s[1..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "contract violated: {}", self.message)?;
+        if let Some(ref backtrace) = self.backtrace {
+            write!(f, "\nstack backtrace:\n{:?}", backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `check`, if it panics, prints the violation to stderr (unless in
+/// `expect_violations`) and continues.
+pub fn log<F: FnOnce()>(check: F) {
+    if let Some(violation) = catch(check) {
+        LOGGED.with(|logged| logged.set(logged.get() + 1));
+        if !EXPECTING.with(|expecting| expecting.get()) {
+            eprintln!("{}", violation);
+        }
+    }
+}
+
+/// The number of violations `log` has caught on the current thread.
+pub fn violations() -> usize {
+    LOGGED.with(|logged| logged.get())
+}
+
+/// Runs `f`, which should cause `n` violations of contracts with
+/// `on_violation = "log"` on the current thread, and returns its result. The
+/// violations are not printed.
+///
+/// # Panics
+///
+/// If `f` causes a different number of violations.
+pub fn expect_violations<F: FnOnce() -> R, R>(n: usize, f: F) -> R {
+    let before = violations();
+    let outer = EXPECTING.with(|expecting| expecting.replace(true));
+    let result = f();
+    EXPECTING.with(|expecting| expecting.set(outer));
+    let count = violations() - before;
+    if count != n {
+        panic!("expected {} contract violations, but there were {}", n, count);
+    }
+    result
+}
+
+/// Runs `check`, if it panics, prints the violation to stderr and aborts the
+/// process.
+pub fn abort<F: FnOnce()>(check: F) {
+    if let Some(violation) = catch(check) {
+        eprintln!("{}", violation);
+        process::abort();
+    }
+}
+
+/// Runs `check`, returning the violation if it panics. The panic hook is not
+/// called for such panics, since the caller reports them.
+pub fn catch<F: FnOnce()>(check: F) -> Option<Violation> {
+    lazy_static! {
+        static ref HOOK: () = {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if !CHECKING.with(|checking| checking.get()) {
+                    return hook(info);
+                }
+                if *CAPTURE_BACKTRACES {
+                    BACKTRACE.with(|backtrace| *backtrace.borrow_mut() = Some(Backtrace::new()));
+                }
+                let location = info.location().map(|location| Location {
+                    file: location.file().to_owned(),
+                    line: location.line(),
+                });
+                LOCATION.with(|l| *l.borrow_mut() = location);
+            }));
+        };
+        // Whether backtraces are enabled, which (as for `std::backtrace`) is
+        // decided once per process.
+        static ref CAPTURE_BACKTRACES: bool =
+            match env::var("RUST_LIB_BACKTRACE").or_else(|_| env::var("RUST_BACKTRACE")) {
+                Ok(value) => value != "0",
+                Err(_) => false,
+            };
+    }
+    lazy_static::initialize(&HOOK);
+
+    let outer = CHECKING.with(|checking| checking.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(check));
+    CHECKING.with(|checking| checking.set(outer));
+    result.err().map(|payload| {
+        let mut violation = Violation::parse(message(&*payload));
+        violation.location = LOCATION.with(|location| location.borrow_mut().take());
+        violation.backtrace = BACKTRACE.with(|backtrace| backtrace.borrow_mut().take());
+        violation
+    })
+}
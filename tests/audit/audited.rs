@@ -0,0 +1,54 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_audit.rs, compiled with `--cfg hoare_audit`.
+
+#![feature(plugin, custom_attribute, stmt_expr_attributes)]
+#![plugin(hoare)]
+
+use std::panic;
+
+struct Items {
+    items: Vec<u32>,
+    // Wrong on purpose, so the audited predicate fails.
+    len: usize,
+}
+
+impl Items {
+    // Also postconditions, to check the block is audited once.
+    #[precond="i < self.len"]
+    #[postcond="return > 0"]
+    fn get(&self, i: usize) -> u32 {
+        #[audited_unsafe(reason="the precondition bounds i", pred="i < self.items.len()")]
+        unsafe { *self.items.get_unchecked(i) }
+    }
+
+    #[precond="self.len > 0"]
+    fn first(&self) -> u32 {
+        #[audited_unsafe="the vector is never empty"]
+        unsafe { *self.items.get_unchecked(0) }
+    }
+}
+
+fn main() {
+    let items = Items {
+        items: vec![1, 2],
+        len: 3,
+    };
+    assert_eq!(items.get(1), 2);
+    assert_eq!(items.first(), 1);
+
+    panic::set_hook(Box::new(|_| {}));
+    let err = panic::catch_unwind(|| items.get(2)).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<String>().unwrap(),
+        "audited unsafe block in get (i < self.items.len()): the precondition bounds i"
+    );
+}
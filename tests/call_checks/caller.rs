@@ -0,0 +1,83 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_call_checks.rs, which calls checked_lib.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate checked_lib;
+
+use std::panic;
+
+#[check_calls]
+mod app {
+    use checked_lib::parser::{self, parse};
+
+    pub fn parse_input(input: &str) -> usize {
+        parse(input)
+    }
+
+    pub fn span(lo: u32, hi: u32) -> u32 {
+        // The names of the parameters don't clash with the arguments.
+        let (hi, lo) = (lo, hi);
+        parser::span(hi, lo)
+    }
+
+    pub fn full_path(input: &str) -> usize {
+        ::checked_lib::parser::parse(input)
+    }
+
+    pub fn private() -> i32 {
+        parser::call_private()
+    }
+}
+
+// Not annotated, so not checked.
+fn unchecked(input: &str) -> usize {
+    checked_lib::parser::parse(input)
+}
+
+fn message(f: fn()) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    match err.downcast_ref::<String>() {
+        Some(msg) => msg.clone(),
+        None => err.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+fn main() {
+    panic::set_hook(Box::new(|_| {}));
+
+    assert_eq!(app::parse_input("abc"), 3);
+    assert_eq!(app::span(1, 5), 4);
+    assert_eq!(app::private(), 0);
+    assert_eq!(unchecked(""), 0);
+
+    let msg = message(|| {
+        app::parse_input("");
+    });
+    assert_eq!(
+        msg,
+        "precondition of checked_lib::parser::parse (!s.is_empty()) checked by the caller"
+    );
+    let msg = message(|| {
+        app::full_path("");
+    });
+    assert!(msg.starts_with("precondition of checked_lib::parser::parse"), "{}", msg);
+    let msg = message(|| {
+        app::span(5, 1);
+    });
+    assert_eq!(msg, "precondition of checked_lib::parser::span (lo <= hi) checked by the caller");
+    let msg = message(|| {
+        app::span(1, 500);
+    });
+    assert_eq!(msg, "precondition of checked_lib::parser::span (hi <= 100) checked by the caller");
+}
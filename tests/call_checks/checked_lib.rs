@@ -0,0 +1,38 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A library for test_call_checks.rs, whose contracts are not checked.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![crate_type = "lib"]
+
+pub mod parser {
+    #[precond(pred="!s.is_empty()", level="off")]
+    pub fn parse(s: &str) -> usize {
+        s.len()
+    }
+
+    #[precond(pred="lo <= hi", level="off")]
+    #[precond(pred="hi <= 100", level="off")]
+    pub fn span(lo: u32, hi: u32) -> u32 {
+        hi.wrapping_sub(lo)
+    }
+
+    // Not exported, it isn't public.
+    #[precond(pred="x > 0", level="off")]
+    fn private(x: i32) -> i32 {
+        x
+    }
+
+    pub fn call_private() -> i32 {
+        private(0)
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Helpers for the tests which compile crates with the plugin and run them.
+
+#![allow(dead_code)]
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The directory of the test's crates, tests/dir.
+pub fn root(dir: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(dir)
+}
+
+// A rustc command which finds the plugin and the runtime crate, which are next
+// to this test.
+pub fn rustc() -> Command {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_owned();
+    let mut command = Command::new(env::var("RUSTC").unwrap_or("rustc".to_owned()));
+    command.arg("-L").arg(deps);
+    command
+}
+
+// A new directory for the test's output, removing any left by an earlier run.
+// The time in its name keeps concurrent runs of the same test apart.
+pub fn out_dir(name: &str) -> PathBuf {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let out = env::temp_dir().join(format!(
+        "hoare-{}-{}{:09}",
+        name,
+        now.as_secs(),
+        now.subsec_nanos()
+    ));
+    let _ = fs::remove_dir_all(&out);
+    fs::create_dir_all(&out).unwrap();
+    out
+}
+
+pub fn read(path: &Path) -> String {
+    let mut contents = String::new();
+    File::open(path).and_then(|mut file| file.read_to_string(&mut contents)).unwrap();
+    contents
+}
+
+pub fn write(path: &Path, contents: &str) {
+    File::create(path).and_then(|mut file| file.write_all(contents.as_bytes())).unwrap();
+}
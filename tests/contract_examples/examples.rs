@@ -0,0 +1,35 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_contract_examples.rs, whose expansion is checked.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![crate_type = "lib"]
+#![allow(unused_variables)]
+
+#[contract_examples]
+#[precond="x > 0 && x <= 100"]
+pub fn bounded(x: i32) {}
+
+// Every u32 satisfies the precondition, so there is no call which panics.
+#[contract_examples]
+#[precond="x >= 0"]
+pub fn unsigned(x: u32) {}
+
+// The first comparison can't be violated, the second can.
+#[contract_examples]
+#[precond="x >= 0 && x < 10"]
+pub fn small(x: u32) {}
+
+// The literal doesn't fit in an i64, so there are no examples.
+#[contract_examples]
+#[precond="x < 18446744073709551615"]
+pub fn huge(x: u64) {}
@@ -0,0 +1,42 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_coverage.rs, compiled with `--cfg hoare_coverage` both as a
+// library and as its test harness.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![crate_type = "lib"]
+
+extern crate libhoare;
+
+#[precond="x > 0"]
+pub fn positive(x: i32) -> i32 {
+    x
+}
+
+#[precond="x < 0"]
+pub fn negative(x: i32) -> i32 {
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    // Only in the test harness, so it is only in its manifest.
+    #[precond="x < 10"]
+    fn small(x: i32) -> i32 {
+        x
+    }
+
+    #[test]
+    fn test_positive() {
+        assert_eq!(small(super::positive(1)), 1);
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_expansion.rs, the expansion of each function is compared with
+// the snapshot in snapshots/.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+#[precond = "x > 0"]
+pub fn precond(x: i32) -> i32 {
+    x
+}
+
+#[postcond = "return > x"]
+pub fn postcond(x: i32) -> i32 {
+    if x > 10 {
+        return x + 2;
+    }
+    x + 1
+}
+
+#[precond = "x > 0"]
+#[postcond = "return > 1"]
+pub fn stacked(x: i32) -> i32 {
+    45 / x + 1
+}
+
+pub struct Bar {
+    pub f1: i32,
+    pub f2: i32,
+}
+
+impl Bar {
+    #[invariant = "self.f1 < self.f2"]
+    pub fn baz(&mut self) {
+        self.f1 += 10;
+        self.f2 += 10;
+    }
+}
+
+#[postcond = "return > 0"]
+pub fn early(x: i32) -> i32 {
+    if x < 0 {
+        return 1;
+    }
+    x + 1
+}
@@ -0,0 +1,51 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_strip.rs, compiled without debug assertions and with the tag
+// `slow` disabled, so none of these contracts are checked.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+#[precond(pred="x > 0", level="off")]
+#[postcond(pred="return > x", level="off")]
+pub fn off(x: i32) -> i32 {
+    if x > 10 {
+        return x + 2;
+    }
+    x + 1
+}
+
+#[debug_precond="x > 0"]
+#[debug_postcond="return > x"]
+pub fn debug_only(x: i32) -> i32 {
+    if x > 10 {
+        return x + 2;
+    }
+    x + 1
+}
+
+#[postcond(pred="return.iter().all(|&x| x > 0)", tag="slow")]
+pub fn tagged(xs: &[i32]) -> Vec<i32> {
+    xs.iter().map(|x| x.abs() + 1).collect()
+}
+
+pub struct Counter {
+    pub count: u32,
+}
+
+impl Counter {
+    #[debug_transition="self.count == old(self.count) + 1"]
+    #[invariant(pred="self.count > 0", level="off")]
+    pub fn incr(&mut self) -> u32 {
+        self.count += 1;
+        self.count
+    }
+}
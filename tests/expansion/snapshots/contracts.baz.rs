@@ -0,0 +1,10 @@
+pub fn baz(&mut self) {
+    assert!(self.f1 < self.f2 , "invariant entering baz (self.f1 < self.f2)");
+    let __result_5 =
+        {
+            self.f1 += 10;
+            self.f2 += 10;
+        };
+    assert!(self.f1 < self.f2 , "invariant leaving baz (self.f1 < self.f2)");
+    __result_5
+}
@@ -0,0 +1,12 @@
+pub fn early(x: i32) -> i32 {
+    #[doc(hidden)]
+    fn __hoare_body_early(x: i32) -> i32 {
+        if x < 0 {
+            return 1;
+        }
+        x + 1
+    }
+    let __result_6: i32 = __hoare_body_early(x);
+    assert!(__result_6 > 0 , "postcondition of early (__result_6 > 0)");
+    __result_6
+}
@@ -0,0 +1,16 @@
+pub fn postcond(x: i32) -> i32 {
+    let mut __result_2: Option<i32> = None;
+    '__hoare_body_2: loop  {
+        if x > 10 {
+            {
+                __result_2 = Some(x + 2);
+                break '__hoare_body_2
+            };
+        }
+        __result_2 = Some(x + 1);
+        break '__hoare_body_2
+    }
+    let __result_2 = __result_2.unwrap();
+    assert!(__result_2 > x , "postcondition of postcond (__result_2 > x)");
+    __result_2
+}
@@ -0,0 +1,8 @@
+pub fn precond(x: i32) -> i32 {
+    assert!(x > 0 , "precondition of precond (x > 0)");
+    let __result_1: i32 =
+        {
+            x
+        };
+    __result_1
+}
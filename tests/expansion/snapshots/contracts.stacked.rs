@@ -0,0 +1,13 @@
+pub fn stacked(x: i32) -> i32 {
+    let __result_4: i32 =
+        {
+            assert!(x > 0 , "precondition of stacked (x > 0)");
+            let __result_3: i32 =
+                {
+                    45 / x + 1
+                };
+            __result_3
+        };
+    assert!(__result_4 > 1 , "postcondition of stacked (__result_4 > 1)");
+    __result_4
+}
@@ -0,0 +1,46 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_field_audit.rs, whose warnings are checked.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![crate_type = "lib"]
+#![allow(dead_code)]
+
+extern crate libhoare;
+
+// Warned about: the fields can be written without checking the invariant.
+#[invariant="self.min <= self.max"]
+pub struct Bounds {
+    pub min: i32,
+    pub(crate) max: i32,
+    step: i32,
+}
+
+#[invariant="self.0 > 0"]
+pub struct Positive(pub i32);
+
+#[invariant="self.min <= self.max"]
+#[invariant_setters]
+pub struct Range {
+    pub min: i32,
+    max: i32,
+}
+
+// Not warned about: only the struct's module can write the fields.
+#[invariant="self.min <= self.max"]
+pub struct Private {
+    min: i32,
+    max: i32,
+}
+
+#[invariant="self.0 > 0"]
+pub struct Hidden(i32);
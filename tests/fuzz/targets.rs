@@ -0,0 +1,82 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_fuzz.rs, compiled with `--cfg fuzzing`, as `cargo fuzz` does.
+// Calls the generated fuzz targets with hand-built inputs: arbitrary decodes
+// integers from little-endian bytes, and missing bytes as zeros.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate arbitrary;
+
+use std::cell::Cell;
+use std::panic;
+
+thread_local! {
+    static CALLS: Cell<u32> = Cell::new(0);
+}
+
+fn called() {
+    CALLS.with(|calls| calls.set(calls.get() + 1));
+}
+
+fn calls() -> u32 {
+    CALLS.with(|calls| calls.get())
+}
+
+#[fuzz]
+#[precond="x > 0"]
+#[postcond="return > 1"]
+pub fn foo(x: i32) -> i32 {
+    called();
+    45 / x + 1
+}
+
+#[fuzz]
+#[invariant="x < y"]
+pub fn ordered(x: u8, y: u8) -> u8 {
+    called();
+    y - x
+}
+
+#[fuzz]
+#[invariant(pred="return > x / 2", exit_only)]
+pub fn half(x: u8) -> u8 {
+    called();
+    x / 2 + 1
+}
+
+fn main() {
+    // Inputs which break the precondition are skipped.
+    fuzz_foo(&[0, 0, 0, 0]);
+    fuzz_foo(&[0xff, 0xff, 0xff, 0xff]);
+    fuzz_foo(&[]);
+    assert_eq!(calls(), 0);
+
+    // Otherwise the function is called.
+    fuzz_foo(&[5, 0, 0, 0]);
+    assert_eq!(calls(), 1);
+
+    // A violated postcondition panics, which the fuzzer reports as a crash.
+    assert!(panic::catch_unwind(|| fuzz_foo(&[100, 0, 0, 0])).is_err());
+    assert_eq!(calls(), 2);
+
+    // So are inputs which break an entry invariant.
+    fuzz_ordered(&[2, 1]);
+    fuzz_ordered(&[1, 1]);
+    assert_eq!(calls(), 2);
+    fuzz_ordered(&[1, 2]);
+    assert_eq!(calls(), 3);
+
+    // But not exit-only invariants, which may refer to `return`.
+    fuzz_half(&[7]);
+    assert_eq!(calls(), 4);
+}
@@ -0,0 +1,30 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_inline.rs, whose expansion is checked with and without debug
+// assertions.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![hoare_policy(predicates="fn")]
+#![crate_type = "lib"]
+#![allow(dead_code)]
+
+extern crate libhoare;
+
+#[precond="x > 0"]
+#[invariant="x < 100"]
+pub fn bounded(x: i32) {}
+
+#[invariant="self.min <= self.max"]
+pub struct Bounds {
+    min: i32,
+    max: i32,
+}
@@ -0,0 +1,68 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_inlining.rs, compiled as a release build.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+use std::panic;
+
+#[precond="x > 0"]
+#[postcond="return > x"]
+fn small(x: i32) -> i32 {
+    x + 1
+}
+
+#[inline(never)]
+#[precond="x > 0"]
+fn never(x: i32) -> i32 {
+    x
+}
+
+#[postcond="return < 10"]
+fn large(x: i32) -> i32 {
+    let y = x * 2;
+    let z = y + 1;
+    if z > 100 {
+        return 0;
+    }
+    z
+}
+
+fn message(f: fn()) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    match err.downcast_ref::<String>() {
+        Some(msg) => msg.clone(),
+        None => err.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+fn main() {
+    panic::set_hook(Box::new(|_| {}));
+
+    assert_eq!(small(1), 2);
+    assert_eq!(never(1), 1);
+    assert_eq!(large(1), 3);
+    assert_eq!(large(100), 0);
+
+    let msg = message(|| {
+        small(0);
+    });
+    assert_eq!(msg, "precondition of small (x > 0)");
+    let msg = message(|| {
+        never(-1);
+    });
+    assert_eq!(msg, "precondition of never (x > 0)");
+    let msg = message(|| {
+        large(5);
+    });
+    assert!(msg.starts_with("postcondition of large"), "{}", msg);
+}
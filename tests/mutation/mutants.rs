@@ -0,0 +1,41 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_mutation.rs, compiled with `--cfg hoare_mutants` both as a
+// library and as its test harness.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![crate_type = "lib"]
+
+extern crate libhoare;
+
+#[precond="x > 0"]
+pub fn positive(x: i32) -> i32 {
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    // Only in the test harness, so its mutants are only in its manifest.
+    #[precond="x < 10"]
+    fn small(x: i32) -> i32 {
+        x
+    }
+
+    #[test]
+    fn test_zero_rejected() {
+        assert_eq!(small(1), 1);
+        assert_eq!(super::positive(1), 1);
+        assert!(panic::catch_unwind(|| super::positive(0)).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// Input for test_plugin_args.rs, whose contracts are off by default.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare(level="off", strict))]
+
+use std::panic;
+
+#[precond="x > 0"]
+fn unchecked(x: i32) -> i32 {
+    x
+}
+
+#[precond(pred="x > 0", level="always")]
+fn checked(x: i32) -> i32 {
+    x
+}
+
+fn main() {
+    panic::set_hook(Box::new(|_| {}));
+
+    assert_eq!(unchecked(0), 0);
+    assert!(panic::catch_unwind(|| checked(0)).is_err());
+    assert_eq!(checked(1), 1);
+}
@@ -0,0 +1,28 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// Input for test_plugin_args.rs, which does not compile.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare(strict))]
+
+#[postcond="Vec::push(&mut v.clone(), 0) == ()"]
+fn borrows(v: &[i32]) -> usize {
+    v.len()
+}
+
+#[precond="{ *x += 1; true }"]
+fn increments(x: &mut i32) {}
+
+#[precond="unsafe { *p > 0 }"]
+fn reads(p: *const i32) {}
+
+fn main() {}
@@ -0,0 +1,58 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_profile.rs, compiled with hoare.toml as the contract profile.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+use std::panic;
+
+mod checked {
+    #[precond="x > 0"]
+    pub fn positive(x: i32) -> i32 {
+        x
+    }
+
+    // Turned off in the profile.
+    #[precond="x > 0"]
+    pub fn unchecked(x: i32) -> i32 {
+        x
+    }
+}
+
+mod unchecked {
+    #[precond="x > 0"]
+    pub fn positive(x: i32) -> i32 {
+        x
+    }
+
+    // Overrides the contract's own level.
+    #[precond(pred="x > 0", level="always")]
+    pub fn always(x: i32) -> i32 {
+        x
+    }
+
+    // Enabled again in the profile.
+    pub mod inner {
+        #[precond="x > 0"]
+        pub fn positive(x: i32) -> i32 {
+            x
+        }
+    }
+}
+
+fn main() {
+    assert!(panic::catch_unwind(|| checked::positive(0)).is_err());
+    assert_eq!(checked::unchecked(0), 0);
+    assert_eq!(unchecked::positive(0), 0);
+    assert_eq!(unchecked::always(0), 0);
+    assert!(panic::catch_unwind(|| unchecked::inner::positive(0)).is_err());
+}
@@ -0,0 +1,42 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A library of predicates for test_shared_predicates.rs.
+
+#![crate_type = "lib"]
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+pub mod seq {
+    #[predicate]
+    pub fn sorted(xs: &[i32]) -> bool {
+        xs.windows(2).all(|w| w[0] <= w[1])
+    }
+}
+
+mod text {
+    // Re-exported at the crate root.
+    #[predicate(path="identifier")]
+    pub fn identifier(s: &str) -> bool {
+        !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+    }
+}
+
+pub use text::identifier;
+
+#[predicate]
+pub fn within<T: PartialOrd>(x: T, lo: T, hi: T) -> bool {
+    lo <= x && x <= hi
+}
+
+// Not a predicate, but can still be called from contracts.
+pub fn max_len() -> usize {
+    8
+}
@@ -0,0 +1,66 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_shared_predicates.rs, which uses the predicates of specs.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate specs;
+
+use std::panic;
+
+mod search {
+    #[precond="specs::sorted(xs)"]
+    pub fn contains(xs: &[i32], x: i32) -> bool {
+        xs.binary_search(&x).is_ok()
+    }
+
+    pub mod names {
+        #[precond="specs::identifier(name) && name.len() <= specs::max_len()"]
+        #[postcond="implies!(specs::within(n, 0, 9), specs::within::<usize>(return, 1, 5))"]
+        pub fn tag(name: &str, n: usize) -> usize {
+            if n < 10 { name.len() } else { 0 }
+        }
+    }
+}
+
+fn message(f: fn()) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    match err.downcast_ref::<String>() {
+        Some(msg) => msg.clone(),
+        None => err.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+fn main() {
+    panic::set_hook(Box::new(|_| {}));
+
+    assert!(search::contains(&[1, 2, 3], 2));
+    assert_eq!(search::names::tag("abc", 3), 3);
+    assert_eq!(search::names::tag("abc", 30), 0);
+
+    let msg = message(|| {
+        search::contains(&[3, 2], 2);
+    });
+    assert_eq!(msg, "precondition of contains (specs::sorted(xs))");
+    let msg = message(|| {
+        search::names::tag("a b", 1);
+    });
+    assert!(msg.starts_with("precondition of tag (specs::identifier(name)"), "{}", msg);
+    let msg = message(|| {
+        search::names::tag("abcdefghijk", 1);
+    });
+    assert!(msg.starts_with("precondition of tag"), "{}", msg);
+    let msg = message(|| {
+        search::names::tag("abcdefg_", 1);
+    });
+    assert!(msg.starts_with("postcondition of tag"), "{}", msg);
+}
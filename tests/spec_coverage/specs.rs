@@ -0,0 +1,66 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Input for test_spec_coverage.rs, compiled as a library.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![crate_type = "lib"]
+
+extern crate libhoare;
+
+pub mod io;
+
+#[precond="x > 0"]
+pub fn specified(x: i32) -> i32 {
+    x
+}
+
+pub fn unspecified(x: i32) -> i32 {
+    x
+}
+
+fn private(x: i32) -> i32 {
+    x
+}
+
+#[cfg(not(hoare_spec_coverage))]
+pub fn not_compiled() {}
+
+#[invariant="self.lo <= self.hi"]
+pub struct Bounds {
+    lo: i32,
+    hi: i32,
+}
+
+impl Bounds {
+    #[precond="lo <= hi"]
+    pub fn new(lo: i32, hi: i32) -> Bounds {
+        Bounds { lo: lo, hi: hi }
+    }
+
+    pub fn lo(&self) -> i32 {
+        private(self.lo)
+    }
+
+    #[allow(dead_code)]
+    fn hi(&self) -> i32 {
+        self.hi
+    }
+}
+
+pub mod util {
+    pub struct Unchecked(pub i32);
+
+    #[postcond="return >= 0"]
+    pub fn abs(x: i32) -> i32 {
+        x.abs()
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute, stmt_expr_attributes)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="self.min <= self.max"]
+struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+#[check_atomic_sections]
+impl Bounds {
+    fn shift(&mut self, by: i32) {
+        #[atomic_section] {
+            self.max += by;
+            self.min += by;
+        }
+    }
+
+    fn set(&mut self, min: i32, max: i32) -> i32 {
+        let width = #[atomic_section] {
+            self.min = min;
+            self.max = max;
+            max - min
+        };
+        width
+    }
+}
+
+#[test]
+fn test_atomic_sections() {
+    let mut b = Bounds { min: 0, max: 2 };
+    b.shift(-5);
+    assert!(b.min == -5 && b.max == -3);
+    assert!(b.set(1, 4) == 3);
+}
+
+#[test]
+#[should_panic(expected = "invariant after atomic section in set")]
+fn test_not_restored() {
+    let mut b = Bounds { min: 0, max: 2 };
+    b.set(3, 1);
+}
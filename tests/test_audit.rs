@@ -0,0 +1,46 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of audited unsafe blocks. Compiles audit/audited.rs with
+// `--cfg hoare_audit`, checks the audit manifest, and runs it.
+
+use std::fs;
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn test_audit() {
+    let out = common::out_dir("audit");
+    let output = common::rustc()
+        .args(&["--cfg", "hoare_audit", "--out-dir"])
+        .arg(&out)
+        .arg(common::root("audit").join("audited.rs"))
+        .env("HOARE_AUDIT_DIR", out.join("audit"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let manifest = common::read(&out.join("audit/audited.audit"));
+    let lines: Vec<&str> = manifest.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", manifest);
+    assert!(lines[0].starts_with("audited::get\t"), "{}", manifest);
+    assert!(
+        lines[0].ends_with("\tthe precondition bounds i\ti < self.items.len()"),
+        "{}",
+        manifest
+    );
+    assert!(lines[1].starts_with("audited::first\t"), "{}", manifest);
+    assert!(lines[1].ends_with("\tthe vector is never empty\t"), "{}", manifest);
+
+    let status = Command::new(out.join("audited")).status().unwrap();
+    assert!(status.success());
+    fs::remove_dir_all(&out).unwrap();
+}
@@ -0,0 +1,84 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate futures;
+extern crate libhoare;
+
+use futures::{future, Future};
+
+trait Store {
+    fn get(&self, key: u32) -> Box<Future<Item = String, Error = ()>>;
+    fn len(&self, key: u32) -> Box<Future<Item = usize, Error = ()> + Send>;
+}
+
+struct Memory {
+    values: Vec<String>,
+}
+
+impl Store for Memory {
+    #[precond="key < 10"]
+    #[postcond="!return.is_empty()"]
+    fn get(&self, key: u32) -> Box<Future<Item = String, Error = ()>> {
+        match self.values.get(key as usize) {
+            Some(value) => Box::new(future::ok(value.clone())),
+            None => Box::new(future::err(())),
+        }
+    }
+
+    #[postcond="return <= key as usize"]
+    fn len(&self, key: u32) -> Box<Future<Item = usize, Error = ()> + Send> {
+        if key == 0 {
+            return Box::new(future::ok(0));
+        }
+        let len = self.values.get(key as usize).map_or(0, |value| value.len());
+        Box::new(future::ok(len))
+    }
+}
+
+fn memory() -> Memory {
+    Memory { values: vec!["zero".to_owned(), String::new(), "two".to_owned()] }
+}
+
+#[test]
+fn test_resolved() {
+    let memory = memory();
+    assert!(memory.get(0).wait() == Ok("zero".to_owned()));
+    assert!(memory.get(5).wait() == Err(()));
+    assert!(memory.len(0).wait() == Ok(0));
+    assert!(memory.len(1).wait() == Ok(0));
+}
+
+#[test]
+fn test_checked_when_resolved() {
+    let memory = memory();
+    // Creating the future doesn't check the postcondition.
+    let _unresolved = memory.get(1);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of get")]
+fn test_item_violates_postcond() {
+    memory().get(1).wait().ok();
+}
+
+#[test]
+#[should_panic(expected = "postcondition of len")]
+fn test_item_violates_postcond_send() {
+    memory().len(2).wait().ok();
+}
+
+#[test]
+#[should_panic(expected = "precondition of get")]
+fn test_precond_checked_on_call() {
+    let _unresolved = memory().get(10);
+}
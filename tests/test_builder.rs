@@ -0,0 +1,74 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="self.min <= self.max"]
+#[derive(Debug)]
+struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+// The builder's invariant only relates the bounds once both are set.
+#[invariant="self.min.map_or(true, |min| self.max.map_or(true, |max| min <= max))"]
+#[invariant_setters]
+#[derive(Default)]
+struct BoundsBuilder {
+    min: Option<i32>,
+    max: Option<i32>,
+}
+
+impl BoundsBuilder {
+    #[establishes_invariant_of="Bounds"]
+    fn build(self) -> Option<Bounds> {
+        Some(Bounds { min: self.min?, max: self.max.unwrap_or(0) })
+    }
+
+    #[establishes_invariant_of="Bounds"]
+    fn build_unbounded(self) -> Bounds {
+        Bounds { min: self.min.unwrap_or(0), max: self.max.unwrap_or(::std::i32::MAX) }
+    }
+}
+
+#[test]
+fn test_builder() {
+    let mut builder = BoundsBuilder::default();
+    builder.set_max(Some(5));
+    builder.set_min(Some(1));
+    assert!(builder.build().unwrap().max == 5);
+
+    let mut builder = BoundsBuilder::default();
+    builder.set_max(Some(5));
+    assert!(builder.build().is_none());
+
+    let mut builder = BoundsBuilder::default();
+    builder.set_min(Some(7));
+    assert!(builder.build_unbounded().min == 7);
+}
+
+#[test]
+#[should_panic(expected = "invariant of Bounds not established by build")]
+fn test_build() {
+    let mut builder = BoundsBuilder::default();
+    builder.set_min(Some(1));
+    builder.build();
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_min")]
+fn test_builder_invariant() {
+    let mut builder = BoundsBuilder::default();
+    builder.set_max(Some(0));
+    builder.set_min(Some(1));
+}
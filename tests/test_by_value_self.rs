@@ -0,0 +1,65 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+struct Request {
+    headers: Vec<(String, String)>,
+    retries: (u32, u32),
+}
+
+impl Request {
+    #[postcond="return.headers.len() == self.headers.len() + 1"]
+    fn header(mut self, name: &str, value: &str) -> Request {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    #[postcond="return == old(self.retries.0) + self.retries.1"]
+    fn total_retries(self) -> u32 {
+        self.retries.0 + self.retries.1
+    }
+
+    #[transition="return.retries.0 == old(self.retries.0) + 1 && self.retries.1 == 0"]
+    fn retry(self) -> Request {
+        Request { retries: (self.retries.0 + 1, 0), ..self }
+    }
+
+    #[postcond="return.headers.len() == self.headers.len()"]
+    fn broken(self) -> Request {
+        Request { headers: Vec::new(), ..self }
+    }
+}
+
+fn request() -> Request {
+    Request { headers: vec![("a".to_owned(), "1".to_owned())], retries: (1, 2) }
+}
+
+#[test]
+fn test_by_value_self() {
+    let r = request().header("b", "2");
+    assert!(r.headers.len() == 2);
+    assert!(request().total_retries() == 3);
+}
+
+#[test]
+#[should_panic(expected = "transition of retry")]
+fn test_transition() {
+    request().retry();
+}
+
+#[test]
+#[should_panic(expected = "postcondition of broken")]
+fn test_postcondition() {
+    request().broken();
+}
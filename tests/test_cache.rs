@@ -0,0 +1,117 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of caching expansions. Compiles a small crate with
+// HOARE_EXPANSION_CACHE set, replaces the cached expansion of its contract by
+// hand, and checks that compiling it again uses the replacement, unless the
+// attribute, the item, the cfgs, or the shared predicates of other crates have
+// changed.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+// The crate, with the contract and the body of `foo` to be filled in.
+const SOURCE: &'static str = "#![feature(plugin, custom_attribute)]\n\
+                              #![plugin(hoare)]\n\
+                              \n\
+                              #[precond=\"PRED\"]\n\
+                              fn foo(x: i32) -> i32 {\n\
+                              \x20   BODY\n\
+                              }\n\
+                              \n\
+                              fn main() {\n\
+                              \x20   println!(\"{}\", foo(1));\n\
+                              }\n";
+
+// What `foo` returns after compiling it with pred, body, and cfg, and with
+// predicates as the manifest of another crate's shared predicates, if any.
+fn compile_and_run(
+    out: &Path,
+    pred: &str,
+    body: &str,
+    cfg: Option<&str>,
+    predicates: Option<&str>,
+) -> String {
+    let src = out.join("cached.rs");
+    common::write(&src, &SOURCE.replace("PRED", pred).replace("BODY", body));
+    let _ = fs::remove_dir_all(out.join("predicates"));
+    if let Some(predicates) = predicates {
+        fs::create_dir_all(out.join("predicates")).unwrap();
+        common::write(&out.join("predicates/other.predicates"), predicates);
+    }
+
+    let mut command = common::rustc();
+    if let Some(cfg) = cfg {
+        command.args(&["--cfg", cfg]);
+    }
+    let output = command
+        .arg("--out-dir")
+        .arg(out)
+        .arg(&src)
+        .env("HOARE_EXPANSION_CACHE", out.join("cache"))
+        .env("HOARE_PREDICATES_DIR", out.join("predicates"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = Command::new(out.join("cached")).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).trim().to_owned()
+}
+
+fn cached(out: &Path) -> BTreeSet<String> {
+    fs::read_dir(out.join("cache"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn test_expansion_cache() {
+    let out = common::out_dir("cache");
+
+    // A miss, which stores the expansion.
+    assert_eq!(compile_and_run(&out, "x > 0", "x", None, None), "1");
+    let files = cached(&out);
+    assert_eq!(files.len(), 1);
+
+    // A hit: the cached expansion is used instead of expanding again.
+    let file = out.join("cache").join(files.iter().next().unwrap());
+    common::write(&file, "// 1000\n// @hoare-cache item\nfn foo(_x: i32) -> i32 {\n    42\n}\n");
+    assert_eq!(compile_and_run(&out, "x > 0", "x", None, None), "42");
+    assert_eq!(cached(&out), files);
+
+    // Changing the attribute, the item, the cfgs, or the shared predicates
+    // gives a new key, so the contract is expanded again.
+    let changes = vec![
+        ("x >= 0", "x", None, None),
+        ("x > 0", "x + 0", None, None),
+        ("x > 0", "x", Some("hoare_cache_test"), None),
+        ("x > 0", "x", None, Some("is_small\tother::checks::is_small\n")),
+        ("x > 0", "x", None, Some("is_small\tother::is_small\n")),
+    ];
+    for (i, &(pred, body, cfg, predicates)) in changes.iter().enumerate() {
+        assert_eq!(compile_and_run(&out, pred, body, cfg, predicates), "1");
+        assert_eq!(cached(&out).len(), files.len() + i + 1);
+    }
+
+    // Each of those is a hit the second time.
+    for &(pred, body, cfg, predicates) in &changes {
+        assert_eq!(compile_and_run(&out, pred, body, cfg, predicates), "1");
+    }
+    assert_eq!(cached(&out).len(), files.len() + changes.len());
+    assert_eq!(compile_and_run(&out, "x > 0", "x", None, None), "42");
+
+    fs::remove_dir_all(&out).unwrap();
+}
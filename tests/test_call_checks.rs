@@ -0,0 +1,46 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of checking preconditions at call sites. Compiles call_checks/
+// checked_lib.rs, whose contracts are off, with `--cfg hoare_call_checks`, then
+// call_checks/caller.rs against it, and runs the caller.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+fn rustc(out: &Path, file: &str) {
+    let output = common::rustc()
+        .args(&["--cfg", "hoare_call_checks", "--out-dir"])
+        .arg(out)
+        .arg("-L")
+        .arg(out)
+        .arg(common::root("call_checks").join(file))
+        .env("HOARE_CONTRACTS_DIR", out.join("contracts"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_call_checks() {
+    let out = common::out_dir("call-checks");
+    rustc(&out, "checked_lib.rs");
+    let manifest = common::read(&out.join("contracts/checked_lib.contracts"));
+    assert!(manifest.contains("checked_lib::parser::parse\ts\t!s.is_empty()\n"), "{}", manifest);
+    assert!(!manifest.contains("private"), "{}", manifest);
+
+    rustc(&out, "caller.rs");
+    let status = Command::new(out.join("caller")).status().unwrap();
+    assert!(status.success());
+    fs::remove_dir_all(&out).unwrap();
+}
@@ -0,0 +1,52 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[capture_violations]
+#[postcond = "return > x"]
+fn inc(x: i32) -> i32 {
+    if x == 3 { x } else { x + 1 }
+}
+
+#[postcond = "return.len() > s.len()"]
+#[capture_violations]
+fn append(s: &str, t: &str) -> String {
+    format!("{}{}", s, t)
+}
+
+#[test]
+fn test_capture_1() {
+    inc(1);
+    append("a", "b");
+}
+
+#[test]
+#[should_panic]
+fn test_capture_fail_1() {
+    inc(3);
+}
+
+#[test]
+#[should_panic]
+fn test_capture_fail_2() {
+    append("a", "");
+}
+
+#[test]
+fn test_replay_1() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/target/hoare-corpus");
+    let _ = ::std::panic::catch_unwind(|| inc(3));
+    let inputs: Vec<(i32,)> = libhoare::corpus::inputs(dir, "test_capture::inc");
+    assert!(inputs.contains(&(3,)));
+}
@@ -0,0 +1,57 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![feature(plugin, custom_attribute, stmt_expr_attributes)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+// `any()` never holds, `all()` always does.
+
+#[invariant="self.min <= self.max"]
+#[invariant_setters]
+pub struct Bounds {
+    min: i32,
+    max: i32,
+    #[cfg(any())]
+    unused: String,
+}
+
+#[postcond="return > 0"]
+fn positive(x: i32) -> i32 {
+    #[cfg(any())]
+    return 0;
+    #[cfg(any())]
+    let x = missing(x);
+    x
+}
+
+#[cfg_attr(all(), precond="x > 0")]
+#[cfg_attr(any(), precond="missing(x)")]
+fn checked(x: i32) -> i32 {
+    x
+}
+
+#[test]
+fn test_cfg_field() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.set_min(5);
+    assert!(bounds.min == 5);
+}
+
+#[test]
+fn test_cfg_stmt() {
+    assert!(positive(1) == 1);
+}
+
+#[test]
+#[should_panic(expected = "precondition of checked (x > 0)")]
+fn test_cfg_attr() {
+    checked(0);
+}
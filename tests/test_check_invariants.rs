@@ -0,0 +1,56 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+
+extern crate libhoare;
+
+use libhoare::{Enforced, InvariantError};
+
+#[invariant="self.min <= self.max"]
+#[invariant="self.max - self.min < 100"]
+#[check_invariants]
+pub struct Bounds {
+    pub min: i32,
+    pub max: i32,
+}
+
+#[invariant="self.0 <= 100"]
+#[check_invariants]
+struct Percent(u32);
+
+#[test]
+fn test_check_invariants() {
+    assert!(Bounds { min: 0, max: 10 }.check_invariants() == Ok(()));
+    assert!(
+        Bounds { min: 10, max: 0 }.check_invariants() ==
+            Err(InvariantError::new("Bounds", "self.min <= self.max"))
+    );
+    let error = Bounds { min: 0, max: 100 }.check_invariants().unwrap_err();
+    assert!(error.predicate() == "self.max - self.min < 100");
+    assert!(error.to_string() == "invariant of Bounds (self.max - self.min < 100) violated");
+}
+
+#[test]
+fn test_newtype() {
+    assert!(Percent::new_unchecked(50).check_invariants().is_ok());
+    assert!(Percent::new_unchecked(101).check_invariants().is_err());
+}
+
+#[test]
+#[should_panic(expected = "(self.min <= self.max) violated in EnforcedMut::drop")]
+fn test_enforced() {
+    let mut bounds = Enforced::new(Bounds { min: 0, max: 10 });
+    bounds.get_mut().max = 20;
+    assert!(bounds.max == 20);
+    bounds.get_mut().min = 30;
+}
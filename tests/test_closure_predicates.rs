@@ -0,0 +1,79 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+// `return` in a closure is the closure's.
+#[postcond="return.iter().all(|&x| { if x < 0 { return false; } x % 2 == 0 })"]
+fn evens(n: i32, odd: bool) -> Vec<i32> {
+    (0..n).map(|x| if odd { 2 * x + 1 } else { 2 * x }).collect()
+}
+
+#[test]
+fn test_closure_return() {
+    assert!(evens(3, false) == vec![0, 2, 4]);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of evens")]
+fn test_closure_return_fails() {
+    evens(3, true);
+}
+
+// Identifiers containing `return` are not renamed.
+#[postcond="return == returned"]
+fn identity(returned: i32) -> i32 {
+    returned
+}
+
+#[test]
+fn test_identifiers() {
+    assert!(identity(4) == 4);
+}
+
+// `return - 1` is the returned value minus one, not a return of `-1`.
+#[postcond="return - 1 < x && return * 2 > x"]
+fn succ(x: i32) -> i32 {
+    x + 1
+}
+
+#[test]
+fn test_operators() {
+    assert!(succ(1) == 2);
+}
+
+#[postcond(pred="xs.iter().all(|&x| x <= return)", closure_return="result")]
+fn max(xs: &[i32]) -> i32 {
+    xs.iter().cloned().fold(i32::min_value(), ::std::cmp::max)
+}
+
+#[postcond="{ let max = return; xs.iter().all(|&x| x >= max) }"]
+fn min(xs: &[i32]) -> i32 {
+    xs.iter().cloned().fold(i32::max_value(), ::std::cmp::min)
+}
+
+#[test]
+fn test_result_in_closure() {
+    assert!(max(&[1, 5, 3]) == 5);
+    assert!(min(&[4, 2, 3]) == 2);
+}
+
+#[postcond="in_range!(return, 0..xs.iter().filter(|&&x| { return x > 0; }).count() + 1)"]
+fn count_positive(xs: &[i32]) -> usize {
+    xs.iter().filter(|&&x| x > 0).count()
+}
+
+#[test]
+fn test_shorthand() {
+    assert!(count_positive(&[1, -1, 2]) == 2);
+}
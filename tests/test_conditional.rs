@@ -0,0 +1,47 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[postcond(when="x > 0", then="return > 0")]
+#[postcond(when="x == 0", then="return == 0", level="debug")]
+fn sign(x: i32, broken: bool) -> i32 {
+    if broken { -x.signum() } else { x.signum() }
+}
+
+#[precond(when="!xs.is_empty()", then="xs[0] > 0")]
+fn first_positive(xs: &[i32]) -> bool {
+    xs.first().map_or(true, |&x| x > 0)
+}
+
+#[test]
+fn test_conditional() {
+    assert!(sign(3, false) == 1);
+    assert!(sign(0, true) == 0);
+    assert!(sign(-3, true) == 1);
+    assert!(first_positive(&[]));
+    assert!(first_positive(&[1]));
+}
+
+#[test]
+#[should_panic(expected = "postcondition of sign (implies!(x > 0, __result_")]
+fn test_then_fails() {
+    sign(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of first_positive (implies!(!xs.is_empty(), xs[0] > 0)): \
+                           !xs.is_empty() holds but xs[0] > 0 does not")]
+fn test_precond() {
+    first_positive(&[-1]);
+}
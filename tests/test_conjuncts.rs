@@ -0,0 +1,55 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond = "x > 0 && x < y && y < 100"]
+fn foo(x: i32, y: i32) -> i32 {
+    y - x
+}
+
+#[precond = "i < v.len() && v[i] > 0"]
+fn get(v: &[i32], i: usize) -> i32 {
+    v[i]
+}
+
+#[test]
+fn test_conjuncts() {
+    assert!(foo(1, 2) == 1);
+    assert!(get(&[1, 2], 1) == 2);
+}
+
+#[test]
+#[should_panic(expected = "precondition of foo (x > 0)")]
+fn test_first() {
+    foo(0, 2);
+}
+
+#[test]
+#[should_panic(expected = "precondition of foo (x < y)")]
+fn test_second() {
+    foo(5, 3);
+}
+
+#[test]
+#[should_panic(expected = "precondition of foo (y < 100)")]
+fn test_third() {
+    foo(5, 300);
+}
+
+// The second conjunct is only evaluated if the first holds.
+#[test]
+#[should_panic(expected = "precondition of get (i < v.len())")]
+fn test_short_circuit() {
+    get(&[1, 2], 2);
+}
@@ -0,0 +1,98 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="self.min <= self.max"]
+#[derive(Debug)]
+struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+#[check_constructors]
+impl Bounds {
+    fn new(min: i32, max: i32) -> Bounds {
+        Bounds { min: min, max: max }
+    }
+
+    fn empty() -> Self {
+        if true {
+            return Bounds { min: 1, max: 0 };
+        }
+        Bounds { min: 0, max: 0 }
+    }
+
+    fn parse(s: &str) -> Result<Bounds, String> {
+        let mut parts = s.split("..");
+        let min = parts.next().and_then(|p| p.parse().ok()).ok_or("bad min".to_owned())?;
+        let max = parts.next().and_then(|p| p.parse().ok()).ok_or("bad max".to_owned())?;
+        Ok(Bounds { min: min, max: max })
+    }
+
+    fn point(x: i32) -> Option<Self> {
+        Some(Bounds { min: x, max: x })
+    }
+
+    fn width(&self) -> i32 {
+        self.max - self.min
+    }
+}
+
+#[test]
+fn test_constructors() {
+    assert!(Bounds::new(0, 3).width() == 3);
+    assert!(Bounds::parse("1..5").unwrap().width() == 4);
+    assert!(Bounds::parse("1..x").is_err());
+    assert!(Bounds::point(2).unwrap().width() == 0);
+}
+
+#[test]
+#[should_panic(expected = "invariant of Bounds not established by new")]
+fn test_new() {
+    Bounds::new(3, 0);
+}
+
+#[test]
+#[should_panic(expected = "invariant of Bounds not established by empty")]
+fn test_return() {
+    Bounds::empty();
+}
+
+#[test]
+#[should_panic(expected = "invariant of Bounds not established by parse")]
+fn test_result() {
+    let _ = Bounds::parse("5..1");
+}
+
+trait Interval: Sized {
+    fn from_width(width: i32) -> Self;
+}
+
+impl Interval for Bounds {
+    #[postcond(invariant(return))]
+    fn from_width(width: i32) -> Bounds {
+        Bounds { min: 0, max: width }
+    }
+}
+
+#[test]
+fn test_trait_constructor() {
+    assert!(Bounds::from_width(3).width() == 3);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of from_width")]
+fn test_trait_constructor_fails() {
+    Bounds::from_width(-1);
+}
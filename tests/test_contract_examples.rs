@@ -0,0 +1,40 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of `#[contract_examples]`. Expands contract_examples/examples.rs and
+// checks the calls in the generated documentation and the warnings.
+
+mod common;
+
+#[test]
+fn test_contract_examples() {
+    let output = common::rustc()
+        .args(&["-Z", "unpretty=expanded"])
+        .arg(common::root("contract_examples").join("examples.rs"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+
+    assert!(stdout.contains("examples::bounded(1);"), "{}", stdout);
+    assert!(stdout.contains("examples::bounded(0);"), "{}", stdout);
+    // Only the valid call.
+    assert_eq!(stdout.matches("examples::unsigned(").count(), 1, "{}", stdout);
+    assert!(stdout.contains("examples::unsigned(0);"), "{}", stdout);
+    assert!(stdout.contains("examples::small(0);"), "{}", stdout);
+    assert!(stdout.contains("examples::small(10);"), "{}", stdout);
+    // Not wrapped around to a negative literal.
+    assert!(!stdout.contains("examples::huge("), "{}", stdout);
+
+    let no_panic = stderr.matches("there is no example of a call which panics").count();
+    assert_eq!(no_panic, 1, "{}", stderr);
+    assert!(stderr.contains("an integer literal must be at most `i64::MAX`"), "{}", stderr);
+}
@@ -0,0 +1,47 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+
+
+fn apply<F: Fn(u32) -> u32>(f: F, x: u32) -> u32 {
+    f(x)
+}
+
+#[test]
+fn test_contract_fn() {
+    let offset = 1;
+    let f = contract_fn!(|x: u32| x * 2 + offset, pre = "x < 100", post = "return % 2 == 1");
+    assert!(apply(&f, 3) == 7);
+    assert!(f(0) == 1);
+
+    let mut calls = 0;
+    {
+        let mut g = contract_fn!(|x: i32, y: i32| { calls += 1; x - y }, post = "return >= 0", name = "diff");
+        assert!(g(3, 2) == 1);
+    }
+    assert!(calls == 1);
+}
+
+#[test]
+#[should_panic(expected = "precondition of closure (x < 100)")]
+fn test_pre() {
+    let f = contract_fn!(|x: u32| x * 2, pre = "x < 100");
+    apply(f, 100);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of diff")]
+fn test_post() {
+    let g = contract_fn!(|x: i32, y: i32| x - y, post = "return >= 0", name = "diff");
+    g(2, 3);
+}
@@ -0,0 +1,56 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+// `#[contract_panics]` only adds documentation, the contracts are still
+// checked.
+
+#[contract_panics]
+#[precond="x > 0 && x <= 100"]
+#[debug_precond="name != \"\""]
+fn foo(x: i32, name: &str) -> usize {
+    x as usize + name.len()
+}
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    #[contract_panics]
+    #[precond="by < 10"]
+    fn add(&mut self, by: u32) {
+        self.count += by;
+    }
+}
+
+#[test]
+fn test_contract_panics() {
+    assert!(foo(1, "a") == 2);
+    let mut counter = Counter { count: 0 };
+    counter.add(3);
+    assert!(counter.count == 3);
+}
+
+#[test]
+#[should_panic(expected = "precondition of foo (x > 0)")]
+fn test_foo() {
+    foo(0, "a");
+}
+
+#[test]
+#[should_panic(expected = "precondition of add (by < 10)")]
+fn test_add() {
+    Counter { count: 0 }.add(10);
+}
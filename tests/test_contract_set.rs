@@ -0,0 +1,103 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+const MAX_BUFFER: usize = 4;
+
+contract_set! io_safety {
+    pre: "self.is_open()",
+    invariant: "self.buffered() <= MAX_BUFFER",
+}
+
+contract_set! non_empty_result {
+    post: "return > 0",
+}
+
+trait Sink {
+    fn is_open(&self) -> bool;
+    fn buffered(&self) -> usize;
+    fn push(&mut self, byte: u8);
+}
+
+struct Channel {
+    open: bool,
+    buffer: Vec<u8>,
+}
+
+#[apply_contract_set(io_safety)]
+impl Sink for Channel {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.buffer.push(byte);
+    }
+}
+
+#[apply_contract_set(io_safety, non_empty_result)]
+trait Flush: Sink {
+    fn flush_len(&mut self) -> usize {
+        let len = self.buffered();
+        len + 1
+    }
+}
+
+impl Flush for Channel {}
+
+#[apply_contract_set(non_empty_result)]
+fn count(xs: &[u8]) -> usize {
+    xs.len()
+}
+
+fn channel() -> Channel {
+    Channel {
+        open: true,
+        buffer: Vec::new(),
+    }
+}
+
+#[test]
+fn test_contract_set() {
+    let mut channel = channel();
+    channel.push(1);
+    channel.push(2);
+    assert!(channel.flush_len() == 3);
+    assert!(count(&[1]) == 1);
+}
+
+#[test]
+#[should_panic(expected = "precondition of push (self.is_open())")]
+fn test_precond() {
+    let mut channel = channel();
+    channel.open = false;
+    channel.push(1);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving push (self.buffered() <= MAX_BUFFER)")]
+fn test_invariant() {
+    let mut channel = channel();
+    for byte in 0..5 {
+        channel.push(byte);
+    }
+}
+
+#[test]
+#[should_panic(expected = "postcondition of count")]
+fn test_postcond() {
+    count(&[]);
+}
@@ -0,0 +1,88 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use libhoare::Contracted;
+
+#[contracted]
+trait Stack {
+    fn len(&self) -> usize;
+
+    #[postcond = "self.len() == old_len + 1"]
+    fn push(&mut self, x: i32, old_len: usize);
+
+    #[precond = "self.len() > 0"]
+    fn pop(&mut self) -> i32;
+}
+
+struct Good(Vec<i32>);
+
+impl Stack for Good {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn push(&mut self, x: i32, _old_len: usize) {
+        self.0.push(x);
+    }
+
+    fn pop(&mut self) -> i32 {
+        self.0.pop().unwrap_or(0)
+    }
+}
+
+struct Bad(Vec<i32>);
+
+impl Stack for Bad {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn push(&mut self, x: i32, _old_len: usize) {
+        self.0.push(x);
+        self.0.push(x);
+    }
+
+    fn pop(&mut self) -> i32 {
+        self.0.pop().unwrap_or(0)
+    }
+}
+
+#[test]
+fn test_contracted_1() {
+    let mut s: Contracted<Box<Stack>> = Contracted::new(Box::new(Good(vec![])));
+    s.push(1, 0);
+    assert!(s.pop() == 1);
+}
+
+#[test]
+#[should_panic]
+fn test_contracted_fail_1() {
+    let mut s: Contracted<Box<Stack>> = Contracted::new(Box::new(Good(vec![])));
+    s.pop();
+}
+
+#[test]
+#[should_panic]
+fn test_contracted_fail_2() {
+    let mut s: Contracted<Box<Stack>> = Contracted::new(Box::new(Bad(vec![])));
+    s.push(1, 0);
+}
+
+#[test]
+fn test_uncontracted_1() {
+    // Without the wrapper, contracts on required methods are not checked.
+    let mut s: Box<Stack> = Box::new(Good(vec![]));
+    s.pop();
+}
@@ -0,0 +1,56 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of contract coverage. Compiles coverage/covered.rs with
+// `--cfg hoare_coverage` as a library and as its test harness, as `cargo test`
+// does, runs the harness, and checks the report has the contracts of both
+// builds.
+
+extern crate libhoare;
+
+use std::fs;
+use std::process::Command;
+
+use libhoare::coverage::Report;
+
+mod common;
+
+#[test]
+fn test_library_and_tests() {
+    let out = common::out_dir("coverage");
+    for test in &[false, true] {
+        let mut command = common::rustc();
+        if *test {
+            command.arg("--test");
+        }
+        let output = command
+            .args(&["--cfg", "hoare_coverage", "--out-dir"])
+            .arg(&out)
+            .arg(common::root("coverage").join("covered.rs"))
+            .env("HOARE_COVERAGE_DIR", out.join("coverage"))
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+    assert!(out.join("coverage/covered.contracts").is_file());
+    assert!(out.join("coverage/covered.test.contracts").is_file());
+
+    let status = Command::new(out.join("covered")).status().unwrap();
+    assert!(status.success());
+
+    let report = Report::load(out.join("coverage")).unwrap();
+    let paths: Vec<&str> = report.contracts.iter().map(|c| &*c.fn_path).collect();
+    assert_eq!(paths.len(), 3, "{:?}", paths);
+    assert!(paths.contains(&"covered::tests::small"), "{:?}", paths);
+    let unevaluated: Vec<&str> = report.unevaluated().iter().map(|c| &*c.fn_path).collect();
+    assert_eq!(unevaluated, vec!["covered::negative"]);
+
+    fs::remove_dir_all(&out).unwrap();
+}
@@ -0,0 +1,41 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond(pred = "x > 0", backend = "debug_assert")]
+fn precond(x: i32) -> i32 {
+    x
+}
+
+#[postcond(pred = "return > x", backend = "debug_assert")]
+fn postcond(x: i32) -> i32 {
+    x
+}
+
+#[test]
+fn test_debug_assert_ok() {
+    assert!(precond(1) == 1);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "precondition of precond (x > 0)"))]
+fn test_debug_assert_precond() {
+    precond(0);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "postcondition of postcond"))]
+fn test_debug_assert_postcond() {
+    postcond(0);
+}
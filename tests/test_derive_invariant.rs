@@ -0,0 +1,58 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute, custom_derive)]
+#![plugin(hoare)]
+
+
+extern crate libhoare;
+
+#[derive(Invariant)]
+#[check(pred="self.name.len() <= self.capacity")]
+#[invariant_setters]
+struct Queue {
+    #[check(non_empty)]
+    name: String,
+    #[check(range="1..100")]
+    capacity: usize,
+    #[check(sorted)]
+    priorities: Vec<i32>,
+}
+
+#[derive(Invariant)]
+struct Percent(#[check(range="0..101")] u32);
+
+#[test]
+fn test_derive_invariant() {
+    let mut queue = Queue { name: "jobs".to_owned(), capacity: 10, priorities: vec![1, 2] };
+    assert!(queue.validate() == Ok(()));
+    queue.set_capacity(20);
+    assert!(Percent::try_new(50).is_ok());
+    assert!(Percent::try_new(101).is_err());
+}
+
+#[test]
+fn test_validate() {
+    let queue = Queue { name: String::new(), capacity: 10, priorities: vec![2, 1] };
+    assert!(queue.validate() == Err("non_empty!(self.name): the collection is empty".to_owned()));
+    let queue = Queue { name: "jobs".to_owned(), capacity: 2, priorities: vec![] };
+    assert!(queue.validate() == Err("self.name.len() <= self.capacity does not hold".to_owned()));
+    assert!(
+        Percent::new_unchecked(101).validate() ==
+            Err("in_range!(self.0, 0..101): 101 is not in 0..101".to_owned())
+    );
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_priorities")]
+fn test_setter() {
+    let mut queue = Queue { name: "jobs".to_owned(), capacity: 10, priorities: vec![] };
+    queue.set_priorities(vec![3, 1]);
+}
@@ -0,0 +1,47 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+#[invariant="self.min <= self.max"]
+#[invariant_deserialize]
+#[derive(Debug)]
+struct Bounds {
+    min: i32,
+    #[serde(rename = "maximum")]
+    max: i32,
+}
+
+#[invariant="self.0 > 0"]
+#[invariant_deserialize]
+#[derive(Debug, PartialEq)]
+struct Positive(i32);
+
+#[test]
+fn test_deserialize() {
+    let bounds: Bounds = serde_json::from_str(r#"{"min": 1, "maximum": 2}"#).unwrap();
+    assert!(bounds.min == 1 && bounds.max == 2);
+    let p: Positive = serde_json::from_str("3").unwrap();
+    assert!(p == Positive::new_unchecked(3));
+}
+
+#[test]
+fn test_deserialize_invalid() {
+    let err = serde_json::from_str::<Bounds>(r#"{"min": 3, "maximum": 2}"#).unwrap_err();
+    assert!(err.to_string().contains("invariant of Bounds (self.min <= self.max)"));
+    assert!(serde_json::from_str::<Positive>("-3").is_err());
+}
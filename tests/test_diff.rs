@@ -0,0 +1,69 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+// Operands are only shown with `--cfg hoare_diff`.
+
+extern crate libhoare;
+
+#[derive(Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+struct NoDebug(i32);
+
+impl PartialEq for NoDebug {
+    fn eq(&self, other: &NoDebug) -> bool {
+        self.0 == other.0
+    }
+}
+
+#[postcond = "return == Point { x: x, y: x }"]
+fn diagonal(x: i32) -> Point {
+    Point { x: x, y: if x > 10 { 0 } else { x } }
+}
+
+#[precond = "a == NoDebug(0)"]
+fn zero(a: NoDebug) {}
+
+#[precond = "(x < 10)"]
+fn small(x: i32) {}
+
+#[test]
+fn test_diff_1() {
+    diagonal(3);
+    zero(NoDebug(0));
+    small(9);
+}
+
+#[test]
+#[cfg(hoare_diff)]
+#[should_panic(expected = "+    y: 11,")]
+fn test_diff_fail_1() {
+    diagonal(11);
+}
+
+#[test]
+#[cfg(hoare_diff)]
+#[should_panic(expected = "left: (does not implement Debug)")]
+fn test_diff_fail_2() {
+    zero(NoDebug(1));
+}
+
+#[test]
+#[cfg(hoare_diff)]
+#[should_panic(expected = "precondition of small (x < 10)\n left: 10\nright: 10")]
+fn test_diff_fail_3() {
+    small(10);
+}
@@ -0,0 +1,68 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[postcond(each="*elem >= 0")]
+fn abs_all(xs: &[i32], broken: bool) -> Vec<i32> {
+    xs.iter().map(|&x| if broken { x } else { x.abs() }).collect()
+}
+
+#[precond(each="!elem.is_empty()", of="names")]
+fn join(names: &[&str]) -> String {
+    names.join(",")
+}
+
+#[postcond="each!(return, |&x| x % 2 == 0)"]
+fn evens(n: usize, broken: bool) -> Vec<usize> {
+    (0..n).map(|i| if broken { i } else { 2 * i }).collect()
+}
+
+#[postcond="return && each!(xs, |x| *x < 10)"]
+fn small(xs: &[u32]) -> bool {
+    true
+}
+
+#[test]
+fn test_each() {
+    assert!(abs_all(&[1, -2, 3], false) == vec![1, 2, 3]);
+    assert!(abs_all(&[], true).is_empty());
+    assert!(join(&["a", "b"]) == "a,b");
+    assert!(evens(3, false) == vec![0, 2, 4]);
+    assert!(small(&[1, 9]));
+}
+
+#[test]
+#[should_panic(expected = "element 1 does not satisfy *elem >= 0")]
+fn test_each_attribute_fails() {
+    abs_all(&[1, -2, -3], true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of join (each!(names, |elem| !elem.is_empty())): element 2 \
+                           does not satisfy !elem.is_empty()")]
+fn test_each_of_fails() {
+    join(&["a", "b", ""]);
+}
+
+#[test]
+#[should_panic(expected = "element 1 does not satisfy x % 2 == 0")]
+fn test_each_shorthand_fails() {
+    evens(3, true);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of small")]
+fn test_each_in_predicate_fails() {
+    small(&[1, 10]);
+}
@@ -0,0 +1,68 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+// Bodies which return early. The first two are moved to a helper function, the
+// postcondition of the last uses an argument, so its body is rewritten instead.
+
+#[postcond = "return > 0"]
+fn early_return(x: i32) -> i32 {
+    if x > 0 {
+        return x;
+    }
+    -x
+}
+
+#[postcond = "return.is_some()"]
+fn first<T: Clone>(xs: Vec<T>) -> Option<T> {
+    if xs.is_empty() {
+        return None;
+    }
+    Some(xs[0].clone())
+}
+
+#[postcond = "return <= xs.len()"]
+fn positives(xs: Vec<i32>) -> usize {
+    if xs.is_empty() {
+        return 0;
+    }
+    xs.iter().filter(|&&x| x > 0).count()
+}
+
+#[test]
+fn test_early_return() {
+    early_return(5);
+    early_return(-5);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of early_return")]
+fn test_early_return_fail() {
+    early_return(0);
+}
+
+#[test]
+fn test_generic_early_return() {
+    assert!(first(vec!["a", "b"]) == Some("a"));
+}
+
+#[test]
+#[should_panic(expected = "postcondition of first")]
+fn test_generic_early_return_fail() {
+    first::<String>(Vec::new());
+}
+
+#[test]
+fn test_early_return_using_args() {
+    assert!(positives(Vec::new()) == 0);
+    assert!(positives(vec![1, -1, 2]) == 2);
+}
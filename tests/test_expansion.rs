@@ -0,0 +1,39 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Snapshot tests of the code generated by the plugin. Compiles
+// expansion/contracts.rs with HOARE_DUMP_EXPANSION set and compares the dumped
+// functions with expansion/snapshots. Set HOARE_BLESS to write the snapshots.
+
+extern crate libhoare;
+
+use std::fs;
+
+use libhoare::snapshot;
+
+mod common;
+
+#[test]
+fn test_expansion_snapshots() {
+    let root = common::root("expansion");
+    let out = common::out_dir("expansion");
+    let status = common::rustc()
+        .arg("--crate-type=lib")
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(root.join("contracts.rs"))
+        .env("HOARE_DUMP_EXPANSION", out.join("expansion"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    snapshot::check(out.join("expansion"), root.join("snapshots"));
+    fs::remove_dir_all(&out).unwrap();
+}
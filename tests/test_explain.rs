@@ -0,0 +1,72 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+// Failures are only explained with `--cfg hoare_explain`.
+
+extern crate libhoare;
+
+struct Stack {
+    items: Vec<i32>,
+}
+
+struct Opaque;
+
+impl Stack {
+    #[precond="self.items.len() < cap && !full"]
+    fn push(&mut self, x: i32, cap: usize, full: bool) {
+        self.items.push(x);
+    }
+}
+
+#[postcond="return.0 * 2 == x + 1"]
+fn half(x: i32, _opaque: &Opaque) -> (i32, i32) {
+    (x / 2, x % 2)
+}
+
+#[precond="!_opaque_ok(o)"]
+fn opaque(o: Opaque) -> Opaque {
+    o
+}
+
+fn _opaque_ok(_: &Opaque) -> bool {
+    true
+}
+
+#[test]
+fn test_explain() {
+    let mut stack = Stack { items: vec![1, 2] };
+    stack.push(3, 4, false);
+    assert!(half(4, &Opaque) == (2, 0));
+}
+
+#[test]
+#[cfg(hoare_explain)]
+#[should_panic(expected = "push (self.items.len() < cap)\n  self.items.len() = 2\n  cap = 2")]
+fn test_leaves() {
+    let mut stack = Stack { items: vec![1, 2] };
+    stack.push(3, 2, false);
+}
+
+#[test]
+#[cfg(hoare_explain)]
+#[should_panic(expected = ".0 = 2\n  x = 5")]
+fn test_result() {
+    half(5, &Opaque);
+}
+
+#[test]
+#[cfg(hoare_explain)]
+#[should_panic(expected = "  _opaque_ok(o) = true")]
+fn test_call() {
+    opaque(Opaque);
+}
@@ -0,0 +1,45 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of the warning about fields which bypass a struct invariant. Compiles
+// field_audit/fields.rs and checks which structs it warns about.
+
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_field_audit() {
+    let out = common::out_dir("field-audit");
+    let output = common::rustc()
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(common::root("field_audit").join("fields.rs"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+
+    for expected in &[
+        "the invariant of Bounds is not enforced, since the fields `min`, `max` can be written \
+         outside its module without checking it; make them private and consider \
+         #[invariant_setters]",
+        "the invariant of Positive is not enforced, since the field `0` can be written outside \
+         its module without checking it; make the field private and use `try_new`",
+        "the invariant of Range is not enforced, since the field `min` can be written outside \
+         its module without checking it; make them private and use the setters",
+    ] {
+        assert!(stderr.contains(expected), "expected `{}` in {}", expected, stderr);
+    }
+    assert_eq!(stderr.matches("is not enforced").count(), 3, "{}", stderr);
+    assert!(!stderr.contains("invariant of Private"), "{}", stderr);
+    assert!(!stderr.contains("invariant of Hidden"), "{}", stderr);
+    let _ = fs::remove_dir_all(&out);
+}
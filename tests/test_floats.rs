@@ -0,0 +1,50 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+
+
+#[precond(finite="x", finite="y")]
+#[postcond(no_nan="return")]
+fn ratio(x: f64, y: f64) -> f64 {
+    x / y
+}
+
+#[postcond(finite="return")]
+fn scale(x: f32, factor: f32) -> f32 {
+    x * factor
+}
+
+#[test]
+fn test_floats() {
+    assert!(ratio(1.0, 2.0) == 0.5);
+    assert!(ratio(1.0, 0.0) == std::f64::INFINITY);
+    assert!(scale(2.0, 3.0) == 6.0);
+}
+
+#[test]
+#[should_panic(expected = "precondition of ratio ((y).is_finite())")]
+fn test_finite_arg() {
+    ratio(1.0, std::f64::NAN);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of ratio")]
+fn test_no_nan() {
+    ratio(0.0, 0.0);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of scale")]
+fn test_finite_return() {
+    scale(std::f32::MAX, 2.0);
+}
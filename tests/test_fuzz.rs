@@ -0,0 +1,34 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of `#[fuzz]`. Compiles fuzz/targets.rs with `--cfg fuzzing`, so that
+// the fuzz targets are generated, and runs it.
+
+use std::fs;
+use std::process::Command;
+
+mod common;
+
+#[test]
+fn test_fuzz_targets() {
+    // common::rustc also finds arbitrary, a dev-dependency.
+    let out = common::out_dir("fuzz");
+    let output = common::rustc()
+        .args(&["--cfg", "fuzzing", "--out-dir"])
+        .arg(&out)
+        .arg(common::root("fuzz").join("targets.rs"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = Command::new(out.join("targets")).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    fs::remove_dir_all(&out).unwrap();
+}
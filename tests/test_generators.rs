@@ -0,0 +1,107 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute, generators, generator_trait, conservative_impl_trait)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::ops::{Generator, GeneratorState};
+
+#[precond="n > 0"]
+#[yields="yield < n"]
+#[completes="return == n"]
+fn count(n: u32, broken: bool) -> impl Generator<Yield = u32, Return = u32> {
+    move || {
+        for i in 0..n {
+            yield if broken { i + 1 } else { i };
+        }
+        n
+    }
+}
+
+fn run<G: Generator<Yield = u32, Return = u32>>(mut gen: G) -> (Vec<u32>, u32) {
+    let mut yielded = Vec::new();
+    loop {
+        match gen.resume() {
+            GeneratorState::Yielded(x) => yielded.push(x),
+            GeneratorState::Complete(x) => return (yielded, x),
+        }
+    }
+}
+
+#[test]
+fn test_generator() {
+    assert!(run(count(3, false)) == (vec![0, 1, 2], 3));
+}
+
+#[test]
+#[should_panic(expected = "precondition of count (n > 0)")]
+fn test_precond_at_creation() {
+    // Fails when the generator is created, before it is resumed.
+    count(0, false);
+}
+
+#[test]
+#[should_panic(expected = "yield of count (__hoare_yielded < n)")]
+fn test_yields() {
+    run(count(2, true));
+}
+
+#[completes="return > 0"]
+fn total(xs: Vec<u32>) -> impl Generator<Yield = u32, Return = u32> {
+    move || {
+        let mut total = 0;
+        for x in xs {
+            total += x;
+            yield total;
+        }
+        total
+    }
+}
+
+#[test]
+#[should_panic(expected = "completion of total (__result_")]
+fn test_completes() {
+    assert!(run(total(vec![1, 2])) == (vec![1, 3], 3));
+    run(total(vec![]));
+}
+
+struct Buffer {
+    items: Vec<u32>,
+    cap: usize,
+}
+
+// The generator shares buf with the code which resumes it, which may look at
+// it whenever the generator suspends.
+#[suspension_invariant="buf.borrow().items.len() <= buf.borrow().cap"]
+fn fill(
+    buf: std::rc::Rc<std::cell::RefCell<Buffer>>,
+    n: u32,
+) -> impl Generator<Yield = (), Return = ()> {
+    move || {
+        for i in 0..n {
+            buf.borrow_mut().items.push(i);
+            yield;
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "invariant suspending fill \
+                           (buf.borrow().items.len() <= buf.borrow().cap)")]
+fn test_suspension_invariant() {
+    let buf = std::rc::Rc::new(std::cell::RefCell::new(Buffer { items: vec![], cap: 2 }));
+    let mut gen = fill(buf.clone(), 3);
+    gen.resume();
+    gen.resume();
+    assert!(buf.borrow().items.len() == 2);
+    gen.resume();
+}
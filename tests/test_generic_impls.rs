@@ -0,0 +1,131 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+
+// Contracts and the items generated for them with generic structs and impls
+// with several bounds and where clauses.
+
+extern crate libhoare;
+
+use std::fmt::Debug;
+
+#[invariant="self.items.len() <= self.capacity"]
+#[invariant_setters]
+#[check_invariants]
+struct Bounded<T>
+where
+    T: Clone,
+{
+    items: Vec<T>,
+    capacity: usize,
+}
+
+impl<T> Bounded<T>
+where
+    T: Clone + PartialOrd + Debug,
+{
+    #[precond="self.items.len() < self.capacity"]
+    #[postcond="self.items.last().map_or(false, |last| *last >= item_copy)"]
+    #[transition="self.items.len() == old(self.items.len()) + 1"]
+    fn push(&mut self, item: T, item_copy: T) {
+        self.items.push(item);
+    }
+
+    #[invariant="self.items.len() <= self.capacity"]
+    fn largest<'a>(&'a self) -> Option<&'a T>
+    where
+        T: 'a,
+    {
+        let mut largest = None;
+        for item in &self.items {
+            match largest {
+                Some(l) if l >= item => {}
+                _ => largest = Some(item),
+            }
+        }
+        largest
+    }
+}
+
+trait Valid {
+    fn is_valid(&self) -> bool;
+}
+
+impl Valid for i32 {
+    fn is_valid(&self) -> bool {
+        *self >= 0
+    }
+}
+
+#[invariant="self.0.is_valid()"]
+struct Checked<T: Valid + Copy = i32>(T);
+
+#[snapshot(Debug)]
+struct Log<'a, T: 'a + Debug>
+where
+    T: Clone,
+{
+    lines: Vec<T>,
+    #[snapshot_skip]
+    source: &'a str,
+}
+
+impl<'a, T: 'a + Debug> Log<'a, T>
+where
+    T: Clone + PartialEq,
+{
+    #[transition="self.lines.len() == old(self).lines.len() + 1"]
+    fn append(&mut self, line: T, broken: bool) {
+        if !broken {
+            self.lines.push(line);
+        }
+    }
+}
+
+#[test]
+fn test_generic_impls() {
+    let mut bounded = Bounded { items: vec![1, 2], capacity: 3 };
+    bounded.push(3, 3);
+    assert!(bounded.largest() == Some(&3));
+    assert!(bounded.check_invariants().is_ok());
+    bounded.set_capacity(4);
+
+    assert!(Checked::try_new(1).is_ok());
+    assert!(Checked::try_new(-1).is_err());
+    assert!(*Checked::new_unchecked(5) == 5);
+
+    let mut log = Log { lines: vec!["a".to_owned()], source: "test" };
+    log.append("b".to_owned(), false);
+    assert!(log.lines.len() == 2 && log.source == "test");
+}
+
+#[test]
+#[should_panic(expected = "precondition of push (self.items.len() < self.capacity)")]
+fn test_precond() {
+    let mut bounded = Bounded { items: vec![1, 2], capacity: 2 };
+    bounded.push(3, 3);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_capacity")]
+fn test_setter() {
+    let mut bounded = Bounded { items: vec!['a', 'b'], capacity: 2 };
+    bounded.set_capacity(1);
+}
+
+#[test]
+#[should_panic(expected = "transition of append")]
+fn test_snapshot() {
+    let mut log = Log { lines: vec![1], source: "test" };
+    log.append(2, true);
+}
@@ -0,0 +1,71 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+struct Doc {
+    version: u32,
+    owner: Option<String>,
+}
+
+impl Doc {
+    #[invariant(monotonic = "self.version", set_once = "self.owner")]
+    fn edit(&mut self, owner: &str) {
+        self.version += 1;
+        if self.owner.is_none() {
+            self.owner = Some(owner.to_owned());
+        }
+    }
+
+    #[invariant(monotonic = "self.version")]
+    fn revert(&mut self) {
+        if self.version > 0 {
+            self.version -= 1;
+        }
+    }
+
+    #[invariant(set_once = "self.owner")]
+    fn steal(&mut self, owner: &str) -> bool {
+        if owner.is_empty() {
+            return false;
+        }
+        self.owner = Some(owner.to_owned());
+        true
+    }
+}
+
+#[test]
+fn test_history_1() {
+    let mut d = Doc { version: 0, owner: None };
+    d.edit("alice");
+    d.edit("bob");
+    assert!(d.owner == Some("alice".to_owned()));
+    d.revert();
+    d.steal("");
+}
+
+#[test]
+#[should_panic(expected = "monotonic invariant of revert (self.version)")]
+fn test_history_fail_1() {
+    let mut d = Doc { version: 0, owner: None };
+    d.edit("alice");
+    d.revert();
+}
+
+#[test]
+#[should_panic(expected = "set-once invariant of steal (self.owner)")]
+fn test_history_fail_2() {
+    let mut d = Doc { version: 0, owner: None };
+    d.steal("bob");
+    d.steal("eve");
+}
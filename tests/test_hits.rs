@@ -0,0 +1,55 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+// Contract evaluations are only recorded with `--cfg hoare_hits`.
+
+extern crate libhoare;
+
+use libhoare::{assert_contract_hit, ContractKind};
+
+#[precond = "x > 0"]
+#[postcond = "return > x"]
+fn inc(x: i32) -> i32 {
+    x + 1
+}
+
+#[invariant = "*x > 0"]
+fn double(x: &mut i32) {
+    *x *= 2;
+}
+
+#[precond = "x > 0"]
+fn never_called(x: i32) -> i32 {
+    x
+}
+
+#[test]
+#[cfg(hoare_hits)]
+fn test_hits_1() {
+    inc(1);
+    double(&mut 1);
+    assert_contract_hit("test_hits::inc", ContractKind::Precond);
+    assert_contract_hit("test_hits::inc", ContractKind::Postcond);
+    assert_contract_hit("double", ContractKind::Invariant);
+}
+
+#[test]
+#[cfg(hoare_hits)]
+#[should_panic(expected = "Precond of never_called was never evaluated")]
+fn test_hits_fail_1() {
+    if false {
+        never_called(1);
+    }
+    inc(1);
+    assert_contract_hit("never_called", ContractKind::Precond);
+}
@@ -0,0 +1,74 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+struct SortedVec {
+    items: Vec<i32>,
+}
+
+impl SortedVec {
+    fn sorted_around(&self, index: usize) -> bool {
+        (index == 0 || self.items[index - 1] <= self.items[index]) &&
+            (index + 1 == self.items.len() || self.items[index] <= self.items[index + 1])
+    }
+
+    fn is_sorted(&self) -> bool {
+        self.items.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    #[invariant(incremental="self.sorted_around(return)", full="self.is_sorted()", every="4")]
+    fn insert(&mut self, x: i32) -> usize {
+        let index = self.items.iter().position(|&y| y > x).unwrap_or(self.items.len());
+        self.items.insert(index, x);
+        index
+    }
+
+    // Inserts without looking for the right place.
+    #[invariant(incremental="self.sorted_around(return)", full="self.is_sorted()")]
+    fn push(&mut self, x: i32) -> usize {
+        self.items.push(x);
+        self.items.len() - 1
+    }
+
+    // Breaks sortedness away from where the incremental check looks.
+    #[invariant(incremental="true", full="self.is_sorted()", every="2")]
+    fn scramble(&mut self) {
+        self.items.swap(0, 1);
+    }
+}
+
+#[test]
+fn test_incremental() {
+    let mut v = SortedVec { items: vec![] };
+    for x in &[5, 3, 8, 1, 9, 4, 4, 0] {
+        v.insert(*x);
+    }
+    assert!(v.items == vec![0, 1, 3, 4, 4, 5, 8, 9]);
+}
+
+#[test]
+#[should_panic(expected = "incremental invariant of push")]
+fn test_incremental_violated() {
+    let mut v = SortedVec { items: vec![1, 2] };
+    v.push(0);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving scramble (self.is_sorted())")]
+fn test_full_violated() {
+    let mut v = SortedVec { items: vec![1, 2, 3] };
+    // The first call is always fully checked, on leaving it finds the vector
+    // unsorted.
+    v.scramble();
+}
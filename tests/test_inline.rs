@@ -0,0 +1,55 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests that the functions which check invariants are always inlined in
+// release builds, and only those. Expands inline/inline.rs with and without
+// debug assertions and checks the attributes of the generated functions.
+
+mod common;
+
+fn expand(debug_assertions: bool) -> String {
+    let output = common::rustc()
+        .args(&["-Z", "unpretty=expanded", "-C"])
+        .arg(format!("debug-assertions={}", if debug_assertions { "on" } else { "off" }))
+        .arg(common::root("inline").join("inline.rs"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// The attributes of the function named name in expanded, i.e., the text
+// between the end of the previous item and the function.
+fn attrs<'a>(expanded: &'a str, name: &str) -> &'a str {
+    let f = expanded.find(&format!("fn {}(", name)).expect(name);
+    let start = expanded[..f].rfind(|c| c == '}' || c == ';').map_or(0, |i| i + 1);
+    &expanded[start..f]
+}
+
+#[test]
+fn test_inline_release() {
+    let expanded = expand(false);
+    let invariant_fn = attrs(&expanded, "__hoare_invariant_bounded_0");
+    assert!(invariant_fn.contains("inline(always)"), "{}", expanded);
+    let method = attrs(&expanded, "__hoare_invariant");
+    assert!(method.contains("inline(always)"), "{}", expanded);
+    let precond_fn = attrs(&expanded, "__hoare_precond_bounded_0");
+    assert!(!precond_fn.contains("inline"), "{}", expanded);
+}
+
+#[test]
+fn test_inline_debug() {
+    let expanded = expand(true);
+    let invariant_fn = attrs(&expanded, "__hoare_invariant_bounded_0");
+    assert!(!invariant_fn.contains("#[inline(always)]"), "{}", expanded);
+    let method = attrs(&expanded, "__hoare_invariant");
+    assert!(!method.contains("#[inline(always)]"), "{}", expanded);
+}
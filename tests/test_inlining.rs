@@ -0,0 +1,52 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of the code generated for release builds. Compiles inlining/release.rs
+// with optimizations (so without debug assertions), checks which functions are
+// hinted inline and have cold violation paths, and runs it.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+fn expansion(dir: &Path, name: &str) -> String {
+    common::read(&dir.join(format!("release.{}.rs", name)))
+}
+
+#[test]
+fn test_release_build() {
+    let out = common::out_dir("inlining");
+    let output = common::rustc()
+        .arg("-O")
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(common::root("inlining").join("release.rs"))
+        .env("HOARE_DUMP_EXPANSION", out.join("expansion"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let dir = out.join("expansion");
+    let small = expansion(&dir, "small");
+    assert!(small.contains("#[inline]"), "{}", small);
+    assert!(small.contains("#[cold]"), "{}", small);
+    let never = expansion(&dir, "never");
+    assert!(!never.contains("#[inline]"), "{}", never);
+    assert!(never.contains("#[cold]"), "{}", never);
+    let large = expansion(&dir, "large");
+    assert!(!large.contains("#[inline]"), "{}", large);
+    assert!(large.contains("#[cold]"), "{}", large);
+
+    let status = Command::new(out.join("release")).status().unwrap();
+    assert!(status.success());
+    fs::remove_dir_all(&out).unwrap();
+}
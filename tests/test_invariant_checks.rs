@@ -0,0 +1,89 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+struct Connection {
+    open: bool,
+}
+
+impl Connection {
+    #[invariant(pred="return.open", exit_only)]
+    fn open(ok: bool) -> Connection {
+        Connection { open: ok }
+    }
+
+    #[invariant(pred="self.open", exit_only)]
+    fn reopen(&mut self) {
+        self.open = true;
+    }
+
+    #[invariant(pred="self.open", entry_only)]
+    fn shut_down(&mut self) {
+        self.open = false;
+    }
+
+    #[invariant(pred="self.open", entry_only)]
+    fn close(self) -> bool {
+        self.open
+    }
+}
+
+#[test]
+fn test_invariant_checks() {
+    let mut c = Connection::open(true);
+    c.shut_down();
+    c.reopen();
+    assert!(c.close());
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving open (__result_")]
+fn test_exit_only() {
+    Connection::open(false);
+}
+
+#[test]
+#[should_panic(expected = "invariant entering shut_down (self.open)")]
+fn test_entry_only() {
+    let mut c = Connection::open(true);
+    c.shut_down();
+    c.shut_down();
+}
+
+// An `exit_only` invariant may refer to `return` and need not hold on entry, so
+// it doesn't restrict the inputs generated for the function. (See
+// fuzz/targets.rs for `#[fuzz]`, which is only compiled with `--cfg fuzzing`.)
+#[valid_input]
+#[precond="x > 0 && x < 1000"]
+#[invariant(pred="return > x", exit_only)]
+fn increment(x: i32) -> i32 {
+    x + 1
+}
+
+#[valid_input]
+#[invariant(pred="x >= 500", exit_only)]
+fn at_least_500(x: u16) -> u16 {
+    x.max(500)
+}
+
+#[test]
+fn test_valid_input_exit_only() {
+    for (x,) in generate_valid_input!(increment).take(100) {
+        assert!(x > 0 && x < 1000);
+        assert_eq!(increment(x), x + 1);
+    }
+    // Inputs below 500 are valid, since the invariant is only checked on exit.
+    let small = generate_valid_input!(at_least_500).take(100).filter(|&(x,)| x < 500).count();
+    assert!(small > 0);
+}
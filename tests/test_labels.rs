@@ -0,0 +1,76 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond="x>0"]
+fn compact(x: i32) -> i32 {
+    x
+}
+
+#[precond="x
+           >   0"]
+fn spread(x: i32) -> i32 {
+    x
+}
+
+#[precond="s != \"no\""]
+fn quoted(s: &str) -> usize {
+    s.len()
+}
+
+#[precond="xs.iter().all(|&x|-x<0)&&!xs.is_empty()"]
+fn closure(xs: &[i32]) -> usize {
+    xs.len()
+}
+
+#[precond="implies!(x>0,y>0)"]
+fn shorthand(x: i32, y: i32) -> i32 {
+    x + y
+}
+
+#[test]
+#[should_panic(expected = "precondition of compact (x > 0)")]
+fn test_compact() {
+    compact(0);
+}
+
+#[test]
+#[should_panic(expected = "precondition of spread (x > 0)")]
+fn test_spread() {
+    spread(0);
+}
+
+#[test]
+#[should_panic(expected = "precondition of quoted (s != \"no\")")]
+fn test_quoted() {
+    quoted("no");
+}
+
+#[test]
+#[should_panic(expected = "precondition of closure (xs.iter().all(|&x| -x < 0))")]
+fn test_closure() {
+    closure(&[1, -1]);
+}
+
+#[test]
+#[should_panic(expected = "precondition of closure (!xs.is_empty())")]
+fn test_conjunct() {
+    closure(&[]);
+}
+
+#[test]
+#[should_panic(expected = "precondition of shorthand (implies!(x > 0, y > 0))")]
+fn test_shorthand() {
+    shorthand(1, 0);
+}
@@ -0,0 +1,107 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::ops::Add;
+
+#[laws(samples="vec![Money(0), Money(5), Money(5), Money(-12)]", partial_ord, ord, hash, add_assoc)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Money(i64);
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+#[laws(samples="vec![Version { major: 1, minor: 2 }, Version { major: 1, minor: 3 }]", partial_ord)]
+#[derive(Debug, PartialEq)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+// Only compares major versions, which is inconsistent with ==.
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<std::cmp::Ordering> {
+        self.major.partial_cmp(&other.major)
+    }
+}
+
+#[test]
+fn test_generated_laws() {
+    laws_money();
+}
+
+#[test]
+#[should_panic(expected = "law partial_ord of Version violated")]
+fn test_inconsistent_partial_ord() {
+    laws_version();
+}
+
+#[invariant="iff!(self.len() == 0, self.is_empty())"]
+#[laws(cleared="{ let mut c = self.clone(); c.clear(); c.is_empty() }")]
+trait Container: Clone {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    fn clear(&mut self);
+}
+
+#[derive(Clone)]
+struct Stack(Vec<i32>);
+
+impl Container for Stack {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+// Never becomes empty.
+#[derive(Clone)]
+struct Sticky(Vec<i32>);
+
+impl Container for Sticky {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.0.truncate(1);
+    }
+}
+
+#[test]
+fn test_trait_laws() {
+    laws::check_laws(&Stack(vec![1, 2]));
+    laws::invariant(&Sticky(vec![1, 2]));
+}
+
+#[test]
+#[should_panic(expected = "law cleared of Container violated")]
+fn test_broken_trait_law() {
+    laws::check_laws(&Sticky(vec![1, 2]));
+}
@@ -0,0 +1,68 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::sync::{Mutex, RwLock};
+
+struct Range {
+    lo: i32,
+    hi: i32,
+}
+
+#[lock_invariants]
+struct Shared {
+    #[lock_invariant = "range.lo <= range.hi"]
+    range: Mutex<Range>,
+    #[lock_invariant = "sorted.windows(2).all(|w| w[0] <= w[1])"]
+    sorted: RwLock<Vec<i32>>,
+}
+
+impl Shared {
+    fn new() -> Shared {
+        Shared {
+            range: Mutex::new(Range { lo: 0, hi: 0 }),
+            sorted: RwLock::new(vec![]),
+        }
+    }
+}
+
+#[test]
+fn test_lock_invariant_1() {
+    let s = Shared::new();
+    {
+        let mut range = s.lock_range();
+        range.hi += 1;
+    }
+    s.write_sorted().push(1);
+    s.write_sorted().push(2);
+    assert!(s.read_sorted().len() == 2);
+}
+
+#[test]
+#[should_panic(expected = "lock invariant releasing Shared::range")]
+fn test_lock_invariant_fail_1() {
+    let s = Shared::new();
+    let mut range = s.lock_range();
+    range.lo += 1;
+}
+
+#[test]
+#[should_panic(expected = "lock invariant acquiring Shared::sorted")]
+fn test_lock_invariant_fail_2() {
+    let s = Shared::new();
+    // Bypassing the checked accessor breaks the invariant unnoticed...
+    s.sorted.write().unwrap().extend(vec![2, 1]);
+    // ...until the next checked access.
+    s.read_sorted();
+}
@@ -0,0 +1,66 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+// The generated loom test only runs with `--cfg loom` and the `loom` feature
+// of libhoare.
+
+extern crate libhoare;
+#[cfg(loom)]
+extern crate loom;
+
+#[cfg(loom)]
+use loom::sync::Mutex;
+#[cfg(not(loom))]
+use std::sync::Mutex;
+
+struct Range {
+    bounds: Mutex<(usize, usize)>,
+}
+
+#[loom_model = "Range::new()"]
+impl Range {
+    fn new() -> Range {
+        Range { bounds: Mutex::new((0, 0)) }
+    }
+
+    fn lo(&self) -> usize {
+        self.bounds.lock().unwrap().0
+    }
+
+    fn hi(&self) -> usize {
+        self.bounds.lock().unwrap().1
+    }
+
+    #[invariant = "self.lo() <= self.hi()"]
+    fn grow(&self) {
+        let mut bounds = self.bounds.lock().unwrap();
+        bounds.1 += 1;
+        bounds.0 += 1;
+    }
+
+    #[invariant = "self.lo() <= self.hi()"]
+    fn shrink(&self) {
+        let mut bounds = self.bounds.lock().unwrap();
+        if bounds.0 < bounds.1 {
+            bounds.1 -= 1;
+        }
+    }
+}
+
+#[test]
+fn test_loom_model_1() {
+    let r = Range::new();
+    r.grow();
+    r.shrink();
+    assert!(r.__hoare_loom_invariant());
+}
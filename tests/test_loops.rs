@@ -0,0 +1,143 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::cell::Cell;
+
+// Returns from inside the user's labelled loops.
+#[postcond="return < 100"]
+fn find(grid: &[Vec<u32>], target: u32) -> u32 {
+    let mut steps = 0;
+    'rows: for row in grid {
+        'cols: for &x in row {
+            steps += 1;
+            if x == 0 {
+                continue 'rows;
+            }
+            if x == target {
+                return steps;
+            }
+            if x > target {
+                break 'cols;
+            }
+        }
+    }
+    steps + 50
+}
+
+// A labelled loop with a value as the tail.
+#[postcond="return % 2 == 0"]
+fn first_even(xs: &[u32]) -> u32 {
+    if xs.is_empty() {
+        return 0;
+    }
+    let mut i = 0;
+    'search: loop {
+        let found = loop {
+            if i >= xs.len() {
+                break 'search 2;
+            }
+            if xs[i] % 2 == 0 {
+                break xs[i];
+            }
+            i += 1;
+        };
+        break 'search found;
+    }
+}
+
+// A return whose value returns.
+#[postcond="return > 0"]
+fn parse_or_default(s: &str) -> u32 {
+    if s.is_empty() {
+        return 1;
+    }
+    return 'digits: loop {
+        for c in s.chars() {
+            if !c.is_digit(10) {
+                return 0;
+            }
+            if c == '0' {
+                break 'digits 10;
+            }
+        }
+        break s.parse().unwrap();
+    };
+}
+
+// Early returns from a function which ends in statements without a value.
+#[postcond="counter.get() > 0"]
+fn count(counter: &Cell<u32>, skip: bool) {
+    if skip {
+        counter.set(1);
+        return;
+    }
+    counter.set(counter.get() + 1);
+    let _done = true;
+}
+
+#[postcond="counter.get() > 0"]
+fn count_macro(counter: &Cell<u32>, skip: bool) {
+    if skip {
+        counter.set(1);
+        return;
+    }
+    counter.set(counter.get() + 1);
+    println!("counted");
+}
+
+#[test]
+fn test_labelled_loops() {
+    let grid = vec![vec![1, 0, 7], vec![2, 3, 9], vec![4]];
+    assert!(find(&grid, 3) == 4);
+    assert!(find(&grid, 8) == 56);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of find")]
+fn test_labelled_loops_postcond() {
+    let grid = vec![vec![1; 60]];
+    find(&grid, 2);
+}
+
+#[test]
+fn test_loop_value() {
+    assert!(first_even(&[]) == 0);
+    assert!(first_even(&[1, 3, 4, 5]) == 4);
+    assert!(first_even(&[1, 3]) == 2);
+}
+
+#[test]
+fn test_return_in_return() {
+    assert!(parse_or_default("") == 1);
+    assert!(parse_or_default("12") == 12);
+    assert!(parse_or_default("10") == 10);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of parse_or_default")]
+fn test_return_in_return_postcond() {
+    parse_or_default("1x");
+}
+
+#[test]
+fn test_statement_tail() {
+    let counter = Cell::new(0);
+    count(&counter, true);
+    count(&counter, false);
+    assert!(counter.get() == 2);
+    count_macro(&counter, false);
+    count_macro(&counter, true);
+    assert!(counter.get() == 1);
+}
@@ -0,0 +1,96 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+// The argument is named in the macro, so it is hygienic.
+macro_rules! halve {
+    ($name: ident, $ty: ty) => {
+        #[precond="x % 2 == 0"]
+        #[postcond="return * 2 == x"]
+        fn $name(x: $ty) -> $ty {
+            x / 2
+        }
+    }
+}
+
+halve!(halve_i32, i32);
+halve!(halve_u64, u64);
+
+// The predicate and the argument are passed to the macro.
+macro_rules! positive {
+    ($name: ident, $arg: ident, $pred: tt) => {
+        #[precond=$pred]
+        fn $name($arg: i32) -> i32 {
+            $arg
+        }
+    }
+}
+
+positive!(identity, n, "n > 0");
+
+// Methods, and structs with derives.
+macro_rules! counter {
+    ($name: ident) => {
+        #[derive(Clone, Debug, PartialEq)]
+        struct $name {
+            count: u32,
+            limit: u32,
+        }
+
+        impl $name {
+            #[invariant="self.count <= self.limit"]
+            fn add(&mut self, by: u32) {
+                self.count += by;
+            }
+
+            #[precond="by <= self.count"]
+            #[postcond="self.count + by == old(self.count)"]
+            fn sub(&mut self, by: u32) {
+                self.count -= by;
+            }
+        }
+    }
+}
+
+counter!(Counter);
+
+#[test]
+fn test_macros() {
+    assert!(halve_i32(4) == 2);
+    assert!(halve_u64(10) == 5);
+    assert!(identity(3) == 3);
+    let mut c = Counter { count: 0, limit: 5 };
+    c.add(3);
+    c.sub(1);
+    assert!(c.clone() == Counter { count: 2, limit: 5 });
+}
+
+#[test]
+#[should_panic(expected = "precondition of halve_i32 (x % 2 == 0)")]
+fn test_hygienic_arg() {
+    halve_i32(3);
+}
+
+#[test]
+#[should_panic(expected = "precondition of identity (n > 0)")]
+fn test_passed_arg() {
+    identity(0);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving add")]
+fn test_method() {
+    let mut c = Counter { count: 0, limit: 5 };
+    c.add(6);
+}
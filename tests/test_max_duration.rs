@@ -0,0 +1,38 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::thread;
+use std::time::Duration;
+
+#[max_duration="10s"]
+fn quick(x: u32) -> u32 {
+    x + 1
+}
+
+#[max_duration="1ms"]
+fn slow() {
+    thread::sleep(Duration::from_millis(5));
+}
+
+#[test]
+fn test_max_duration() {
+    assert!(quick(1) == 2);
+}
+
+#[test]
+#[should_panic(expected = "max_duration of slow violated")]
+fn test_exceeded() {
+    slow();
+}
@@ -0,0 +1,48 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![hoare_policy(on_violation="log")]
+
+// Metrics are only counted with `--cfg hoare_metrics`, and need the `metrics`
+// feature of libhoare:
+// RUSTFLAGS="--cfg hoare_metrics" cargo test --features libhoare/metrics
+
+extern crate libhoare;
+
+#[precond = "x >= 0.0"]
+#[postcond = "return >= 0.0"]
+fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[test]
+#[cfg(hoare_metrics)]
+fn test_metrics() {
+    use libhoare::metrics;
+    use libhoare::violation;
+
+    sqrt(4.0);
+    // The result is NaN, so the postcondition fails too.
+    violation::expect_violations(2, || {
+        sqrt(-1.0);
+    });
+
+    let counts: Vec<_> = metrics::snapshot()
+        .into_iter()
+        .filter(|m| m.fn_path == "test_metrics::sqrt")
+        .map(|m| (m.kind, m.checks, m.violations))
+        .collect();
+    assert_eq!(counts, vec![("postcondition", 2, 1), ("precondition", 2, 1)]);
+    assert!(metrics::prometheus_text().contains(
+        "hoare_contract_violations_total{function=\"test_metrics::sqrt\",kind=\"precondition\"} 1"
+    ));
+}
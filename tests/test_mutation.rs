@@ -0,0 +1,71 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of mutation testing. Compiles mutation/mutants.rs with
+// `--cfg hoare_mutants` as a library and as its test harness, as `cargo test`
+// does, checks both builds give the library's mutants the same ids, and runs
+// the test harness with one of them active.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+fn compile(out: &Path, test: bool) {
+    let mut command = common::rustc();
+    if test {
+        command.arg("--test");
+    }
+    let output = command
+        .args(&["--cfg", "hoare_mutants", "--out-dir"])
+        .arg(out)
+        .arg(common::root("mutation").join("mutants.rs"))
+        .env("HOARE_MUTANTS_DIR", out.join("mutants"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+fn manifest(out: &Path, name: &str) -> Vec<String> {
+    common::read(&out.join("mutants").join(name)).lines().map(|line| line.to_owned()).collect()
+}
+
+#[test]
+fn test_same_mutants_in_each_build() {
+    let out = common::out_dir("mutation");
+    compile(&out, false);
+    compile(&out, true);
+
+    // Neither build overwrote the other's manifest.
+    let lib = manifest(&out, "mutants.mutants");
+    let test = manifest(&out, "mutants.test.mutants");
+    assert!(!lib.is_empty());
+    assert!(lib.iter().all(|line| line.contains("\tmutants::positive\t")), "{:?}", lib);
+    for line in &lib {
+        assert!(test.contains(line), "{} is not in {:?}", line, test);
+    }
+    assert!(test.iter().any(|line| line.contains("\tmutants::tests::small\t")), "{:?}", test);
+
+    // The harness passes with no mutant active, and fails with the mutant of
+    // the library's precondition which accepts 0, selected by its id in the
+    // library's manifest.
+    let status = Command::new(out.join("mutants")).status().unwrap();
+    assert!(status.success());
+    let mutant = lib.iter().find(|line| line.ends_with("\tx >= 0")).unwrap();
+    let id = mutant.split('\t').next().unwrap();
+    let status = Command::new(out.join("mutants"))
+        .env("HOARE_MUTANT", format!("mutants:{}", id))
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    fs::remove_dir_all(&out).unwrap();
+}
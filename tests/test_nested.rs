@@ -0,0 +1,106 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[postcond="return > 0"]
+fn total(xs: &[i32]) -> i32 {
+    #[precond="x >= 0"]
+    #[postcond="return > 0"]
+    fn weight(x: i32) -> i32 {
+        if x == 0 {
+            return 1;
+        }
+        x
+    }
+
+    let clamp = |x: i32| {
+        if x < 0 {
+            return 0;
+        }
+        x
+    };
+    if xs.is_empty() {
+        return 1;
+    }
+    xs.iter().map(|&x| weight(clamp(x))).sum()
+}
+
+#[postcond="return % 2 == 0"]
+fn outer(x: u32) -> u32 {
+    struct Counter {
+        n: u32,
+    }
+
+    impl Counter {
+        #[precond="by > 0"]
+        fn add(&mut self, by: u32) -> u32 {
+            if by == 1 {
+                self.n += 1;
+                return self.n;
+            }
+            self.n += by;
+            self.n
+        }
+    }
+
+    let mut counter = Counter { n: 0 };
+    counter.add(x)
+}
+
+#[postcond="return < 10"]
+fn first_big(xs: &[u32]) -> u32 {
+    let find = || {
+        for &x in xs {
+            if x >= 10 {
+                return Some(x);
+            }
+        }
+        None
+    };
+    match find() {
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+#[test]
+fn test_nested_fn() {
+    assert!(total(&[]) == 1);
+    assert!(total(&[0, -3, 2]) == 4);
+}
+
+#[test]
+fn test_nested_method() {
+    assert!(outer(2) == 2);
+    assert!(outer(4) == 4);
+}
+
+#[test]
+#[should_panic(expected = "precondition of add (by > 0)")]
+fn test_nested_method_precond() {
+    outer(0);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of outer")]
+fn test_outer_postcond() {
+    outer(3);
+}
+
+#[test]
+fn test_closure_return() {
+    // The closure's return of 12 is not the function's result.
+    assert!(first_big(&[1, 12]) == 1);
+    assert!(first_big(&[1, 2]) == 0);
+}
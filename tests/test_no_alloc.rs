@@ -0,0 +1,58 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute, global_allocator, allocator_api)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::heap::{Alloc, AllocErr, Layout, System};
+
+// The system allocator, counting allocations for `#[no_alloc]`.
+struct Counting;
+
+unsafe impl<'a> Alloc for &'a Counting {
+    unsafe fn alloc(&mut self, layout: Layout) -> Result<*mut u8, AllocErr> {
+        libhoare::alloc::record_allocation();
+        (&System).alloc(layout)
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        (&System).dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[no_alloc]
+fn mix(samples: &mut [f32], gain: f32) {
+    for s in samples.iter_mut() {
+        *s *= gain;
+    }
+}
+
+#[no_alloc]
+fn mix_copy(samples: &[f32], gain: f32) -> Vec<f32> {
+    samples.iter().map(|s| s * gain).collect()
+}
+
+#[test]
+fn test_no_alloc() {
+    let mut samples = vec![0.5, 1.0];
+    mix(&mut samples, 2.0);
+    assert!(samples == vec![1.0, 2.0]);
+}
+
+#[test]
+#[should_panic(expected = "no_alloc of mix_copy violated: 1 allocation")]
+fn test_alloc() {
+    mix_copy(&[0.5, 1.0], 2.0);
+}
@@ -0,0 +1,41 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond="i < xs.len()"]
+#[no_panic]
+fn get(xs: &[u32], i: usize, offset: usize) -> u32 {
+    if offset == 0 {
+        return xs[i];
+    }
+    xs[i + offset]
+}
+
+#[test]
+fn test_no_panic() {
+    assert!(get(&[1, 2, 3], 1, 0) == 2);
+    assert!(get(&[1, 2, 3], 0, 2) == 3);
+}
+
+#[test]
+#[should_panic(expected = "precondition of get")]
+fn test_precond() {
+    get(&[1, 2, 3], 3, 0);
+}
+
+#[test]
+#[should_panic(expected = "panic-freedom contract violated in get: index out of bounds")]
+fn test_panic() {
+    get(&[1, 2, 3], 1, 5);
+}
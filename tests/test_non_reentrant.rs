@@ -0,0 +1,70 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::cell::RefCell;
+
+struct Button {
+    clicks: RefCell<u32>,
+    on_click: Box<Fn(&Button)>,
+}
+
+impl Button {
+    #[non_reentrant(per_object)]
+    fn click(&self) {
+        *self.clicks.borrow_mut() += 1;
+        (self.on_click)(self);
+    }
+}
+
+#[non_reentrant]
+fn notify(depth: u32) -> u32 {
+    if depth == 0 {
+        0
+    } else {
+        notify(depth - 1) + 1
+    }
+}
+
+#[test]
+fn test_not_reentered() {
+    let inner = Button {
+        clicks: RefCell::new(0),
+        on_click: Box::new(|_| {}),
+    };
+    inner.click();
+    let outer = Button {
+        clicks: RefCell::new(0),
+        on_click: Box::new(move |_| inner.click()),
+    };
+    outer.click();
+    assert!(*outer.clicks.borrow() == 1);
+    assert!(notify(0) == 0);
+}
+
+#[test]
+#[should_panic(expected = "non_reentrant of click violated: entered again while already executing on the same object")]
+fn test_reentered_object() {
+    let button = Button {
+        clicks: RefCell::new(0),
+        on_click: Box::new(|b| b.click()),
+    };
+    button.click();
+}
+
+#[test]
+#[should_panic(expected = "non_reentrant of notify violated")]
+fn test_reentered_fn() {
+    notify(1);
+}
@@ -0,0 +1,112 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::fs::File;
+
+use libhoare::Snapshot;
+
+#[snapshot(Debug, PartialEq)]
+struct Editor {
+    lines: Vec<String>,
+    cursor: usize,
+    #[snapshot_skip]
+    #[allow(dead_code)]
+    file: Option<File>,
+}
+
+impl Editor {
+    #[transition = "self.lines.len() == old(self).lines.len() + 1"]
+    #[transition = "old(self).cursor == self.cursor"]
+    fn append(&mut self, line: &str) {
+        self.lines.push(line.to_owned());
+    }
+
+    #[transition = "old(self) == self.snapshot()"]
+    fn broken_noop(&mut self) {
+        self.cursor += 1;
+    }
+}
+
+// Only its length is saved by `old`.
+struct Log(Vec<String>);
+
+impl Snapshot for Log {
+    type Snapshot = usize;
+    fn snapshot(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[transition = "log.0.len() == old(*log) + 1"]
+fn record(log: &mut Log, entry: &str) {
+    log.0.push(entry.to_owned());
+}
+
+fn editor() -> Editor {
+    Editor {
+        lines: vec![],
+        cursor: 0,
+        file: None,
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    let mut e = editor();
+    e.append("hello");
+    assert!(e.lines.len() == 1);
+
+    let mut log = Log(vec![]);
+    record(&mut log, "a");
+    assert!(log.0.len() == 1);
+}
+
+#[test]
+#[should_panic(expected = "transition of broken_noop")]
+fn test_snapshot_changed() {
+    editor().broken_noop();
+}
+
+struct Queue {
+    items: Vec<u32>,
+}
+
+impl Queue {
+    #[transition(pred="self.items.starts_with(&old(self.items))", old="pooled")]
+    #[transition(pred="self.items.len() == old(self.items).len() + 1", old="pooled")]
+    fn push(&mut self, item: u32) {
+        self.items.push(item);
+    }
+
+    #[transition(pred="self.items == old(self.items)", old="pooled")]
+    fn broken_peek(&mut self) -> Option<u32> {
+        self.items.pop()
+    }
+}
+
+#[test]
+fn test_pooled() {
+    let mut queue = Queue { items: vec![] };
+    for i in 0..10 {
+        queue.push(i);
+    }
+    assert!(queue.items.len() == 10);
+}
+
+#[test]
+#[should_panic(expected = "transition of broken_peek (self.items == old(self.items))")]
+fn test_pooled_changed() {
+    Queue { items: vec![1] }.broken_peek();
+}
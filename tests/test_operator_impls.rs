@@ -0,0 +1,102 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::ops::{Deref, Index, IndexMut};
+
+struct Name {
+    name: String,
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    #[postcond="return.len() == self.name.len()"]
+    fn deref(&self) -> &str {
+        if self.name.is_empty() {
+            return "";
+        }
+        &self.name
+    }
+}
+
+struct Grid {
+    width: usize,
+    cells: Vec<u8>,
+}
+
+impl Index<(usize, usize)> for Grid {
+    type Output = u8;
+
+    #[precond="x < self.width"]
+    fn index(&self, (x, y): (usize, usize)) -> &u8 {
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Grid {
+    #[invariant="self.cells.len() % self.width == 0"]
+    #[postcond="*return <= 9"]
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut u8 {
+        if x >= self.width {
+            return &mut self.cells[0];
+        }
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+fn grid() -> Grid {
+    Grid {
+        width: 2,
+        cells: vec![1, 2, 3, 4],
+    }
+}
+
+#[test]
+fn test_deref() {
+    let name = Name { name: "hoare".to_owned() };
+    assert_eq!(name.len(), 5);
+    assert!(name.starts_with("ho"));
+}
+
+#[test]
+fn test_index() {
+    let mut grid = grid();
+    assert_eq!(grid[(1, 1)], 4);
+    grid[(0, 1)] = 7;
+    assert_eq!(grid[(0, 1)], 7);
+}
+
+#[test]
+#[should_panic(expected = "precondition of index (x < self.width)")]
+fn test_index_out_of_range() {
+    let grid = grid();
+    let _ = grid[(2, 0)];
+}
+
+#[test]
+#[should_panic(expected = "postcondition of index_mut")]
+fn test_index_mut_postcond() {
+    let mut grid = grid();
+    grid.cells[3] = 10;
+    grid[(1, 1)] += 1;
+}
+
+#[test]
+#[should_panic(expected = "invariant entering index_mut")]
+fn test_index_mut_invariant() {
+    let mut grid = grid();
+    grid.cells.push(5);
+    grid[(0, 0)] = 0;
+}
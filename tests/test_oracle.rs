@@ -0,0 +1,57 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+fn slow_sum(n: u64) -> u64 {
+    (0..n + 1).sum()
+}
+
+#[oracle = "slow_sum"]
+fn sum(n: u64) -> u64 {
+    n * (n + 1) / 2
+}
+
+#[oracle = "slow_sum"]
+fn wrong_sum(n: u64) -> u64 {
+    if n > 10 {
+        return n * n / 2;
+    }
+    n * (n + 1) / 2
+}
+
+fn reverse_ref(v: &[i32]) -> Vec<i32> {
+    v.iter().rev().cloned().collect()
+}
+
+#[precond = "v.len() > 0"]
+#[oracle = "reverse_ref"]
+fn reverse(v: &[i32]) -> Vec<i32> {
+    let mut v = v.to_vec();
+    v.reverse();
+    v
+}
+
+#[test]
+fn test_oracle_1() {
+    sum(0);
+    sum(100);
+    wrong_sum(10);
+    reverse(&[1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "oracle of wrong_sum (slow_sum)")]
+fn test_oracle_fail_1() {
+    wrong_sum(11);
+}
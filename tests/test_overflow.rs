@@ -0,0 +1,60 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond(no_overflow="a * b + c")]
+fn mul_add(a: u32, b: u32, c: u32) -> u32 {
+    a * b + c
+}
+
+#[precond(no_overflow="x - y", no_overflow="-(x * 2)")]
+fn diff(x: i8, y: i8) -> i8 {
+    x - y
+}
+
+#[postcond(no_overflow="return * return")]
+fn square_root(x: u64) -> u64 {
+    (x as f64).sqrt() as u64
+}
+
+#[test]
+fn test_no_overflow() {
+    assert!(mul_add(3, 4, 5) == 17);
+    assert!(diff(-100, 27) == -127);
+    assert!(square_root(16) == 4);
+}
+
+#[test]
+#[should_panic(expected = "precondition of mul_add")]
+fn test_overflow_mul() {
+    mul_add(1 << 16, 1 << 16, 0);
+}
+
+#[test]
+#[should_panic(expected = "precondition of mul_add")]
+fn test_overflow_add() {
+    mul_add(1 << 16, 1 << 15, u32::max_value());
+}
+
+#[test]
+#[should_panic(expected = "precondition of diff ((x).checked_sub(y).is_some())")]
+fn test_overflow_sub() {
+    diff(-100, 29);
+}
+
+#[test]
+#[should_panic(expected = "precondition of diff")]
+fn test_overflow_neg() {
+    diff(-64, 0);
+}
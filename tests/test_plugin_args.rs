@@ -0,0 +1,66 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of the plugin's arguments. Compiles the crates in plugin_args/, checks
+// the errors of those which don't compile, and runs the one which does.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+mod common;
+
+fn rustc(out: &Path, file: &str) -> Output {
+    common::rustc()
+        .arg("--out-dir")
+        .arg(out)
+        .arg(common::root("plugin_args").join(file))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_configured() {
+    let out = common::out_dir("plugin-args-configured");
+    let output = rustc(&out, "configured.rs");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let status = Command::new(out.join("configured")).status().unwrap();
+    assert!(status.success());
+    fs::remove_dir_all(&out).unwrap();
+}
+
+#[test]
+fn test_impure() {
+    let out = common::out_dir("plugin-args-impure");
+    let output = rustc(&out, "impure.rs");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("the predicate `Vec::push(&mut v.clone(), 0) == ()` is not pure, \
+                             since it borrows mutably (`&mut v.clone()`)"),
+            "{}",
+            stderr);
+    assert!(stderr.contains("since it assigns (`*x += 1`)"), "{}", stderr);
+    assert!(stderr.contains("since it uses an unsafe block (`unsafe { *p > 0 }`)"), "{}", stderr);
+    assert!(stderr.contains("which is required by #![plugin(hoare(strict))]"), "{}", stderr);
+    let _ = fs::remove_dir_all(&out);
+}
+
+#[test]
+fn test_invalid() {
+    let out = common::out_dir("plugin-args-invalid");
+    let output = rustc(&out, "invalid.rs");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("invalid value `sometimes` for the plugin argument `level`"),
+            "{}",
+            stderr);
+    assert!(stderr.contains("expected `strict` or a policy option"), "{}", stderr);
+    let _ = fs::remove_dir_all(&out);
+}
@@ -0,0 +1,65 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![hoare_policy(on_violation = "log", old = "clone")]
+
+extern crate libhoare;
+
+// Logs violations, following the crate's policy.
+#[precond = "x > 0"]
+fn logged(x: i32) -> i32 {
+    x
+}
+
+#[precond(pred = "x > 0", on_violation = "panic")]
+fn panics(x: i32) -> i32 {
+    x
+}
+
+#[postcond(pred = "return > x", level = "off")]
+fn off(x: i32) -> i32 {
+    x
+}
+
+#[transition(pred = "*x == old(*x) + 1", on_violation = "panic")]
+fn inc(x: &mut i32) {
+    *x += 1;
+}
+
+#[transition(pred = "*x == old(*x) + 1", on_violation = "panic")]
+fn dec(x: &mut i32) {
+    *x -= 1;
+}
+
+#[test]
+fn test_policy() {
+    assert!(logged(1) == 1);
+    assert!(logged(0) == 0);
+    assert!(panics(1) == 1);
+    assert!(off(0) == 0);
+    let mut x = 0;
+    inc(&mut x);
+    assert!(x == 1);
+}
+
+#[test]
+#[should_panic(expected = "precondition of panics (x > 0)")]
+fn test_override() {
+    panics(0);
+}
+
+#[test]
+#[should_panic(expected = "transition of dec")]
+fn test_transition() {
+    let mut x = 0;
+    dec(&mut x);
+}
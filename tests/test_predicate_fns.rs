@@ -0,0 +1,46 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+#![hoare_policy(predicates="fn")]
+
+extern crate libhoare;
+
+#[precond="y != 0"]
+#[precond="x >= 0"]
+#[postcond="return <= x"]
+fn div(x: i32, y: i32) -> i32 {
+    x / y
+}
+
+#[precond="!xs.is_empty() && xs.iter().all(|x| *x > 0)"]
+fn min(xs: Vec<u32>) -> u32 {
+    *xs.iter().min().unwrap()
+}
+
+#[test]
+fn test_predicate_fns() {
+    assert!(__hoare_precond_div_0(&10, &2));
+    assert!(!__hoare_precond_div_0(&10, &0));
+    assert!(!__hoare_precond_div_1(&-1, &2));
+    assert!(__hoare_postcond_div_0(&10, &2, &5));
+    assert!(!__hoare_postcond_div_0(&10, &2, &11));
+    assert!(__hoare_precond_min_0(&vec![2, 1]));
+    assert!(!__hoare_precond_min_0(&vec![]));
+    assert!(div(10, 2) == 5);
+    assert!(min(vec![3, 1, 2]) == 1);
+}
+
+#[test]
+#[should_panic(expected = "precondition of div (x >= 0)")]
+fn test_predicate_fn_fails() {
+    div(-4, 2);
+}
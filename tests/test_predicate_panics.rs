@@ -0,0 +1,58 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond(pred="v[i] > 0", predicate_panics="report")]
+fn get(v: &[i32], i: usize) -> i32 {
+    v[i]
+}
+
+#[postcond(pred="return > 0 && v[return as usize] > 0", predicate_panics="report")]
+fn last(v: &[i32]) -> i32 {
+    v.len() as i32
+}
+
+#[precond="v[i] > 0"]
+fn propagated(v: &[i32], i: usize) -> i32 {
+    v[i]
+}
+
+#[test]
+fn test_predicate_panics() {
+    assert!(get(&[1, 2], 1) == 2);
+}
+
+#[test]
+#[should_panic(expected = "contract predicate crashed in get (v[i] > 0): index out of bounds")]
+fn test_crashed() {
+    get(&[1, 2], 2);
+}
+
+#[test]
+#[should_panic(expected = "precondition of get (v[i] > 0)")]
+fn test_violated() {
+    get(&[1, -2], 1);
+}
+
+#[test]
+#[should_panic(expected = "contract predicate crashed in last")]
+fn test_conjunct_crashed() {
+    last(&[1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn test_propagated() {
+    propagated(&[1, 2], 2);
+}
@@ -0,0 +1,182 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond="in_range!(percent, 0..101)"]
+fn scale(x: u32, percent: u32) -> u32 {
+    x * percent / 100
+}
+
+#[precond="non_empty!(xs)"]
+#[postcond="is_sorted!(return)"]
+fn sorted(xs: &[i32], broken: bool) -> Vec<i32> {
+    let mut result = xs.to_vec();
+    if !broken {
+        result.sort();
+    }
+    result
+}
+
+#[precond="non_empty!(s) && in_range!(s.len(), 1..10)"]
+fn first(s: &str) -> char {
+    s.chars().next().unwrap()
+}
+
+#[postcond="implies!(x > 0, return > 0)"]
+fn sign(x: i32, broken: bool) -> i32 {
+    if broken { -x.signum() } else { x.signum() }
+}
+
+#[postcond="iff!(x == 0, return)"]
+fn is_zero(x: i32, broken: bool) -> bool {
+    if broken { x != 0 } else { x == 0 }
+}
+
+#[precond="implies!(!xs.is_empty(), xs[0] > 0)"]
+fn first_positive(xs: &[i32]) -> bool {
+    xs.first().map_or(true, |&x| x > 0)
+}
+
+#[test]
+fn test_predicates() {
+    assert!(scale(50, 10) == 5);
+    assert!(sorted(&[3, 1, 2], false) == vec![1, 2, 3]);
+    assert!(first("abc") == 'a');
+    assert!(sign(3, false) == 1);
+    assert!(sign(-3, true) == 1);
+    assert!(is_zero(0, false));
+    assert!(!is_zero(3, false));
+    assert!(first_positive(&[]));
+}
+
+#[test]
+#[should_panic(expected = "precondition of scale (in_range!(percent, 0..101)): 101 is not in 0..101")]
+fn test_in_range() {
+    scale(50, 101);
+}
+
+#[test]
+#[should_panic(expected = "precondition of sorted (non_empty!(xs)): the collection is empty")]
+fn test_non_empty() {
+    sorted(&[], false);
+}
+
+#[test]
+#[should_panic(expected = "): not sorted at index 1: 3 is not <= 1")]
+fn test_is_sorted() {
+    sorted(&[3, 1, 2], true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of first")]
+fn test_combined() {
+    first("");
+}
+
+#[test]
+#[should_panic(expected = "postcondition of sign (implies!(x > 0, __result_")]
+fn test_implies() {
+    sign(1, true);
+}
+
+#[test]
+#[should_panic(expected = ": x == 0 holds but __result_")]
+fn test_iff() {
+    is_zero(0, true);
+}
+
+#[test]
+#[should_panic(expected = " holds but x == 0 does not")]
+fn test_iff_converse() {
+    is_zero(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of first_positive (implies!(!xs.is_empty(), xs[0] > 0)): \
+                           !xs.is_empty() holds but xs[0] > 0 does not")]
+fn test_implies_message() {
+    first_positive(&[-1]);
+}
+
+struct Names {
+    items: Vec<String>,
+}
+
+impl Names {
+    #[postcond="valid_index!(return, self.items)"]
+    fn find(&self, name: &str, broken: bool) -> Option<usize> {
+        if broken {
+            return Some(self.items.len());
+        }
+        self.items.iter().position(|item| item == name)
+    }
+
+    #[precond="checked_at!(self.items, i)"]
+    fn get(&self, i: usize) -> &str {
+        &self.items[i]
+    }
+}
+
+#[test]
+fn test_indices() {
+    let names = Names { items: vec!["a".to_owned(), "b".to_owned()] };
+    assert!(names.find("b", false) == Some(1));
+    assert!(names.find("c", false) == None);
+    assert!(names.get(0) == "a");
+}
+
+#[test]
+#[should_panic(expected = "postcondition of find (valid_index!(__result_")]
+fn test_invalid_index() {
+    let names = Names { items: vec!["a".to_owned()] };
+    names.find("a", true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of get (checked_at!(self.items, i)): index 2 is out of \
+                           bounds for a length of 1")]
+fn test_out_of_bounds() {
+    let names = Names { items: vec!["a".to_owned()] };
+    names.get(2);
+}
+
+#[precond="is_char_boundary!(s, at)"]
+#[postcond="is_ascii!(return)"]
+fn escape_from(s: &str, at: usize) -> String {
+    s[at..].escape_default().to_string()
+}
+
+#[precond="valid_utf8!(bytes)"]
+fn decode(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn test_strings() {
+    assert!(escape_from("aé", 1) == "\\u{e9}");
+    assert!(decode(b"ok") == "ok");
+}
+
+#[test]
+#[should_panic(expected = "precondition of escape_from (is_char_boundary!(s, at)): offset 2 is \
+                           not a char boundary, it is inside 'é' at 1..3")]
+fn test_not_char_boundary() {
+    escape_from("aé", 2);
+}
+
+#[test]
+#[should_panic(expected = "precondition of decode (valid_utf8!(bytes)): invalid UTF-8 at offset 1")]
+fn test_invalid_utf8() {
+    decode(b"a\xff");
+}
@@ -0,0 +1,82 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+
+extern crate libhoare;
+
+// Predicates can use private items of the module which defines the contract,
+// even when the contracted items are used from other modules.
+
+mod shapes {
+    fn valid_side(side: i32) -> bool {
+        side > 0
+    }
+
+    #[invariant="valid_side(self.width) && valid_side(self.height)"]
+    pub struct Rect {
+        width: i32,
+        height: i32,
+    }
+
+    impl Rect {
+        pub fn raw(width: i32, height: i32) -> Rect {
+            Rect { width: width, height: height }
+        }
+
+        #[precond="valid_side(width)"]
+        pub fn set_width(&mut self, width: i32) {
+            self.width = width;
+        }
+
+        pub fn area(&self) -> i32 {
+            self.width * self.height
+        }
+    }
+}
+
+mod builders {
+    use shapes::Rect;
+
+    pub struct RectBuilder {
+        pub width: i32,
+        pub height: i32,
+    }
+
+    impl RectBuilder {
+        #[establishes_invariant_of="Rect"]
+        pub fn build(self) -> Rect {
+            Rect::raw(self.width, self.height)
+        }
+    }
+}
+
+use builders::RectBuilder;
+
+#[test]
+fn test_private_helpers() {
+    let mut rect = RectBuilder { width: 2, height: 3 }.build();
+    rect.set_width(4);
+    assert!(rect.area() == 12);
+}
+
+#[test]
+#[should_panic(expected = "precondition of set_width (valid_side(width))")]
+fn test_precond() {
+    shapes::Rect::raw(2, 3).set_width(0);
+}
+
+#[test]
+#[should_panic(expected = "invariant of Rect not established by build")]
+fn test_builder() {
+    RectBuilder { width: 2, height: -3 }.build();
+}
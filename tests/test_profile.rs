@@ -0,0 +1,50 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of contract profiles. Compiles profile/profiled.rs with a profile (see
+// HOARE_PROFILE) and runs it.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+mod common;
+
+fn compile(profile: &str) -> (Output, PathBuf) {
+    let root = common::root("profile");
+    let out = common::out_dir(&format!("profile-{}", profile));
+    let output = common::rustc()
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(root.join("profiled.rs"))
+        .env("HOARE_PROFILE", root.join(profile))
+        .output()
+        .unwrap();
+    (output, out)
+}
+
+#[test]
+fn test_profile() {
+    let (output, out) = compile("hoare.toml");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let status = Command::new(out.join("profiled")).status().unwrap();
+    assert!(status.success());
+    fs::remove_dir_all(&out).unwrap();
+}
+
+#[test]
+fn test_invalid_profile() {
+    let (output, out) = compile("invalid.toml");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("invalid.toml:2: invalid `level = sometimes`"), "{}", stderr);
+    let _ = fs::remove_dir_all(&out);
+}
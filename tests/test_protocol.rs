@@ -0,0 +1,80 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[protocol]
+#[derive(Clone, Debug, PartialEq)]
+struct Connection {
+    sent: Vec<String>,
+}
+
+#[protocol(initial = "idle",
+           connect = "idle -> connected",
+           send = "connected -> connected",
+           disconnect = "connected -> idle",
+           shutdown = "idle -> finished")]
+impl Connection {
+    fn new() -> Connection {
+        Connection { sent: vec![] }
+    }
+
+    fn connect(&mut self) {}
+
+    fn send(&mut self, msg: &str) -> usize {
+        if msg.is_empty() {
+            return 0;
+        }
+        self.sent.push(msg.to_owned());
+        msg.len()
+    }
+
+    fn disconnect(&mut self) {}
+
+    fn shutdown(self) -> Vec<String> {
+        self.sent
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sent.is_empty()
+    }
+}
+
+#[test]
+fn test_protocol_1() {
+    let mut c = Connection::new();
+    assert!(c.is_empty());
+    c.connect();
+    c.send("hello");
+    c.send("");
+    c.disconnect();
+    c.connect();
+    c.disconnect();
+    assert!(c.clone() == c);
+    assert!(c.shutdown() == vec!["hello".to_owned()]);
+}
+
+#[test]
+#[should_panic(expected = "protocol of Connection violated: send called in state idle, expected connected")]
+fn test_protocol_fail_1() {
+    let mut c = Connection::new();
+    c.send("hello");
+}
+
+#[test]
+#[should_panic(expected = "protocol of Connection violated: shutdown called in state connected")]
+fn test_protocol_fail_2() {
+    let mut c = Connection::new();
+    c.connect();
+    c.shutdown();
+}
@@ -0,0 +1,78 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+#[race_checks]
+struct Stats {
+    #[race_check]
+    hits: AtomicUsize,
+    name: &'static str,
+}
+
+impl Stats {
+    fn new(name: &'static str) -> Stats {
+        Stats {
+            hits: AtomicUsize::new(0).into(),
+            name: name,
+        }
+    }
+
+    // Not atomic, another thread may increment hits between the load and the
+    // store.
+    #[race_free]
+    fn record(&self, barrier: Option<&Barrier>) {
+        let n = self.hits.load(Ordering::SeqCst);
+        if let Some(barrier) = barrier {
+            barrier.wait();
+            barrier.wait();
+        }
+        self.hits.write().store(n + 1, Ordering::SeqCst);
+    }
+
+    #[race_free]
+    fn bump(&self) {
+        self.hits.write().fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_race_free() {
+    let stats = Stats::new("test");
+    stats.record(None);
+    stats.bump();
+    assert!(stats.hits.load(Ordering::SeqCst) == 2);
+    assert!(stats.name == "test");
+}
+
+#[test]
+#[should_panic(expected = "race check of Stats::hits failed: modified by another thread during record")]
+fn test_race() {
+    let stats = Arc::new(Stats::new("test"));
+    let barrier = Arc::new(Barrier::new(2));
+    let other = {
+        let stats = stats.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            barrier.wait();
+            stats.bump();
+            barrier.wait();
+        })
+    };
+    stats.record(Some(&barrier));
+    other.join().unwrap();
+}
@@ -0,0 +1,41 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="self.0 > 0"]
+#[derive(Debug, PartialEq)]
+struct Positive(i32);
+
+#[invariant="self.0.len() <= 4"]
+#[invariant="self.0.chars().all(|c| '0' <= c && c <= '9')"]
+#[derive(Debug, PartialEq)]
+pub struct Pin(String);
+
+#[test]
+fn test_refinement() {
+    let p = Positive::try_new(42).unwrap();
+    assert!(*p == 42);
+    assert!(*p + 1 == 43);
+    assert!(Positive::try_new(0) == Err(0));
+    assert!(Positive::try_new(-5) == Err(-5));
+    assert!(*Positive::new_unchecked(-5) == -5);
+}
+
+#[test]
+fn test_refinement_multiple() {
+    assert!(Pin::try_new("1234".to_owned()).is_ok());
+    assert!(Pin::try_new("12345".to_owned()) == Err("12345".to_owned()));
+    assert!(Pin::try_new("12a4".to_owned()) == Err("12a4".to_owned()));
+    assert!(Pin::try_new("42".to_owned()).unwrap().len() == 2);
+}
@@ -0,0 +1,58 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+// Tests are usually built with debug assertions, when release_ contracts are
+// not checked, but the tests also run in release builds.
+
+#[release_precond = "x > 0"]
+fn precond(x: i32) -> i32 {
+    x
+}
+
+#[release_postcond = "return > x"]
+fn postcond(x: i32) -> i32 {
+    x
+}
+
+#[release_invariant = "*x > 0"]
+fn invariant(x: &mut i32) {
+    *x -= 1;
+}
+
+#[test]
+fn test_release_ok() {
+    assert!(precond(1) == 1);
+    let mut x = 2;
+    invariant(&mut x);
+}
+
+#[test]
+#[cfg_attr(not(debug_assertions), should_panic(expected = "precondition of precond (x > 0)"))]
+fn test_release_precond() {
+    precond(0);
+}
+
+#[test]
+#[cfg_attr(not(debug_assertions), should_panic(expected = "postcondition of postcond"))]
+fn test_release_postcond() {
+    postcond(0);
+}
+
+#[test]
+#[cfg_attr(not(debug_assertions), should_panic(expected = "invariant leaving invariant"))]
+fn test_release_invariant() {
+    let mut x = 1;
+    invariant(&mut x);
+}
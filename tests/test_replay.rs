@@ -0,0 +1,72 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[capture_violations]
+#[replay_violations]
+#[postcond = "return > x"]
+fn inc(x: i32) -> i32 {
+    if x == 3 { x } else { x + 1 }
+}
+
+#[replay_violations]
+#[precond = "!xs.is_empty()"]
+#[postcond = "xs.len() > n"]
+fn extend(xs: &mut Vec<i32>, ys: &[i32], sep: &str, n: usize) {
+    if sep.is_empty() {
+        return;
+    }
+    xs.extend(ys);
+}
+
+#[replay_violations]
+#[postcond = "return"]
+fn unit() -> bool {
+    true
+}
+
+#[test]
+fn test_replay() {
+    replay_inc("[1]");
+    replay_extend("[[1], [2, 3], \",\", 2]");
+    replay_unit("null");
+}
+
+#[test]
+#[should_panic(expected = "postcondition of inc")]
+fn test_replay_violation() {
+    replay_inc("[3]");
+}
+
+#[test]
+#[should_panic(expected = "precondition of extend")]
+fn test_replay_precond() {
+    replay_extend("[[], [2], \",\", 0]");
+}
+
+#[test]
+#[should_panic(expected = "postcondition of extend")]
+fn test_replay_references() {
+    replay_extend("[[1], [2], \"\", 1]");
+}
+
+#[test]
+fn test_replay_captured() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/target/hoare-corpus");
+    let _ = ::std::panic::catch_unwind(|| inc(3));
+    let inputs: Vec<(i32,)> = libhoare::corpus::inputs(dir, "test_replay::inc");
+    assert!(inputs.contains(&(3,)));
+    let captured = libhoare::corpus::serialize(&(&3,)).unwrap();
+    assert!(::std::panic::catch_unwind(|| replay_inc(&captured)).is_err());
+}
@@ -0,0 +1,83 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Cents(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Dollars(f64);
+
+#[round_trip]
+impl From<Dollars> for Cents {
+    fn from(d: Dollars) -> Cents {
+        Cents((d.0 * 100.0).round() as u64)
+    }
+}
+
+#[round_trip]
+impl From<Cents> for Dollars {
+    fn from(c: Cents) -> Dollars {
+        Dollars(c.0 as f64 / 100.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Name(String);
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Bug: names are displayed in upper case, but parsed as written.
+        write!(f, "{}", self.0.to_uppercase())
+    }
+}
+
+#[round_trip]
+impl FromStr for Name {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Name, ()> {
+        let name = s.trim();
+        if name.is_empty() {
+            return Err(());
+        }
+        Ok(Name(name.to_owned()))
+    }
+}
+
+#[test]
+fn test_from() {
+    assert!(Dollars::from(Cents(29)) == Dollars(0.29));
+    assert!(Cents::from(Dollars(0.29)) == Cents(29));
+}
+
+#[test]
+#[should_panic(expected = "round trip of From<Dollars> for Cents violated")]
+fn test_from_violated() {
+    Cents::from(Dollars(0.125));
+}
+
+#[test]
+fn test_from_str() {
+    assert!(" ADA ".parse::<Name>() == Ok(Name("ADA".to_owned())));
+    assert!("".parse::<Name>().is_err());
+}
+
+#[test]
+#[should_panic(expected = "round trip of FromStr for Name violated")]
+fn test_from_str_violated() {
+    let _ = "ada".parse::<Name>();
+}
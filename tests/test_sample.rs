@@ -0,0 +1,63 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[postcond(pred="return > 0 && return < 100 && return != 50", sample="1")]
+fn halve(x: i32) -> i32 {
+    x / 2
+}
+
+#[postcond(pred="return > 0 && return != 50", sample="1", sample_weights="1, 0.000001")]
+fn rarely_checked(x: i32) -> i32 {
+    x
+}
+
+#[test]
+fn test_sample() {
+    for x in 2..200 {
+        if x / 2 != 50 {
+            halve(x);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "postcondition of halve (__result_")]
+fn test_sampled_clause_is_checked() {
+    // The failing clause is checked on a third of the calls.
+    for _ in 0..200 {
+        halve(100);
+    }
+}
+
+#[test]
+fn test_weights() {
+    for _ in 0..100 {
+        assert!(rarely_checked(50) == 50);
+    }
+}
+
+#[test]
+fn test_seed_is_reproducible() {
+    // The same seed and thread name check the same clauses.
+    let checked = |name: &str| {
+        std::thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(|| (0..100).filter(|_| std::panic::catch_unwind(|| halve(100)).is_err()).count())
+            .unwrap()
+            .join()
+            .unwrap()
+    };
+    assert_eq!(checked("halve"), checked("halve"));
+}
@@ -0,0 +1,60 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="self.min <= self.max"]
+#[invariant_setters]
+#[derive(Debug)]
+pub struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+#[test]
+fn test_setters() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.set_max(5);
+    bounds.set_min(5);
+    assert!(bounds.min == 5 && bounds.max == 5);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_min (self.min <= self.max)")]
+fn test_setter_violates_invariant() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.set_min(11);
+}
+
+impl Bounds {
+    #[invariant(invariant(self))]
+    fn widen(&mut self, by: i32) {
+        self.min -= by;
+        self.max += by;
+    }
+}
+
+#[test]
+fn test_method_checks_struct_invariant() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.set_min(1);
+    bounds.widen(2);
+    assert!(bounds.min == -1 && bounds.max == 12);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving widen")]
+fn test_method_breaks_struct_invariant() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.widen(-6);
+}
@@ -0,0 +1,48 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of predicates shared between crates. Compiles shared_predicates/
+// specs.rs, a library of predicates, then shared_predicates/user.rs, whose
+// contracts use them from nested modules, and runs it.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+
+fn rustc(out: &Path, file: &str) {
+    let output = common::rustc()
+        .arg("--out-dir")
+        .arg(out)
+        .arg("-L")
+        .arg(out)
+        .arg(common::root("shared_predicates").join(file))
+        .env("HOARE_PREDICATES_DIR", out.join("predicates"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_shared_predicates() {
+    let out = common::out_dir("shared-predicates");
+    rustc(&out, "specs.rs");
+    let manifest = common::read(&out.join("predicates/specs.predicates"));
+    assert!(manifest.contains("sorted\tspecs::seq::sorted\n"), "{}", manifest);
+    assert!(manifest.contains("identifier\tspecs::identifier\n"), "{}", manifest);
+    assert!(manifest.contains("within\tspecs::within\n"), "{}", manifest);
+    assert!(!manifest.contains("max_len"), "{}", manifest);
+
+    rustc(&out, "user.rs");
+    let status = Command::new(out.join("user")).status().unwrap();
+    assert!(status.success());
+    fs::remove_dir_all(&out).unwrap();
+}
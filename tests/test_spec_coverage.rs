@@ -0,0 +1,51 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of specification coverage. Compiles spec_coverage/specs.rs with `--cfg
+// hoare_spec_coverage` and checks its manifest lists its public functions and
+// structs, and which of them are specified.
+
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_manifest() {
+    let out = common::out_dir("spec-coverage");
+    let output = common::rustc()
+        .arg("--cfg")
+        .arg("hoare_spec_coverage")
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(common::root("spec_coverage").join("specs.rs"))
+        .env("HOARE_SPEC_COVERAGE_DIR", out.join("specs"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let manifest = common::read(&out.join("specs/specs.specs"));
+    let mut lines: Vec<&str> = manifest.lines().collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        vec![
+            "fn\tspecs\tBounds::lo\t0",
+            "fn\tspecs\tBounds::new\t1",
+            "fn\tspecs\tspecified\t1",
+            "fn\tspecs\tunspecified\t0",
+            "fn\tspecs::io\tHandle::read\t0",
+            "fn\tspecs::util\tabs\t1",
+            "type\tspecs\tBounds\t1",
+            "type\tspecs::io\tHandle\t0",
+            "type\tspecs::util\tUnchecked\t0",
+        ]
+    );
+    fs::remove_dir_all(&out).unwrap();
+}
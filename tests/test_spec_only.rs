@@ -0,0 +1,56 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[precond="b != 0"]
+#[postcond(spec_only="return is the greatest common divisor of a and b")]
+fn gcd(a: u64, b: u64) -> u64 {
+    if a % b == 0 {
+        return b;
+    }
+    gcd(b, a % b)
+}
+
+// Not a check, so it doesn't matter that the clause is false.
+#[precond(spec_only="false")]
+#[debug_postcond(spec_only="return is negative", spec_only="return is odd")]
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[invariant(spec_only="the items are sorted")]
+struct Sorted {
+    items: Vec<i32>,
+}
+
+impl Sorted {
+    #[invariant(spec_only="self.items is unchanged")]
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+#[test]
+fn test_spec_only() {
+    assert!(gcd(12, 18) == 6);
+    assert!(double(3) == 6);
+    let sorted = Sorted { items: vec![2, 1] };
+    assert!(sorted.len() == 2);
+}
+
+#[test]
+#[should_panic(expected = "precondition of gcd (b != 0)")]
+fn test_checked_contract() {
+    gcd(1, 0);
+}
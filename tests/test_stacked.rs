@@ -0,0 +1,352 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+// Each pair of contracts, stacked in both orders, on a function which returns
+// early. `broken` makes the function return a value which violates the
+// postcondition and transition.
+
+#[precond="x >= 0"]
+#[postcond="return > x"]
+fn pre_post(x: i32, broken: bool) -> i32 {
+    if broken {
+        return x;
+    }
+    x + 1
+}
+
+#[postcond="return > x"]
+#[precond="x >= 0"]
+fn post_pre(x: i32, broken: bool) -> i32 {
+    if broken {
+        return x;
+    }
+    x + 1
+}
+
+#[postcond="return > x"]
+#[postcond="return < x + 10"]
+fn post_post(x: i32, broken: bool) -> i32 {
+    if broken {
+        return x + 10;
+    }
+    x + 1
+}
+
+// Contracts stacked with attributes which check effects, which also generate
+// names in the function body.
+
+#[precond="x >= 0"]
+#[max_duration="10s"]
+fn pre_duration(x: i32) -> i32 {
+    x + 1
+}
+
+#[max_duration="10s"]
+#[postcond="return > x"]
+fn duration_post(x: i32, broken: bool) -> i32 {
+    if broken {
+        return x;
+    }
+    x + 1
+}
+
+#[max_duration="10s"]
+#[non_reentrant]
+fn duration_reentrant(x: i32) -> i32 {
+    x + 1
+}
+
+struct Counter {
+    count: u32,
+    limit: u32,
+}
+
+impl Counter {
+    #[precond="n > 0"]
+    #[invariant="self.count <= self.limit"]
+    fn pre_inv(&mut self, n: u32, broken: bool) -> u32 {
+        if broken {
+            self.count = self.limit + 1;
+            return self.count;
+        }
+        self.count += n;
+        self.count
+    }
+
+    #[invariant="self.count <= self.limit"]
+    #[precond="n > 0"]
+    fn inv_pre(&mut self, n: u32, broken: bool) -> u32 {
+        if broken {
+            self.count = self.limit + 1;
+            return self.count;
+        }
+        self.count += n;
+        self.count
+    }
+
+    #[postcond="return == self.count"]
+    #[invariant="self.count <= self.limit"]
+    fn post_inv(&mut self, n: u32, broken: bool) -> u32 {
+        self.count += n;
+        if broken {
+            return self.count + 1;
+        }
+        self.count
+    }
+
+    #[invariant="self.count <= self.limit"]
+    #[postcond="return == self.count"]
+    fn inv_post(&mut self, n: u32, broken: bool) -> u32 {
+        self.count += n;
+        if broken {
+            return self.count + 1;
+        }
+        self.count
+    }
+
+    #[transition="self.count == old(self.count) + n"]
+    #[postcond="return == self.count"]
+    fn trans_post(&mut self, n: u32, broken: bool) -> u32 {
+        if broken {
+            return self.count;
+        }
+        self.count += n;
+        self.count
+    }
+
+    #[postcond="return == self.count"]
+    #[transition="self.count == old(self.count) + n"]
+    fn post_trans(&mut self, n: u32, broken: bool) -> u32 {
+        if broken {
+            return self.count;
+        }
+        self.count += n;
+        self.count
+    }
+
+    #[precond="n > 0"]
+    #[transition="return == old(self.count) + n"]
+    fn pre_trans(&mut self, n: u32, broken: bool) -> u32 {
+        if broken {
+            return 0;
+        }
+        self.count += n;
+        self.count
+    }
+
+    #[transition="return == old(self.count) + n"]
+    #[precond="n > 0"]
+    fn trans_pre(&mut self, n: u32, broken: bool) -> u32 {
+        if broken {
+            return 0;
+        }
+        self.count += n;
+        self.count
+    }
+
+    #[invariant="self.count <= self.limit"]
+    #[transition="self.count >= old(self.count)"]
+    fn inv_trans(&mut self, n: u32, broken: bool) {
+        if broken {
+            self.count = 0;
+            return;
+        }
+        self.count += n;
+    }
+
+    #[transition="self.count >= old(self.count)"]
+    #[invariant="self.count <= self.limit"]
+    fn trans_inv(&mut self, n: u32, broken: bool) {
+        if broken {
+            self.count = 0;
+            return;
+        }
+        self.count += n;
+    }
+}
+
+fn counter() -> Counter {
+    Counter { count: 1, limit: 10 }
+}
+
+#[test]
+fn test_stacked() {
+    assert!(pre_post(1, false) == 2);
+    assert!(post_pre(1, false) == 2);
+    assert!(post_post(1, false) == 2);
+    assert!(counter().pre_inv(1, false) == 2);
+    assert!(counter().inv_pre(1, false) == 2);
+    assert!(counter().post_inv(1, false) == 2);
+    assert!(counter().inv_post(1, false) == 2);
+    assert!(counter().trans_post(1, false) == 2);
+    assert!(counter().post_trans(1, false) == 2);
+    assert!(counter().pre_trans(1, false) == 2);
+    assert!(counter().trans_pre(1, false) == 2);
+    counter().inv_trans(1, false);
+    counter().trans_inv(1, false);
+    assert!(pre_duration(1) == 2);
+    assert!(duration_post(1, false) == 2);
+    assert!(duration_reentrant(1) == 2);
+}
+
+#[test]
+#[should_panic(expected = "precondition of pre_post (x >= 0)")]
+fn test_pre_post_pre() {
+    pre_post(-1, false);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of pre_post")]
+fn test_pre_post_post() {
+    pre_post(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of post_pre (x >= 0)")]
+fn test_post_pre_pre() {
+    post_pre(-1, false);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of post_pre")]
+fn test_post_pre_post() {
+    post_pre(1, true);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of post_post")]
+fn test_post_post() {
+    post_post(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of pre_inv (n > 0)")]
+fn test_pre_inv_pre() {
+    counter().pre_inv(0, false);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving pre_inv (self.count <= self.limit)")]
+fn test_pre_inv_inv() {
+    counter().pre_inv(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of inv_pre (n > 0)")]
+fn test_inv_pre_pre() {
+    counter().inv_pre(0, false);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving inv_pre (self.count <= self.limit)")]
+fn test_inv_pre_inv() {
+    counter().inv_pre(1, true);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of post_inv")]
+fn test_post_inv_post() {
+    counter().post_inv(1, true);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving post_inv (self.count <= self.limit)")]
+fn test_post_inv_inv() {
+    counter().post_inv(20, false);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of inv_post")]
+fn test_inv_post_post() {
+    counter().inv_post(1, true);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving inv_post (self.count <= self.limit)")]
+fn test_inv_post_inv() {
+    counter().inv_post(20, false);
+}
+
+#[test]
+#[should_panic(expected = "transition of trans_post")]
+fn test_trans_post() {
+    counter().trans_post(1, true);
+}
+
+#[test]
+#[should_panic(expected = "transition of post_trans")]
+fn test_post_trans() {
+    counter().post_trans(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of pre_trans (n > 0)")]
+fn test_pre_trans_pre() {
+    counter().pre_trans(0, false);
+}
+
+#[test]
+#[should_panic(expected = "transition of pre_trans")]
+fn test_pre_trans_trans() {
+    counter().pre_trans(1, true);
+}
+
+#[test]
+#[should_panic(expected = "precondition of trans_pre (n > 0)")]
+fn test_trans_pre_pre() {
+    counter().trans_pre(0, false);
+}
+
+#[test]
+#[should_panic(expected = "transition of trans_pre")]
+fn test_trans_pre_trans() {
+    counter().trans_pre(1, true);
+}
+
+#[test]
+#[should_panic(expected = "transition of inv_trans")]
+fn test_inv_trans_trans() {
+    counter().inv_trans(1, true);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving inv_trans (self.count <= self.limit)")]
+fn test_inv_trans_inv() {
+    counter().inv_trans(20, false);
+}
+
+#[test]
+#[should_panic(expected = "transition of trans_inv")]
+fn test_trans_inv_trans() {
+    counter().trans_inv(1, true);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving trans_inv (self.count <= self.limit)")]
+fn test_trans_inv_inv() {
+    counter().trans_inv(20, false);
+}
+
+#[test]
+#[should_panic(expected = "precondition of pre_duration (x >= 0)")]
+fn test_pre_duration() {
+    pre_duration(-1);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of duration_post")]
+fn test_duration_post() {
+    duration_post(1, true);
+}
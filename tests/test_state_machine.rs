@@ -0,0 +1,91 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    enum Door {
+        Open { angle: u32 },
+        Closed,
+        Locked(u32),
+    }
+
+    states {
+        Open => "*angle > 0 && *angle <= 90",
+        Locked => "*_0 != 0",
+    }
+
+    transitions {
+        #[from = "Closed"]
+        #[to = "Open"]
+        fn open(self, angle: u32) -> Door {
+            Door::Open { angle: angle }
+        }
+
+        #[from = "Open"]
+        #[to = "Open"]
+        fn push(&mut self, by: u32) {
+            if let Door::Open { ref mut angle } = *self {
+                *angle += by;
+            }
+        }
+
+        #[from = "Open | Closed"]
+        #[to = "Closed"]
+        fn close(self) -> Door {
+            Door::Closed
+        }
+
+        #[from = "Closed"]
+        #[to = "Locked"]
+        #[precond = "code > 999"]
+        fn lock(self, code: u32) -> Door {
+            Door::Locked(code)
+        }
+    }
+}
+
+#[test]
+fn test_state_machine_1() {
+    let mut door = Door::Closed.open(10);
+    door.push(20);
+    assert!(door == Door::Open { angle: 30 });
+    assert!(door.close().lock(1234) == Door::Locked(1234));
+}
+
+#[test]
+#[should_panic(expected = "precondition of lock")]
+fn test_state_machine_fail_1() {
+    Door::Open { angle: 10 }.lock(1234);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of open")]
+fn test_state_machine_fail_2() {
+    // The invariant of Open doesn't hold.
+    Door::Closed.open(0);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of push")]
+fn test_state_machine_fail_3() {
+    let mut door = Door::Closed.open(80);
+    door.push(20);
+}
+
+#[test]
+#[should_panic(expected = "precondition of lock (code > 999)")]
+fn test_state_machine_fail_4() {
+    Door::Closed.lock(1);
+}
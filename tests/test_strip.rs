@@ -0,0 +1,53 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Checks compiling with `--cfg hoare_strip` removes all contract code, as do
+// contracts which are disabled in the build. Compiles expansion/contracts.rs
+// (or expansion/disabled.rs) with HOARE_DUMP_EXPANSION set and checks neither
+// the dumped functions nor the object code contain any.
+
+extern crate libhoare;
+
+use std::fs;
+
+use libhoare::strip;
+
+mod common;
+
+#[test]
+fn test_strip() {
+    check_stripped("contracts.rs", &["--cfg", "hoare_strip"]);
+}
+
+#[test]
+fn test_disabled() {
+    check_stripped("disabled.rs", &["--cfg", "hoare_disable_tag=\"slow\""]);
+}
+
+// Compiles file in expansion/ with args and checks no contract code is left.
+fn check_stripped(file: &str, args: &[&str]) {
+    let out = common::out_dir(&format!("strip-{}", file));
+    let status = common::rustc()
+        .arg("--crate-type=lib")
+        .arg("--emit=obj")
+        .arg("-O")
+        .args(args)
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(common::root("expansion").join(file))
+        .env("HOARE_DUMP_EXPANSION", out.join("expansion"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    strip::check_expansion(out.join("expansion"));
+    strip::check_binary(out.join(file.replace(".rs", ".o")));
+    fs::remove_dir_all(&out).unwrap();
+}
@@ -0,0 +1,132 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="self.min <= self.max"]
+#[invariant_setters]
+#[invariant_suspension]
+pub struct Bounds {
+    min: i32,
+    max: i32,
+}
+
+impl Bounds {
+    #[invariant(invariant(self))]
+    fn shift(&mut self, by: i32) {
+        self.min += by;
+        self.max += by;
+    }
+}
+
+#[invariant="self.items.len() <= self.capacity"]
+#[invariant_suspension]
+struct Stack<T> {
+    items: Vec<T>,
+    capacity: usize,
+}
+
+#[invariant="self.0 > 0"]
+#[invariant_suspension]
+struct Positive(i32);
+
+impl Positive {
+    #[invariant(invariant(self))]
+    fn add(&mut self, x: i32) {
+        self.0 += x;
+    }
+}
+
+#[test]
+fn test_batch() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    let sum = bounds.with_invariant_suspended(|b| {
+        b.set_min(20);
+        b.shift(1);
+        b.set_max(30);
+        b.min + b.max
+    });
+    assert!(sum == 51);
+    assert!(bounds.min == 21 && bounds.max == 30);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving with_invariant_suspended (self.min <= self.max)")]
+fn test_batch_breaks_invariant() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.with_invariant_suspended(|b| b.set_min(20));
+}
+
+#[test]
+fn test_nested() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.with_invariant_suspended(|b| {
+        b.with_invariant_suspended(|b| b.set_min(20));
+        b.set_max(20);
+    });
+    assert!(bounds.min == 20 && bounds.max == 20);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_min")]
+fn test_other_values_checked() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    let mut other = Bounds { min: 0, max: 10 };
+    bounds.with_invariant_suspended(|_| other.set_min(20));
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_min")]
+fn test_resumed() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    bounds.with_invariant_suspended(|b| b.set_min(5));
+    bounds.set_min(20);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving set_min")]
+fn test_resumed_after_panic() {
+    let mut bounds = Bounds { min: 0, max: 10 };
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        bounds.with_invariant_suspended(|_| panic!("in the batch"));
+    }));
+    assert!(result.is_err());
+    bounds.set_min(20);
+}
+
+#[test]
+fn test_newtype() {
+    let mut p = Positive::try_new(1).ok().unwrap();
+    p.with_invariant_suspended(|p| {
+        p.add(-5);
+        p.add(10);
+    });
+    assert!(*p == 6);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving with_invariant_suspended (self.0 > 0)")]
+fn test_newtype_breaks_invariant() {
+    let mut p = Positive::try_new(1).ok().unwrap();
+    p.with_invariant_suspended(|p| p.add(-5));
+}
+
+#[test]
+fn test_generic() {
+    let mut stack = Stack { items: vec![1, 2], capacity: 2 };
+    stack.with_invariant_suspended(|s| {
+        s.items.push(3);
+        s.items.remove(0);
+    });
+    assert!(stack.items == vec![2, 3]);
+}
@@ -0,0 +1,35 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+use std::env;
+
+#[precond(pred = "x > 0", tag = "fast")]
+fn fast(x: i32) -> i32 {
+    x
+}
+
+#[postcond(pred = "return > x", tag = "expensive")]
+fn expensive(x: i32) -> i32 {
+    x
+}
+
+// The tags are read once, so this is the only test in this file.
+#[test]
+fn test_tags() {
+    env::set_var("HOARE_DISABLE_TAGS", "expensive");
+    assert!(expensive(0) == 0);
+    assert!(fast(1) == 1);
+    assert!(::std::panic::catch_unwind(|| fast(0)).is_err());
+}
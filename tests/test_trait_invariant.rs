@@ -0,0 +1,75 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[invariant="iff!(self.len() == 0, self.is_empty())"]
+trait Container {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    fn push(&mut self, x: i32);
+    fn clear(&mut self);
+
+    fn push_all(&mut self, xs: &[i32]) {
+        for &x in xs {
+            self.push(x);
+        }
+    }
+}
+
+struct Stack {
+    items: Vec<i32>,
+    empty: bool,
+}
+
+impl Stack {
+    fn new() -> Stack {
+        Stack { items: Vec::new(), empty: true }
+    }
+}
+
+#[check_trait_invariant]
+impl Container for Stack {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    fn push(&mut self, x: i32) {
+        self.items.push(x);
+        self.empty = false;
+    }
+
+    // Forgets to set `empty`.
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+#[test]
+fn test_trait_invariant() {
+    let mut s = Stack::new();
+    s.push_all(&[1, 2, 3]);
+    assert!(s.len() == 3);
+}
+
+#[test]
+#[should_panic(expected = "invariant leaving clear")]
+fn test_broken_impl() {
+    let mut s = Stack::new();
+    s.push(1);
+    s.clear();
+}
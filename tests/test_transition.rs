@@ -0,0 +1,94 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+struct Account {
+    balance: i64,
+    history: Vec<i64>,
+}
+
+impl Account {
+    #[precond = "amount >= 0"]
+    #[transition = "self.balance == old(self.balance) - amount"]
+    #[transition = "self.history.len() == old(self.history.len()) + 1"]
+    fn withdraw(&mut self, amount: i64) {
+        self.balance -= amount;
+        self.history.push(-amount);
+    }
+
+    #[transition = "self.balance == old(self.balance) + amount"]
+    fn deposit(&mut self, amount: i64) {
+        if amount > 1000 {
+            // Oops.
+            self.balance += amount / 2;
+            return;
+        }
+        self.balance += amount;
+    }
+
+    #[transition = "return == old(self.balance) && self.balance == 0"]
+    fn close(&mut self) -> i64 {
+        let balance = self.balance;
+        self.balance = 0;
+        balance
+    }
+}
+
+#[test]
+fn test_transition_1() {
+    let mut a = Account { balance: 100, history: vec![] };
+    a.withdraw(30);
+    a.deposit(10);
+    assert!(a.close() == 80);
+}
+
+#[test]
+#[should_panic(expected = "transition of deposit")]
+fn test_transition_fail_1() {
+    let mut a = Account { balance: 0, history: vec![] };
+    a.deposit(2000);
+}
+
+struct Log {
+    lines: Vec<String>,
+    written: usize,
+}
+
+impl Log {
+    #[transition = "grew_by!(old(self.lines.len()), self.lines.len(), 1)"]
+    #[transition = "increased_by!(old(self.written), self.written, line.len())"]
+    fn push(&mut self, line: &str) {
+        self.written += line.len();
+        if !line.is_empty() {
+            self.lines.push(line.to_owned());
+        }
+    }
+}
+
+#[test]
+fn test_grew_by() {
+    let mut log = Log { lines: vec![], written: 0 };
+    log.push("abc");
+    log.push("de");
+    assert!(log.written == 5);
+}
+
+#[test]
+#[should_panic(expected = "transition of push (grew_by!(old(self.lines.len()), self.lines.len(), 1)): \
+                           expected to grow by 1 from 1, but it became 1")]
+fn test_grew_by_fail() {
+    let mut log = Log { lines: vec![], written: 0 };
+    log.push("abc");
+    log.push("");
+}
@@ -0,0 +1,47 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests of the errors for contracts on items which are not functions. Compiles
+// unsupported/items.rs, which has one on each kind of item, and checks its
+// errors.
+
+use std::fs;
+
+mod common;
+
+#[test]
+fn test_unsupported_items() {
+    let out = common::out_dir("unsupported");
+    let output = common::rustc()
+        .arg("--out-dir")
+        .arg(&out)
+        .arg(common::root("unsupported").join("items.rs"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for expected in &[
+        "Precondition on a struct, which is not a function",
+        "see `Struct invariants` in libhoare's readme",
+        "Postcondition on an enum, which is not a function",
+        "Precondition on a static, which is not a function",
+        "see `Refinement types` in libhoare's readme",
+        "Precondition on an impl, which is not a function",
+        "`#[apply_contract_set(name)]`",
+        "Postcondition on a required trait method, which has no body",
+        "put `#[contracted]` on the trait",
+        "Invariant on a trait impl, which is not a function",
+        "`#[check_trait_invariant]` on the impl",
+        "Precondition on a module, which is not a function",
+        "see `Contract profiles` in libhoare's readme",
+    ] {
+        assert!(stderr.contains(expected), "expected `{}` in {}", expected, stderr);
+    }
+    let _ = fs::remove_dir_all(&out);
+}
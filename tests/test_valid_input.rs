@@ -0,0 +1,52 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+extern crate libhoare;
+
+#[valid_input]
+#[precond = "x > 0 && x < y"]
+fn ordered(x: i32, y: i32) -> i32 {
+    y - x
+}
+
+#[test]
+fn test_valid_input_1() {
+    for (x, y) in generate_valid_input!(ordered).take(100) {
+        assert!(x > 0 && x < y);
+        ordered(x, y);
+    }
+}
+
+mod strings {
+    #[valid_input]
+    #[invariant = "s.len() < 10"]
+    pub fn short(s: String) {}
+}
+
+#[test]
+fn test_valid_input_2() {
+    for (s,) in generate_valid_input!(strings::short).take(100) {
+        assert!(s.len() < 10);
+        strings::short(s);
+    }
+}
+
+#[valid_input]
+#[precond = "x == 42 && y == 42"]
+fn unlikely(x: u64, y: u64) {}
+
+#[test]
+#[should_panic]
+fn test_valid_input_fail_1() {
+    generate_valid_input!(unlikely).attempts(10).next();
+}
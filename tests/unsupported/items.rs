@@ -0,0 +1,52 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// Input for test_unsupported.rs, which does not compile.
+
+#![feature(plugin, custom_attribute)]
+#![plugin(hoare)]
+
+#[precond="self.x > 0"]
+struct Point {
+    x: i32,
+}
+
+#[postcond="true"]
+enum Shape {
+    Dot,
+}
+
+#[precond="LIMIT > 0"]
+static LIMIT: i32 = 10;
+
+#[precond="self.x > 0"]
+impl Point {
+    fn x(&self) -> i32 {
+        self.x
+    }
+}
+
+trait Area {
+    #[postcond="return >= 0"]
+    fn area(&self) -> i32;
+}
+
+#[invariant="self.x > 0"]
+impl Area for Point {
+    fn area(&self) -> i32 {
+        0
+    }
+}
+
+#[precond="true"]
+mod nested {}
+
+fn main() {}